@@ -1,16 +1,16 @@
 use actix::prelude::*;
 use anchor_client::{
     anchor_lang::prelude::{Clock, Pubkey},
-    solana_sdk::signature::{Keypair, Signature},
-    Client,
+    solana_sdk::signature::{Keypair, Signature, Signer},
+    Client, Program,
 };
 use serde::{Deserialize, Serialize};
 
 use staratlas_sage_based_sdk::{
     calc,
     program::{CARGO_ID, SAGE_ID},
-    Fleet, FleetState, FleetWithState, Game, Idle, MineAsteroid, MineItem, Planet, Resource,
-    SageBasedGameHandler, StarbaseLoadingBay,
+    CargoWeights, Fleet, FleetInventory, FleetState, FleetWithState, Game, Idle, MineAsteroid,
+    MineItem, Planet, PodInventory, Resource, SageBasedGameHandler, StarbaseLoadingBay,
 };
 
 use std::rc::Rc;
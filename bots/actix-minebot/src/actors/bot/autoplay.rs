@@ -12,55 +12,59 @@ pub enum BotOps {
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
-pub(crate) enum IdleActions {
+pub enum IdleActions {
     DockeToStarbase,
     MineAsteroid,
     WarpToSector([i64; 2]),
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
-pub(crate) struct IdleOps {
-    pub(crate) sector: [i64; 2],
-    pub(crate) cargo_capacity_fraction: f64,
-    pub(crate) stopwatch: timers::Stopwatch,
-    pub(crate) next_action: IdleActions,
+pub struct IdleOps {
+    pub sector: [i64; 2],
+    pub cargo_capacity_fraction: f64,
+    pub stopwatch: timers::Stopwatch,
+    pub next_action: IdleActions,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
-pub(crate) struct MiningOps {
-    pub(crate) mining_location: String,
-    pub(crate) currently_mining: String,
-    pub(crate) resource_mining_rate_per_second: f32,
-    pub(crate) amount_mined: f32,
-    pub(crate) timer: timers::Timer,
+pub struct MiningOps {
+    pub mining_location: String,
+    pub currently_mining: String,
+    pub resource_mining_rate_per_second: f32,
+    pub amount_mined: f32,
+    #[serde(default)]
+    pub binding_constraint: String, // the end of the cycle (cargo hold, ammo or food)
+    pub timer: timers::Timer,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
-pub(crate) struct WarpOps {
-    pub(crate) sector: [i64; 2],
-    pub(crate) timer: timers::Timer,
-    pub(crate) cooldown: timers::Timer,
+pub struct WarpOps {
+    pub sector: [i64; 2],
+    pub timer: timers::Timer,
+    pub cooldown: timers::Timer,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
-pub(crate) struct TxsSageBasedOps {
-    pub(crate) stopwatch: timers::Stopwatch,
+pub struct TxsSageBasedOps {
+    pub stopwatch: timers::Stopwatch,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
-pub(crate) enum StarbaseActions {
+pub enum StarbaseActions {
     IdleHangar,
     CargoDeposit(Pubkey, Pubkey, u64), // (CargoPodTo, Mint, Amount)
     CargoWithdraw(Pubkey, u64),        // (Mint, Amount)
     CheckFuelStatus,
     CheckAmmoStatus,
     CheckFoodStatus,
+    CheckStarbaseInventory,
+    RestockConsumable(Pubkey, u64, u64), // (Mint, Amount, MaxPrice)
     UndockFromStarbase,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
-pub(crate) struct StarbaseLoadingBayOps {
-    pub(crate) starbase: Pubkey,
-    pub(crate) stopwatch: timers::Stopwatch,
-    pub(crate) next_action: StarbaseActions,
+pub struct StarbaseLoadingBayOps {
+    pub starbase: Pubkey,
+    pub stopwatch: timers::Stopwatch,
+    pub next_action: StarbaseActions,
 }
@@ -0,0 +1,235 @@
+use super::*;
+
+use crate::timers;
+
+/// Ledger side-effect of a Sage action, applied once the action's transaction is confirmed.
+#[derive(Debug, Clone)]
+pub enum LedgerEvent {
+    Mined {
+        mint: Pubkey,
+        amount: f64,
+        fuel: f64,
+        ammo: f64,
+        food: f64,
+    },
+    Warped {
+        fuel: f64,
+    },
+    /// Cargo unloaded at a starbase (a transport's delivery), it completes the cycle but only
+    /// moves the cargo: no revenue is booked.
+    Unloaded {
+        mint: Pubkey,
+        amount: f64,
+    },
+}
+
+/// Accumulates the consumables burned and SOL fees paid by a fleet until its cycle completes.
+#[derive(Debug)]
+pub struct CycleLedger {
+    started_at: f64,
+    fuel_burned: f64,
+    ammo_burned: f64,
+    food_burned: f64,
+    fees_lamports: u64,
+    pub pending: Option<LedgerEvent>,
+    /// The completed cycle, recorded once the fee of its last transaction is known.
+    completed: Option<(Signature, db::CycleRecord)>,
+}
+
+impl Default for CycleLedger {
+    fn default() -> Self {
+        CycleLedger {
+            started_at: timers::get_time(),
+            fuel_burned: 0.0,
+            ammo_burned: 0.0,
+            food_burned: 0.0,
+            fees_lamports: 0,
+            pending: None,
+            completed: None,
+        }
+    }
+}
+
+impl CycleLedger {
+    /// Adds the fee of a transaction to its cycle, returns the completed cycle it was the last
+    /// transaction of.
+    pub(crate) fn add_fee(
+        &mut self,
+        signature: &Signature,
+        lamports: u64,
+    ) -> Option<db::CycleRecord> {
+        match self.completed.take() {
+            Some((completed_by, mut cycle)) if &completed_by == signature => {
+                cycle.fees_lamports += lamports;
+                Some(cycle)
+            }
+            completed => {
+                self.completed = completed;
+                self.fees_lamports += lamports;
+                None
+            }
+        }
+    }
+
+    /// Applies the pending event of a confirmed transaction, a cycle it completes waits on the
+    /// transaction's fee. Returns an earlier completed cycle whose fee never came.
+    pub(crate) fn confirm_pending(
+        &mut self,
+        fleet_id: &Pubkey,
+        role: &str,
+        signature: Signature,
+    ) -> Option<db::CycleRecord> {
+        let (resource_mint, resource_amount) = match self.pending.take()? {
+            LedgerEvent::Mined {
+                mint,
+                amount,
+                fuel,
+                ammo,
+                food,
+            } => {
+                self.fuel_burned += fuel;
+                self.ammo_burned += ammo;
+                self.food_burned += food;
+                (Some(mint), amount)
+            }
+            LedgerEvent::Warped { fuel } => {
+                self.fuel_burned += fuel;
+                return None;
+            }
+            LedgerEvent::Unloaded { mint, amount } => {
+                log::info!("Unloaded {} of {} ({})", amount, mint, fleet_id);
+                (None, 0.0)
+            }
+        };
+
+        let record = db::CycleRecord {
+            fleet: fleet_id.to_string(),
+            role: role.to_string(),
+            started_at: self.started_at,
+            finished_at: timers::get_time(),
+            resource_mint: resource_mint.map(|mint| mint.to_string()),
+            resource_amount,
+            fuel_burned: self.fuel_burned,
+            ammo_burned: self.ammo_burned,
+            food_burned: self.food_burned,
+            fees_lamports: self.fees_lamports,
+        };
+
+        let unrecorded = self.take_completed();
+        *self = CycleLedger {
+            completed: Some((signature, record)),
+            ..CycleLedger::default()
+        };
+        unrecorded
+    }
+
+    /// The completed cycle still waiting on its fee (the bot stops).
+    pub(crate) fn take_completed(&mut self) -> Option<db::CycleRecord> {
+        self.completed.take().map(|(_, cycle)| cycle)
+    }
+}
+
+// cargo test -p actix-minebot
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mined() -> LedgerEvent {
+        LedgerEvent::Mined {
+            mint: Pubkey::new_unique(),
+            amount: 100.0,
+            fuel: 1.0,
+            ammo: 2.0,
+            food: 3.0,
+        }
+    }
+
+    #[test]
+    fn warps_accumulate_until_the_cycle_completes() {
+        let fleet_id = Pubkey::new_unique();
+        let mut ledger = CycleLedger {
+            pending: Some(LedgerEvent::Warped { fuel: 10.0 }),
+            ..Default::default()
+        };
+
+        assert!(ledger
+            .confirm_pending(&fleet_id, "MineAsteroid", Signature::new_unique())
+            .is_none());
+
+        ledger.pending = Some(mined());
+        let signature = Signature::new_unique();
+        assert!(ledger
+            .confirm_pending(&fleet_id, "MineAsteroid", signature)
+            .is_none());
+
+        let cycle = ledger.add_fee(&signature, 5_000).unwrap();
+        assert_eq!(cycle.fuel_burned, 11.0);
+        assert_eq!(cycle.ammo_burned, 2.0);
+        assert_eq!(cycle.food_burned, 3.0);
+        assert_eq!(cycle.resource_amount, 100.0);
+        assert!(ledger.take_completed().is_none());
+    }
+
+    #[test]
+    fn fees_are_charged_to_their_own_cycle() {
+        let fleet_id = Pubkey::new_unique();
+        let mut ledger = CycleLedger::default();
+
+        // the fee of a warp of the cycle
+        let warp = Signature::new_unique();
+        ledger.pending = Some(LedgerEvent::Warped { fuel: 1.0 });
+        ledger.confirm_pending(&fleet_id, "CargoTransport", warp);
+        assert!(ledger.add_fee(&warp, 5_000).is_none());
+
+        // the fee of the transaction completing the cycle
+        let unload = Signature::new_unique();
+        ledger.pending = Some(LedgerEvent::Unloaded {
+            mint: Pubkey::new_unique(),
+            amount: 10.0,
+        });
+        ledger.confirm_pending(&fleet_id, "CargoTransport", unload);
+
+        // the next cycle's first transaction is confirmed before the fee comes
+        let next = Signature::new_unique();
+        ledger.pending = Some(LedgerEvent::Warped { fuel: 1.0 });
+        ledger.confirm_pending(&fleet_id, "CargoTransport", next);
+        assert!(ledger.add_fee(&next, 7_000).is_none());
+
+        let cycle = ledger.add_fee(&unload, 6_000).unwrap();
+        assert_eq!(cycle.fees_lamports, 11_000);
+        assert_eq!(ledger.fees_lamports, 7_000);
+    }
+
+    #[test]
+    fn unloading_books_no_revenue() {
+        let fleet_id = Pubkey::new_unique();
+        let mut ledger = CycleLedger::default();
+
+        let signature = Signature::new_unique();
+        ledger.pending = Some(LedgerEvent::Unloaded {
+            mint: Pubkey::new_unique(),
+            amount: 10.0,
+        });
+        ledger.confirm_pending(&fleet_id, "CargoTransport", signature);
+
+        let cycle = ledger.take_completed().unwrap();
+        assert_eq!(cycle.resource_mint, None);
+        assert_eq!(cycle.resource_amount, 0.0);
+    }
+
+    #[test]
+    fn a_cycle_whose_fee_never_came_is_returned() {
+        let fleet_id = Pubkey::new_unique();
+        let mut ledger = CycleLedger {
+            pending: Some(mined()),
+            ..Default::default()
+        };
+
+        ledger.confirm_pending(&fleet_id, "MineAsteroid", Signature::new_unique());
+
+        ledger.pending = Some(mined());
+        let unrecorded = ledger.confirm_pending(&fleet_id, "MineAsteroid", Signature::new_unique());
+        assert_eq!(unrecorded.unwrap().fees_lamports, 0);
+        assert!(ledger.take_completed().is_some());
+    }
+}
@@ -2,23 +2,49 @@ use super::*;
 
 use std::sync::{Arc, Mutex};
 
-mod autoplay;
+pub mod autoplay;
 pub use autoplay::*;
 
+mod ledger;
+pub use ledger::*;
+
+mod restock;
+pub use restock::*;
+
+mod reload;
+pub use reload::*;
+
+mod risk;
+pub use risk::*;
+
 mod roles;
 pub use roles::*;
 
 pub struct BotActor {
     db: Arc<Mutex<db::MinebotDB>>,
-    clock: Option<Clock>,
-    pub(crate) operation: Option<BotOps>,
-    pub(crate) addr_sage: Addr<SageBasedActor>,
-    role: BotRole,
-    fleet: (Pubkey, FleetWithState),
-    pub(crate) fleet_cargo_hold: Vec<(String, u64)>,
-    pub(crate) fleet_fuel_tank: Vec<(String, u64)>,
-    pub(crate) fleet_ammo_bank: Vec<(String, u64)>,
-    pub(crate) fleet_food_cargo: Vec<(String, u64)>,
+    pub clock: Option<Clock>,
+    /// The bot's current operation, set by its role (and shown by the TUI).
+    pub operation: Option<BotOps>,
+    pub addr_sage: Addr<SageBasedActor>,
+    role: Option<Box<dyn Role>>,
+    roles: Rc<RoleFactory>,
+    next_role: Option<Box<dyn Role>>,
+    retiring: bool,
+    handover: bool, // the retired bot hands its role over to the next bot of the fleet
+    pub fleet: (Pubkey, FleetWithState),
+    fleet_subscribed: bool, // the fleet follows its account subscription (see `request_fleet`)
+    fleet_stale: bool,      // a change of the fleet may not be notified yet
+    fleet_notified: Option<FleetWithState>, // the change notified while a transaction is in flight
+    pub fleet_cargo_hold: Vec<(String, u64)>,
+    pub fleet_fuel_tank: Vec<(String, u64)>,
+    pub fleet_ammo_bank: Vec<(String, u64)>,
+    pub fleet_food_cargo: Vec<(String, u64)>,
+    /// The role books the ledger event of a `SageAction` in `ledger.pending`.
+    pub ledger: CycleLedger,
+    restock: Option<RestockPolicy>,
+    pub(crate) restock_checked: bool,
+    restock_reservation: Option<i64>,
+    risk: Option<RiskPolicy>,
 }
 
 impl BotActor {
@@ -26,27 +52,34 @@ impl BotActor {
         db: Arc<Mutex<db::MinebotDB>>,
         addr_sage: Addr<SageBasedActor>,
         fleet: (Pubkey, FleetWithState),
-        role: BotRole,
-        // planet: (Pubkey, Planet),
-        // mine_item: (Pubkey, MineItem),
-        // resource: (Pubkey, Resource),
+        role: Box<dyn Role>,
+        roles: Rc<RoleFactory>,
+        restock: Option<RestockPolicy>,
+        risk: Option<RiskPolicy>,
     ) -> Self {
         Self {
             db,
             addr_sage,
             fleet,
-            role,
-            // role: BotRole::MineAsteroid {
-            //     planet,
-            //     mine_item,
-            //     resource,
-            // },
+            fleet_subscribed: false,
+            fleet_stale: false,
+            fleet_notified: None,
+            role: Some(role),
+            roles,
+            next_role: None,
+            retiring: false,
+            handover: false,
             fleet_cargo_hold: vec![],
             fleet_fuel_tank: vec![],
             fleet_ammo_bank: vec![],
             fleet_food_cargo: vec![],
             clock: None,
             operation: None,
+            ledger: CycleLedger::default(),
+            restock,
+            restock_checked: false,
+            restock_reservation: None,
+            risk,
         }
     }
 }
@@ -56,10 +89,154 @@ impl BotActor {
         let (_, FleetWithState(_, state)) = &self.fleet;
         state
     }
+
+    pub fn role_name(&self) -> String {
+        self.role
+            .as_ref()
+            .map(|role| role.name().to_string())
+            .unwrap_or_default()
+    }
+
+    /// The cargo space of a unit of each mint (the game's cargo types).
+    pub fn cargo_weights(&self) -> &CargoWeights {
+        &self.roles.ctx.cargo_weights
+    }
+
+    /// The fleet's cargo hold (its last known token amounts).
+    pub fn cargo_hold(&self) -> PodInventory {
+        let (_, FleetWithState(fleet, _)) = &self.fleet;
+        let capacity = fleet.stats.cargo_stats.cargo_capacity as u64;
+        PodInventory::from_token_amounts(&fleet.cargo_hold, capacity, &self.fleet_cargo_hold)
+    }
+
+    pub fn fuel_tank(&self) -> PodInventory {
+        let (_, FleetWithState(fleet, _)) = &self.fleet;
+        let capacity = fleet.stats.cargo_stats.fuel_capacity as u64;
+        PodInventory::from_token_amounts(&fleet.fuel_tank, capacity, &self.fleet_fuel_tank)
+    }
+
+    pub fn ammo_bank(&self) -> PodInventory {
+        let (_, FleetWithState(fleet, _)) = &self.fleet;
+        let capacity = fleet.stats.cargo_stats.ammo_capacity as u64;
+        PodInventory::from_token_amounts(&fleet.ammo_bank, capacity, &self.fleet_ammo_bank)
+    }
+
+    /// The cargo pod a mint is deposited to, fuel to the fuel tank, ammo to the ammo bank and the
+    /// rest to the cargo hold.
+    pub fn deposit_pod(&self, mint: &Pubkey) -> Pubkey {
+        let (_, FleetWithState(fleet, _)) = &self.fleet;
+        let mints = &self.roles.ctx.game.mints;
+
+        if mint == &mints.fuel {
+            fleet.fuel_tank
+        } else if mint == &mints.ammo {
+            fleet.ammo_bank
+        } else {
+            fleet.cargo_hold
+        }
+    }
+
+    /// The cargo pod of a deposit (the fuel tank, the ammo bank or the cargo hold).
+    pub fn cargo_pod(&self, cargo_pod: &Pubkey) -> PodInventory {
+        let (_, FleetWithState(fleet, _)) = &self.fleet;
+
+        if cargo_pod == &fleet.fuel_tank {
+            self.fuel_tank()
+        } else if cargo_pod == &fleet.ammo_bank {
+            self.ammo_bank()
+        } else {
+            self.cargo_hold()
+        }
+    }
+
+    /// The units of a mint that fit in the free space of a fleet's cargo pod.
+    pub fn units_fit(&self, cargo_pod: &Pubkey, mint: &Pubkey) -> u64 {
+        self.cargo_pod(cargo_pod)
+            .units_fit(self.cargo_weights(), mint)
+    }
+
+    /// The ammo in the ammo bank, `None` until the ammo bank is read.
+    pub fn ammo_on_hand(&self) -> Option<u64> {
+        let ammo_mint = &self.roles.ctx.game.mints.ammo;
+        (!self.fleet_ammo_bank.is_empty()).then(|| self.ammo_bank().amount(ammo_mint))
+    }
+
+    /// The food in the cargo hold, `None` until the cargo hold is read.
+    pub fn food_on_hand(&self) -> Option<u64> {
+        let food_mint = &self.roles.ctx.game.mints.food;
+        (!self.fleet_cargo_hold.is_empty()).then(|| self.cargo_hold().amount(food_mint))
+    }
+
+    /// Requests the fleet's account for the role (a `SageResponse::Fleet`), the fleet of the
+    /// account subscription while it is live and up to date, otherwise a read of the account.
+    pub fn request_fleet(&self, addr: Addr<BotActor>) {
+        if self.fleet_subscribed && !self.fleet_stale {
+            addr.do_send(SageResponse::Fleet(self.fleet.1.clone()));
+        } else {
+            self.addr_sage
+                .do_send(SageRequest::Fleet(self.fleet.0, addr));
+        }
+    }
+
+    fn record_cycle(&self, cycle: &db::CycleRecord) {
+        if let Ok(db) = self.db.lock() {
+            if let Err(err) = db.insert_cycle(cycle) {
+                log::error!("{:?}", &err);
+            }
+        }
+    }
+
+    /// Hands the bot to its role, the role is taken out of the bot for the duration of the hook.
+    fn with_role(&mut self, hook: impl FnOnce(&mut dyn Role, &mut BotActor)) {
+        if self.retiring {
+            return; // waits on the transaction in flight, nothing new is started
+        }
+
+        if let Some(mut role) = self.role.take() {
+            hook(role.as_mut(), self);
+            self.role = Some(role);
+        }
+    }
 }
 
 impl Actor for BotActor {
     type Context = Context<Self>;
+
+    fn stopped(&mut self, ctx: &mut Self::Context) {
+        log::info!("Bot stopped: {}", self.fleet.0);
+
+        self.addr_sage
+            .do_send(UnsubscribeClockTime(ctx.address().recipient()));
+        self.addr_sage
+            .do_send(UnsubscribeFleet(self.fleet.0, self.fleet.1 .0));
+
+        if let Some(cycle) = self.ledger.take_completed() {
+            self.record_cycle(&cycle);
+        }
+        self.release_restock();
+
+        if self.handover {
+            if let Some(role) = self.role.take() {
+                let handover = Handover {
+                    role,
+                    ledger: std::mem::take(&mut self.ledger),
+                };
+                self.roles
+                    .handovers
+                    .borrow_mut()
+                    .insert(self.fleet.0, handover);
+            }
+        }
+
+        if let Ok(db) = self.db.lock() {
+            db.conn
+                .execute(
+                    "DELETE FROM bot_ops WHERE pubkey = ?1",
+                    rusqlite::params![self.fleet.0.to_string()],
+                )
+                .ok();
+        }
+    }
 }
 
 #[derive(Message)]
@@ -69,9 +246,53 @@ pub struct Ping(pub Option<Signature>);
 impl Handler<Ping> for BotActor {
     type Result = ();
 
-    fn handle(&mut self, msg: Ping, _ctx: &mut Context<Self>) {
+    fn handle(&mut self, msg: Ping, ctx: &mut Context<Self>) {
         log::info!("Pong: {:?}", msg.0);
         self.operation = None; // Clear operation
+
+        // a landed transaction changed the fleet, notified while in flight or (later) read
+        let notified = self.fleet_notified.take();
+        if msg.0.is_some() && notified.is_none() {
+            self.fleet_stale = true;
+        }
+
+        match msg.0 {
+            Some(signature) => {
+                // a completed cycle is recorded with the fee of its last transaction
+                let role_name = self.role_name();
+                if let Some(cycle) =
+                    self.ledger
+                        .confirm_pending(&self.fleet.0, &role_name, signature)
+                {
+                    self.record_cycle(&cycle);
+                }
+
+                self.addr_sage
+                    .do_send(SageRequest::TransactionFee(signature, ctx.address()));
+            }
+            None => {
+                self.ledger.pending = None; // the action failed, nothing to record
+                self.release_restock();
+            }
+        }
+
+        if self.retiring {
+            // the role knows the result of its last transaction before it is handed over
+            if let Some(mut role) = self.role.take() {
+                role.transaction_result(self, msg.0);
+                self.role = Some(role);
+            }
+
+            ctx.stop();
+            return;
+        }
+
+        self.with_role(|role, bot| role.transaction_result(bot, msg.0));
+        self.apply_next_role(ctx.address());
+
+        if let Some(fleet_with_state) = notified {
+            self.handle(FleetChanged(fleet_with_state), ctx);
+        }
     }
 }
 
@@ -83,14 +304,7 @@ impl Handler<ClockTimeUpdate> for BotActor {
     type Result = ();
 
     fn handle(&mut self, msg: ClockTimeUpdate, _: &mut Context<Self>) {
-        match &self.role {
-            BotRole::MineAsteroid { .. } => {
-                roles::mine_asteroid::clock_time_update(self, msg);
-            }
-            BotRole::CargoTransport { .. } => {
-                roles::cargo_transport::clock_time_update(self, msg);
-            }
-        }
+        self.with_role(|role, bot| role.clock_time_update(bot, msg));
     }
 }
 
@@ -98,14 +312,117 @@ impl Handler<SageResponse> for BotActor {
     type Result = ();
 
     fn handle(&mut self, msg: SageResponse, ctx: &mut Context<Self>) {
-        let addr = ctx.address();
-        match &self.role {
-            BotRole::MineAsteroid { .. } => {
-                roles::mine_asteroid::sage_response(self, msg, addr);
+        match msg {
+            SageResponse::TransactionFee(signature, lamports) => {
+                if let Some(cycle) = self.ledger.add_fee(&signature, lamports) {
+                    self.record_cycle(&cycle);
+                }
+                return;
             }
-            BotRole::CargoTransport { .. } => {
-                roles::cargo_transport::sage_response(self, msg, addr);
+            SageResponse::StarbaseCargo(starbase_cargo) => {
+                restock::starbase_cargo_update(self, starbase_cargo);
+                return;
             }
+            SageResponse::ConsumableRestock(mint, amount, atlas_cost, deposit) => {
+                log::info!("Restocked {} of {} ({:?})", amount, mint, deposit);
+
+                self.settle_restock(amount, atlas_cost);
+
+                // once deposited, check the starbase inventory for the next consumable to restock
+                if deposit.is_some() {
+                    self.restock_checked = false;
+                }
+                return;
+            }
+            SageResponse::Fleet(FleetWithState(_, ref state)) => {
+                self.fleet_stale = false;
+
+                // the roles don't know a newer SAGE's state, wait for the fleet to change
+                if let FleetState::Unknown { discriminator, .. } = state {
+                    log::error!(
+                        "Fleet {} is in an unknown state ({}), waiting for a known one",
+                        self.fleet.0,
+                        discriminator
+                    );
+                    self.operation = None;
+                    return;
+                }
+
+                // a new docking gets a new check of the starbase inventory
+                if !matches!(state, FleetState::StarbaseLoadingBay(_)) {
+                    self.restock_checked = false;
+                }
+
+                let from = self.fleet_state().clone();
+                if std::mem::discriminant(&from) != std::mem::discriminant(state) {
+                    let to = state.clone();
+                    self.with_role(|role, bot| role.state_changed(bot, &from, &to));
+                }
+            }
+            _ => {}
+        }
+
+        let addr = ctx.address();
+        self.with_role(|role, bot| role.sage_response(bot, msg, addr));
+    }
+}
+
+/// The fleet's account changed (account subscription).
+#[derive(Message)]
+#[rtype(result = "()")]
+pub struct FleetChanged(pub FleetWithState);
+
+impl Handler<FleetChanged> for BotActor {
+    type Result = ();
+
+    fn handle(&mut self, msg: FleetChanged, ctx: &mut Context<Self>) {
+        // the change is handed to the role after the Ping of the transaction in flight
+        if matches!(self.operation, Some(BotOps::TxsSageBased(_))) {
+            self.fleet_notified = Some(msg.0);
+            return;
+        }
+        self.fleet_stale = false;
+
+        // a new state is handed to the role as a response, the fleet's data is kept up to date
+        let FleetWithState(fleet, state) = &msg.0;
+        if state != self.fleet_state() {
+            self.handle(SageResponse::Fleet(msg.0), ctx);
+        } else {
+            self.fleet.1 .0 = *fleet;
+        }
+    }
+}
+
+/// The fleet's account subscription is live (subscribed) or down (reconnecting).
+#[derive(Message)]
+#[rtype(result = "()")]
+pub struct FleetSubscription(pub bool);
+
+impl Handler<FleetSubscription> for BotActor {
+    type Result = ();
+
+    fn handle(&mut self, msg: FleetSubscription, _: &mut Context<Self>) {
+        // the changes of the fleet while the subscription was down are read once
+        self.fleet_subscribed = msg.0;
+        self.fleet_stale = true;
+    }
+}
+
+/// The amounts of a fleet's cargo pod changed (account subscription).
+#[derive(Message)]
+#[rtype(result = "()")]
+pub struct CargoChanged(pub CargoPod, pub Vec<(String, u64)>);
+
+impl Handler<CargoChanged> for BotActor {
+    type Result = ();
+
+    fn handle(&mut self, msg: CargoChanged, _: &mut Context<Self>) {
+        let CargoChanged(cargo_pod, amounts) = msg;
+
+        match cargo_pod {
+            CargoPod::CargoHold => self.fleet_cargo_hold = amounts,
+            CargoPod::FuelTank => self.fleet_fuel_tank = amounts,
+            CargoPod::AmmoBank => self.fleet_ammo_bank = amounts,
         }
     }
 }
@@ -121,8 +438,8 @@ impl Handler<Tick> for BotActor {
         // log::info!("Tick {:?}", msg.0);
 
         {
-            if let (Some(db), Some(state), Some(data)) = (
-                self.db.lock().ok(),
+            if let (Ok(db), Some(state), Some(data)) = (
+                self.db.lock(),
                 Some(format!("{:#?}", self.fleet_state())),
                 serde_json::to_string(&self.operation).ok(),
             ) {
@@ -136,13 +453,6 @@ impl Handler<Tick> for BotActor {
         }
 
         let addr = ctx.address();
-        match &self.role {
-            BotRole::MineAsteroid { .. } => {
-                roles::mine_asteroid::tick(self, msg, addr);
-            }
-            BotRole::CargoTransport { .. } => {
-                roles::cargo_transport::tick(self, msg, addr);
-            }
-        }
+        self.with_role(|role, bot| role.tick(bot, msg, addr));
     }
 }
@@ -0,0 +1,186 @@
+use super::*;
+
+use crate::config::RoleCfg;
+
+impl BotActor {
+    fn transaction_in_flight(&self) -> bool {
+        matches!(self.operation, Some(BotOps::TxsSageBased(_)))
+    }
+
+    /// Swaps in the role of a reloaded config, the new role starts over from the clock time.
+    pub(crate) fn apply_next_role(&mut self, addr: Addr<BotActor>) {
+        if let Some(role) = self.next_role.take() {
+            log::info!("Role of {}: {}", self.fleet.0, role.name());
+            self.addr_sage.do_send(CargoMints(role.mints()));
+
+            self.role = Some(role);
+            self.operation = None;
+            self.addr_sage.do_send(ClockTimeRequest(addr));
+        }
+    }
+}
+
+/// Replaces the bot's role once its transaction in flight (if any) completes.
+#[derive(Message)]
+#[rtype(result = "()")]
+pub struct SetRole(pub RoleCfg);
+
+impl Handler<SetRole> for BotActor {
+    type Result = ();
+
+    fn handle(&mut self, msg: SetRole, ctx: &mut Context<Self>) {
+        let roles = self.roles.clone();
+        let fut = async move { roles.build(&msg.0).await };
+
+        let actor_future = fut.into_actor(self).map(|result, bot, ctx| match result {
+            Ok(role) => {
+                if let Some(Err(err)) = bot.risk.as_ref().map(|risk| risk.check_role(&*role)) {
+                    log::error!("Role of {}: {:?}", bot.fleet.0, err);
+                    return;
+                }
+
+                bot.next_role = Some(role);
+
+                if !bot.transaction_in_flight() {
+                    bot.apply_next_role(ctx.address());
+                }
+            }
+            Err(err) => log::error!("Role of {}: {:?}", bot.fleet.0, err),
+        });
+
+        ctx.spawn(actor_future);
+    }
+}
+
+#[derive(Message)]
+#[rtype(result = "()")]
+pub struct SetRestock(pub Option<RestockPolicy>);
+
+impl Handler<SetRestock> for BotActor {
+    type Result = ();
+
+    fn handle(&mut self, msg: SetRestock, _: &mut Context<Self>) {
+        self.restock = msg.0;
+        self.restock_checked = false;
+    }
+}
+
+/// The role (and the cycle in progress) a retired bot hands over to the next bot of its fleet.
+pub struct Handover {
+    pub role: Box<dyn Role>,
+    pub ledger: CycleLedger,
+}
+
+/// Stops the bot once its transaction in flight (if any) completes, a fleet moved to another
+/// bot gets the `handover` of its role.
+#[derive(Message)]
+#[rtype(result = "()")]
+pub struct Retire {
+    pub handover: bool,
+}
+
+impl Handler<Retire> for BotActor {
+    type Result = ();
+
+    fn handle(&mut self, msg: Retire, ctx: &mut Context<Self>) {
+        self.retiring = true;
+        self.handover = msg.handover;
+
+        if !self.transaction_in_flight() {
+            ctx.stop();
+        }
+    }
+}
+
+// cargo test -p actix-minebot
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use staratlas_utils_rpc::{EndpointsCfg, RpcEndpoints};
+
+    use std::time::Duration;
+
+    /// A bot of an idle fleet, on a Sage Based actor of the (unreached) local cluster.
+    async fn bot(roles: Rc<RoleFactory>) -> BotActor {
+        let endpoints =
+            RpcEndpoints::new(EndpointsCfg::parse_list("http://localhost:8899").unwrap()).unwrap();
+        let scheduler = RpcScheduler::new(endpoints, &Default::default());
+        let addr_sage = SageBasedActor::new(
+            Rc::new(Keypair::new()),
+            roles.ctx.game_id,
+            roles.ctx.game,
+            scheduler,
+            false,
+        )
+        .start();
+
+        let fleet: Fleet = borsh::BorshDeserialize::deserialize(&mut &[0u8; 1024][..]).unwrap();
+        let fleet = FleetWithState(fleet, FleetState::Idle(Idle { sector: [0, 0] }));
+        let role = roles
+            .build(&RoleCfg(
+                serde_json::from_value(serde_json::json!({
+                    "Mission": { "steps": [{ "Wait": 60 }] }
+                }))
+                .unwrap(),
+            ))
+            .await
+            .unwrap();
+
+        BotActor::new(
+            Arc::new(Mutex::new(db::MinebotDB::open(":memory:").unwrap())),
+            addr_sage,
+            (Pubkey::new_unique(), fleet),
+            role,
+            roles,
+            None,
+            None,
+        )
+    }
+
+    fn roles() -> Rc<RoleFactory> {
+        Rc::new(RoleFactory {
+            registry: RoleRegistry::default(),
+            ctx: roles::tests::ctx(),
+            handovers: Default::default(),
+        })
+    }
+
+    // lets the stopping bot run its `stopped`
+    async fn settle() {
+        actix::clock::sleep(Duration::from_millis(10)).await;
+    }
+
+    #[actix::test]
+    async fn a_retired_bot_stops_after_its_transaction() {
+        let roles = roles();
+        let mut bot = bot(roles.clone()).await;
+        bot.operation = Some(BotOps::TxsSageBased(TxsSageBasedOps {
+            stopwatch: timers::Stopwatch::new(),
+        }));
+        let fleet_id = bot.fleet.0;
+        let addr = bot.start();
+
+        addr.send(Retire { handover: true }).await.unwrap();
+        settle().await;
+        assert!(addr.connected());
+
+        // the transaction's result, then the role is handed over to the fleet's next bot
+        addr.send(Ping(None)).await.unwrap();
+        settle().await;
+        assert!(!addr.connected());
+        assert!(roles.handovers.borrow().contains_key(&fleet_id));
+    }
+
+    #[actix::test]
+    async fn a_removed_bot_without_a_transaction_stops_at_once() {
+        let roles = roles();
+        let bot = bot(roles.clone()).await;
+        let addr = bot.start();
+
+        addr.send(Retire { handover: false }).await.unwrap();
+        settle().await;
+        assert!(!addr.connected());
+        assert!(roles.handovers.borrow().is_empty());
+    }
+}
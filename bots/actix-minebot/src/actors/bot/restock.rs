@@ -0,0 +1,165 @@
+use super::*;
+
+use sa_marketplace_cli::ATLAS_DECIMALS;
+use staratlas_sage_based_sdk::types::Mints;
+
+use crate::config;
+
+/// Starbase inventory threshold, purchase amount and price cap (ATLAS base units) of a consumable.
+#[derive(Debug, Clone)]
+pub struct ConsumableRestock {
+    pub mint: Pubkey,
+    pub threshold: u64,
+    pub amount: u64,
+    pub max_price: u64,
+}
+
+/// Reservations older than this (seconds) were left in flight by a stopped bot, or a crash.
+pub const RESTOCK_RESERVATION_TTL: i64 = 600;
+
+/// Consumables restocked from the Galactic Marketplace and the ATLAS budget (base units) of the
+/// bots, per budget period (seconds).
+#[derive(Debug, Clone)]
+pub struct RestockPolicy {
+    pub atlas_budget: u64,
+    pub budget_period: i64,
+    pub consumables: Vec<ConsumableRestock>,
+}
+
+impl RestockPolicy {
+    pub fn new(cfg: &config::RestockCfg, mints: &Mints) -> Self {
+        let atlas = |amount: f64| (amount * 10f64.powi(ATLAS_DECIMALS as i32)) as u64;
+
+        let consumables = [
+            (mints.fuel, &cfg.fuel),
+            (mints.ammo, &cfg.ammo),
+            (mints.food, &cfg.food),
+        ]
+        .into_iter()
+        .filter_map(|(mint, restock)| {
+            restock.as_ref().map(|restock| ConsumableRestock {
+                mint,
+                threshold: restock.threshold,
+                amount: restock.amount,
+                max_price: atlas(restock.max_price),
+            })
+        })
+        .collect();
+
+        RestockPolicy {
+            atlas_budget: atlas(cfg.atlas_budget),
+            budget_period: (cfg.budget_period_hours * 3600.0) as i64,
+            consumables,
+        }
+    }
+}
+
+impl BotActor {
+    /// First check of the starbase loading bay, the starbase inventory is checked once per docking.
+    pub(crate) fn starbase_first_check(&self) -> StarbaseActions {
+        match (&self.restock, self.restock_checked) {
+            (Some(_), false) => StarbaseActions::CheckStarbaseInventory,
+            _ => StarbaseActions::CheckFuelStatus,
+        }
+    }
+
+    /// Reserves the ATLAS budget (base units) of a restock, shared by all the bots. When the
+    /// budget of the period is spent the bot skips the restock (and checks its fuel).
+    pub(crate) fn reserve_restock(
+        &mut self,
+        mint: &Pubkey,
+        amount: u64,
+        max_price: u64,
+    ) -> Option<u64> {
+        let (atlas_budget, budget_period) = self
+            .restock
+            .as_ref()
+            .map(|restock| (restock.atlas_budget, restock.budget_period))?;
+
+        let reservation = match self.db.lock() {
+            Ok(db) => db
+                .reserve_restock(
+                    &self.fleet.0.to_string(),
+                    &mint.to_string(),
+                    atlas_budget,
+                    amount.saturating_mul(max_price),
+                    timers::get_time() as i64,
+                    budget_period,
+                )
+                .unwrap_or_else(|err| {
+                    log::error!("{:?}", &err);
+                    None
+                }),
+            Err(_) => None,
+        };
+
+        match reservation {
+            Some((id, budget)) => {
+                self.restock_reservation = Some(id);
+                Some(budget)
+            }
+            None => {
+                log::warn!("Restock budget spent, {} isn't restocked", mint);
+                if let Some(BotOps::StarbaseLoadingBay(ops)) = &mut self.operation {
+                    ops.next_action = StarbaseActions::CheckFuelStatus;
+                }
+                None
+            }
+        }
+    }
+
+    /// Books the restock of the reservation.
+    pub(crate) fn settle_restock(&mut self, amount: u64, atlas_cost: u64) {
+        if let (Some(id), Ok(db)) = (self.restock_reservation.take(), self.db.lock()) {
+            if let Err(err) = db.settle_restock(id, amount, atlas_cost) {
+                log::error!("{:?}", &err);
+            }
+        }
+    }
+
+    /// Returns the reservation of a restock that failed to the budget.
+    pub(crate) fn release_restock(&mut self) {
+        if let (Some(id), Ok(db)) = (self.restock_reservation.take(), self.db.lock()) {
+            if let Err(err) = db.release_restock(id) {
+                log::error!("{:?}", &err);
+            }
+        }
+    }
+}
+
+/// Picks the consumable (lowest starbase inventory relative to its threshold) to restock.
+pub(crate) fn starbase_cargo_update(bot: &mut BotActor, starbase_cargo: Vec<(String, u64)>) {
+    let restock = bot.restock.as_ref().and_then(|restock| {
+        restock
+            .consumables
+            .iter()
+            .filter_map(|consumable| {
+                let amount = starbase_cargo
+                    .iter()
+                    .find(|(mint, _)| mint == &consumable.mint.to_string())
+                    .map(|(_, amount)| *amount)
+                    .unwrap_or(0);
+
+                if amount < consumable.threshold {
+                    Some((amount as f64 / consumable.threshold as f64, consumable))
+                } else {
+                    None
+                }
+            })
+            .min_by(|(a, _), (b, _)| a.total_cmp(b))
+            .map(|(_, consumable)| consumable.clone())
+    });
+
+    let next_action = match restock {
+        Some(consumable) => StarbaseActions::RestockConsumable(
+            consumable.mint,
+            consumable.amount,
+            consumable.max_price,
+        ),
+        None => StarbaseActions::CheckFuelStatus,
+    };
+
+    if let Some(BotOps::StarbaseLoadingBay(starbase_loading_bay_ops)) = &mut bot.operation {
+        starbase_loading_bay_ops.next_action = next_action;
+    }
+}
@@ -0,0 +1,137 @@
+use super::*;
+
+use color_eyre::{eyre::eyre, Result};
+use staratlas_sage_based_sdk::{risk::RiskLevel, types::RiskZonesData};
+
+/// The riskiest zone a bot mines in or warps to (the game's risk zones).
+#[derive(Debug, Clone, Copy)]
+pub struct RiskPolicy {
+    pub max_level: RiskLevel,
+    pub zones: RiskZonesData,
+}
+
+impl RiskPolicy {
+    pub fn new(max_risk: &str, zones: RiskZonesData) -> Result<Self> {
+        let max_level = RiskLevel::from_str(max_risk).map_err(|err| eyre!(err))?;
+        Ok(RiskPolicy { max_level, zones })
+    }
+
+    pub fn allows(&self, sector: [i64; 2]) -> bool {
+        self.zones.risk_level(sector) <= self.max_level
+    }
+
+    /// Errors on the first sector of the role above the max risk level.
+    pub fn check_role(&self, role: &dyn Role) -> Result<()> {
+        match role
+            .sectors()
+            .into_iter()
+            .find(|sector| !self.allows(*sector))
+        {
+            Some(sector) => Err(eyre!(
+                "{} goes to {:?} in {:?}, above the max risk level {:?}",
+                role.name(),
+                sector,
+                self.zones.zone(sector),
+                self.max_level
+            )),
+            None => Ok(()),
+        }
+    }
+
+    /// The sectors a route avoids (above the max risk level).
+    pub fn avoid(&self) -> Box<dyn Fn([i64; 2]) -> bool> {
+        let policy = *self;
+        Box::new(move |sector| !policy.allows(sector))
+    }
+}
+
+impl BotActor {
+    /// The sector is within the bot's risk policy (any sector without a policy).
+    pub fn risk_allows(&self, sector: [i64; 2]) -> bool {
+        self.risk.as_ref().is_none_or(|risk| risk.allows(sector))
+    }
+}
+
+// cargo test -p actix-minebot
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use staratlas_sage_based_sdk::types::RiskZoneData;
+
+    /// A security zone around the origin, medium risk zone within 10 sectors and high risk
+    /// within 20 sectors (unzoned beyond).
+    fn zones() -> RiskZonesData {
+        let zone = |center, radius| RiskZoneData { center, radius };
+        RiskZonesData {
+            mud_security_zone: zone([0, 0], 2),
+            oni_security_zone: zone([100, 100], 2),
+            ustur_security_zone: zone([-100, 100], 2),
+            medium_risk_zone: zone([0, 0], 10),
+            high_risk_zone: zone([0, 0], 20),
+        }
+    }
+
+    /// A role warping between two sectors.
+    struct WarpRole(Vec<[i64; 2]>);
+
+    impl Role for WarpRole {
+        fn name(&self) -> &str {
+            "Warp"
+        }
+
+        fn sectors(&self) -> Vec<[i64; 2]> {
+            self.0.clone()
+        }
+
+        fn clock_time_update(&mut self, _bot: &mut BotActor, _msg: ClockTimeUpdate) {}
+
+        fn sage_response(
+            &mut self,
+            _bot: &mut BotActor,
+            _msg: SageResponse,
+            _addr: Addr<BotActor>,
+        ) {
+        }
+
+        fn tick(&mut self, _bot: &mut BotActor, _msg: Tick, _addr: Addr<BotActor>) {}
+    }
+
+    #[test]
+    fn allows_the_sectors_up_to_the_max_risk_level() {
+        assert!(RiskPolicy::new("low", zones()).is_err());
+
+        let secure = RiskPolicy::new("secure", zones()).unwrap();
+        assert!(secure.allows([1, 1]));
+        assert!(secure.allows([100, 101]));
+        assert!(!secure.allows([5, 0]));
+
+        let medium = RiskPolicy::new("medium", zones()).unwrap();
+        assert!(medium.allows([5, 0]));
+        assert!(!medium.allows([15, 0]));
+        assert!(!medium.allows([50, 0])); // unzoned, as high risk
+
+        let high = RiskPolicy::new("high", zones()).unwrap();
+        assert!(high.allows([15, 0]) && high.allows([50, 0]));
+    }
+
+    #[test]
+    fn checks_the_sectors_of_the_role() {
+        let medium = RiskPolicy::new("medium", zones()).unwrap();
+
+        assert!(medium.check_role(&WarpRole(vec![[0, 0], [5, 5]])).is_ok());
+
+        let err = medium
+            .check_role(&WarpRole(vec![[0, 0], [15, 0], [30, 0]]))
+            .unwrap_err();
+        assert!(err.to_string().contains("[15, 0]"));
+    }
+
+    #[test]
+    fn avoids_the_sectors_above_the_max_risk_level() {
+        let avoid = RiskPolicy::new("secure", zones()).unwrap().avoid();
+
+        assert!(!avoid([0, 1]));
+        assert!(avoid([0, 5]));
+    }
+}
@@ -1,10 +1,160 @@
 use super::*;
 
-pub(crate) fn clock_time_update(bot: &mut BotActor, msg: ClockTimeUpdate) {
-    let clock = msg.0;
+use staratlas_sage_based_sdk::route::{self, Route, RouteCost, RouteOptions};
+
+#[derive(Deserialize)]
+struct CargoTransportArgs {
+    cargo_mint: String,
+    cargo_amount: u64,
+    from_sector: [i64; 2],
+    to_sector: [i64; 2],
+    #[serde(default)]
+    route_cost: Option<String>, // time (default) or fuel
+}
+
+pub struct CargoTransportRole {
+    cargo_mint: Pubkey,
+    cargo_amount: u64,
+    from_sector: [i64; 2],
+    from_starbase: Pubkey,
+    to_sector: [i64; 2],
+    to_starbase: Pubkey,
+    route_cost: RouteCost,
+}
+
+impl CargoTransportRole {
+    pub(crate) fn build(args: serde_json::Value, ctx: &RoleContext) -> RoleFuture<'_> {
+        Box::pin(async move {
+            let args: CargoTransportArgs = serde_json::from_value(args)?;
+            let (from_starbase, _) = addr::starbase_address(&ctx.game_id, args.from_sector);
+            let (to_starbase, _) = addr::starbase_address(&ctx.game_id, args.to_sector);
+            let route_cost = match &args.route_cost {
+                Some(route_cost) => RouteCost::from_str(route_cost).map_err(|err| eyre!(err))?,
+                None => RouteCost::default(),
+            };
+
+            let role: Box<dyn Role> = Box::new(CargoTransportRole {
+                cargo_mint: Pubkey::from_str(&args.cargo_mint)?,
+                cargo_amount: args.cargo_amount,
+                from_sector: args.from_sector,
+                from_starbase,
+                to_sector: args.to_sector,
+                to_starbase,
+                route_cost,
+            });
+            Ok(role)
+        })
+    }
+
+    /// The warps between two sectors, beyond the max warp distance by multiple hops (around the
+    /// sectors above the risk policy).
+    fn route(
+        &self,
+        fleet: &Fleet,
+        from_sector: [i64; 2],
+        to_sector: [i64; 2],
+        risk: Option<&RiskPolicy>,
+    ) -> Result<Route> {
+        let options = RouteOptions {
+            cost: self.route_cost,
+            avoid: risk.map(RiskPolicy::avoid),
+            ..Default::default()
+        };
+
+        route::plan_route(&fleet.stats, from_sector, to_sector, &options).ok_or_else(|| {
+            eyre!(
+                "No route from {:?} to {:?} (max warp distance {:.2})",
+                from_sector,
+                to_sector,
+                calc::max_warp_distance(&fleet.stats)
+            )
+        })
+    }
+}
 
-    match bot.role {
-        BotRole::CargoTransport { .. } => match &bot.fleet_state() {
+impl Role for CargoTransportRole {
+    fn name(&self) -> &str {
+        "CargoTransport"
+    }
+
+    fn mints(&self) -> Vec<Pubkey> {
+        vec![self.cargo_mint]
+    }
+
+    fn sectors(&self) -> Vec<[i64; 2]> {
+        vec![self.from_sector, self.to_sector]
+    }
+
+    fn check<'a>(
+        &'a self,
+        fleet: &'a FleetWithState,
+        ctx: &'a RoleContext,
+        risk: Option<&'a RiskPolicy>,
+    ) -> CheckFuture<'a> {
+        Box::pin(async move {
+            let CargoTransportRole {
+                cargo_amount,
+                from_sector,
+                to_sector,
+                ..
+            } = self;
+
+            ctx.starbase(*from_sector).await?;
+            ctx.starbase(*to_sector).await?;
+
+            let stats = &fleet.0.stats;
+            let cargo_units = ctx
+                .cargo_weights
+                .units_fit(&self.cargo_mint, stats.cargo_stats.cargo_capacity as u64);
+            if *cargo_amount > cargo_units {
+                return Err(eyre!(
+                    "Cargo amount {} exceeds the cargo capacity {} (units of {})",
+                    cargo_amount,
+                    cargo_units,
+                    self.cargo_mint
+                ));
+            }
+
+            // the fleet refuels at both starbases, a leg's warps must fit in the fuel tank
+            let there = self.route(&fleet.0, *from_sector, *to_sector, risk)?;
+            let back = self.route(&fleet.0, *to_sector, *from_sector, risk)?;
+            let fuel_capacity = stats.cargo_stats.fuel_capacity as f32;
+            if there.fuel.max(back.fuel) > fuel_capacity {
+                return Err(eyre!(
+                    "Route fuel {:.0} exceeds the fuel capacity {:.0}",
+                    there.fuel.max(back.fuel),
+                    fuel_capacity
+                ));
+            }
+
+            // a hop waits for the warp's cooldown before the next one (docking excluded)
+            Ok(vec![
+                ("Route".to_string(), format!("{:?}", there.waypoints())),
+                (
+                    "Route duration".to_string(),
+                    format!("{:.0}s", there.duration),
+                ),
+                (
+                    "Fuel per cycle".to_string(),
+                    format!("{:.0}", there.fuel + back.fuel),
+                ),
+                ("Ammo per cycle".to_string(), "0".to_string()),
+                ("Food per cycle".to_string(), "0".to_string()),
+                (
+                    "Output per hour".to_string(),
+                    format!(
+                        "{:.0}",
+                        *cargo_amount as f32 * 3600.0 / (there.duration + back.duration)
+                    ),
+                ),
+            ])
+        })
+    }
+
+    fn clock_time_update(&mut self, bot: &mut BotActor, msg: ClockTimeUpdate) {
+        let clock = msg.0;
+
+        match &bot.fleet_state() {
             FleetState::MoveWarp(move_warp) => {
                 let time_elapsed = clock.unix_timestamp - move_warp.warp_start;
                 let elapsed = std::time::Duration::from_secs_f64(time_elapsed as f64);
@@ -43,154 +193,189 @@ pub(crate) fn clock_time_update(bot: &mut BotActor, msg: ClockTimeUpdate) {
                 }
             }
             _ => {}
-        },
-        _ => unimplemented!(),
-    }
+        }
 
-    bot.clock = Some(clock);
-}
+        bot.clock = Some(clock);
+    }
 
-pub(crate) fn sage_response(bot: &mut BotActor, msg: SageResponse, addr: Addr<BotActor>) {
-    match &bot.role {
-        BotRole::CargoTransport {
+    fn sage_response(&mut self, bot: &mut BotActor, msg: SageResponse, addr: Addr<BotActor>) {
+        let CargoTransportRole {
             cargo_mint,
+            cargo_amount,
             from_sector,
+            from_starbase,
             to_sector,
+            to_starbase,
             ..
-        } => {
-            let (_, FleetWithState(fleet, state)) = &mut bot.fleet;
-
-            match msg {
-                SageResponse::Fleet(FleetWithState(fleet, new_state)) => {
-                    bot.operation = None;
-
-                    match &new_state {
-                        FleetState::Idle(_idle) => {
-                            // fleet is "idle" request a check on cargo hold to determine next operation
-                            bot.addr_sage
-                                .do_send(SageRequest::FleetCargoHold(fleet.cargo_hold, addr));
-                        }
-                        FleetState::MoveWarp(_move_warp) => {
-                            match &bot.operation {
-                                Some(BotOps::Warp(_)) => {} // Do nothing, already performing a warping operation
-                                _ => {
-                                    // Request a "Clock" to kick-off the warping operation
-                                    bot.addr_sage.do_send(ClockTimeRequest(addr));
-                                }
+        } = &*self;
+
+        let starbase_first_check = bot.starbase_first_check();
+        let roles = bot.roles.clone();
+        let cargo_weights = &roles.ctx.cargo_weights;
+        let risk = bot.risk;
+        let (_, FleetWithState(fleet, state)) = &mut bot.fleet;
+
+        match msg {
+            SageResponse::Fleet(FleetWithState(fleet, new_state)) => {
+                bot.operation = None;
+
+                match &new_state {
+                    FleetState::Idle(_idle) => {
+                        // fleet is "idle" request a check on cargo hold to determine next operation
+                        bot.addr_sage
+                            .do_send(SageRequest::FleetCargoHold(fleet.cargo_hold, addr));
+                    }
+                    FleetState::MoveWarp(_move_warp) => {
+                        match &bot.operation {
+                            Some(BotOps::Warp(_)) => {} // Do nothing, already performing a warping operation
+                            _ => {
+                                // Request a "Clock" to kick-off the warping operation
+                                bot.addr_sage.do_send(ClockTimeRequest(addr));
                             }
                         }
-                        FleetState::StarbaseLoadingBay(_starbase_loading_bay) => {
-                            match &bot.operation {
-                                Some(BotOps::StarbaseLoadingBay(_)) => {} // Do nothing, already performing a starbase loading operation
-                                _ => {
-                                    // Request a "FleetCargoHold" to kick-off the starbase loading operation
-                                    bot.addr_sage.do_send(SageRequest::FleetCargoHold(
-                                        fleet.cargo_hold,
-                                        addr,
-                                    ));
-                                }
+                    }
+                    FleetState::StarbaseLoadingBay(_starbase_loading_bay) => {
+                        match &bot.operation {
+                            Some(BotOps::StarbaseLoadingBay(_)) => {} // Do nothing, already performing a starbase loading operation
+                            _ => {
+                                // Request a "FleetCargoHold" to kick-off the starbase loading operation
+                                bot.addr_sage
+                                    .do_send(SageRequest::FleetCargoHold(fleet.cargo_hold, addr));
                             }
                         }
-                        _ => {}
                     }
-
-                    bot.fleet.1 = FleetWithState(fleet, new_state);
+                    _ => {}
                 }
-                SageResponse::FleetCargoHold(cargo_hold) => match &state {
-                    FleetState::Idle(idle) => {
-                        let default = (cargo_mint.to_string(), 0);
-                        let cargo = cargo_hold
-                            .iter()
-                            .find(|(mint, _)| mint == &cargo_mint.to_string())
-                            .unwrap_or(&default);
-
-                        let cargo_capacity_fraction =
-                            cargo.1 as f64 / fleet.stats.cargo_stats.cargo_capacity as f64;
-
-                        if from_sector == &idle.sector {
-                            if cargo_capacity_fraction < 0.5 {
-                                let ops = BotOps::Idle(IdleOps {
-                                    sector: idle.sector.clone(),
-                                    cargo_capacity_fraction,
-                                    stopwatch: timers::Stopwatch::new(),
-                                    next_action: IdleActions::DockeToStarbase,
-                                });
-                                bot.operation = Some(ops);
-                            } else {
-                                let ops = BotOps::Idle(IdleOps {
-                                    sector: idle.sector.clone(),
-                                    cargo_capacity_fraction,
-                                    stopwatch: timers::Stopwatch::new(),
-                                    next_action: IdleActions::WarpToSector(to_sector.clone()),
-                                });
-                                bot.operation = Some(ops);
-                            }
-                        }
 
-                        if to_sector == &idle.sector {
-                            if cargo_capacity_fraction > 0.5 {
-                                let ops = BotOps::Idle(IdleOps {
-                                    sector: idle.sector.clone(),
-                                    cargo_capacity_fraction,
-                                    stopwatch: timers::Stopwatch::new(),
-                                    next_action: IdleActions::DockeToStarbase,
-                                });
-                                bot.operation = Some(ops);
-                            } else {
-                                let ops = BotOps::Idle(IdleOps {
-                                    sector: idle.sector.clone(),
-                                    cargo_capacity_fraction,
-                                    stopwatch: timers::Stopwatch::new(),
-                                    next_action: IdleActions::WarpToSector(from_sector.clone()),
-                                });
-                                bot.operation = Some(ops);
+                bot.fleet.1 = FleetWithState(fleet, new_state);
+            }
+            SageResponse::FleetCargoHold(cargo_hold) => match &state {
+                FleetState::Idle(idle) => {
+                    let default = (cargo_mint.to_string(), 0);
+                    let cargo = cargo_hold
+                        .iter()
+                        .find(|(mint, _)| mint == &cargo_mint.to_string())
+                        .unwrap_or(&default);
+
+                    let cargo_space = cargo.1 * cargo_weights.weight(cargo_mint);
+                    let cargo_capacity_fraction =
+                        cargo_space as f64 / fleet.stats.cargo_stats.cargo_capacity as f64;
+
+                    // docks to load at the origin or to unload at the destination, else warps on
+                    let next_action = if (from_sector == &idle.sector
+                        && cargo_capacity_fraction < 0.5)
+                        || (to_sector == &idle.sector && cargo_capacity_fraction > 0.5)
+                    {
+                        IdleActions::DockeToStarbase
+                    } else {
+                        let destination = if to_sector == &idle.sector {
+                            from_sector
+                        } else if from_sector == &idle.sector || cargo_capacity_fraction >= 0.5 {
+                            to_sector
+                        } else {
+                            from_sector
+                        };
+
+                        // one hop at a time, the route is planned again at each waypoint
+                        match self.route(fleet, idle.sector, *destination, risk.as_ref()) {
+                            Ok(route) => match route.next_waypoint() {
+                                Some(waypoint) => IdleActions::WarpToSector(waypoint),
+                                None => IdleActions::DockeToStarbase,
+                            },
+                            Err(err) => {
+                                // no warp beyond the max warp distance or the risk policy
+                                log::error!("{:?}, the fleet stays idle", err);
+                                bot.operation = None;
+                                return;
                             }
                         }
+                    };
+
+                    bot.operation = Some(BotOps::Idle(IdleOps {
+                        sector: idle.sector.clone(),
+                        cargo_capacity_fraction,
+                        stopwatch: timers::Stopwatch::new(),
+                        next_action,
+                    }));
+                }
+                FleetState::StarbaseLoadingBay(starbase_loading_bay) => {
+                    let default = (cargo_mint.to_string(), 0);
+                    let cargo = cargo_hold
+                        .iter()
+                        .find(|(mint, _)| mint == &cargo_mint.to_string())
+                        .unwrap_or(&default);
+
+                    let cargo_space = cargo.1 * cargo_weights.weight(cargo_mint);
+                    let cargo_capacity_fraction =
+                        cargo_space as f64 / fleet.stats.cargo_stats.cargo_capacity as f64;
+
+                    let mut next_action = StarbaseActions::IdleHangar;
+
+                    if &starbase_loading_bay.starbase == from_starbase {
+                        if cargo_capacity_fraction < 0.5 {
+                            let cargo_hold = PodInventory::from_token_amounts(
+                                &fleet.cargo_hold,
+                                fleet.stats.cargo_stats.cargo_capacity as u64,
+                                &cargo_hold,
+                            );
+                            let amount = (*cargo_amount)
+                                .min(cargo_hold.units_fit(cargo_weights, cargo_mint));
+                            next_action = StarbaseActions::CargoDeposit(
+                                fleet.cargo_hold,
+                                *cargo_mint,
+                                amount,
+                            );
+                        } else {
+                            next_action = StarbaseActions::UndockFromStarbase;
+                        }
                     }
-                    FleetState::StarbaseLoadingBay(starbase_loading_bay) => {
-                        let default = (cargo_mint.to_string(), 0);
-                        let cargo = cargo_hold
-                            .iter()
-                            .find(|(mint, _)| mint == &cargo_mint.to_string())
-                            .unwrap_or(&default);
-
-                        let cargo_capacity_fraction =
-                            cargo.1 as f64 / fleet.stats.cargo_stats.cargo_capacity as f64;
-
-                        let (cargo_mint, cargo_amount, from_starbase, to_starbase) = match &bot.role
-                        {
-                            BotRole::CargoTransport {
-                                cargo_mint,
-                                cargo_amount,
-                                from_starbase,
-                                to_starbase,
-                                ..
-                            } => (cargo_mint, cargo_amount, from_starbase, to_starbase),
-                            _ => unimplemented!(),
-                        };
 
-                        let mut next_action = StarbaseActions::IdleHangar;
-
-                        if &starbase_loading_bay.starbase == from_starbase {
-                            if cargo_capacity_fraction < 0.5 {
-                                next_action = StarbaseActions::CargoDeposit(
-                                    fleet.cargo_hold,
-                                    *cargo_mint,
-                                    *cargo_amount,
-                                );
-                            } else {
-                                next_action = StarbaseActions::UndockFromStarbase;
-                            }
+                    if &starbase_loading_bay.starbase == to_starbase {
+                        if cargo_capacity_fraction > 0.5 {
+                            next_action =
+                                StarbaseActions::CargoWithdraw(*cargo_mint, *cargo_amount);
+                        } else {
+                            next_action = starbase_first_check;
                         }
+                    }
 
-                        if &starbase_loading_bay.starbase == to_starbase {
-                            if cargo_capacity_fraction > 0.5 {
-                                next_action =
-                                    StarbaseActions::CargoWithdraw(*cargo_mint, *cargo_amount);
-                            } else {
-                                next_action = StarbaseActions::CheckFuelStatus;
-                            }
+                    let starbase_ops = match &bot.operation {
+                        Some(BotOps::StarbaseLoadingBay(starbase_ops)) => {
+                            let mut starbase_ops = starbase_ops.clone();
+                            starbase_ops.next_action = next_action;
+                            starbase_ops
                         }
+                        _ => StarbaseLoadingBayOps {
+                            starbase: starbase_loading_bay.starbase,
+                            stopwatch: timers::Stopwatch::new(),
+                            next_action,
+                        },
+                    };
+
+                    bot.operation = Some(BotOps::StarbaseLoadingBay(starbase_ops));
+                }
+                _ => {}
+            },
+            SageResponse::FleetFuelTank(fuel_tank) => {
+                bot.fleet_fuel_tank = fuel_tank;
+
+                match state {
+                    FleetState::StarbaseLoadingBay(starbase_loading_bay) => {
+                        let fuel_mint = roles.ctx.game.mints.fuel;
+                        let fuel_tank = PodInventory::from_token_amounts(
+                            &fleet.fuel_tank,
+                            fleet.stats.cargo_stats.fuel_capacity as u64,
+                            &bot.fleet_fuel_tank,
+                        );
+                        let fuel_tank_fraction = fuel_tank.used_space(cargo_weights) as f32
+                            / fleet.stats.cargo_stats.fuel_capacity as f32;
+
+                        let next_action = if fuel_tank_fraction < 0.5 {
+                            let amount = fuel_tank.units_fit(cargo_weights, &fuel_mint);
+                            StarbaseActions::CargoDeposit(fleet.fuel_tank, fuel_mint, amount)
+                        } else {
+                            StarbaseActions::UndockFromStarbase
+                        };
 
                         let starbase_ops = match &bot.operation {
                             Some(BotOps::StarbaseLoadingBay(starbase_ops)) => {
@@ -208,171 +393,168 @@ pub(crate) fn sage_response(bot: &mut BotActor, msg: SageResponse, addr: Addr<Bo
                         bot.operation = Some(BotOps::StarbaseLoadingBay(starbase_ops));
                     }
                     _ => {}
-                },
-                SageResponse::FleetFuelTank(fuel_tank) => {
-                    bot.fleet_fuel_tank = fuel_tank;
-
-                    match state {
-                        FleetState::StarbaseLoadingBay(starbase_loading_bay) => {
-                            let (fuel_mint, fuel_amount) = &bot.fleet_fuel_tank[0];
-                            let fuel_tank_fraction =
-                                *fuel_amount as f32 / fleet.stats.cargo_stats.fuel_capacity as f32;
-
-                            let next_action = if fuel_tank_fraction < 0.5 {
-                                let amount =
-                                    fleet.stats.cargo_stats.fuel_capacity as u64 - *fuel_amount;
-
-                                let fuel_mint = Pubkey::from_str(&fuel_mint).unwrap();
-                                StarbaseActions::CargoDeposit(fleet.fuel_tank, fuel_mint, amount)
-                            } else {
-                                StarbaseActions::UndockFromStarbase
-                            };
-
-                            let starbase_ops = match &bot.operation {
-                                Some(BotOps::StarbaseLoadingBay(starbase_ops)) => {
-                                    let mut starbase_ops = starbase_ops.clone();
-                                    starbase_ops.next_action = next_action;
-                                    starbase_ops
-                                }
-                                _ => StarbaseLoadingBayOps {
-                                    starbase: starbase_loading_bay.starbase,
-                                    stopwatch: timers::Stopwatch::new(),
-                                    next_action,
-                                },
-                            };
-
-                            bot.operation = Some(BotOps::StarbaseLoadingBay(starbase_ops));
-                        }
-                        _ => {}
-                    }
                 }
-                _ => unimplemented!(),
             }
+            _ => unimplemented!(),
         }
-        _ => unimplemented!(),
     }
-}
 
-pub(crate) fn tick(bot: &mut BotActor, msg: Tick, addr: Addr<BotActor>) {
-    let (fleet_id, FleetWithState(fleet, state)) = &bot.fleet;
-    let fleet = (*fleet_id, *fleet);
+    fn tick(&mut self, bot: &mut BotActor, msg: Tick, addr: Addr<BotActor>) {
+        let (fleet_id, FleetWithState(fleet, state)) = &bot.fleet;
+        let fleet = (*fleet_id, *fleet);
 
-    match &mut bot.operation {
-        Some(BotOps::Idle(ops)) => {
-            ops.stopwatch.tick(msg.0);
-            log::info!("{:#?}", &ops);
+        match &mut bot.operation {
+            Some(BotOps::Idle(ops)) => {
+                ops.stopwatch.tick(msg.0);
+                log::info!("{:#?}", &ops);
 
-            match ops.next_action {
-                IdleActions::DockeToStarbase => {
-                    bot.addr_sage
-                        .do_send(SageAction::StarbaseDock(fleet, ops.sector, addr));
+                match ops.next_action {
+                    IdleActions::DockeToStarbase => {
+                        bot.addr_sage
+                            .do_send(SageAction::StarbaseDock(fleet, ops.sector, addr));
 
-                    bot.operation =
-                        Some(autoplay::BotOps::TxsSageBased(autoplay::TxsSageBasedOps {
-                            stopwatch: timers::Stopwatch::new(),
-                        }));
-                }
-                IdleActions::WarpToSector(sector) => {
-                    bot.addr_sage.do_send(SageAction::Warp(fleet, sector, addr));
+                        bot.operation =
+                            Some(autoplay::BotOps::TxsSageBased(autoplay::TxsSageBasedOps {
+                                stopwatch: timers::Stopwatch::new(),
+                            }));
+                    }
+                    IdleActions::WarpToSector(sector) => {
+                        bot.ledger.pending = Some(LedgerEvent::Warped {
+                            fuel: calc::warp_fuel_consumption(&fleet.1.stats, ops.sector, sector)
+                                as f64,
+                        });
 
-                    bot.operation =
-                        Some(autoplay::BotOps::TxsSageBased(autoplay::TxsSageBasedOps {
-                            stopwatch: timers::Stopwatch::new(),
-                        }));
-                }
-                _ => unimplemented!("{:?}", ops.next_action),
-            }
-        }
-        Some(BotOps::TxsSageBased(ops)) => {
-            ops.stopwatch.tick(msg.0);
-            log::info!("{:#?}", &ops);
-        }
-        Some(BotOps::Warp(ops)) => {
-            ops.timer.tick(msg.0);
-            ops.cooldown.tick(msg.0);
-
-            match state {
-                // FleetState::Idle(_) => {
-                //     bot.addr_sage
-                //         .do_send(SageAction::Warp(fleet, ops.sector.clone(), addr));
-
-                //     bot.operation =
-                //         Some(autoplay::BotOps::TxsSageBased(autoplay::TxsSageBasedOps {
-                //             stopwatch: timers::Stopwatch::new(),
-                //         }));
-                // }
-                FleetState::MoveWarp(_) => {
-                    if ops.timer.finished() && ops.cooldown.finished() {
-                        bot.addr_sage.do_send(SageAction::WarpExit(fleet, addr));
+                        bot.addr_sage.do_send(SageAction::Warp(fleet, sector, addr));
 
                         bot.operation =
                             Some(autoplay::BotOps::TxsSageBased(autoplay::TxsSageBasedOps {
                                 stopwatch: timers::Stopwatch::new(),
                             }));
                     }
+                    _ => unimplemented!("{:?}", ops.next_action),
                 }
-                _ => unimplemented!("{:?}", state),
             }
-        }
-        Some(BotOps::StarbaseLoadingBay(ops)) => {
-            ops.stopwatch.tick(msg.0);
-            log::info!("{:#?}", &ops);
-
-            match ops.next_action {
-                StarbaseActions::IdleHangar => {
-                    // Request a "FleetCargoHold" to kick-off the starbase loading operation
-                    bot.addr_sage
-                        .do_send(SageRequest::FleetCargoHold(fleet.1.cargo_hold, addr));
-                }
-                StarbaseActions::CargoDeposit(cargo_pod_to, mint, amount) => {
-                    bot.addr_sage.do_send(SageAction::CargoDeposit(
-                        fleet,
-                        ops.starbase,
-                        cargo_pod_to,
-                        mint,
-                        amount,
-                        addr,
-                    ));
-
-                    bot.operation =
-                        Some(autoplay::BotOps::TxsSageBased(autoplay::TxsSageBasedOps {
-                            stopwatch: timers::Stopwatch::new(),
-                        }));
-                }
-                StarbaseActions::CargoWithdraw(mint, amount) => {
-                    bot.addr_sage.do_send(SageAction::CargoWithdraw(
-                        fleet,
-                        ops.starbase,
-                        mint,
-                        amount,
-                        addr,
-                    ));
-
-                    bot.operation =
-                        Some(autoplay::BotOps::TxsSageBased(autoplay::TxsSageBasedOps {
-                            stopwatch: timers::Stopwatch::new(),
-                        }));
-                }
-                StarbaseActions::CheckFuelStatus => {
-                    bot.addr_sage
-                        .do_send(SageRequest::FleetFuelTank(fleet.1.fuel_tank, addr));
+            Some(BotOps::TxsSageBased(ops)) => {
+                ops.stopwatch.tick(msg.0);
+                log::info!("{:#?}", &ops);
+            }
+            Some(BotOps::Warp(ops)) => {
+                ops.timer.tick(msg.0);
+                ops.cooldown.tick(msg.0);
+
+                match state {
+                    // FleetState::Idle(_) => {
+                    //     bot.addr_sage
+                    //         .do_send(SageAction::Warp(fleet, ops.sector.clone(), addr));
+
+                    //     bot.operation =
+                    //         Some(autoplay::BotOps::TxsSageBased(autoplay::TxsSageBasedOps {
+                    //             stopwatch: timers::Stopwatch::new(),
+                    //         }));
+                    // }
+                    FleetState::MoveWarp(_) => {
+                        if ops.timer.finished() && ops.cooldown.finished() {
+                            bot.addr_sage.do_send(SageAction::WarpExit(fleet, addr));
+
+                            bot.operation =
+                                Some(autoplay::BotOps::TxsSageBased(autoplay::TxsSageBasedOps {
+                                    stopwatch: timers::Stopwatch::new(),
+                                }));
+                        }
+                    }
+                    _ => unimplemented!("{:?}", state),
                 }
-                StarbaseActions::UndockFromStarbase => {
-                    bot.addr_sage
-                        .do_send(SageAction::StarbaseUndock(fleet, ops.starbase, addr));
+            }
+            Some(BotOps::StarbaseLoadingBay(ops)) => {
+                ops.stopwatch.tick(msg.0);
+                log::info!("{:#?}", &ops);
+
+                match ops.next_action {
+                    StarbaseActions::IdleHangar => {
+                        // Request a "FleetCargoHold" to kick-off the starbase loading operation
+                        bot.addr_sage
+                            .do_send(SageRequest::FleetCargoHold(fleet.1.cargo_hold, addr));
+                    }
+                    StarbaseActions::CargoDeposit(cargo_pod_to, mint, amount) => {
+                        bot.addr_sage.do_send(SageAction::CargoDeposit(
+                            fleet,
+                            ops.starbase,
+                            cargo_pod_to,
+                            mint,
+                            amount,
+                            addr,
+                        ));
 
-                    bot.operation =
-                        Some(autoplay::BotOps::TxsSageBased(autoplay::TxsSageBasedOps {
-                            stopwatch: timers::Stopwatch::new(),
-                        }));
+                        bot.operation =
+                            Some(autoplay::BotOps::TxsSageBased(autoplay::TxsSageBasedOps {
+                                stopwatch: timers::Stopwatch::new(),
+                            }));
+                    }
+                    StarbaseActions::CargoWithdraw(mint, amount) => {
+                        bot.ledger.pending = Some(LedgerEvent::Unloaded {
+                            mint,
+                            amount: amount as f64,
+                        });
+
+                        bot.addr_sage.do_send(SageAction::CargoWithdraw(
+                            fleet,
+                            ops.starbase,
+                            mint,
+                            amount,
+                            addr,
+                        ));
+
+                        bot.operation =
+                            Some(autoplay::BotOps::TxsSageBased(autoplay::TxsSageBasedOps {
+                                stopwatch: timers::Stopwatch::new(),
+                            }));
+                    }
+                    StarbaseActions::CheckFuelStatus => {
+                        bot.addr_sage
+                            .do_send(SageRequest::FleetFuelTank(fleet.1.fuel_tank, addr));
+                    }
+                    StarbaseActions::CheckStarbaseInventory => {
+                        bot.restock_checked = true;
+                        bot.addr_sage.do_send(SageRequest::StarbaseCargo(
+                            fleet.1.owner_profile,
+                            ops.starbase,
+                            addr,
+                        ));
+                    }
+                    StarbaseActions::RestockConsumable(mint, amount, max_price) => {
+                        let starbase = ops.starbase;
+
+                        if let Some(budget) = bot.reserve_restock(&mint, amount, max_price) {
+                            bot.addr_sage.do_send(SageAction::ConsumableRestock(
+                                fleet, starbase, mint, amount, max_price, budget, addr,
+                            ));
+
+                            bot.operation =
+                                Some(autoplay::BotOps::TxsSageBased(autoplay::TxsSageBasedOps {
+                                    stopwatch: timers::Stopwatch::new(),
+                                }));
+                        }
+                    }
+                    StarbaseActions::UndockFromStarbase => {
+                        bot.addr_sage.do_send(SageAction::StarbaseUndock(
+                            fleet,
+                            ops.starbase,
+                            addr,
+                        ));
+
+                        bot.operation =
+                            Some(autoplay::BotOps::TxsSageBased(autoplay::TxsSageBasedOps {
+                                stopwatch: timers::Stopwatch::new(),
+                            }));
+                    }
+                    _ => unimplemented!("{:?}", ops.next_action),
                 }
-                _ => unimplemented!("{:?}", ops.next_action),
             }
+            None => {
+                // if operation is None, request the fleet state to kick-off the bot
+                bot.request_fleet(addr);
+            }
+            _ => unimplemented!("{:?}", bot.operation),
         }
-        None => {
-            // if operation is None, request the fleet state to kick-off the bot
-            bot.addr_sage.do_send(SageRequest::Fleet(*fleet_id, addr));
-        }
-        _ => unimplemented!("{:?}", bot.operation),
     }
 }
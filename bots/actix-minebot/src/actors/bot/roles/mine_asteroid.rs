@@ -1,39 +1,154 @@
 use super::*;
 
-pub(crate) fn clock_time_update(bot: &mut BotActor, msg: ClockTimeUpdate) {
-    match &bot.role {
-        BotRole::MineAsteroid {
+#[derive(Deserialize)]
+struct MineAsteroidArgs {
+    planet_id: String,
+    mine_item_id: String,
+}
+
+pub struct MineAsteroidRole {
+    planet: (Pubkey, Planet),
+    mine_item: (Pubkey, MineItem),
+    resource: (Pubkey, Resource),
+}
+
+impl MineAsteroidRole {
+    pub(crate) fn build(args: serde_json::Value, ctx: &RoleContext) -> RoleFuture<'_> {
+        Box::pin(async move {
+            let args: MineAsteroidArgs = serde_json::from_value(args)?;
+            let planet_id = Pubkey::from_str(&args.planet_id)?;
+            let mine_item_id = Pubkey::from_str(&args.mine_item_id)?;
+
+            let (planet, mine_item, resource) = ctx.asteroid(&planet_id, &mine_item_id).await?;
+
+            let role: Box<dyn Role> = Box::new(MineAsteroidRole {
+                planet,
+                mine_item,
+                resource,
+            });
+            Ok(role)
+        })
+    }
+}
+
+impl Role for MineAsteroidRole {
+    fn name(&self) -> &str {
+        "MineAsteroid"
+    }
+
+    fn mints(&self) -> Vec<Pubkey> {
+        vec![self.mine_item.1.mint]
+    }
+
+    fn sectors(&self) -> Vec<[i64; 2]> {
+        vec![self.planet.1.sector]
+    }
+
+    fn check<'a>(
+        &'a self,
+        fleet: &'a FleetWithState,
+        ctx: &'a RoleContext,
+        _risk: Option<&'a RiskPolicy>,
+    ) -> CheckFuture<'a> {
+        Box::pin(async move {
+            let MineAsteroidRole {
+                planet,
+                mine_item,
+                resource,
+            } = self;
+
+            ctx.starbase(planet.1.sector).await?;
+
+            let stats = &fleet.0.stats;
+            // the units of the resource that fit in the (empty) cargo hold
+            let cargo_capacity = ctx
+                .cargo_weights
+                .units_fit(&mine_item.1.mint, stats.cargo_stats.cargo_capacity as u64)
+                as u32;
+            // with a full ammo bank
+            let ammo_capacity = ctx
+                .cargo_weights
+                .units_fit(&ctx.game.mints.ammo, stats.cargo_stats.ammo_capacity as u64);
+            let plan = calc::asteroid_mining_plan(
+                stats,
+                &mine_item.1,
+                &resource.1,
+                cargo_capacity,
+                Some(ammo_capacity),
+                None,
+            );
+            let mining_duration = plan.max_duration;
+            if mining_duration <= 0.0 {
+                return Err(eyre!("The fleet can't mine {}", mine_item.1.mint));
+            }
+
+            Ok(vec![
+                (
+                    "Mining duration".to_string(),
+                    format!("{:.0}s", mining_duration),
+                ),
+                (
+                    "Fuel per cycle".to_string(),
+                    stats.movement_stats.planet_exit_fuel_amount.to_string(),
+                ),
+                (
+                    "Ammo per cycle".to_string(),
+                    format!(
+                        "{:.0}",
+                        calc::asteroid_mining_ammo_consumption(stats, mining_duration)
+                    ),
+                ),
+                (
+                    "Food per cycle".to_string(),
+                    format!(
+                        "{:.0}",
+                        calc::asteroid_mining_food_consumption(stats, mining_duration)
+                    ),
+                ),
+                (
+                    "Binding constraint".to_string(),
+                    format!("{:?}", plan.constraint),
+                ),
+                (
+                    "Output per hour".to_string(),
+                    format!("{:.0}", plan.output * 3600.0 / mining_duration),
+                ),
+            ])
+        })
+    }
+
+    fn clock_time_update(&mut self, bot: &mut BotActor, msg: ClockTimeUpdate) {
+        let MineAsteroidRole {
             planet,
             mine_item,
             resource,
-        } => {
-            let clock = msg.0;
-
-            match &bot.fleet_state() {
-                FleetState::MineAsteroid(mine_asteroid) => {
-                    let mining_ops = autoplay_mine_asteroid(
-                        &bot,
-                        &mine_asteroid,
-                        &clock,
-                        &planet,
-                        &mine_item,
-                        &resource,
-                    );
-                    let operation = autoplay::BotOps::Mining(mining_ops);
-                    bot.operation = Some(operation);
-                }
-                _ => {}
+        } = &*self;
+
+        let clock = msg.0;
+
+        match &bot.fleet_state() {
+            FleetState::MineAsteroid(mine_asteroid) => {
+                let mining_ops = autoplay_mine_asteroid(
+                    &bot,
+                    &mine_asteroid,
+                    &clock,
+                    &planet,
+                    &mine_item,
+                    &resource,
+                );
+                let operation = autoplay::BotOps::Mining(mining_ops);
+                bot.operation = Some(operation);
             }
-
-            bot.clock = Some(clock);
+            _ => {}
         }
-        _ => unimplemented!(),
+
+        bot.clock = Some(clock);
     }
-}
 
-pub(crate) fn sage_response(bot: &mut BotActor, msg: SageResponse, addr: Addr<BotActor>) {
-    match &bot.role {
-        BotRole::MineAsteroid { mine_item, .. } => match msg {
+    fn sage_response(&mut self, bot: &mut BotActor, msg: SageResponse, addr: Addr<BotActor>) {
+        let mine_item = &self.mine_item;
+
+        match msg {
             SageResponse::Fleet(fleet_with_state) => {
                 autoplay_fleet_with_state_update(bot, fleet_with_state, addr);
             }
@@ -104,80 +219,120 @@ pub(crate) fn sage_response(bot: &mut BotActor, msg: SageResponse, addr: Addr<Bo
                     _ => {}
                 }
             }
-        },
-        _ => unimplemented!(),
+            SageResponse::TransactionFee(..)
+            | SageResponse::StarbaseCargo(_)
+            | SageResponse::ConsumableRestock(..) => {}
+        }
     }
-}
 
-pub(crate) fn tick(bot: &mut BotActor, msg: Tick, addr: Addr<BotActor>) {
-    match &bot.role {
-        BotRole::MineAsteroid {
+    fn tick(&mut self, bot: &mut BotActor, msg: Tick, addr: Addr<BotActor>) {
+        let MineAsteroidRole {
             planet,
             mine_item,
             resource,
-        } => {
-            let (fleet_id, FleetWithState(fleet, _)) = bot.fleet;
-            let fleet = (fleet_id, fleet);
-
-            if let Some(operation) = &mut bot.operation {
-                match operation {
-                    autoplay::BotOps::TxsSageBased(txs_sage_based_ops) => {
-                        txs_sage_based_ops.stopwatch.tick(msg.0);
-                        log::info!("{:#?}", &txs_sage_based_ops);
-                    }
-                    autoplay::BotOps::Idle(idle_ops) => {
-                        idle_ops.stopwatch.tick(msg.0);
-                        log::info!("{:#?}", &idle_ops);
+        } = &*self;
 
-                        let sector = idle_ops.sector;
+        let (fleet_id, FleetWithState(fleet, _)) = bot.fleet;
+        let fleet = (fleet_id, fleet);
 
-                        match idle_ops.next_action {
-                            autoplay::IdleActions::DockeToStarbase => {
-                                bot.addr_sage
-                                    .do_send(SageAction::StarbaseDock(fleet, sector, addr));
+        if let Some(operation) = &mut bot.operation {
+            match operation {
+                autoplay::BotOps::TxsSageBased(txs_sage_based_ops) => {
+                    txs_sage_based_ops.stopwatch.tick(msg.0);
+                    log::info!("{:#?}", &txs_sage_based_ops);
+                }
+                autoplay::BotOps::Idle(idle_ops) => {
+                    idle_ops.stopwatch.tick(msg.0);
+                    log::info!("{:#?}", &idle_ops);
 
-                                let operation =
-                                    autoplay::BotOps::TxsSageBased(autoplay::TxsSageBasedOps {
-                                        stopwatch: timers::Stopwatch::new(),
-                                    });
-                                bot.operation = Some(operation);
-                            }
-                            autoplay::IdleActions::MineAsteroid => {
-                                let planet = planet.0;
-                                let mine_item = mine_item.0;
-                                let resource = resource.0;
+                    let sector = idle_ops.sector;
 
-                                bot.addr_sage.do_send(SageAction::StartMining(
-                                    fleet, mine_item, resource, planet, sector, addr,
-                                ));
+                    match idle_ops.next_action {
+                        autoplay::IdleActions::DockeToStarbase => {
+                            bot.addr_sage
+                                .do_send(SageAction::StarbaseDock(fleet, sector, addr));
 
-                                let operation =
-                                    autoplay::BotOps::TxsSageBased(autoplay::TxsSageBasedOps {
-                                        stopwatch: timers::Stopwatch::new(),
-                                    });
-                                bot.operation = Some(operation);
-                            }
-                            _ => unimplemented!("{:?}", idle_ops.next_action),
+                            let operation =
+                                autoplay::BotOps::TxsSageBased(autoplay::TxsSageBasedOps {
+                                    stopwatch: timers::Stopwatch::new(),
+                                });
+                            bot.operation = Some(operation);
                         }
-                    }
-                    autoplay::BotOps::Mining(mining_ops) => {
-                        mining_ops.timer.tick(msg.0);
-                        log::info!("{:#?}", &mining_ops);
-
-                        if mining_ops.timer.finished() {
-                            let planet_id = planet.0;
-                            let mine_item_id = mine_item.0;
-                            let mine_item_mint = mine_item.1.mint;
+                        autoplay::IdleActions::MineAsteroid => {
+                            let planet = planet.0;
+                            let mine_item = mine_item.0;
                             let resource = resource.0;
-                            let sector = planet.1.sector;
 
-                            bot.addr_sage.do_send(SageAction::StopMining(
+                            bot.addr_sage.do_send(SageAction::StartMining(
+                                fleet, mine_item, resource, planet, sector, addr,
+                            ));
+
+                            let operation =
+                                autoplay::BotOps::TxsSageBased(autoplay::TxsSageBasedOps {
+                                    stopwatch: timers::Stopwatch::new(),
+                                });
+                            bot.operation = Some(operation);
+                        }
+                        _ => unimplemented!("{:?}", idle_ops.next_action),
+                    }
+                }
+                autoplay::BotOps::Mining(mining_ops) => {
+                    mining_ops.timer.tick(msg.0);
+                    log::info!("{:#?}", &mining_ops);
+
+                    if mining_ops.timer.finished() {
+                        let planet_id = planet.0;
+                        let mine_item_id = mine_item.0;
+                        let mine_item_mint = mine_item.1.mint;
+                        let resource = resource.0;
+                        let sector = planet.1.sector;
+
+                        let stats = &fleet.1.stats;
+                        let mining_duration = mining_ops.timer.elapsed_secs();
+                        bot.ledger.pending = Some(LedgerEvent::Mined {
+                            mint: mine_item_mint,
+                            amount: mining_ops
+                                .amount_mined
+                                .min(bot.units_fit(&fleet.1.cargo_hold, &mine_item_mint) as f32)
+                                as f64,
+                            fuel: stats.movement_stats.planet_exit_fuel_amount as f64,
+                            ammo: calc::asteroid_mining_ammo_consumption(stats, mining_duration)
+                                as f64,
+                            food: calc::asteroid_mining_food_consumption(stats, mining_duration)
+                                as f64,
+                        });
+
+                        bot.addr_sage.do_send(SageAction::StopMining(
+                            fleet,
+                            mine_item_id,
+                            mine_item_mint,
+                            resource,
+                            planet_id,
+                            sector,
+                            addr,
+                        ));
+
+                        let operation = autoplay::BotOps::TxsSageBased(autoplay::TxsSageBasedOps {
+                            stopwatch: timers::Stopwatch::new(),
+                        });
+                        bot.operation = Some(operation);
+                    }
+                }
+                autoplay::BotOps::StarbaseLoadingBay(starbase_loading_bay_ops) => {
+                    starbase_loading_bay_ops.stopwatch.tick(msg.0);
+                    log::info!("{:#?}", &starbase_loading_bay_ops);
+
+                    match starbase_loading_bay_ops.next_action {
+                        autoplay::StarbaseActions::IdleHangar => {
+                            unimplemented!("{:?}", starbase_loading_bay_ops.next_action)
+                        }
+                        autoplay::StarbaseActions::CargoDeposit(cargo_pod_to, mint, amount) => {
+                            bot.addr_sage.do_send(SageAction::CargoDeposit(
                                 fleet,
-                                mine_item_id,
-                                mine_item_mint,
-                                resource,
-                                planet_id,
-                                sector,
+                                starbase_loading_bay_ops.starbase,
+                                cargo_pod_to,
+                                mint,
+                                amount,
                                 addr,
                             ));
 
@@ -187,38 +342,47 @@ pub(crate) fn tick(bot: &mut BotActor, msg: Tick, addr: Addr<BotActor>) {
                                 });
                             bot.operation = Some(operation);
                         }
-                    }
-                    autoplay::BotOps::StarbaseLoadingBay(starbase_loading_bay_ops) => {
-                        starbase_loading_bay_ops.stopwatch.tick(msg.0);
-                        log::info!("{:#?}", &starbase_loading_bay_ops);
+                        autoplay::StarbaseActions::CargoWithdraw(mint, amount) => {
+                            bot.addr_sage.do_send(SageAction::CargoWithdraw(
+                                fleet,
+                                starbase_loading_bay_ops.starbase,
+                                mint,
+                                amount,
+                                addr,
+                            ));
 
-                        match starbase_loading_bay_ops.next_action {
-                            autoplay::StarbaseActions::IdleHangar => {
-                                unimplemented!("{:?}", starbase_loading_bay_ops.next_action)
-                            }
-                            autoplay::StarbaseActions::CargoDeposit(cargo_pod_to, mint, amount) => {
-                                bot.addr_sage.do_send(SageAction::CargoDeposit(
-                                    fleet,
-                                    starbase_loading_bay_ops.starbase,
-                                    cargo_pod_to,
-                                    mint,
-                                    amount,
-                                    addr,
-                                ));
+                            let operation =
+                                autoplay::BotOps::TxsSageBased(autoplay::TxsSageBasedOps {
+                                    stopwatch: timers::Stopwatch::new(),
+                                });
+                            bot.operation = Some(operation);
+                        }
+                        autoplay::StarbaseActions::CheckFuelStatus => {
+                            bot.addr_sage
+                                .do_send(SageRequest::FleetFuelTank(fleet.1.fuel_tank, addr));
+                        }
+                        autoplay::StarbaseActions::CheckAmmoStatus => {
+                            bot.addr_sage
+                                .do_send(SageRequest::FleetAmmoBank(fleet.1.ammo_bank, addr));
+                        }
+                        autoplay::StarbaseActions::CheckFoodStatus => {
+                            bot.addr_sage
+                                .do_send(SageRequest::FleetFoodCargoHold(fleet.1.cargo_hold, addr));
+                        }
+                        autoplay::StarbaseActions::CheckStarbaseInventory => {
+                            bot.restock_checked = true;
+                            bot.addr_sage.do_send(SageRequest::StarbaseCargo(
+                                fleet.1.owner_profile,
+                                starbase_loading_bay_ops.starbase,
+                                addr,
+                            ));
+                        }
+                        autoplay::StarbaseActions::RestockConsumable(mint, amount, max_price) => {
+                            let starbase = starbase_loading_bay_ops.starbase;
 
-                                let operation =
-                                    autoplay::BotOps::TxsSageBased(autoplay::TxsSageBasedOps {
-                                        stopwatch: timers::Stopwatch::new(),
-                                    });
-                                bot.operation = Some(operation);
-                            }
-                            autoplay::StarbaseActions::CargoWithdraw(mint, amount) => {
-                                bot.addr_sage.do_send(SageAction::CargoWithdraw(
-                                    fleet,
-                                    starbase_loading_bay_ops.starbase,
-                                    mint,
-                                    amount,
-                                    addr,
+                            if let Some(budget) = bot.reserve_restock(&mint, amount, max_price) {
+                                bot.addr_sage.do_send(SageAction::ConsumableRestock(
+                                    fleet, starbase, mint, amount, max_price, budget, addr,
                                 ));
 
                                 let operation =
@@ -227,42 +391,27 @@ pub(crate) fn tick(bot: &mut BotActor, msg: Tick, addr: Addr<BotActor>) {
                                     });
                                 bot.operation = Some(operation);
                             }
-                            autoplay::StarbaseActions::CheckFuelStatus => {
-                                bot.addr_sage
-                                    .do_send(SageRequest::FleetFuelTank(fleet.1.fuel_tank, addr));
-                            }
-                            autoplay::StarbaseActions::CheckAmmoStatus => {
-                                bot.addr_sage
-                                    .do_send(SageRequest::FleetAmmoBank(fleet.1.ammo_bank, addr));
-                            }
-                            autoplay::StarbaseActions::CheckFoodStatus => {
-                                bot.addr_sage.do_send(SageRequest::FleetFoodCargoHold(
-                                    fleet.1.cargo_hold,
-                                    addr,
-                                ));
-                            }
-                            autoplay::StarbaseActions::UndockFromStarbase => {
-                                bot.addr_sage.do_send(SageAction::StarbaseUndock(
-                                    fleet,
-                                    starbase_loading_bay_ops.starbase,
-                                    addr,
-                                ));
+                        }
+                        autoplay::StarbaseActions::UndockFromStarbase => {
+                            bot.addr_sage.do_send(SageAction::StarbaseUndock(
+                                fleet,
+                                starbase_loading_bay_ops.starbase,
+                                addr,
+                            ));
 
-                                let operation =
-                                    autoplay::BotOps::TxsSageBased(autoplay::TxsSageBasedOps {
-                                        stopwatch: timers::Stopwatch::new(),
-                                    });
-                                bot.operation = Some(operation);
-                            }
+                            let operation =
+                                autoplay::BotOps::TxsSageBased(autoplay::TxsSageBasedOps {
+                                    stopwatch: timers::Stopwatch::new(),
+                                });
+                            bot.operation = Some(operation);
                         }
                     }
-                    _ => unimplemented!("{:?}", operation),
                 }
-            } else {
-                bot.addr_sage.do_send(SageRequest::Fleet(fleet_id, addr));
+                _ => unimplemented!("{:?}", operation),
             }
+        } else {
+            bot.request_fleet(addr);
         }
-        _ => unimplemented!(),
     }
 }
 
@@ -314,7 +463,7 @@ fn autoplay_fleet_with_state_update(
 
 fn autoplay_idle(bot: &BotActor, idle: &Idle) -> IdleOps {
     let (_, FleetWithState(fleet, _)) = &bot.fleet;
-    let current_capacity = bot.fleet_cargo_hold.iter().fold(0, |x, (_, v)| x + v);
+    let current_capacity = bot.cargo_hold().used_space(bot.cargo_weights());
     let cargo_capacity_fraction =
         current_capacity as f64 / fleet.stats.cargo_stats.cargo_capacity as f64;
 
@@ -348,29 +497,32 @@ fn autoplay_mine_asteroid(
     let mining_location = planet.1.name();
     let currently_mining = mine_item.1.name();
 
-    let mining_rate = calc::asteroid_mining_emission_rate(&fleet.stats, &mine_item.1, &resource.1);
+    // the units of the resource that fit in the cargo hold's free space
+    let cargo_space = bot.units_fit(&fleet.cargo_hold, &mine_item.1.mint) as u32;
 
-    let cargo_space = fleet.stats.cargo_stats.cargo_capacity;
-
-    let mining_duration = calc::asteroid_mining_resource_extraction_duration(
+    // the mining stops at the binding constraint (a full cargo hold, or out of ammo or food)
+    let plan = calc::asteroid_mining_plan(
         &fleet.stats,
         &mine_item.1,
         &resource.1,
         cargo_space,
+        bot.ammo_on_hand(),
+        bot.food_on_hand(),
     );
 
     let time_elapsed = clock.unix_timestamp - mine_asteroid.start;
-    let amount_mined = time_elapsed as f32 * mining_rate;
+    let amount_mined = plan.mined(time_elapsed as f32);
 
-    let mut mining_timer = timers::Timer::from_seconds(mining_duration);
+    let mut mining_timer = timers::Timer::from_seconds(plan.max_duration);
     let elapsed = std::time::Duration::from_secs_f64(time_elapsed as f64);
     mining_timer.set_elapsed(elapsed);
 
     MiningOps {
         mining_location: mining_location.into(),
         currently_mining: currently_mining.into(),
-        resource_mining_rate_per_second: mining_rate,
+        resource_mining_rate_per_second: plan.emission_rate,
         amount_mined,
+        binding_constraint: format!("{:?}", plan.constraint),
         timer: mining_timer,
     }
 }
@@ -407,15 +559,13 @@ fn autoplay_starbase_loading(
                 match starbase_loading_bay_ops.next_action {
                     StarbaseActions::CheckFuelStatus => {
                         let (_, FleetWithState(fleet, _)) = &bot.fleet;
-                        let (fuel_mint, fuel_amount) = &bot.fleet_fuel_tank[0];
-                        let fuel_tank_fraction =
-                            *fuel_amount as f32 / fleet.stats.cargo_stats.fuel_capacity as f32;
+                        let fuel_mint = bot.roles.ctx.game.mints.fuel;
+                        let fuel_tank_fraction = bot.fuel_tank().used_space(bot.cargo_weights())
+                            as f32
+                            / fleet.stats.cargo_stats.fuel_capacity as f32;
 
                         if fuel_tank_fraction < 0.5 {
-                            let amount =
-                                fleet.stats.cargo_stats.fuel_capacity as u64 - *fuel_amount;
-
-                            let fuel_mint = Pubkey::from_str(&fuel_mint).unwrap();
+                            let amount = bot.units_fit(&fleet.fuel_tank, &fuel_mint);
                             StarbaseActions::CargoDeposit(fleet.fuel_tank, fuel_mint, amount)
                         } else {
                             if fleet.stats.cargo_stats.ammo_consumption_rate == 0 {
@@ -427,15 +577,13 @@ fn autoplay_starbase_loading(
                     }
                     StarbaseActions::CheckAmmoStatus => {
                         let (_, FleetWithState(fleet, _)) = &bot.fleet;
-                        let (ammo_mint, ammo_amount) = &bot.fleet_ammo_bank[0];
-                        let ammo_bank_fraction =
-                            *ammo_amount as f32 / fleet.stats.cargo_stats.ammo_capacity as f32;
+                        let ammo_mint = bot.roles.ctx.game.mints.ammo;
+                        let ammo_bank_fraction = bot.ammo_bank().used_space(bot.cargo_weights())
+                            as f32
+                            / fleet.stats.cargo_stats.ammo_capacity as f32;
 
                         if ammo_bank_fraction < 0.5 {
-                            let amount =
-                                fleet.stats.cargo_stats.ammo_capacity as u64 - *ammo_amount;
-
-                            let ammo_mint = Pubkey::from_str(&ammo_mint).unwrap();
+                            let amount = bot.units_fit(&fleet.ammo_bank, &ammo_mint);
                             StarbaseActions::CargoDeposit(fleet.ammo_bank, ammo_mint, amount)
                         } else {
                             StarbaseActions::CheckFoodStatus
@@ -443,23 +591,30 @@ fn autoplay_starbase_loading(
                     }
                     StarbaseActions::CheckFoodStatus => {
                         let (_, FleetWithState(fleet, _)) = &bot.fleet;
-                        let (food_mint, food_amount) = &bot.fleet_food_cargo[0];
-                        let min_food =
-                            (fleet.stats.cargo_stats.cargo_capacity as f32 * 0.075) as u64;
-
-                        if *food_amount < min_food {
-                            let amount = min_food - *food_amount;
-
-                            let food_mint = Pubkey::from_str(&food_mint).unwrap();
+                        let food_mint = bot.roles.ctx.game.mints.food;
+                        let food_amount = bot
+                            .fleet_food_cargo
+                            .iter()
+                            .find(|(mint, _)| mint == &food_mint.to_string())
+                            .map_or(0, |(_, amount)| *amount);
+                        // the food of 7.5% of the cargo hold's space
+                        let min_food = bot.cargo_weights().units_fit(
+                            &food_mint,
+                            (fleet.stats.cargo_stats.cargo_capacity as f32 * 0.075) as u64,
+                        );
+
+                        if food_amount < min_food {
+                            let amount = (min_food - food_amount)
+                                .min(bot.units_fit(&fleet.cargo_hold, &food_mint));
                             StarbaseActions::CargoDeposit(fleet.cargo_hold, food_mint, amount)
                         } else {
                             StarbaseActions::UndockFromStarbase
                         }
                     }
-                    _ => StarbaseActions::CheckFuelStatus,
+                    _ => bot.starbase_first_check(),
                 }
             }
-            _ => StarbaseActions::CheckFuelStatus,
+            _ => bot.starbase_first_check(),
         }
     };
 
@@ -0,0 +1,660 @@
+use super::*;
+
+/// A step of a mission, in config form (`"Dock"`, `{ "Warp": [x, y] }`, ...).
+#[derive(Debug, Deserialize)]
+enum MissionStepCfg {
+    Warp([i64; 2]),
+    Dock,
+    Undock,
+    Withdraw {
+        mint: String,
+        amount: Option<u64>,
+    },
+    Deposit {
+        mint: String,
+        amount: u64,
+    },
+    MineUntilFull {
+        planet_id: String,
+        mine_item_id: String,
+    },
+    Wait(u64),
+}
+
+#[derive(Debug, Deserialize)]
+struct MissionArgs {
+    #[serde(default)]
+    repeat: bool,
+    steps: Vec<MissionStepCfg>,
+}
+
+#[derive(Debug, Clone)]
+enum MissionStep {
+    Warp([i64; 2]),
+    Dock,
+    Undock,
+    Withdraw(Pubkey, Option<u64>), // (Mint, Amount), "None" withdraws the whole cargo hold's amount
+    Deposit(Pubkey, u64),          // (Mint, Amount)
+    MineUntilFull {
+        planet: Box<(Pubkey, Planet)>,
+        mine_item: Box<(Pubkey, MineItem)>,
+        resource: Box<(Pubkey, Resource)>,
+    },
+    Wait(u64),
+}
+
+#[derive(Debug, Clone)]
+enum StepState {
+    Refresh,                  // refresh the fleet (and its state) before starting the step
+    AwaitingFleet,            // waiting on the refreshed fleet
+    Ready,                    // start the step
+    AwaitingCargo,            // waiting on the fleet's cargo hold
+    InFlight(Box<StepState>), // waiting on the transaction, then continue with the state
+    Warping(timers::Timer, timers::Timer), // (Warp, Cooldown)
+    Mining(timers::Timer, f32), // (Mining, Amount)
+    Waiting(timers::Timer),
+    Advance, // the step is done
+    Done,
+    Failed, // a step couldn't start (or its transaction failed) MAX_STEP_ATTEMPTS times
+}
+
+/// Attempts of a step before the mission fails.
+const MAX_STEP_ATTEMPTS: u32 = 5;
+
+/// Executes an ordered (optionally looping) list of steps with the `SageAction` messages.
+pub struct MissionRole {
+    steps: Vec<MissionStep>,
+    repeat: bool,
+    current: usize,
+    state: StepState,
+    attempts: u32, // of the current step
+}
+
+impl MissionRole {
+    pub(crate) fn build(args: serde_json::Value, ctx: &RoleContext) -> RoleFuture<'_> {
+        Box::pin(async move {
+            let args: MissionArgs = serde_json::from_value(args)?;
+            if args.steps.is_empty() {
+                return Err(eyre!("A mission requires at least one step"));
+            }
+
+            let mut steps = vec![];
+            for step in args.steps {
+                let step = match step {
+                    MissionStepCfg::Warp(sector) => MissionStep::Warp(sector),
+                    MissionStepCfg::Dock => MissionStep::Dock,
+                    MissionStepCfg::Undock => MissionStep::Undock,
+                    MissionStepCfg::Withdraw { mint, amount } => {
+                        MissionStep::Withdraw(Pubkey::from_str(&mint)?, amount)
+                    }
+                    MissionStepCfg::Deposit { mint, amount } => {
+                        MissionStep::Deposit(Pubkey::from_str(&mint)?, amount)
+                    }
+                    MissionStepCfg::MineUntilFull {
+                        planet_id,
+                        mine_item_id,
+                    } => {
+                        let planet_id = Pubkey::from_str(&planet_id)?;
+                        let mine_item_id = Pubkey::from_str(&mine_item_id)?;
+
+                        let (planet, mine_item, resource) =
+                            ctx.asteroid(&planet_id, &mine_item_id).await?;
+
+                        MissionStep::MineUntilFull {
+                            planet: Box::new(planet),
+                            mine_item: Box::new(mine_item),
+                            resource: Box::new(resource),
+                        }
+                    }
+                    MissionStepCfg::Wait(seconds) => MissionStep::Wait(seconds),
+                };
+
+                steps.push(step);
+            }
+
+            let role: Box<dyn Role> = Box::new(MissionRole {
+                steps,
+                repeat: args.repeat,
+                current: 0,
+                state: StepState::Refresh,
+                attempts: 0,
+            });
+            Ok(role)
+        })
+    }
+
+    fn advance(&mut self) {
+        self.current += 1;
+        self.attempts = 0;
+
+        if self.current >= self.steps.len() {
+            if self.repeat {
+                self.current = 0;
+            } else {
+                log::info!("Mission completed");
+                self.state = StepState::Done;
+                return;
+            }
+        }
+
+        self.state = StepState::Refresh;
+    }
+
+    /// Refreshes the fleet and retries the step, the mission fails once the step's attempts run
+    /// out (the fleet waits for a new config).
+    fn retry(&mut self) {
+        self.attempts += 1;
+
+        if self.attempts >= MAX_STEP_ATTEMPTS {
+            log::error!(
+                "Mission step {} ({:?}) failed {} times, the mission is stopped",
+                self.current,
+                self.steps[self.current],
+                self.attempts
+            );
+            self.state = StepState::Failed;
+        } else {
+            self.state = StepState::Refresh;
+        }
+    }
+
+    fn send(&mut self, bot: &mut BotActor, action: SageAction, then: StepState) {
+        bot.addr_sage.do_send(action);
+
+        bot.operation = Some(BotOps::TxsSageBased(TxsSageBasedOps {
+            stopwatch: timers::Stopwatch::new(),
+        }));
+        self.state = StepState::InFlight(Box::new(then));
+    }
+
+    fn start_step(&mut self, bot: &mut BotActor, addr: Addr<BotActor>) {
+        let (fleet_id, FleetWithState(fleet, state)) = &bot.fleet;
+        let fleet = (*fleet_id, *fleet);
+        let state = state.clone();
+
+        let step = self.steps[self.current].clone();
+        log::info!("Mission step {}: {:?}", self.current, step);
+
+        match (step, state) {
+            (MissionStep::Warp(sector), FleetState::Idle(idle)) => {
+                bot.ledger.pending = Some(LedgerEvent::Warped {
+                    fuel: calc::warp_fuel_consumption(&fleet.1.stats, idle.sector, sector) as f64,
+                });
+
+                // the warp is exited once the fleet's "MoveWarp" is known
+                self.send(
+                    bot,
+                    SageAction::Warp(fleet, sector, addr),
+                    StepState::Refresh,
+                );
+            }
+            (MissionStep::Dock, FleetState::Idle(idle)) => {
+                let action = SageAction::StarbaseDock(fleet, idle.sector, addr);
+                self.send(bot, action, StepState::Advance);
+            }
+            (MissionStep::Undock, FleetState::StarbaseLoadingBay(loading_bay)) => {
+                let action = SageAction::StarbaseUndock(fleet, loading_bay.starbase, addr);
+                self.send(bot, action, StepState::Advance);
+            }
+            (MissionStep::Withdraw(mint, amount), FleetState::StarbaseLoadingBay(loading_bay)) => {
+                match amount {
+                    Some(amount) => {
+                        bot.ledger.pending = Some(LedgerEvent::Unloaded {
+                            mint,
+                            amount: amount as f64,
+                        });
+
+                        let action = SageAction::CargoWithdraw(
+                            fleet,
+                            loading_bay.starbase,
+                            mint,
+                            amount,
+                            addr,
+                        );
+                        self.send(bot, action, StepState::Advance);
+                    }
+                    None => {
+                        bot.addr_sage
+                            .do_send(SageRequest::FleetCargoHold(fleet.1.cargo_hold, addr));
+                        self.state = StepState::AwaitingCargo;
+                    }
+                }
+            }
+            (MissionStep::Deposit(mint, amount), FleetState::StarbaseLoadingBay(loading_bay)) => {
+                let cargo_pod_to = bot.deposit_pod(&mint);
+
+                // no more than the units that fit in the cargo pod
+                let amount = amount.min(bot.units_fit(&cargo_pod_to, &mint));
+
+                let action = SageAction::CargoDeposit(
+                    fleet,
+                    loading_bay.starbase,
+                    cargo_pod_to,
+                    mint,
+                    amount,
+                    addr,
+                );
+                self.send(bot, action, StepState::Advance);
+            }
+            (
+                MissionStep::MineUntilFull {
+                    planet,
+                    mine_item,
+                    resource,
+                },
+                FleetState::Idle(idle),
+            ) => {
+                let stats = &fleet.1.stats;
+                // the units of the resource that fit in the cargo hold's free space
+                let cargo_space = bot.units_fit(&fleet.1.cargo_hold, &mine_item.1.mint) as u32;
+
+                // the mining stops at the binding constraint (a full cargo hold, or out of ammo
+                // or food)
+                let plan = calc::asteroid_mining_plan(
+                    stats,
+                    &mine_item.1,
+                    &resource.1,
+                    cargo_space,
+                    bot.ammo_on_hand(),
+                    bot.food_on_hand(),
+                );
+                log::info!(
+                    "{} mines {:.0} of {} ({:?})",
+                    fleet.0,
+                    plan.output,
+                    mine_item.1.mint,
+                    plan.constraint
+                );
+                let (mining_duration, mining_rate) = (plan.max_duration, plan.emission_rate);
+
+                let action = SageAction::StartMining(
+                    fleet,
+                    mine_item.0,
+                    resource.0,
+                    planet.0,
+                    idle.sector,
+                    addr,
+                );
+                let mining =
+                    StepState::Mining(timers::Timer::from_seconds(mining_duration), mining_rate);
+                self.send(bot, action, mining);
+            }
+            (
+                MissionStep::MineUntilFull {
+                    mine_item,
+                    resource,
+                    ..
+                },
+                FleetState::MineAsteroid(mine_asteroid),
+            ) => {
+                // resume the mining (e.g. the bot restarted or the "StopMining" failed)
+                let plan = calc::asteroid_mining_plan(
+                    &fleet.1.stats,
+                    &mine_item.1,
+                    &resource.1,
+                    bot.units_fit(&fleet.1.cargo_hold, &mine_item.1.mint) as u32,
+                    bot.ammo_on_hand(),
+                    bot.food_on_hand(),
+                );
+                let (mining_duration, mining_rate) = (plan.max_duration, plan.emission_rate);
+
+                let mut timer = timers::Timer::from_seconds(mining_duration);
+                if let Some(clock) = &bot.clock {
+                    let time_elapsed = clock.unix_timestamp - mine_asteroid.start;
+                    timer.set_elapsed(std::time::Duration::from_secs_f64(time_elapsed as f64));
+                }
+
+                self.state = StepState::Mining(timer, mining_rate);
+            }
+            (MissionStep::Dock, FleetState::StarbaseLoadingBay(_))
+            | (MissionStep::Undock, FleetState::Idle(_)) => {
+                self.advance(); // already there
+            }
+            (MissionStep::Wait(seconds), _) => {
+                self.state = StepState::Waiting(timers::Timer::from_seconds(seconds as f32));
+            }
+            (step, state) => {
+                log::warn!("Mission step {:?} can't start from {:?}", step, state);
+                self.retry();
+            }
+        }
+    }
+}
+
+impl Role for MissionRole {
+    fn name(&self) -> &str {
+        "Mission"
+    }
+
+    fn mints(&self) -> Vec<Pubkey> {
+        self.steps
+            .iter()
+            .filter_map(|step| match step {
+                MissionStep::Withdraw(mint, _) => Some(*mint),
+                MissionStep::MineUntilFull { mine_item, .. } => Some(mine_item.1.mint),
+                _ => None,
+            })
+            .collect()
+    }
+
+    fn sectors(&self) -> Vec<[i64; 2]> {
+        self.steps
+            .iter()
+            .filter_map(|step| match step {
+                MissionStep::Warp(sector) => Some(*sector),
+                MissionStep::MineUntilFull { planet, .. } => Some(planet.1.sector),
+                _ => None,
+            })
+            .collect()
+    }
+
+    fn check<'a>(
+        &'a self,
+        fleet: &'a FleetWithState,
+        ctx: &'a RoleContext,
+        _risk: Option<&'a RiskPolicy>,
+    ) -> CheckFuture<'a> {
+        Box::pin(async move {
+            let FleetWithState(fleet, state) = fleet;
+            let stats = &fleet.stats;
+
+            // the sector of the fleet, unknown while docked (until the first warp)
+            let mut sector = match state {
+                FleetState::Idle(idle) => Some(idle.sector),
+                FleetState::MoveWarp(move_warp) => Some(move_warp.to_sector),
+                FleetState::MoveSubwarp(move_subwarp) => Some(move_subwarp.to_sector),
+                FleetState::Respawn(respawn) => Some(respawn.sector),
+                _ => None,
+            };
+
+            let (mut fuel, mut ammo, mut food, mut mining_duration) = (0.0, 0.0, 0.0, 0.0);
+            for step in &self.steps {
+                match step {
+                    MissionStep::Warp(to_sector) => {
+                        if let Some(from_sector) = sector {
+                            check_warp(fleet, from_sector, *to_sector)?;
+                            fuel += calc::warp_fuel_consumption(stats, from_sector, *to_sector);
+                        }
+                        sector = Some(*to_sector);
+                    }
+                    MissionStep::Dock => {
+                        if let Some(sector) = sector {
+                            ctx.starbase(sector).await?;
+                        }
+                    }
+                    MissionStep::MineUntilFull {
+                        planet,
+                        mine_item,
+                        resource,
+                    } => {
+                        let cargo_space = ctx
+                            .cargo_weights
+                            .units_fit(&mine_item.1.mint, stats.cargo_stats.cargo_capacity as u64);
+                        let duration = calc::asteroid_mining_resource_extraction_duration(
+                            stats,
+                            &mine_item.1,
+                            &resource.1,
+                            cargo_space as u32,
+                        );
+                        if duration <= 0.0 {
+                            return Err(eyre!("The fleet can't mine {}", mine_item.1.mint));
+                        }
+
+                        mining_duration += duration;
+                        fuel += stats.movement_stats.planet_exit_fuel_amount as f32;
+                        ammo += calc::asteroid_mining_ammo_consumption(stats, duration);
+                        food += calc::asteroid_mining_food_consumption(stats, duration);
+                        sector = Some(planet.1.sector);
+                    }
+                    _ => {}
+                }
+            }
+
+            Ok(vec![
+                ("Steps".to_string(), self.steps.len().to_string()),
+                (
+                    "Mining duration".to_string(),
+                    format!("{:.0}s", mining_duration),
+                ),
+                ("Fuel per cycle".to_string(), format!("{:.0}", fuel)),
+                ("Ammo per cycle".to_string(), format!("{:.0}", ammo)),
+                ("Food per cycle".to_string(), format!("{:.0}", food)),
+            ])
+        })
+    }
+
+    fn clock_time_update(&mut self, bot: &mut BotActor, msg: ClockTimeUpdate) {
+        bot.clock = Some(msg.0);
+    }
+
+    fn sage_response(&mut self, bot: &mut BotActor, msg: SageResponse, addr: Addr<BotActor>) {
+        match msg {
+            SageResponse::Fleet(fleet_with_state) => {
+                if let FleetState::MoveWarp(move_warp) = &fleet_with_state.1 {
+                    if matches!(self.state, StepState::AwaitingFleet) {
+                        let warp_duration = move_warp.warp_finish - move_warp.warp_start;
+                        let cooldown_duration =
+                            fleet_with_state.0.stats.movement_stats.warp_cool_down;
+
+                        let mut timer = timers::Timer::from_seconds(warp_duration as f32);
+                        let mut cooldown = timers::Timer::from_seconds(cooldown_duration as f32);
+
+                        if let Some(clock) = &bot.clock {
+                            let time_elapsed = clock.unix_timestamp - move_warp.warp_start;
+                            let elapsed = std::time::Duration::from_secs_f64(time_elapsed as f64);
+                            timer.set_elapsed(elapsed);
+                            cooldown.set_elapsed(elapsed);
+                        }
+
+                        self.state = StepState::Warping(timer, cooldown);
+                    }
+                } else if matches!(self.state, StepState::AwaitingFleet) {
+                    self.state = StepState::Ready;
+                }
+
+                bot.fleet.1 = fleet_with_state;
+            }
+            SageResponse::FleetCargoHold(cargo_hold) => {
+                bot.fleet_cargo_hold = cargo_hold;
+
+                if let (StepState::AwaitingCargo, MissionStep::Withdraw(mint, None)) =
+                    (&self.state, &self.steps[self.current])
+                {
+                    let mint = *mint;
+                    let amount = bot
+                        .fleet_cargo_hold
+                        .iter()
+                        .find(|(cargo_mint, _)| cargo_mint == &mint.to_string())
+                        .map(|(_, amount)| *amount)
+                        .unwrap_or(0);
+
+                    match (amount, bot.fleet_state().clone()) {
+                        (0, _) => self.advance(), // nothing to withdraw
+                        (amount, FleetState::StarbaseLoadingBay(loading_bay)) => {
+                            bot.ledger.pending = Some(LedgerEvent::Unloaded {
+                                mint,
+                                amount: amount as f64,
+                            });
+
+                            let (fleet_id, FleetWithState(fleet, _)) = &bot.fleet;
+                            let action = SageAction::CargoWithdraw(
+                                (*fleet_id, *fleet),
+                                loading_bay.starbase,
+                                mint,
+                                amount,
+                                addr,
+                            );
+                            self.send(bot, action, StepState::Advance);
+                        }
+                        _ => self.state = StepState::Refresh,
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn tick(&mut self, bot: &mut BotActor, msg: Tick, addr: Addr<BotActor>) {
+        let (fleet_id, FleetWithState(fleet, _)) = &bot.fleet;
+        let fleet = (*fleet_id, *fleet);
+
+        match &mut self.state {
+            StepState::Refresh => {
+                bot.addr_sage.do_send(ClockTimeRequest(addr.clone()));
+                bot.request_fleet(addr);
+                self.state = StepState::AwaitingFleet;
+            }
+            StepState::Ready => self.start_step(bot, addr),
+            StepState::AwaitingFleet
+            | StepState::AwaitingCargo
+            | StepState::InFlight(_)
+            | StepState::Done
+            | StepState::Failed => {}
+            StepState::Warping(timer, cooldown) => {
+                timer.tick(msg.0);
+                cooldown.tick(msg.0);
+
+                if timer.finished() && cooldown.finished() {
+                    self.send(bot, SageAction::WarpExit(fleet, addr), StepState::Advance);
+                }
+            }
+            StepState::Mining(timer, mining_rate) => {
+                timer.tick(msg.0);
+
+                if timer.finished() {
+                    if let MissionStep::MineUntilFull {
+                        planet,
+                        mine_item,
+                        resource,
+                    } = self.steps[self.current].clone()
+                    {
+                        let stats = &fleet.1.stats;
+                        let mining_duration = timer.elapsed_secs();
+                        bot.ledger.pending = Some(LedgerEvent::Mined {
+                            mint: mine_item.1.mint,
+                            amount: (mining_duration * *mining_rate)
+                                .min(bot.units_fit(&fleet.1.cargo_hold, &mine_item.1.mint) as f32)
+                                as f64,
+                            fuel: stats.movement_stats.planet_exit_fuel_amount as f64,
+                            ammo: calc::asteroid_mining_ammo_consumption(stats, mining_duration)
+                                as f64,
+                            food: calc::asteroid_mining_food_consumption(stats, mining_duration)
+                                as f64,
+                        });
+
+                        let action = SageAction::StopMining(
+                            fleet,
+                            mine_item.0,
+                            mine_item.1.mint,
+                            resource.0,
+                            planet.0,
+                            planet.1.sector,
+                            addr,
+                        );
+                        self.send(bot, action, StepState::Advance);
+                    }
+                }
+            }
+            StepState::Waiting(timer) => {
+                timer.tick(msg.0);
+
+                if timer.finished() {
+                    self.advance();
+                }
+            }
+            StepState::Advance => self.advance(),
+        }
+    }
+
+    fn transaction_result(&mut self, _bot: &mut BotActor, signature: Option<Signature>) {
+        if let StepState::InFlight(then) = &self.state {
+            match signature {
+                Some(_) => self.state = *then.clone(),
+                None => self.retry(),
+            }
+        }
+    }
+}
+
+// cargo test -p actix-minebot
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mission(steps: Vec<MissionStep>, repeat: bool) -> MissionRole {
+        MissionRole {
+            steps,
+            repeat,
+            current: 0,
+            state: StepState::Refresh,
+            attempts: 0,
+        }
+    }
+
+    #[test]
+    fn steps_parse_from_the_config() {
+        let args: MissionArgs = serde_json::from_value(serde_json::json!({
+            "repeat": true,
+            "steps": [
+                { "Warp": [-40, 30] },
+                "Dock",
+                { "Withdraw": { "mint": "11111111111111111111111111111111" } },
+                { "Deposit": { "mint": "11111111111111111111111111111111", "amount": 10 } },
+                "Undock",
+                { "Wait": 60 }
+            ]
+        }))
+        .unwrap();
+
+        assert!(args.repeat);
+        assert_eq!(args.steps.len(), 6);
+        assert!(matches!(args.steps[0], MissionStepCfg::Warp([-40, 30])));
+        assert!(matches!(
+            args.steps[2],
+            MissionStepCfg::Withdraw { amount: None, .. }
+        ));
+        assert!(matches!(args.steps[5], MissionStepCfg::Wait(60)));
+    }
+
+    #[test]
+    fn a_mission_completes_or_repeats() {
+        let mut role = mission(vec![MissionStep::Dock, MissionStep::Undock], false);
+        role.advance();
+        assert_eq!(role.current, 1);
+        assert!(matches!(role.state, StepState::Refresh));
+        role.advance();
+        assert!(matches!(role.state, StepState::Done));
+
+        let mut role = mission(vec![MissionStep::Dock, MissionStep::Undock], true);
+        role.advance();
+        role.advance();
+        assert_eq!(role.current, 0);
+        assert!(matches!(role.state, StepState::Refresh));
+    }
+
+    #[test]
+    fn a_step_is_retried_a_bounded_number_of_times() {
+        let mut role = mission(vec![MissionStep::Dock, MissionStep::Wait(1)], true);
+
+        for _ in 1..MAX_STEP_ATTEMPTS {
+            role.retry();
+            assert!(matches!(role.state, StepState::Refresh));
+        }
+        role.retry();
+        assert!(matches!(role.state, StepState::Failed));
+        assert_eq!(role.current, 0);
+    }
+
+    #[test]
+    fn the_attempts_are_per_step() {
+        let mut role = mission(vec![MissionStep::Dock, MissionStep::Undock], true);
+
+        for _ in 1..MAX_STEP_ATTEMPTS {
+            role.retry();
+        }
+        role.advance();
+        role.retry();
+        assert!(matches!(role.state, StepState::Refresh));
+        assert_eq!(role.attempts, 1);
+    }
+}
@@ -1,24 +1,269 @@
 use super::*;
 
+use crate::config::RoleCfg;
+use anchor_client::ClientError;
+use color_eyre::{eyre::eyre, Result};
+use staratlas_sage_based_sdk::{addr, types::Mints};
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::future::Future;
+use std::pin::Pin;
+
 pub(crate) mod cargo_transport;
 pub(crate) mod mine_asteroid;
+pub(crate) mod mission;
+pub(crate) mod script;
+
+pub use cargo_transport::CargoTransportRole;
+pub use mine_asteroid::MineAsteroidRole;
+pub use mission::MissionRole;
+pub use script::ScriptRole;
+
+/// Behaviour of a bot, the `BotActor` forwards its messages to the role's hooks.
+pub trait Role {
+    fn name(&self) -> &str;
+
+    /// Mints produced or moved by the role (valued by the ledger).
+    fn mints(&self) -> Vec<Pubkey> {
+        vec![]
+    }
+
+    /// Sectors the role mines in or warps to (checked against the bot's risk policy).
+    fn sectors(&self) -> Vec<[i64; 2]> {
+        vec![]
+    }
+
+    fn clock_time_update(&mut self, bot: &mut BotActor, msg: ClockTimeUpdate);
+
+    fn sage_response(&mut self, bot: &mut BotActor, msg: SageResponse, addr: Addr<BotActor>);
+
+    fn tick(&mut self, bot: &mut BotActor, msg: Tick, addr: Addr<BotActor>);
+
+    /// Called before the fleet's new state is handed to `sage_response`.
+    fn state_changed(&mut self, _bot: &mut BotActor, _from: &FleetState, _to: &FleetState) {}
+
+    /// Called with the signature of a `SageAction`'s transaction, `None` when it failed.
+    fn transaction_result(&mut self, _bot: &mut BotActor, _signature: Option<Signature>) {}
+
+    /// Validates the role against the fleet and the bot's risk policy (`minebot check`), returns
+    /// its projected cycle.
+    fn check<'a>(
+        &'a self,
+        _fleet: &'a FleetWithState,
+        _ctx: &'a RoleContext,
+        _risk: Option<&'a RiskPolicy>,
+    ) -> CheckFuture<'a> {
+        Box::pin(async { Ok(vec![]) })
+    }
+}
+
+/// Accounts available to the role builders.
+pub struct RoleContext {
+    pub program: Program<Rc<Keypair>>,
+    pub game_id: Pubkey,
+    pub game: Game,
+    pub cargo_weights: CargoWeights,
+}
+
+impl RoleContext {
+    /// The starbase of the sector, an error when the sector has none.
+    pub async fn starbase(&self, sector: [i64; 2]) -> Result<Pubkey> {
+        let (starbase, _) = addr::starbase_address(&self.game_id, sector);
+
+        self.program
+            .async_rpc()
+            .get_account(&starbase)
+            .await
+            .map_err(|_| eyre!("No starbase in sector {:?}", sector))?;
+        Ok(starbase)
+    }
+
+    /// The planet, mine item and resource of an asteroid, an error when the planet has no
+    /// resource of the mine item.
+    pub async fn asteroid(&self, planet_id: &Pubkey, mine_item_id: &Pubkey) -> Result<Asteroid> {
+        let program = &self.program;
+        let mine_item = SageBasedGameHandler::get_mine_item(program, mine_item_id).await?;
+        let planet = SageBasedGameHandler::get_planet(program, planet_id).await?;
+        let resource =
+            SageBasedGameHandler::find_resource(program, &self.game_id, planet_id, mine_item_id)
+                .await
+                .map_err(|err| match err {
+                    ClientError::AccountNotFound => eyre!(
+                        "No resource of the mine item {} on the planet {}",
+                        mine_item_id,
+                        planet_id
+                    ),
+                    err => err.into(),
+                })?;
+
+        Ok((planet, mine_item, resource))
+    }
+}
+
+/// The planet, mine item and resource of an asteroid.
+pub type Asteroid = ((Pubkey, Planet), (Pubkey, MineItem), (Pubkey, Resource));
+
+/// Errors when the sectors are beyond the fleet's max warp distance.
+pub(crate) fn check_warp(fleet: &Fleet, from_sector: [i64; 2], to_sector: [i64; 2]) -> Result<()> {
+    let travel = calc::travel(&fleet.stats, from_sector, to_sector);
+
+    if !travel.is_single_warp() {
+        return Err(eyre!(
+            "Warp from {:?} to {:?} ({:.2}) exceeds the max warp distance ({:.2})",
+            from_sector,
+            to_sector,
+            travel.distance,
+            travel.max_warp_distance
+        ));
+    }
+    Ok(())
+}
+
+/// Projected cycle of a role, `(label, value)` lines.
+pub type CyclePlan = Vec<(String, String)>;
+
+pub type CheckFuture<'a> = Pin<Box<dyn Future<Output = Result<CyclePlan>> + 'a>>;
+
+pub type RoleFuture<'a> = Pin<Box<dyn Future<Output = Result<Box<dyn Role>>> + 'a>>;
+
+/// Builds a role from its config arguments.
+pub type RoleBuilder = for<'a> fn(serde_json::Value, &'a RoleContext) -> RoleFuture<'a>;
+
+/// Role builders keyed by the name used in the bot's config.
+pub struct RoleRegistry {
+    builders: HashMap<String, RoleBuilder>,
+}
+
+impl Default for RoleRegistry {
+    fn default() -> Self {
+        let mut registry = RoleRegistry {
+            builders: HashMap::new(),
+        };
+
+        registry.register("MineAsteroid", MineAsteroidRole::build);
+        registry.register("CargoTransport", CargoTransportRole::build);
+        registry.register("Mission", MissionRole::build);
+        registry.register("Script", ScriptRole::build);
+        registry
+    }
+}
+
+/// The registry and its context, shared by the bots to build the roles of a reloaded config.
+pub struct RoleFactory {
+    pub registry: RoleRegistry,
+    pub ctx: RoleContext,
+    /// The roles handed over by the retired bots to the next bots of their fleets.
+    pub handovers: RefCell<HashMap<Pubkey, Handover>>,
+}
+
+impl RoleFactory {
+    pub async fn build(&self, role_cfg: &RoleCfg) -> Result<Box<dyn Role>> {
+        let (name, args) = role_cfg.name_and_args()?;
+        self.registry.build(name, args, &self.ctx).await
+    }
+}
+
+impl RoleRegistry {
+    pub fn register(&mut self, name: &str, builder: RoleBuilder) {
+        self.builders.insert(name.to_string(), builder);
+    }
+
+    pub async fn build(
+        &self,
+        name: &str,
+        args: serde_json::Value,
+        ctx: &RoleContext,
+    ) -> Result<Box<dyn Role>> {
+        let builder = self
+            .builders
+            .get(name)
+            .ok_or(eyre!("Unknown role: {}", name))?;
+
+        builder(args, ctx).await
+    }
+}
+
+// cargo test -p actix-minebot
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    use anchor_client::{Client, Cluster};
+    use borsh::BorshDeserialize;
+    use futures::executor::block_on;
+
+    struct ExternalRole;
+
+    impl ExternalRole {
+        fn build(_args: serde_json::Value, _ctx: &RoleContext) -> RoleFuture<'_> {
+            Box::pin(async {
+                let role: Box<dyn Role> = Box::new(ExternalRole);
+                Ok(role)
+            })
+        }
+    }
+
+    impl Role for ExternalRole {
+        fn name(&self) -> &str {
+            "External"
+        }
+
+        fn clock_time_update(&mut self, _bot: &mut BotActor, _msg: ClockTimeUpdate) {}
+
+        fn sage_response(&mut self, _bot: &mut BotActor, _msg: SageResponse, _: Addr<BotActor>) {}
+
+        fn tick(&mut self, _bot: &mut BotActor, _msg: Tick, _addr: Addr<BotActor>) {}
+    }
+
+    // the builders don't reach the cluster (a role without account reads)
+    pub(crate) fn ctx() -> RoleContext {
+        let client = Client::new(Cluster::Localnet, Rc::new(Keypair::new()));
+
+        RoleContext {
+            program: client.program(SAGE_ID).unwrap(),
+            game_id: Pubkey::new_unique(),
+            game: Game::deserialize(&mut &[0u8; 2048][..]).unwrap(),
+            cargo_weights: CargoWeights::new(&[], 1),
+        }
+    }
+
+    #[test]
+    fn a_role_is_built_by_its_name() {
+        let registry = RoleRegistry::default();
+        let ctx = ctx();
+
+        let args = serde_json::json!({ "steps": ["Dock", { "Wait": 60 }] });
+        let role = block_on(registry.build("Mission", args, &ctx)).unwrap();
+        assert_eq!(role.name(), "Mission");
+
+        // the role's own arguments are still checked
+        let args = serde_json::json!({ "steps": [] });
+        assert!(block_on(registry.build("Mission", args, &ctx)).is_err());
+    }
+
+    #[test]
+    fn an_unknown_role_is_an_error() {
+        let registry = RoleRegistry::default();
+
+        let err = block_on(registry.build("Mine", serde_json::Value::Null, &ctx()))
+            .err()
+            .unwrap();
+        assert_eq!(err.to_string(), "Unknown role: Mine");
+    }
+
+    #[test]
+    fn an_external_role_is_registered() {
+        let mut registry = RoleRegistry::default();
+        let ctx = ctx();
+        assert!(block_on(registry.build("External", serde_json::Value::Null, &ctx)).is_err());
+
+        registry.register("External", ExternalRole::build);
+        let role = block_on(registry.build("External", serde_json::Value::Null, &ctx)).unwrap();
+        assert_eq!(role.name(), "External");
 
-pub enum BotRole {
-    MineAsteroid {
-        planet: (Pubkey, Planet),
-        mine_item: (Pubkey, MineItem),
-        resource: (Pubkey, Resource),
-        // pub(crate) fleet_cargo_hold: Vec<(String, u64)>,
-        // pub(crate) fleet_fuel_tank: Vec<(String, u64)>,
-        // pub(crate) fleet_ammo_bank: Vec<(String, u64)>,
-        // pub(crate) fleet_food_cargo: Vec<(String, u64)>,
-    },
-    CargoTransport {
-        cargo_mint: Pubkey,
-        cargo_amount: u64,
-        from_sector: [i64; 2],
-        from_starbase: Pubkey,
-        to_sector: [i64; 2],
-        to_starbase: Pubkey,
-    },
+        // the built-in roles stay registered
+        let args = serde_json::json!({ "steps": ["Undock"] });
+        assert!(block_on(registry.build("Mission", args, &ctx)).is_ok());
+    }
 }
@@ -0,0 +1,595 @@
+use super::*;
+
+use rhai::{Dynamic, Engine, Scope, AST};
+
+use std::time::SystemTime;
+
+#[derive(Deserialize)]
+struct MiningCfg {
+    planet_id: String,
+    mine_item_id: String,
+}
+
+#[derive(Deserialize)]
+struct ScriptArgs {
+    path: String,
+    #[serde(default = "default_interval")]
+    interval: f32,
+    #[serde(default = "default_max_operations")]
+    max_operations: u64,
+    #[serde(default)]
+    mining: Vec<MiningCfg>,
+}
+
+fn default_interval() -> f32 {
+    10.0
+}
+
+fn default_max_operations() -> u64 {
+    100_000
+}
+
+/// The action returned by a script's `next_action` (`"Dock"`, `#{ Warp: [x, y] }`, ...).
+#[derive(Debug, Deserialize)]
+enum ScriptAction {
+    Warp([i64; 2]),
+    WarpExit,
+    Dock,
+    Undock,
+    Withdraw { mint: String, amount: u64 },
+    Deposit { mint: String, amount: u64 },
+    StartMining(String), // MineItem (one of the role's "mining")
+    StopMining,
+}
+
+enum ScriptState {
+    Sleeping(timers::Timer),
+    AwaitingFleet,
+    AwaitingCargo,
+    InFlight,
+}
+
+/// Compiled script, recompiled whenever the file's modification time changes.
+struct Script {
+    path: String,
+    modified: Option<SystemTime>,
+    ast: Option<AST>,
+}
+
+impl Script {
+    fn reload(&mut self, engine: &Engine) {
+        let modified = std::fs::metadata(&self.path)
+            .and_then(|metadata| metadata.modified())
+            .ok();
+
+        if self.ast.is_some() && modified == self.modified {
+            return;
+        }
+        self.modified = modified;
+
+        // a broken script keeps the previous version running
+        match engine.compile_file(self.path.clone().into()) {
+            Ok(ast) => {
+                log::info!("Script loaded: {}", self.path);
+                self.ast = Some(ast);
+            }
+            Err(err) => log::error!("Script {}: {}", self.path, err),
+        }
+    }
+}
+
+/// Delegates the fleet's decisions to a Rhai script's `next_action(fleet)` function.
+pub struct ScriptRole {
+    engine: Engine,
+    script: Script,
+    interval: f32,
+    mining: Vec<Asteroid>,
+    mints: Mints,
+    state: ScriptState,
+}
+
+impl ScriptRole {
+    pub(crate) fn build(args: serde_json::Value, ctx: &RoleContext) -> RoleFuture<'_> {
+        Box::pin(async move {
+            let args: ScriptArgs = serde_json::from_value(args)?;
+
+            let mut mining = vec![];
+            for MiningCfg {
+                planet_id,
+                mine_item_id,
+            } in args.mining
+            {
+                let planet_id = Pubkey::from_str(&planet_id)?;
+                let mine_item_id = Pubkey::from_str(&mine_item_id)?;
+
+                mining.push(ctx.asteroid(&planet_id, &mine_item_id).await?);
+            }
+
+            let engine = sandboxed_engine(args.max_operations);
+
+            let mut script = Script {
+                path: args.path,
+                modified: None,
+                ast: None,
+            };
+            script.reload(&engine);
+            if script.ast.is_none() {
+                return Err(eyre!("Failed to compile the script: {}", script.path));
+            }
+
+            let role: Box<dyn Role> = Box::new(ScriptRole {
+                engine,
+                script,
+                interval: args.interval,
+                mining,
+                mints: ctx.game.mints,
+                state: ScriptState::Sleeping(timers::Timer::from_seconds(0.0)),
+            });
+            Ok(role)
+        })
+    }
+
+    fn sleep(&mut self) {
+        self.state = ScriptState::Sleeping(timers::Timer::from_seconds(self.interval));
+    }
+
+    /// The fleet as seen by the script.
+    fn fleet_info(&self, bot: &BotActor) -> serde_json::Value {
+        let (fleet_id, FleetWithState(fleet, state)) = &bot.fleet;
+        let stats = &fleet.stats;
+
+        let amount = |balances: &Vec<(String, u64)>, mint: &Pubkey| -> u64 {
+            let mint = mint.to_string();
+            balances
+                .iter()
+                .filter(|(balance_mint, _)| balance_mint == &mint)
+                .map(|(_, amount)| amount)
+                .sum()
+        };
+
+        let mut cargo = serde_json::Map::new();
+        for (mint, amount) in &bot.fleet_cargo_hold {
+            let total = cargo.get(mint).and_then(|a| a.as_u64()).unwrap_or(0) + amount;
+            cargo.insert(mint.clone(), total.into());
+        }
+
+        let state = match state {
+            FleetState::StarbaseLoadingBay(loading_bay) => serde_json::json!({
+                "name": "StarbaseLoadingBay",
+                "starbase": loading_bay.starbase.to_string(),
+            }),
+            FleetState::Idle(idle) => serde_json::json!({
+                "name": "Idle",
+                "sector": idle.sector,
+            }),
+            FleetState::MineAsteroid(mine_asteroid) => {
+                // the mining cycle (until its binding constraint), known for the role's "mining"
+                // asteroids
+                let plan = self
+                    .mining
+                    .iter()
+                    .find(|(_, _, resource)| resource.0 == mine_asteroid.resource)
+                    .map(|(_, mine_item, resource)| {
+                        calc::asteroid_mining_plan(
+                            stats,
+                            &mine_item.1,
+                            &resource.1,
+                            bot.units_fit(&fleet.cargo_hold, &mine_item.1.mint) as u32,
+                            bot.ammo_on_hand(),
+                            bot.food_on_hand(),
+                        )
+                    });
+
+                serde_json::json!({
+                    "name": "MineAsteroid",
+                    "resource": mine_asteroid.resource.to_string(),
+                    "start": mine_asteroid.start,
+                    "mining_duration": plan.map(|plan| plan.max_duration),
+                    "mining_constraint": plan.map(|plan| format!("{:?}", plan.constraint)),
+                })
+            }
+            FleetState::MoveWarp(move_warp) => serde_json::json!({
+                "name": "MoveWarp",
+                "from_sector": move_warp.from_sector,
+                "to_sector": move_warp.to_sector,
+                "warp_finish": move_warp.warp_finish,
+            }),
+            FleetState::MoveSubwarp(move_subwarp) => serde_json::json!({
+                "name": "MoveSubwarp",
+                "from_sector": move_subwarp.from_sector,
+                "to_sector": move_subwarp.to_sector,
+                "arrival_time": move_subwarp.arrival_time,
+            }),
+            FleetState::Respawn(respawn) => serde_json::json!({
+                "name": "Respawn",
+                "sector": respawn.sector,
+            }),
+            FleetState::Unknown { discriminator, .. } => serde_json::json!({
+                "name": "Unknown",
+                "discriminator": discriminator,
+            }),
+        };
+
+        serde_json::json!({
+            "id": fleet_id.to_string(),
+            "state": state,
+            "clock": bot.clock.as_ref().map(|clock| clock.unix_timestamp),
+            "cargo": cargo,
+            "fuel": amount(&bot.fleet_fuel_tank, &self.mints.fuel),
+            "ammo": amount(&bot.fleet_ammo_bank, &self.mints.ammo),
+            "food": amount(&bot.fleet_cargo_hold, &self.mints.food),
+            "stats": {
+                "cargo_capacity": stats.cargo_stats.cargo_capacity,
+                "fuel_capacity": stats.cargo_stats.fuel_capacity,
+                "ammo_capacity": stats.cargo_stats.ammo_capacity,
+                "mining_rate": stats.cargo_stats.mining_rate,
+                "warp_speed": stats.movement_stats.warp_speed,
+                "max_warp_distance": stats.movement_stats.max_warp_distance,
+                "warp_cool_down": stats.movement_stats.warp_cool_down,
+                "warp_fuel_consumption_rate": stats.movement_stats.warp_fuel_consumption_rate,
+            },
+        })
+    }
+
+    fn next_action(&mut self, bot: &BotActor) -> Option<ScriptAction> {
+        self.script.reload(&self.engine);
+        let ast = self.script.ast.as_ref()?;
+
+        let fleet = rhai::serde::to_dynamic(self.fleet_info(bot)).ok()?;
+        let result =
+            self.engine
+                .call_fn::<Dynamic>(&mut Scope::new(), ast, "next_action", (fleet,));
+
+        match result {
+            Ok(action) if action.is_unit() => None,
+            Ok(action) => match rhai::serde::from_dynamic::<ScriptAction>(&action) {
+                Ok(action) => Some(action),
+                Err(err) => {
+                    log::error!(
+                        "Script {}: invalid action {}: {}",
+                        self.script.path,
+                        action,
+                        err
+                    );
+                    None
+                }
+            },
+            Err(err) => {
+                log::error!("Script {}: {}", self.script.path, err);
+                None
+            }
+        }
+    }
+
+    fn dispatch(&mut self, bot: &mut BotActor, action: ScriptAction, addr: Addr<BotActor>) {
+        let (fleet_id, FleetWithState(fleet, state)) = &bot.fleet;
+        let fleet = (*fleet_id, *fleet);
+        let state = state.clone();
+
+        log::info!("Script action: {:?}", action);
+
+        let action = match (action, state) {
+            (ScriptAction::Warp(sector), FleetState::Idle(_)) if !bot.risk_allows(sector) => {
+                log::error!("Script warp to {:?} refused by the risk policy", sector);
+                None
+            }
+            (ScriptAction::Warp(sector), FleetState::Idle(idle)) => {
+                bot.ledger.pending = Some(LedgerEvent::Warped {
+                    fuel: calc::warp_fuel_consumption(&fleet.1.stats, idle.sector, sector) as f64,
+                });
+                Some(SageAction::Warp(fleet, sector, addr))
+            }
+            (ScriptAction::WarpExit, FleetState::MoveWarp(_)) => {
+                Some(SageAction::WarpExit(fleet, addr))
+            }
+            (ScriptAction::Dock, FleetState::Idle(idle)) => {
+                Some(SageAction::StarbaseDock(fleet, idle.sector, addr))
+            }
+            (ScriptAction::Undock, FleetState::StarbaseLoadingBay(loading_bay)) => Some(
+                SageAction::StarbaseUndock(fleet, loading_bay.starbase, addr),
+            ),
+            (
+                ScriptAction::Withdraw { mint, amount },
+                FleetState::StarbaseLoadingBay(loading_bay),
+            ) => Pubkey::from_str(&mint).ok().map(|mint| {
+                bot.ledger.pending = Some(LedgerEvent::Unloaded {
+                    mint,
+                    amount: amount as f64,
+                });
+                SageAction::CargoWithdraw(fleet, loading_bay.starbase, mint, amount, addr)
+            }),
+            (
+                ScriptAction::Deposit { mint, amount },
+                FleetState::StarbaseLoadingBay(loading_bay),
+            ) => Pubkey::from_str(&mint).ok().map(|mint| {
+                let cargo_pod_to = bot.deposit_pod(&mint);
+
+                // no more than the units that fit in the cargo pod
+                let amount = amount.min(bot.units_fit(&cargo_pod_to, &mint));
+
+                SageAction::CargoDeposit(
+                    fleet,
+                    loading_bay.starbase,
+                    cargo_pod_to,
+                    mint,
+                    amount,
+                    addr,
+                )
+            }),
+            (ScriptAction::StartMining(mine_item_id), FleetState::Idle(idle)) => self
+                .mining
+                .iter()
+                .find(|(_, mine_item, _)| mine_item.0.to_string() == mine_item_id)
+                .map(|(planet, mine_item, resource)| {
+                    SageAction::StartMining(
+                        fleet,
+                        mine_item.0,
+                        resource.0,
+                        planet.0,
+                        idle.sector,
+                        addr,
+                    )
+                }),
+            (ScriptAction::StopMining, FleetState::MineAsteroid(mine_asteroid)) => self
+                .mining
+                .iter()
+                .find(|(_, _, resource)| resource.0 == mine_asteroid.resource)
+                .map(|(planet, mine_item, resource)| {
+                    SageAction::StopMining(
+                        fleet,
+                        mine_item.0,
+                        mine_item.1.mint,
+                        resource.0,
+                        planet.0,
+                        planet.1.sector,
+                        addr,
+                    )
+                }),
+            (action, state) => {
+                log::error!("Script action {:?} can't start from {:?}", action, state);
+                None
+            }
+        };
+
+        match action {
+            Some(action) => {
+                bot.addr_sage.do_send(action);
+
+                bot.operation = Some(BotOps::TxsSageBased(TxsSageBasedOps {
+                    stopwatch: timers::Stopwatch::new(),
+                }));
+                self.state = ScriptState::InFlight;
+            }
+            None => self.sleep(),
+        }
+    }
+}
+
+/// An engine without modules, bounded in operations, call depth and data sizes.
+fn sandboxed_engine(max_operations: u64) -> Engine {
+    let mut engine = Engine::new();
+
+    engine.set_module_resolver(rhai::module_resolvers::DummyModuleResolver::new());
+    engine.set_max_operations(max_operations);
+    engine.set_max_call_levels(32);
+    engine.set_max_expr_depths(64, 32);
+    engine.set_max_string_size(4 * 1024);
+    engine.set_max_array_size(1024);
+    engine.set_max_map_size(1024);
+
+    engine.on_print(|text| log::info!("[script] {}", text));
+    engine.on_debug(|text, _, pos| log::debug!("[script] {:?} {}", pos, text));
+
+    engine
+}
+
+impl Role for ScriptRole {
+    fn name(&self) -> &str {
+        "Script"
+    }
+
+    fn mints(&self) -> Vec<Pubkey> {
+        self.mining
+            .iter()
+            .map(|(_, mine_item, _)| mine_item.1.mint)
+            .collect()
+    }
+
+    fn sectors(&self) -> Vec<[i64; 2]> {
+        self.mining
+            .iter()
+            .map(|(planet, _, _)| planet.1.sector)
+            .collect()
+    }
+
+    fn check<'a>(
+        &'a self,
+        _fleet: &'a FleetWithState,
+        _ctx: &'a RoleContext,
+        _risk: Option<&'a RiskPolicy>,
+    ) -> CheckFuture<'a> {
+        Box::pin(async move {
+            let has_next_action = self.script.ast.as_ref().is_some_and(|ast| {
+                ast.iter_functions()
+                    .any(|function| function.name == "next_action")
+            });
+            if !has_next_action {
+                return Err(eyre!("{} has no next_action(fleet)", self.script.path));
+            }
+
+            Ok(vec![("Script".to_string(), self.script.path.clone())])
+        })
+    }
+
+    fn clock_time_update(&mut self, bot: &mut BotActor, msg: ClockTimeUpdate) {
+        bot.clock = Some(msg.0);
+    }
+
+    fn sage_response(&mut self, bot: &mut BotActor, msg: SageResponse, addr: Addr<BotActor>) {
+        match msg {
+            SageResponse::Fleet(fleet_with_state) => {
+                bot.fleet.1 = fleet_with_state;
+
+                if let ScriptState::AwaitingFleet = self.state {
+                    let FleetWithState(fleet, _) = &bot.fleet.1;
+                    bot.addr_sage
+                        .do_send(SageRequest::FleetCargoHold(fleet.cargo_hold, addr));
+                    self.state = ScriptState::AwaitingCargo;
+                }
+            }
+            SageResponse::FleetCargoHold(cargo_hold) => {
+                bot.fleet_cargo_hold = cargo_hold;
+
+                if let ScriptState::AwaitingCargo = self.state {
+                    match self.next_action(bot) {
+                        Some(action) => self.dispatch(bot, action, addr),
+                        None => self.sleep(),
+                    }
+                }
+            }
+            SageResponse::FleetFuelTank(fuel_tank) => {
+                bot.fleet_fuel_tank = fuel_tank;
+            }
+            SageResponse::FleetAmmoBank(ammo_bank) => {
+                bot.fleet_ammo_bank = ammo_bank;
+            }
+            _ => {}
+        }
+    }
+
+    fn tick(&mut self, bot: &mut BotActor, msg: Tick, addr: Addr<BotActor>) {
+        if let ScriptState::Sleeping(timer) = &mut self.state {
+            timer.tick(msg.0);
+
+            if timer.finished() {
+                let (_, FleetWithState(fleet, _)) = &bot.fleet;
+
+                bot.addr_sage.do_send(ClockTimeRequest(addr.clone()));
+                bot.addr_sage
+                    .do_send(SageRequest::FleetFuelTank(fleet.fuel_tank, addr.clone()));
+                bot.addr_sage
+                    .do_send(SageRequest::FleetAmmoBank(fleet.ammo_bank, addr.clone()));
+                bot.request_fleet(addr);
+                self.state = ScriptState::AwaitingFleet;
+            }
+        }
+    }
+
+    fn transaction_result(&mut self, _bot: &mut BotActor, signature: Option<Signature>) {
+        if let ScriptState::InFlight = self.state {
+            if signature.is_none() {
+                log::warn!("Script action failed: {}", self.script.path);
+            }
+            self.sleep();
+        }
+    }
+}
+
+// cargo test -p actix-minebot
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::time::Duration;
+
+    fn script_file(name: &str) -> String {
+        std::env::temp_dir()
+            .join(format!("minebot-{}-{}.rhai", name, std::process::id()))
+            .to_str()
+            .unwrap()
+            .to_string()
+    }
+
+    // an edit in the same second as the previous version gets a later modification time
+    fn edit(path: &str, source: &str, modified: SystemTime) {
+        std::fs::write(path, source).unwrap();
+        let file = std::fs::File::options().write(true).open(path).unwrap();
+        file.set_modified(modified).unwrap();
+    }
+
+    fn has_function(script: &Script, name: &str) -> bool {
+        script
+            .ast
+            .as_ref()
+            .is_some_and(|ast| ast.iter_functions().any(|function| function.name == name))
+    }
+
+    #[test]
+    fn a_runaway_script_is_aborted() {
+        let engine = sandboxed_engine(1_000);
+
+        let result = engine.eval::<Dynamic>("let x = 0; loop { x += 1; }");
+        assert!(matches!(
+            *result.unwrap_err(),
+            rhai::EvalAltResult::ErrorTooManyOperations(_)
+        ));
+
+        // a script within the operations runs
+        assert_eq!(engine.eval::<i64>("40 + 2").unwrap(), 42);
+    }
+
+    #[test]
+    fn a_script_imports_no_modules() {
+        let engine = sandboxed_engine(1_000);
+
+        let result = engine.eval::<Dynamic>(r#"import "std" as std; 1"#);
+        assert!(matches!(
+            *result.unwrap_err(),
+            rhai::EvalAltResult::ErrorModuleNotFound(..)
+        ));
+    }
+
+    #[test]
+    fn a_broken_edit_keeps_the_previous_script() {
+        let path = script_file("reload");
+        let engine = sandboxed_engine(1_000);
+        let start = SystemTime::now();
+
+        edit(&path, "fn next_action(fleet) { \"Dock\" }", start);
+        let mut script = Script {
+            path: path.clone(),
+            modified: None,
+            ast: None,
+        };
+        script.reload(&engine);
+        assert!(has_function(&script, "next_action"));
+
+        edit(
+            &path,
+            "fn next_action(fleet) { ",
+            start + Duration::from_secs(1),
+        );
+        script.reload(&engine);
+        assert!(has_function(&script, "next_action"));
+
+        edit(
+            &path,
+            "fn next_action(fleet) { () }\nfn helper() { 1 }",
+            start + Duration::from_secs(2),
+        );
+        script.reload(&engine);
+        assert!(has_function(&script, "helper"));
+
+        std::fs::remove_file(&path).ok();
+    }
+
+    #[test]
+    fn the_actions_parse_from_the_script() {
+        let engine = sandboxed_engine(1_000);
+        let action = |source: &str| {
+            let action = engine.eval::<Dynamic>(source).unwrap();
+            rhai::serde::from_dynamic::<ScriptAction>(&action)
+        };
+
+        assert!(matches!(action(r#""Dock""#), Ok(ScriptAction::Dock)));
+        assert!(matches!(
+            action("#{ Warp: [-40, 30] }"),
+            Ok(ScriptAction::Warp([-40, 30]))
+        ));
+        assert!(matches!(
+            action(r#"#{ Deposit: #{ mint: "m", amount: 10 } }"#),
+            Ok(ScriptAction::Deposit { amount: 10, .. })
+        ));
+        assert!(action(r#""Fly""#).is_err());
+    }
+}
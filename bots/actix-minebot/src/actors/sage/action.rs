@@ -1,10 +1,23 @@
 use super::*;
 
+use anchor_client::ClientError;
+use staratlas_sage_based_sdk::{BROADCAST_CLIENTS, RPC_BUDGET};
+use staratlas_utils_rpc::{is_endpoint_error, RpcEndpoints};
+
 #[derive(Debug, Message)]
 #[rtype(result = "()")]
 pub enum SageAction {
     CargoDeposit((Pubkey, Fleet), Pubkey, Pubkey, Pubkey, u64, Addr<BotActor>), // ((FleetId, Fleet), Starbase, CargoPodTo, Mint, Amount, Addr<Bot>)
     CargoWithdraw((Pubkey, Fleet), Pubkey, Pubkey, u64, Addr<BotActor>), // ((FleetId, Fleet), Starbase, Mint, Amount, Addr<Bot>)
+    ConsumableRestock(
+        (Pubkey, Fleet),
+        Pubkey,
+        Pubkey,
+        u64,
+        u64,
+        u64,
+        Addr<BotActor>,
+    ), // ((FleetId, Fleet), Starbase, Mint, Amount, MaxPrice, Budget, Addr<Bot>)
     StarbaseDock((Pubkey, Fleet), [i64; 2], Addr<BotActor>), // ((FleetId, Fleet), Sector, Addr<Bot>)
     StarbaseUndock((Pubkey, Fleet), Pubkey, Addr<BotActor>), // ((FleetId, Fleet), Starbase, Addr<Bot>)
     StartMining(
@@ -32,10 +45,16 @@ impl Handler<SageAction> for SageBasedActor {
     type Result = ();
 
     fn handle(&mut self, msg: SageAction, ctx: &mut Context<Self>) -> Self::Result {
+        // the transactions of an action go to one send endpoint (and the broadcast endpoints)
+        let endpoints = self.scheduler.endpoints();
+        let send_url = endpoints.send_url();
+        let broadcast_clients = endpoints.broadcast_clients(&send_url);
+        let send_budget = self.scheduler.budget(Priority::Send);
+
         match msg {
             SageAction::CargoDeposit(fleet, starbase, cargo_pod_to, mint, amount, addr_bot) => {
-                let sage_program = self.client.program(SAGE_ID).unwrap();
-                let cargo_program = self.client.program(CARGO_ID).unwrap();
+                let sage_program = self.program(SAGE_ID, Some(&send_url));
+                let cargo_program = self.program(CARGO_ID, None);
                 let payer = self.payer.clone();
 
                 let game_id = self.game_id.clone();
@@ -56,6 +75,7 @@ impl Handler<SageAction> for SageBasedActor {
                         amount,
                     )
                     .await;
+                    report_send(&endpoints, &send_url, &result);
 
                     let signature = match result {
                         Some(Ok(signature)) => Some(signature),
@@ -72,12 +92,14 @@ impl Handler<SageAction> for SageBasedActor {
                     addr_bot.do_send(Ping(signature));
                 });
 
+                let fut =
+                    RPC_BUDGET.scope(send_budget, BROADCAST_CLIENTS.scope(broadcast_clients, fut));
                 let actor_future = fut.into_actor(self);
                 ctx.spawn(actor_future);
             }
             SageAction::CargoWithdraw(fleet, starbase, mint, amount, addr_bot) => {
-                let sage_program = self.client.program(SAGE_ID).unwrap();
-                let cargo_program = self.client.program(CARGO_ID).unwrap();
+                let sage_program = self.program(SAGE_ID, Some(&send_url));
+                let cargo_program = self.program(CARGO_ID, None);
                 let payer = self.payer.clone();
 
                 let game_id = self.game_id.clone();
@@ -97,6 +119,7 @@ impl Handler<SageAction> for SageBasedActor {
                         amount,
                     )
                     .await;
+                    report_send(&endpoints, &send_url, &result);
 
                     let signature = match result {
                         Some(Ok(signature)) => Some(signature),
@@ -113,12 +136,158 @@ impl Handler<SageAction> for SageBasedActor {
                     addr_bot.do_send(Ping(signature));
                 });
 
+                let fut =
+                    RPC_BUDGET.scope(send_budget, BROADCAST_CLIENTS.scope(broadcast_clients, fut));
+                let actor_future = fut.into_actor(self);
+
+                ctx.spawn(actor_future);
+            }
+            SageAction::ConsumableRestock(
+                fleet,
+                starbase,
+                mint,
+                amount,
+                max_price,
+                budget,
+                addr_bot,
+            ) => {
+                let sage_program = self.program(SAGE_ID, Some(&send_url));
+                let cargo_program = self.program(CARGO_ID, None);
+                let payer = self.payer.clone();
+
+                let game_id = self.game_id.clone();
+                let game = self.game.clone();
+
+                let (_, fleet) = fleet;
+                let player_profile = fleet.owner_profile;
+                let rpc_url = sage_program.async_rpc().url();
+
+                let scheduler = self.scheduler.clone();
+
+                let fut = Box::pin(async move {
+                    // a purchase whose deposit failed waits in the payer's wallet, it's deposited
+                    // (at no ATLAS cost) instead of buying again
+                    let stranded = match SageBasedGameHandler::associated_token_amount(
+                        &sage_program.async_rpc(),
+                        &payer.pubkey(),
+                        &mint,
+                    )
+                    .await
+                    {
+                        Ok(stranded) => stranded.min(amount),
+                        Err(err) => {
+                            log::error!("{:?}", &err);
+                            addr_bot.do_send(Ping(None));
+                            return;
+                        }
+                    };
+
+                    let (quantity, atlas_cost) = if stranded > 0 {
+                        log::info!("Depositing {} {} left in the wallet", stranded, mint);
+                        (stranded, 0)
+                    } else {
+                        // the marketplace reads go out of the SDK (a blocking client)
+                        for _ in 0..sa_marketplace_cli::BUY_EXCHANGE_RPC_CALLS {
+                            scheduler.acquire(Priority::Send).await;
+                        }
+
+                        let exchange = match sa_marketplace_cli::fetch_buy_exchange(
+                            rpc_url,
+                            payer.pubkey(),
+                            mint,
+                            amount,
+                            max_price,
+                            budget,
+                        )
+                        .await
+                        {
+                            Ok(Some(exchange)) => exchange,
+                            Ok(None) => {
+                                log::warn!("No sell order of {} within the restock caps", mint);
+                                addr_bot.do_send(Ping(None));
+                                return;
+                            }
+                            Err(err) => {
+                                log::error!("{:?}", &err);
+                                addr_bot.do_send(Ping(None));
+                                return;
+                            }
+                        };
+
+                        // 1. buy the consumable (to the payer's wallet)
+                        let result = SageBasedGameHandler::simulate_and_send_transaction(
+                            &sage_program,
+                            &payer,
+                            &exchange.instructions,
+                        )
+                        .await;
+                        report_send(&endpoints, &send_url, &result);
+
+                        match result {
+                            Some(Ok(_)) => {}
+                            Some(Err(err)) => {
+                                log::error!("{:?}", &err);
+                                addr_bot.do_send(Ping(None));
+                                return;
+                            }
+                            None => {
+                                log::error!("Simulation failed?");
+                                addr_bot.do_send(Ping(None));
+                                return;
+                            }
+                        }
+
+                        (exchange.quantity, exchange.quantity * exchange.price)
+                    };
+
+                    // 2. deposit to the starbase, the ATLAS is spent even when the deposit fails (the
+                    // purchase is deposited at the next restock)
+                    let result = SageBasedGameHandler::cargo_deposit_to_game(
+                        &sage_program,
+                        &cargo_program,
+                        &payer,
+                        (&game_id, &game),
+                        &player_profile,
+                        &starbase,
+                        &mint,
+                        quantity,
+                    )
+                    .await;
+                    report_send(&endpoints, &send_url, &result);
+
+                    let signature = match result {
+                        Some(Ok(signature)) => Some(signature),
+                        Some(Err(err)) => {
+                            log::error!("{:?}", &err);
+                            None
+                        }
+                        None => {
+                            log::error!("Simulation failed?");
+                            None
+                        }
+                    };
+                    if signature.is_none() {
+                        log::warn!(
+                            "{} {} stay in the wallet until the next restock",
+                            quantity,
+                            mint
+                        );
+                    }
+
+                    addr_bot.do_send(SageResponse::ConsumableRestock(
+                        mint, quantity, atlas_cost, signature,
+                    ));
+                    addr_bot.do_send(Ping(signature));
+                });
+
+                let fut =
+                    RPC_BUDGET.scope(send_budget, BROADCAST_CLIENTS.scope(broadcast_clients, fut));
                 let actor_future = fut.into_actor(self);
 
                 ctx.spawn(actor_future);
             }
             SageAction::StarbaseDock(fleet, sector, addr_bot) => {
-                let program = self.client.program(SAGE_ID).unwrap();
+                let program = self.program(SAGE_ID, Some(&send_url));
                 let payer = self.payer.clone();
 
                 let game_id = self.game_id.clone();
@@ -135,6 +304,7 @@ impl Handler<SageAction> for SageBasedActor {
                         sector,
                     )
                     .await;
+                    report_send(&endpoints, &send_url, &result);
 
                     let signature = match result {
                         Some(Ok(signature)) => Some(signature),
@@ -151,12 +321,14 @@ impl Handler<SageAction> for SageBasedActor {
                     addr_bot.do_send(Ping(signature));
                 });
 
+                let fut =
+                    RPC_BUDGET.scope(send_budget, BROADCAST_CLIENTS.scope(broadcast_clients, fut));
                 let actor_future = fut.into_actor(self);
 
                 ctx.spawn(actor_future);
             }
             SageAction::StarbaseUndock(fleet, starbase, addr_bot) => {
-                let program = self.client.program(SAGE_ID).unwrap();
+                let program = self.program(SAGE_ID, Some(&send_url));
                 let payer = self.payer.clone();
 
                 let game_id = self.game_id.clone();
@@ -173,6 +345,7 @@ impl Handler<SageAction> for SageBasedActor {
                         &starbase,
                     )
                     .await;
+                    report_send(&endpoints, &send_url, &result);
 
                     let signature = match result {
                         Some(Ok(signature)) => Some(signature),
@@ -189,12 +362,14 @@ impl Handler<SageAction> for SageBasedActor {
                     addr_bot.do_send(Ping(signature));
                 });
 
+                let fut =
+                    RPC_BUDGET.scope(send_budget, BROADCAST_CLIENTS.scope(broadcast_clients, fut));
                 let actor_future = fut.into_actor(self);
 
                 ctx.spawn(actor_future);
             }
             SageAction::StartMining(fleet, mine_item, resource, planet, sector, addr_bot) => {
-                let program = self.client.program(SAGE_ID).unwrap();
+                let program = self.program(SAGE_ID, Some(&send_url));
                 let payer = self.payer.clone();
 
                 let game_id = self.game_id.clone();
@@ -214,6 +389,7 @@ impl Handler<SageAction> for SageBasedActor {
                         sector,
                     )
                     .await;
+                    report_send(&endpoints, &send_url, &result);
 
                     let signature = match result {
                         Some(Ok(signature)) => Some(signature),
@@ -230,6 +406,8 @@ impl Handler<SageAction> for SageBasedActor {
                     addr_bot.do_send(Ping(signature));
                 });
 
+                let fut =
+                    RPC_BUDGET.scope(send_budget, BROADCAST_CLIENTS.scope(broadcast_clients, fut));
                 let actor_future = fut.into_actor(self);
 
                 ctx.spawn(actor_future);
@@ -243,7 +421,7 @@ impl Handler<SageAction> for SageBasedActor {
                 sector,
                 addr_bot,
             ) => {
-                let program = self.client.program(SAGE_ID).unwrap();
+                let program = self.program(SAGE_ID, Some(&send_url));
                 let payer = self.payer.clone();
 
                 let game_id = self.game_id.clone();
@@ -264,6 +442,7 @@ impl Handler<SageAction> for SageBasedActor {
                         sector,
                     )
                     .await;
+                    report_send(&endpoints, &send_url, &result);
 
                     let signature = match result {
                         Some(Ok(signature)) => Some(signature),
@@ -280,12 +459,14 @@ impl Handler<SageAction> for SageBasedActor {
                     addr_bot.do_send(Ping(signature));
                 });
 
+                let fut =
+                    RPC_BUDGET.scope(send_budget, BROADCAST_CLIENTS.scope(broadcast_clients, fut));
                 let actor_future = fut.into_actor(self);
 
                 ctx.spawn(actor_future);
             }
             SageAction::Warp(fleet, sector, addr_bot) => {
-                let program = self.client.program(SAGE_ID).unwrap();
+                let program = self.program(SAGE_ID, Some(&send_url));
                 let payer = self.payer.clone();
 
                 let game_id = self.game_id.clone();
@@ -302,6 +483,7 @@ impl Handler<SageAction> for SageBasedActor {
                         sector,
                     )
                     .await;
+                    report_send(&endpoints, &send_url, &result);
 
                     let signature = match result {
                         Some(Ok(signature)) => Some(signature),
@@ -318,12 +500,14 @@ impl Handler<SageAction> for SageBasedActor {
                     addr_bot.do_send(Ping(signature));
                 });
 
+                let fut =
+                    RPC_BUDGET.scope(send_budget, BROADCAST_CLIENTS.scope(broadcast_clients, fut));
                 let actor_future = fut.into_actor(self);
 
                 ctx.spawn(actor_future);
             }
             SageAction::WarpExit(fleet, add_bot) => {
-                let program = self.client.program(SAGE_ID).unwrap();
+                let program = self.program(SAGE_ID, Some(&send_url));
                 let payer = self.payer.clone();
 
                 let game_id = self.game_id.clone();
@@ -339,6 +523,7 @@ impl Handler<SageAction> for SageBasedActor {
                         (&fleet_id, &fleet),
                     )
                     .await;
+                    report_send(&endpoints, &send_url, &result);
 
                     let signature = match result {
                         Some(Ok(signature)) => Some(signature),
@@ -355,6 +540,8 @@ impl Handler<SageAction> for SageBasedActor {
                     add_bot.do_send(Ping(signature));
                 });
 
+                let fut =
+                    RPC_BUDGET.scope(send_budget, BROADCAST_CLIENTS.scope(broadcast_clients, fut));
                 let actor_future = fut.into_actor(self);
 
                 ctx.spawn(actor_future);
@@ -362,3 +549,17 @@ impl Handler<SageAction> for SageBasedActor {
         }
     }
 }
+
+/// Records the outcome of a send on its endpoint, the endpoint errors count toward its error rate
+/// (a failed simulation or program error is the transaction's).
+fn report_send(
+    endpoints: &RpcEndpoints,
+    send_url: &str,
+    result: &Option<Result<Signature, ClientError>>,
+) {
+    match result {
+        Some(Err(err)) if is_endpoint_error(err) => endpoints.report(send_url, false),
+        Some(_) => endpoints.report(send_url, true),
+        None => {}
+    }
+}
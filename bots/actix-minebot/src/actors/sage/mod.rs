@@ -1,41 +1,86 @@
 use super::*;
 
+use anchor_client::{solana_sdk::commitment_config::CommitmentConfig, Cluster};
+
+use std::collections::{HashMap, HashSet};
+
 mod action;
 pub use action::*;
 
 mod request;
 pub use request::*;
 
+mod scheduler;
+pub use scheduler::*;
+
+mod subscriptions;
+pub use subscriptions::*;
+
 pub struct SageBasedActor {
-    client: Client<Rc<Keypair>>,
     payer: Rc<Keypair>,
     game_id: Pubkey,
     game: Game,
+    scheduler: RpcScheduler,
+    subscribe: bool,
+    subscriptions: Subscriptions,
+    cargo_mints: HashSet<Pubkey>,
     subscribers: Vec<Recipient<ClockTimeUpdate>>,
+    programs: HashMap<(String, Pubkey), Rc<Program<Rc<Keypair>>>>, // ((Endpoint Url, ProgramId), Program)
 }
 
 impl SageBasedActor {
     pub fn new(
-        client: Client<Rc<Keypair>>,
         payer: Rc<Keypair>,
         game_id: Pubkey,
         game: Game,
+        scheduler: RpcScheduler,
+        subscribe: bool,
     ) -> Self {
         SageBasedActor {
-            client,
             payer,
             game_id,
             game,
+            scheduler,
+            subscribe,
+            subscriptions: Subscriptions::default(),
+            cargo_mints: HashSet::new(),
             subscribers: vec![],
+            programs: HashMap::new(),
         }
     }
+
+    /// A program of the signer's client on an endpoint (the reads on the next read endpoint),
+    /// built once per endpoint.
+    fn program(&mut self, program_id: Pubkey, url: Option<&str>) -> Rc<Program<Rc<Keypair>>> {
+        let url = match url {
+            Some(url) => url.to_string(),
+            None => self.scheduler.endpoints().read_url(),
+        };
+        let payer = self.payer.clone();
+
+        self.programs
+            .entry((url.clone(), program_id))
+            .or_insert_with(|| {
+                let client = Client::new_with_options(
+                    Cluster::Custom(url.clone(), url),
+                    payer,
+                    CommitmentConfig::confirmed(),
+                );
+                Rc::new(client.program(program_id).unwrap())
+            })
+            .clone()
+    }
 }
 
 impl Actor for SageBasedActor {
     type Context = Context<Self>;
 
-    fn started(&mut self, _ctx: &mut Self::Context) {
+    fn started(&mut self, ctx: &mut Self::Context) {
         log::info!("SageBased Actor started...");
+
+        if self.subscribe {
+            start_subscriptions(self, ctx);
+        }
     }
 }
 
@@ -47,8 +92,7 @@ impl Handler<BlockHeight> for SageBasedActor {
     type Result = ();
 
     fn handle(&mut self, _: BlockHeight, ctx: &mut Context<Self>) -> Self::Result {
-        let program = self.client.program(SAGE_ID).unwrap();
-        let rpc = program.async_rpc();
+        let rpc = self.scheduler.rpc();
 
         let fut = Box::pin(async move {
             let block_height = rpc.get_block_height().await.unwrap();
@@ -74,6 +118,18 @@ impl Handler<SubscribeClockTime> for SageBasedActor {
     }
 }
 
+#[derive(Message)]
+#[rtype(result = "()")]
+pub struct UnsubscribeClockTime(pub Recipient<ClockTimeUpdate>);
+
+impl Handler<UnsubscribeClockTime> for SageBasedActor {
+    type Result = ();
+
+    fn handle(&mut self, msg: UnsubscribeClockTime, _: &mut Self::Context) {
+        self.subscribers.retain(|subscr| subscr != &msg.0);
+    }
+}
+
 #[derive(Message)]
 #[rtype(result = "()")]
 pub struct ClockTime;
@@ -85,12 +141,22 @@ impl Handler<ClockTime> for SageBasedActor {
         use anchor_client::anchor_lang::solana_program::sysvar;
 
         let subscribers = self.subscribers.clone();
-        let program = self.client.program(SAGE_ID).unwrap();
-        let rpc = program.async_rpc();
+        let scheduler = self.scheduler.clone();
 
         let fut = Box::pin(async move {
-            let account = rpc.get_account(&sysvar::clock::id()).await.unwrap();
-            let clock = account.deserialize_data::<Clock>().unwrap();
+            let account = scheduler.get_account(sysvar::clock::id()).await;
+
+            let clock = match account.map(|account| account.deserialize_data::<Clock>()) {
+                Ok(Ok(clock)) => clock,
+                Ok(Err(err)) => {
+                    log::error!("{:?}", &err);
+                    return;
+                }
+                Err(err) => {
+                    log::error!("{:?}", &err);
+                    return;
+                }
+            };
 
             for subscr in subscribers {
                 subscr.do_send(ClockTimeUpdate(clock.clone()));
@@ -114,12 +180,29 @@ impl Handler<ClockTimeRequest> for SageBasedActor {
         use anchor_client::anchor_lang::solana_program::sysvar;
         let addr_bot = msg.0;
 
-        let program = self.client.program(SAGE_ID).unwrap();
-        let rpc = program.async_rpc();
+        // the clock of the subscription (without a request)
+        if let Some(clock) = self.subscriptions.clock() {
+            addr_bot.do_send(ClockTimeUpdate(clock));
+            return;
+        }
+
+        let scheduler = self.scheduler.clone();
 
+        // the clock requests of all bots (within the batch window) share one request
         let fut = Box::pin(async move {
-            let account = rpc.get_account(&sysvar::clock::id()).await.unwrap();
-            let clock = account.deserialize_data::<Clock>().unwrap();
+            let account = scheduler.get_account(sysvar::clock::id()).await;
+
+            let clock = match account.map(|account| account.deserialize_data::<Clock>()) {
+                Ok(Ok(clock)) => clock,
+                Ok(Err(err)) => {
+                    log::error!("{:?}", &err);
+                    return;
+                }
+                Err(err) => {
+                    log::error!("{:?}", &err);
+                    return;
+                }
+            };
 
             addr_bot.do_send(ClockTimeUpdate(clock.clone()));
         });
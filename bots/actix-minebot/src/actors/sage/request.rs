@@ -1,13 +1,18 @@
 use super::*;
 
+use anchor_client::ClientError;
+use borsh::BorshDeserialize;
+
 #[derive(Debug, Message)]
 #[rtype(result = "()")]
 pub enum SageRequest {
-    Fleet(Pubkey, Addr<BotActor>),              // (Fleet, Callback)
-    FleetAmmoBank(Pubkey, Addr<BotActor>),      // (Fleet's Ammo Bank, Callback
-    FleetCargoHold(Pubkey, Addr<BotActor>),     // (Fleet's Cargo Hold, Callback)
-    FleetFuelTank(Pubkey, Addr<BotActor>),      // (Fleet's Fuel Tank, Callback)
-    FleetFoodCargoHold(Pubkey, Addr<BotActor>), // (Fleet's Cargo Hold, Callback)
+    Fleet(Pubkey, Addr<BotActor>),                 // (Fleet, Callback)
+    FleetAmmoBank(Pubkey, Addr<BotActor>),         // (Fleet's Ammo Bank, Callback
+    FleetCargoHold(Pubkey, Addr<BotActor>),        // (Fleet's Cargo Hold, Callback)
+    FleetFuelTank(Pubkey, Addr<BotActor>),         // (Fleet's Fuel Tank, Callback)
+    FleetFoodCargoHold(Pubkey, Addr<BotActor>),    // (Fleet's Cargo Hold, Callback)
+    TransactionFee(Signature, Addr<BotActor>),     // (Transaction's Signature, Callback)
+    StarbaseCargo(Pubkey, Pubkey, Addr<BotActor>), // (PlayerProfile, Starbase, Callback)
 }
 
 #[derive(Message)]
@@ -18,19 +23,28 @@ pub enum SageResponse {
     FleetCargoHold(Vec<(String, u64)>),
     FleetFuelTank(Vec<(String, u64)>),
     FleetFoodCargoHold(Vec<(String, u64)>),
+    TransactionFee(Signature, u64),
+    StarbaseCargo(Vec<(String, u64)>),
+    ConsumableRestock(Pubkey, u64, u64, Option<Signature>), // (Mint, Amount, AtlasCost, Deposit)
 }
 
 impl Handler<SageRequest> for SageBasedActor {
     type Result = ();
 
     fn handle(&mut self, msg: SageRequest, ctx: &mut Context<Self>) -> Self::Result {
-        let program = self.client.program(SAGE_ID).unwrap();
-
         match msg {
             SageRequest::Fleet(fleet_id, addr_bot) => {
+                let scheduler = self.scheduler.clone();
+
                 let fut = async move {
-                    match SageBasedGameHandler::get_fleet_with_state(&program, &fleet_id).await {
-                        Ok((_, fleet_with_state)) => {
+                    let fleet_with_state =
+                        scheduler.get_account(fleet_id).await.and_then(|account| {
+                            FleetWithState::deserialize(&mut account.data.as_slice())
+                                .map_err(|err| Rc::new(err.into()))
+                        });
+
+                    match fleet_with_state {
+                        Ok(fleet_with_state) => {
                             addr_bot.do_send(SageResponse::Fleet(fleet_with_state));
                         }
                         Err(err) => {
@@ -45,12 +59,19 @@ impl Handler<SageRequest> for SageBasedActor {
                 ctx.spawn(actor_future);
             }
             SageRequest::FleetAmmoBank(ammo_bank, addr_bot) => {
-                let rpc = program.async_rpc();
+                let token_amounts =
+                    self.pod_token_amounts(ammo_bank, self.pod_mints(CargoPod::AmmoBank));
 
                 let fut = Box::pin(async move {
-                    let token_accounts =
-                        SageBasedGameHandler::parsed_token_account_amounts(&rpc, &ammo_bank).await;
-                    addr_bot.do_send(SageResponse::FleetAmmoBank(token_accounts));
+                    match token_amounts.await {
+                        Ok(token_amounts) => {
+                            addr_bot.do_send(SageResponse::FleetAmmoBank(token_amounts));
+                        }
+                        Err(err) => {
+                            log::error!("{:?}", &err);
+                            addr_bot.do_send(Ping(None));
+                        }
+                    }
                 });
 
                 let actor_future = fut.into_actor(self);
@@ -58,45 +79,134 @@ impl Handler<SageRequest> for SageBasedActor {
                 ctx.spawn(actor_future);
             }
             SageRequest::FleetCargoHold(cargo_hold, addr_bot) => {
-                let rpc = program.async_rpc();
+                let scheduler = self.scheduler.clone();
+                let rpc = scheduler.rpc();
+
+                // every token account of the cargo hold (any mint, one request)
+                let fut = async move {
+                    let read_key = format!("token_accounts:{}", cargo_hold);
+                    let read = scheduler.read(read_key, async move {
+                        SageBasedGameHandler::parsed_token_account_amounts(&rpc, &cargo_hold)
+                            .await
+                            .map_err(Rc::new)
+                    });
+
+                    read.await
+                };
+
+                // ...the mints found join the batched reads of the cargo hold (subscriptions)
+                let actor_future =
+                    fut.into_actor(self)
+                        .map(move |token_amounts, act, _| match token_amounts {
+                            Ok(token_amounts) => {
+                                act.cargo_mints.extend(
+                                    token_amounts
+                                        .iter()
+                                        .filter_map(|(mint, _)| Pubkey::from_str(mint).ok()),
+                                );
+                                addr_bot.do_send(SageResponse::FleetCargoHold(token_amounts));
+                            }
+                            Err(err) => {
+                                log::error!("{:?}", &err);
+                                addr_bot.do_send(Ping(None));
+                            }
+                        });
+
+                ctx.spawn(actor_future);
+            }
+            SageRequest::FleetFuelTank(fuel_tank, addr_bot) => {
+                let token_amounts =
+                    self.pod_token_amounts(fuel_tank, self.pod_mints(CargoPod::FuelTank));
 
                 let fut = Box::pin(async move {
-                    let token_accounts =
-                        SageBasedGameHandler::parsed_token_account_amounts(&rpc, &cargo_hold).await;
-                    addr_bot.do_send(SageResponse::FleetCargoHold(token_accounts));
+                    match token_amounts.await {
+                        Ok(token_amounts) => {
+                            addr_bot.do_send(SageResponse::FleetFuelTank(token_amounts));
+                        }
+                        Err(err) => {
+                            log::error!("{:?}", &err);
+                            addr_bot.do_send(Ping(None));
+                        }
+                    }
                 });
 
                 let actor_future = fut.into_actor(self);
 
                 ctx.spawn(actor_future);
             }
-            SageRequest::FleetFuelTank(fuel_tank, addr_bot) => {
-                let rpc = program.async_rpc();
+            SageRequest::FleetFoodCargoHold(cargo_hold, addr_bot) => {
+                let token_amounts = self.pod_token_amounts(cargo_hold, vec![self.game.mints.food]);
 
                 let fut = Box::pin(async move {
-                    let token_accounts =
-                        SageBasedGameHandler::parsed_token_account_amounts(&rpc, &fuel_tank).await;
-                    addr_bot.do_send(SageResponse::FleetFuelTank(token_accounts));
+                    match token_amounts.await {
+                        Ok(food_token_amounts) => {
+                            addr_bot.do_send(SageResponse::FleetFoodCargoHold(food_token_amounts));
+                        }
+                        Err(err) => {
+                            log::error!("{:?}", &err);
+                            addr_bot.do_send(Ping(None));
+                        }
+                    }
                 });
 
                 let actor_future = fut.into_actor(self);
 
                 ctx.spawn(actor_future);
             }
-            SageRequest::FleetFoodCargoHold(cargo_hold, addr_bot) => {
-                let rpc = program.async_rpc();
-                let game = self.game.clone();
+            SageRequest::TransactionFee(signature, addr_bot) => {
+                let scheduler = self.scheduler.clone();
+                let rpc = scheduler.rpc();
 
                 let fut = Box::pin(async move {
-                    let token_accounts =
-                        SageBasedGameHandler::parsed_token_account_amounts(&rpc, &cargo_hold).await;
+                    let read_key = format!("transaction_fee:{}", signature);
+                    let read = scheduler.read(read_key, async move {
+                        SageBasedGameHandler::get_transaction_fee(&rpc, &signature)
+                            .await
+                            .map_err(Rc::new)
+                    });
+
+                    match read.await {
+                        Ok(fee) => {
+                            addr_bot.do_send(SageResponse::TransactionFee(signature, fee));
+                        }
+                        Err(err) => {
+                            log::error!("{:?}", &err);
+                        }
+                    }
+                });
+
+                let actor_future = fut.into_actor(self);
 
-                    let food_token_accounts: Vec<(String, u64)> = token_accounts
-                        .into_iter()
-                        .filter(|(mint, _amount)| mint == &game.mints.food.to_string())
-                        .collect();
+                ctx.spawn(actor_future);
+            }
+            SageRequest::StarbaseCargo(player_profile, starbase, addr_bot) => {
+                let cargo_program = self.program(CARGO_ID, None);
+                let game_id = self.game_id.clone();
 
-                    addr_bot.do_send(SageResponse::FleetFoodCargoHold(food_token_accounts));
+                let scheduler = self.scheduler.clone();
+
+                let fut = Box::pin(async move {
+                    let read_key = format!("starbase_cargo:{}:{}", player_profile, starbase);
+                    let read = scheduler.read(read_key, async move {
+                        SageBasedGameHandler::starbase_cargo_amounts(
+                            &cargo_program,
+                            &game_id,
+                            &player_profile,
+                            &starbase,
+                        )
+                        .await
+                        .map_err(Rc::new)
+                    });
+
+                    match read.await {
+                        Ok(token_accounts) => {
+                            addr_bot.do_send(SageResponse::StarbaseCargo(token_accounts));
+                        }
+                        Err(err) => {
+                            log::error!("{:?}", &err);
+                            addr_bot.do_send(Ping(None));
+                        }
+                    }
                 });
 
                 let actor_future = fut.into_actor(self);
@@ -106,3 +216,67 @@ impl Handler<SageRequest> for SageBasedActor {
         }
     }
 }
+
+#[derive(Message)]
+#[rtype(result = "()")]
+pub struct CargoMints(pub Vec<Pubkey>); // the mints of a bot's role (in its cargo hold)
+
+impl Handler<CargoMints> for SageBasedActor {
+    type Result = ();
+
+    fn handle(&mut self, msg: CargoMints, _: &mut Self::Context) {
+        self.cargo_mints.extend(msg.0);
+    }
+}
+
+impl SageBasedActor {
+    /// The mints of a cargo pod's token accounts (the cargo hold's from the game, the roles and
+    /// the cargo hold's reads).
+    pub(super) fn pod_mints(&self, cargo_pod: CargoPod) -> Vec<Pubkey> {
+        let mints = &self.game.mints;
+
+        match cargo_pod {
+            CargoPod::CargoHold => {
+                let cargo_mints: Vec<Pubkey> = self.cargo_mints.iter().copied().collect();
+                FleetInventory::cargo_hold_mints(mints, &cargo_mints)
+            }
+            CargoPod::FuelTank => vec![mints.fuel],
+            CargoPod::AmmoBank => vec![mints.ammo],
+        }
+    }
+
+    /// The token amounts of the mints in a cargo pod, its (associated) token accounts are
+    /// batched with the other account reads (one `getMultipleAccounts`).
+    pub(super) fn pod_token_amounts(
+        &self,
+        cargo_pod: Pubkey,
+        mints: Vec<Pubkey>,
+    ) -> impl std::future::Future<Output = RpcResult<Vec<(String, u64)>>> {
+        let scheduler = self.scheduler.clone();
+
+        async move {
+            let token_accounts = PodInventory::token_accounts(&cargo_pod, &mints);
+            let accounts = futures::future::join_all(
+                token_accounts
+                    .into_iter()
+                    .map(|token_account| scheduler.get_account(token_account)),
+            )
+            .await;
+
+            let accounts = accounts
+                .into_iter()
+                .map(|account| match account {
+                    Ok(account) => Ok(Some(account)),
+                    Err(err) if matches!(*err, ClientError::AccountNotFound) => Ok(None),
+                    Err(err) => Err(err),
+                })
+                .collect::<RpcResult<Vec<_>>>()?;
+
+            let amounts = PodInventory::decode_amounts(&mints, &accounts);
+            Ok(amounts
+                .into_iter()
+                .map(|(mint, amount)| (mint.to_string(), amount))
+                .collect())
+        }
+    }
+}
@@ -0,0 +1,353 @@
+use super::*;
+
+use anchor_client::{
+    solana_client::nonblocking::rpc_client::RpcClient,
+    solana_sdk::{account::Account, commitment_config::CommitmentConfig},
+    ClientError,
+};
+use futures::future::{FutureExt, LocalBoxFuture, Shared};
+use tokio::sync::oneshot;
+use tokio::time::{self, Duration, Instant};
+
+use std::any::Any;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::future::Future;
+
+use staratlas_sage_based_sdk::{RpcBudget, MAX_MULTIPLE_ACCOUNTS, RPC_BUDGET};
+use staratlas_utils_rpc::RpcEndpoints;
+
+use crate::config::RpcCfg;
+
+pub type RpcResult<T> = Result<T, Rc<ClientError>>;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Priority {
+    Send, // transactions (simulate and send), served first
+    Read,
+}
+
+/// RPC requests of the Sage Based actors, shared by all signers (one provider, one budget).
+///
+/// Requests are limited to a requests-per-second budget (sends before reads) charged per RPC call,
+/// identical reads in flight are coalesced and account reads within the batch window go out as
+/// one `getMultipleAccounts`.
+#[derive(Clone)]
+pub struct RpcScheduler(Rc<RefCell<SchedulerState>>);
+
+struct SchedulerState {
+    endpoints: RpcEndpoints,
+    rpcs: HashMap<String, Rc<RpcClient>>, // (Endpoint Url, Client)
+    requests_per_second: f64,
+    batch_window: Duration,
+    budget: f64,
+    refilled_at: Instant,
+    waiting_sends: usize,
+    in_flight: HashMap<String, Box<dyn Any>>, // (Read Key, Shared Future)
+    pending_accounts: Vec<(Pubkey, oneshot::Sender<RpcResult<Account>>)>,
+}
+
+impl SchedulerState {
+    fn refill(&mut self) {
+        let now = Instant::now();
+        let elapsed = now.duration_since(self.refilled_at).as_secs_f64();
+
+        // the budget bursts up to one second of requests
+        self.budget =
+            (self.budget + elapsed * self.requests_per_second).min(self.requests_per_second);
+        self.refilled_at = now;
+    }
+}
+
+/// A send waiting for the budget (the reads wait until it's served, or dropped).
+struct WaitingSend(Rc<RefCell<SchedulerState>>);
+
+impl WaitingSend {
+    fn new(state: Rc<RefCell<SchedulerState>>) -> Self {
+        state.borrow_mut().waiting_sends += 1;
+        WaitingSend(state)
+    }
+}
+
+impl Drop for WaitingSend {
+    fn drop(&mut self) {
+        self.0.borrow_mut().waiting_sends -= 1;
+    }
+}
+
+impl RpcScheduler {
+    pub fn new(endpoints: RpcEndpoints, rpc_cfg: &RpcCfg) -> Self {
+        let requests_per_second = rpc_cfg.requests_per_second.max(1.0);
+
+        RpcScheduler(Rc::new(RefCell::new(SchedulerState {
+            endpoints,
+            rpcs: HashMap::new(),
+            requests_per_second,
+            batch_window: Duration::from_millis(rpc_cfg.batch_window_ms),
+            budget: requests_per_second,
+            refilled_at: Instant::now(),
+            waiting_sends: 0,
+            in_flight: HashMap::new(),
+            pending_accounts: vec![],
+        })))
+    }
+
+    pub fn endpoints(&self) -> RpcEndpoints {
+        self.0.borrow().endpoints.clone()
+    }
+
+    /// The client of the next read endpoint (the healthy endpoints, by weight).
+    pub fn rpc(&self) -> Rc<RpcClient> {
+        let mut state = self.0.borrow_mut();
+        let url = state.endpoints.read_url();
+
+        state
+            .rpcs
+            .entry(url.clone())
+            .or_insert_with(|| {
+                Rc::new(RpcClient::new_with_commitment(
+                    url,
+                    CommitmentConfig::confirmed(),
+                ))
+            })
+            .clone()
+    }
+
+    /// Waits for one request of the budget, the reads yield to the waiting sends.
+    pub async fn acquire(&self, priority: Priority) {
+        let _waiting_send = (priority == Priority::Send).then(|| WaitingSend::new(self.0.clone()));
+
+        loop {
+            let wait = {
+                let mut state = self.0.borrow_mut();
+                state.refill();
+
+                // a waiting send counts itself (the reads yield to any send)
+                let yields = priority == Priority::Read && state.waiting_sends > 0;
+
+                if !yields && state.budget >= 1.0 {
+                    state.budget -= 1.0;
+                    return;
+                }
+
+                Duration::from_secs_f64(1.0 / state.requests_per_second)
+            };
+
+            time::sleep(wait).await;
+        }
+    }
+
+    /// The budget of the SDK's RPC calls (`RPC_BUDGET.scope`), one request per call.
+    pub fn budget(&self, priority: Priority) -> RpcBudget {
+        let scheduler = self.clone();
+
+        Rc::new(move || {
+            let scheduler = scheduler.clone();
+            Box::pin(async move { scheduler.acquire(priority).await })
+        })
+    }
+
+    /// Shares the result of an identical read in flight (same key) instead of a new request.
+    pub async fn coalesce<T, F>(&self, key: String, fut: F) -> T
+    where
+        T: Clone + 'static,
+        F: Future<Output = T> + 'static,
+    {
+        let in_flight = self
+            .0
+            .borrow()
+            .in_flight
+            .get(&key)
+            .and_then(|shared| shared.downcast_ref::<Shared<LocalBoxFuture<'static, T>>>())
+            .cloned();
+
+        let shared = match in_flight {
+            Some(shared) => shared,
+            None => {
+                let state = self.0.clone();
+                let read_key = key.clone();
+
+                let shared = async move {
+                    let result = fut.await;
+                    state.borrow_mut().in_flight.remove(&read_key);
+                    result
+                }
+                .boxed_local()
+                .shared();
+
+                self.0
+                    .borrow_mut()
+                    .in_flight
+                    .insert(key, Box::new(shared.clone()));
+                shared
+            }
+        };
+
+        shared.await
+    }
+
+    /// A coalesced read of the SDK, each of its RPC calls within the budget.
+    pub async fn read<T, F>(&self, key: String, fut: F) -> T
+    where
+        T: Clone + 'static,
+        F: Future<Output = T> + 'static,
+    {
+        let budget = self.budget(Priority::Read);
+
+        self.coalesce(key, RPC_BUDGET.scope(budget, fut)).await
+    }
+
+    /// An account, batched with the other accounts requested within the batch window.
+    pub async fn get_account(&self, pubkey: Pubkey) -> RpcResult<Account> {
+        let scheduler = self.clone();
+
+        self.coalesce(format!("account:{}", pubkey), async move {
+            let (tx, rx) = oneshot::channel();
+
+            let first_pending = {
+                let mut state = scheduler.0.borrow_mut();
+                state.pending_accounts.push((pubkey, tx));
+                state.pending_accounts.len() == 1
+            };
+
+            if first_pending {
+                actix::spawn(scheduler.clone().flush_accounts());
+            }
+
+            rx.await
+                .unwrap_or_else(|_| Err(Rc::new(ClientError::AccountNotFound)))
+        })
+        .await
+    }
+
+    async fn flush_accounts(self) {
+        let batch_window = self.0.borrow().batch_window;
+        time::sleep(batch_window).await;
+
+        let mut pending = std::mem::take(&mut self.0.borrow_mut().pending_accounts);
+        let endpoints = self.endpoints();
+
+        while !pending.is_empty() {
+            let batch: Vec<_> = pending
+                .drain(..pending.len().min(MAX_MULTIPLE_ACCOUNTS))
+                .collect();
+            let pubkeys: Vec<Pubkey> = batch.iter().map(|(pubkey, _)| *pubkey).collect();
+
+            self.acquire(Priority::Read).await;
+
+            let rpc = self.rpc();
+            let result = rpc.get_multiple_accounts(&pubkeys).await;
+            endpoints.report(&rpc.url(), result.is_ok());
+
+            match result {
+                Ok(accounts) => {
+                    for ((_, tx), account) in batch.into_iter().zip(accounts) {
+                        let _ = tx.send(account.ok_or(Rc::new(ClientError::AccountNotFound)));
+                    }
+                }
+                Err(err) => {
+                    let err = Rc::new(ClientError::from(err));
+
+                    for (_, tx) in batch {
+                        let _ = tx.send(Err(err.clone()));
+                    }
+                }
+            }
+        }
+    }
+}
+
+// cargo test -p actix-minebot
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use staratlas_utils_rpc::EndpointsCfg;
+
+    use std::cell::Cell;
+
+    const URL: &str = "http://localhost:8899";
+
+    /// A scheduler of one endpoint answered by a mock client.
+    fn scheduler(batch_window_ms: u64) -> RpcScheduler {
+        let rpc_cfg = RpcCfg {
+            batch_window_ms,
+            ..Default::default()
+        };
+        let endpoints = RpcEndpoints::new(EndpointsCfg::parse_list(URL).unwrap()).unwrap();
+
+        let scheduler = RpcScheduler::new(endpoints, &rpc_cfg);
+        scheduler.0.borrow_mut().rpcs.insert(
+            URL.to_string(),
+            Rc::new(RpcClient::new_mock(URL.to_string())),
+        );
+        scheduler
+    }
+
+    fn budget(scheduler: &RpcScheduler) -> f64 {
+        let mut state = scheduler.0.borrow_mut();
+        state.refilled_at = Instant::now(); // no refill within the test
+        state.budget
+    }
+
+    #[actix::test]
+    async fn coalesces_identical_reads_in_flight() {
+        let scheduler = scheduler(0);
+        let calls = Rc::new(Cell::new(0));
+
+        let read = |key: &str| {
+            let calls = calls.clone();
+            scheduler.coalesce(key.to_string(), async move {
+                calls.set(calls.get() + 1);
+                let call = calls.get();
+                time::sleep(Duration::from_millis(10)).await;
+                call
+            })
+        };
+
+        let (a, b, c) = futures::join!(read("account:a"), read("account:a"), read("account:b"));
+        assert_eq!(calls.get(), 2);
+        assert_eq!(a, b);
+        assert_ne!(a, c);
+
+        // a completed read isn't shared anymore
+        assert_eq!(read("account:a").await, 3);
+        assert!(scheduler.0.borrow().in_flight.is_empty());
+    }
+
+    #[actix::test]
+    async fn batches_the_accounts_of_the_window() {
+        let scheduler = scheduler(20);
+        let (a, b) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let budget_before = budget(&scheduler);
+
+        let (account_a, account_a2, account_b) = futures::join!(
+            scheduler.get_account(a),
+            scheduler.get_account(a),
+            scheduler.get_account(b)
+        );
+
+        // one getMultipleAccounts (the mock finds none of the accounts)
+        assert!((budget_before - budget(&scheduler) - 1.0).abs() < 0.1);
+        for account in [account_a, account_a2, account_b] {
+            assert!(matches!(
+                *account.unwrap_err(),
+                ClientError::AccountNotFound
+            ));
+        }
+        assert!(scheduler.0.borrow().pending_accounts.is_empty());
+    }
+
+    #[actix::test]
+    async fn charges_each_call_of_a_budget() {
+        let scheduler = scheduler(0);
+        let budget_before = budget(&scheduler);
+
+        let rpc_budget = scheduler.budget(Priority::Send);
+        for _ in 0..3 {
+            rpc_budget().await;
+        }
+
+        assert!((budget_before - budget(&scheduler) - 3.0).abs() < 0.1);
+    }
+}
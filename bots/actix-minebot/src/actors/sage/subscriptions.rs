@@ -0,0 +1,558 @@
+use super::*;
+
+use anchor_client::{
+    anchor_lang::solana_program::sysvar,
+    solana_client::{
+        nonblocking::pubsub_client::{PubsubClient, PubsubClientError},
+        rpc_config::RpcAccountInfoConfig,
+    },
+    solana_sdk::{account::Account, commitment_config::CommitmentConfig},
+};
+use borsh::BorshDeserialize;
+use futures::{
+    future::BoxFuture,
+    stream::{BoxStream, SelectAll, StreamExt},
+};
+use solana_account_decoder::{UiAccount, UiAccountEncoding};
+use tokio::sync::mpsc;
+use tokio::time::{self, Duration, Instant};
+
+use std::collections::{HashMap, HashSet};
+
+use staratlas_utils_rpc::RpcEndpoints;
+
+/// The cached clock answers the clock requests while its subscription is live.
+const CLOCK_MAX_AGE: Duration = Duration::from_secs(5);
+const MAX_RECONNECT_DELAY: Duration = Duration::from_secs(60);
+/// Delay of the next subscription of the accounts the endpoint refused (on the same connection).
+const SUBSCRIBE_RETRY_DELAY: Duration = Duration::from_secs(10);
+
+type UnsubscribeFn = Box<dyn FnOnce() -> BoxFuture<'static, ()> + Send>;
+
+#[derive(Clone, Copy, Debug)]
+pub enum CargoPod {
+    CargoHold,
+    FuelTank,
+    AmmoBank,
+}
+
+enum Watch {
+    Subscribe(Pubkey),
+    Unsubscribe(Pubkey),
+}
+
+/// Account subscriptions (websocket) of the bots' fleets, their cargo pods and the clock.
+#[derive(Default)]
+pub(crate) struct Subscriptions {
+    watch: Option<mpsc::UnboundedSender<Watch>>,
+    fleets: HashMap<Pubkey, Addr<BotActor>>, // (Fleet, Bot)
+    cargo_pods: HashMap<Pubkey, (CargoPod, Addr<BotActor>)>, // (Cargo Pod, (Kind, Bot))
+    clock: Option<(Clock, Instant)>,
+}
+
+impl Subscriptions {
+    fn watch(&self, watch: Watch) {
+        if let Some(tx) = &self.watch {
+            let _ = tx.send(watch);
+        }
+    }
+
+    /// The clock of the live subscription (a recent notification).
+    pub(crate) fn clock(&self) -> Option<Clock> {
+        match &self.clock {
+            Some((clock, received)) if received.elapsed() < CLOCK_MAX_AGE => Some(clock.clone()),
+            _ => None,
+        }
+    }
+}
+
+/// Starts the websocket subscriptions of the Sage Based actor (reconnects and resubscribes).
+pub(crate) fn start_subscriptions(actor: &mut SageBasedActor, ctx: &mut Context<SageBasedActor>) {
+    let (tx, rx) = mpsc::unbounded_channel();
+    actor.subscriptions.watch = Some(tx);
+
+    let endpoints = actor.scheduler.endpoints();
+    let events = ctx.address().recipient();
+
+    actix::spawn(run_subscriptions(
+        endpoints,
+        events,
+        rx,
+        SUBSCRIBE_RETRY_DELAY,
+    ));
+}
+
+fn ws_url(url: &str) -> String {
+    if let Some(url) = url.strip_prefix("https://") {
+        format!("wss://{}", url)
+    } else if let Some(url) = url.strip_prefix("http://") {
+        format!("ws://{}", url)
+    } else {
+        url.to_string()
+    }
+}
+
+/// Subscribes to an account, the refused subscriptions are retried (`failed`). `false` once the
+/// connection is lost.
+async fn subscribe<'a>(
+    client: &'a PubsubClient,
+    pubkey: Pubkey,
+    streams: &mut SelectAll<BoxStream<'a, (Pubkey, UiAccount)>>,
+    unsubscribes: &mut HashMap<Pubkey, UnsubscribeFn>,
+    failed: &mut HashSet<Pubkey>,
+    events: &Recipient<SubscriptionEvent>,
+) -> bool {
+    let config = RpcAccountInfoConfig {
+        encoding: Some(UiAccountEncoding::Base64),
+        commitment: Some(CommitmentConfig::confirmed()),
+        ..Default::default()
+    };
+
+    match client.account_subscribe(&pubkey, Some(config)).await {
+        Ok((stream, unsubscribe)) => {
+            streams.push(stream.map(move |response| (pubkey, response.value)).boxed());
+            unsubscribes.insert(pubkey, unsubscribe);
+            events.do_send(SubscriptionEvent::Live(pubkey));
+            true
+        }
+        Err(err @ PubsubClientError::SubscribeFailed { .. }) => {
+            log::error!("Subscription of {}: {:?}", pubkey, err);
+            failed.insert(pubkey);
+            true
+        }
+        Err(err) => {
+            log::error!("Subscription of {}: {:?}", pubkey, err);
+            failed.insert(pubkey);
+            false
+        }
+    }
+}
+
+async fn run_subscriptions(
+    endpoints: RpcEndpoints,
+    events: Recipient<SubscriptionEvent>,
+    mut watch_rx: mpsc::UnboundedReceiver<Watch>,
+    retry_delay: Duration,
+) {
+    let mut watched = HashSet::from([sysvar::clock::id()]);
+    let mut reconnect_delay = Duration::from_secs(1);
+
+    loop {
+        let url = ws_url(&endpoints.read_url());
+
+        match PubsubClient::new(&url).await {
+            Ok(client) => {
+                log::info!("Subscriptions connected: {}", url);
+                reconnect_delay = Duration::from_secs(1);
+
+                let mut streams = SelectAll::new();
+                let mut unsubscribes = HashMap::new();
+                let mut failed = HashSet::new();
+                let mut connected = true;
+
+                for pubkey in &watched {
+                    connected &= subscribe(
+                        &client,
+                        *pubkey,
+                        &mut streams,
+                        &mut unsubscribes,
+                        &mut failed,
+                        &events,
+                    )
+                    .await;
+                }
+
+                let mut retry = time::interval_at(Instant::now() + retry_delay, retry_delay);
+
+                while connected {
+                    tokio::select! {
+                        notification = streams.next(), if !streams.is_empty() => match notification {
+                            Some((pubkey, ui_account)) => {
+                                if let Some(account) = ui_account.decode::<Account>() {
+                                    events.do_send(SubscriptionEvent::Account(pubkey, account));
+                                }
+                            }
+                            None => connected = false,
+                        },
+                        _ = retry.tick(), if !failed.is_empty() => {
+                            for pubkey in std::mem::take(&mut failed) {
+                                connected &= subscribe(
+                                    &client,
+                                    pubkey,
+                                    &mut streams,
+                                    &mut unsubscribes,
+                                    &mut failed,
+                                    &events,
+                                )
+                                .await;
+                            }
+                        }
+                        watch = watch_rx.recv() => match watch {
+                            Some(Watch::Subscribe(pubkey)) => {
+                                if watched.insert(pubkey) {
+                                    connected = subscribe(
+                                        &client,
+                                        pubkey,
+                                        &mut streams,
+                                        &mut unsubscribes,
+                                        &mut failed,
+                                        &events,
+                                    )
+                                    .await;
+                                }
+                            }
+                            Some(Watch::Unsubscribe(pubkey)) => {
+                                watched.remove(&pubkey);
+                                failed.remove(&pubkey);
+                                if let Some(unsubscribe) = unsubscribes.remove(&pubkey) {
+                                    unsubscribe().await;
+                                }
+                            }
+                            None => return, // the actor stopped
+                        },
+                    }
+                }
+
+                events.do_send(SubscriptionEvent::Down);
+                log::warn!("Subscriptions disconnected: {}", url);
+            }
+            Err(err) => {
+                log::warn!("Subscriptions of {}: {:?}", url, err);
+            }
+        }
+
+        time::sleep(reconnect_delay).await;
+        reconnect_delay = (reconnect_delay * 2).min(MAX_RECONNECT_DELAY);
+    }
+}
+
+#[derive(Message)]
+#[rtype(result = "()")]
+pub struct SubscribeFleet(pub Pubkey, pub Fleet, pub Addr<BotActor>); // (FleetId, Fleet, Bot)
+
+impl Handler<SubscribeFleet> for SageBasedActor {
+    type Result = ();
+
+    fn handle(&mut self, msg: SubscribeFleet, _: &mut Self::Context) {
+        let SubscribeFleet(fleet_id, fleet, addr_bot) = msg;
+
+        let cargo_pods = [
+            (fleet.cargo_hold, CargoPod::CargoHold),
+            (fleet.fuel_tank, CargoPod::FuelTank),
+            (fleet.ammo_bank, CargoPod::AmmoBank),
+        ];
+
+        for (cargo_pod, kind) in cargo_pods {
+            self.subscriptions
+                .cargo_pods
+                .insert(cargo_pod, (kind, addr_bot.clone()));
+            self.subscriptions.watch(Watch::Subscribe(cargo_pod));
+        }
+
+        self.subscriptions.fleets.insert(fleet_id, addr_bot);
+        self.subscriptions.watch(Watch::Subscribe(fleet_id));
+    }
+}
+
+#[derive(Message)]
+#[rtype(result = "()")]
+pub struct UnsubscribeFleet(pub Pubkey, pub Fleet); // (FleetId, Fleet)
+
+impl Handler<UnsubscribeFleet> for SageBasedActor {
+    type Result = ();
+
+    fn handle(&mut self, msg: UnsubscribeFleet, _: &mut Self::Context) {
+        let UnsubscribeFleet(fleet_id, fleet) = msg;
+
+        for cargo_pod in [fleet.cargo_hold, fleet.fuel_tank, fleet.ammo_bank] {
+            self.subscriptions.cargo_pods.remove(&cargo_pod);
+            self.subscriptions.watch(Watch::Unsubscribe(cargo_pod));
+        }
+
+        self.subscriptions.fleets.remove(&fleet_id);
+        self.subscriptions.watch(Watch::Unsubscribe(fleet_id));
+    }
+}
+
+/// An event of the subscriptions: an account subscribed, the connection lost (every account
+/// is subscribed again on the next connection) or an account's notification.
+#[derive(Debug, Message)]
+#[rtype(result = "()")]
+pub(crate) enum SubscriptionEvent {
+    Live(Pubkey),
+    Down,
+    Account(Pubkey, Account),
+}
+
+impl Handler<SubscriptionEvent> for SageBasedActor {
+    type Result = ();
+
+    fn handle(&mut self, msg: SubscriptionEvent, ctx: &mut Self::Context) {
+        let (pubkey, account) = match msg {
+            SubscriptionEvent::Live(pubkey) => {
+                if let Some(addr_bot) = self.subscriptions.fleets.get(&pubkey) {
+                    addr_bot.do_send(FleetSubscription(true));
+                }
+                return;
+            }
+            SubscriptionEvent::Down => {
+                for addr_bot in self.subscriptions.fleets.values() {
+                    addr_bot.do_send(FleetSubscription(false));
+                }
+                return;
+            }
+            SubscriptionEvent::Account(pubkey, account) => (pubkey, account),
+        };
+        if pubkey == sysvar::clock::id() {
+            if let Ok(clock) = account.deserialize_data::<Clock>() {
+                self.subscriptions.clock = Some((clock, Instant::now()));
+            }
+            return;
+        }
+
+        if let Some(addr_bot) = self.subscriptions.fleets.get(&pubkey) {
+            match FleetWithState::deserialize(&mut account.data.as_slice()) {
+                Ok(fleet_with_state) => addr_bot.do_send(FleetChanged(fleet_with_state)),
+                Err(err) => log::error!("{:?}", &err),
+            }
+            return;
+        }
+
+        if let Some((kind, addr_bot)) = self.subscriptions.cargo_pods.get(&pubkey) {
+            let (kind, addr_bot) = (*kind, addr_bot.clone());
+            let token_amounts = self.pod_token_amounts(pubkey, self.pod_mints(kind));
+
+            let fut = Box::pin(async move {
+                match token_amounts.await {
+                    Ok(token_amounts) => addr_bot.do_send(CargoChanged(kind, token_amounts)),
+                    Err(err) => log::error!("{:?}", &err),
+                }
+            });
+
+            ctx.spawn(fut.into_actor(self));
+        }
+    }
+}
+
+// cargo test -p actix-minebot
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use futures::SinkExt;
+    use serde_json::json;
+    use staratlas_utils_rpc::EndpointsCfg;
+    use tokio::net::TcpListener;
+    use tokio_tungstenite::{accept_async, tungstenite::Message as WsMessage};
+
+    use std::str::FromStr;
+    use std::sync::{Arc, Mutex};
+
+    type SubscribeLog = Arc<Mutex<Vec<(usize, Pubkey, bool)>>>; // (Connection, Account, Subscribed)
+
+    /// A websocket endpoint of account subscriptions: it refuses the first subscriptions of the
+    /// `refused` accounts, notifies each subscribed account once and closes the first connection
+    /// after `close_after` subscriptions.
+    async fn pubsub_stub(
+        refused: HashMap<Pubkey, usize>,
+        close_after: Option<usize>,
+    ) -> (String, SubscribeLog) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let refused = Arc::new(Mutex::new(refused));
+        let log = SubscribeLog::default();
+
+        let subscribe_log = log.clone();
+        tokio::spawn(async move {
+            for connection in 0.. {
+                let (stream, _) = listener.accept().await.unwrap();
+                let mut ws = accept_async(stream).await.unwrap();
+                let (refused, log) = (refused.clone(), subscribe_log.clone());
+
+                tokio::spawn(async move {
+                    let mut subscriptions = 0;
+
+                    while let Some(Ok(WsMessage::Text(text))) = ws.next().await {
+                        let request: serde_json::Value = serde_json::from_str(&text).unwrap();
+                        let id = request["id"].clone();
+
+                        if request["method"] != "accountSubscribe" {
+                            let response = json!({ "jsonrpc": "2.0", "result": true, "id": id });
+                            ws.send(WsMessage::Text(response.to_string()))
+                                .await
+                                .unwrap();
+                            continue;
+                        }
+
+                        let pubkey = Pubkey::from_str(request["params"][0].as_str().unwrap());
+                        let pubkey = pubkey.unwrap();
+                        let refuse = match refused.lock().unwrap().get_mut(&pubkey) {
+                            Some(refusals) if *refusals > 0 => {
+                                *refusals -= 1;
+                                true
+                            }
+                            _ => false,
+                        };
+                        log.lock().unwrap().push((connection, pubkey, !refuse));
+
+                        if refuse {
+                            let error = json!({ "code": -32602, "message": "Invalid params" });
+                            let response = json!({ "jsonrpc": "2.0", "error": error, "id": id });
+                            ws.send(WsMessage::Text(response.to_string()))
+                                .await
+                                .unwrap();
+                            continue;
+                        }
+
+                        subscriptions += 1;
+                        let response =
+                            json!({ "jsonrpc": "2.0", "result": subscriptions, "id": id });
+                        ws.send(WsMessage::Text(response.to_string()))
+                            .await
+                            .unwrap();
+
+                        let account = Account {
+                            lamports: 1,
+                            ..Default::default()
+                        };
+                        let value = UiAccount::encode(
+                            &pubkey,
+                            &account,
+                            UiAccountEncoding::Base64,
+                            None,
+                            None,
+                        );
+                        let notification = json!({
+                            "jsonrpc": "2.0",
+                            "method": "accountNotification",
+                            "params": {
+                                "result": { "context": { "slot": 1 }, "value": value },
+                                "subscription": subscriptions,
+                            },
+                        });
+                        ws.send(WsMessage::Text(notification.to_string()))
+                            .await
+                            .unwrap();
+
+                        if connection == 0 && Some(subscriptions) == close_after {
+                            ws.close(None).await.ok();
+                            return;
+                        }
+                    }
+                });
+            }
+        });
+
+        (url, log)
+    }
+
+    /// Forwards the subscription events to the test.
+    struct Events(mpsc::UnboundedSender<SubscriptionEvent>);
+
+    impl Actor for Events {
+        type Context = Context<Self>;
+    }
+
+    impl Handler<SubscriptionEvent> for Events {
+        type Result = ();
+
+        fn handle(&mut self, msg: SubscriptionEvent, _: &mut Self::Context) {
+            self.0.send(msg).ok();
+        }
+    }
+
+    /// Runs the subscriptions of the fleet (and the clock) on the endpoint, the events until
+    /// `until` holds (or a timeout).
+    async fn run_until(
+        url: &str,
+        fleet: Pubkey,
+        until: impl Fn(&[SubscriptionEvent]) -> bool,
+    ) -> Vec<SubscriptionEvent> {
+        let endpoints = RpcEndpoints::new(EndpointsCfg::parse_list(url).unwrap()).unwrap();
+        let (events_tx, mut events_rx) = mpsc::unbounded_channel();
+        let events = Events(events_tx).start().recipient();
+
+        let (watch_tx, watch_rx) = mpsc::unbounded_channel();
+        watch_tx.send(Watch::Subscribe(fleet)).unwrap();
+        actix::spawn(run_subscriptions(
+            endpoints,
+            events,
+            watch_rx,
+            Duration::from_millis(100),
+        ));
+
+        let mut received = vec![];
+        while !until(&received) {
+            match time::timeout(Duration::from_secs(10), events_rx.recv()).await {
+                Ok(Some(event)) => received.push(event),
+                _ => break,
+            }
+        }
+        drop(watch_tx);
+
+        received
+    }
+
+    fn lives(events: &[SubscriptionEvent], pubkey: &Pubkey) -> usize {
+        events
+            .iter()
+            .filter(|event| matches!(event, SubscriptionEvent::Live(live) if live == pubkey))
+            .count()
+    }
+
+    #[actix::test]
+    async fn a_lost_connection_is_resubscribed() {
+        let fleet = Pubkey::new_unique();
+        let (url, log) = pubsub_stub(HashMap::new(), Some(2)).await;
+
+        // the fleet and the clock subscribe in either order
+        let clock = sysvar::clock::id();
+        let events = run_until(&url, fleet, |events| {
+            lives(events, &fleet) == 2 && lives(events, &clock) == 2
+        })
+        .await;
+
+        // the fleet and the clock on both connections, down in between
+        assert_eq!(lives(&events, &fleet), 2);
+        assert_eq!(lives(&events, &clock), 2);
+        let down = events
+            .iter()
+            .position(|event| matches!(event, SubscriptionEvent::Down))
+            .unwrap();
+        assert_eq!(lives(&events[..down], &fleet), 1);
+
+        let log = log.lock().unwrap();
+        for connection in [0, 1] {
+            assert!(log.contains(&(connection, fleet, true)));
+            assert!(log.contains(&(connection, clock, true)));
+        }
+
+        // the fleet's notifications of the first connection
+        assert!(events[..down].iter().any(
+            |event| matches!(event, SubscriptionEvent::Account(pubkey, _) if pubkey == &fleet)
+        ));
+    }
+
+    #[actix::test]
+    async fn a_refused_subscription_is_retried() {
+        let fleet = Pubkey::new_unique();
+        let (url, log) = pubsub_stub(HashMap::from([(fleet, 1)]), None).await;
+
+        let events = run_until(&url, fleet, |events| lives(events, &fleet) == 1).await;
+
+        // subscribed on the same connection, after the refusal
+        assert_eq!(lives(&events, &fleet), 1);
+        assert!(!events
+            .iter()
+            .any(|event| matches!(event, SubscriptionEvent::Down)));
+
+        let fleet_log: Vec<_> = log
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|(_, pubkey, _)| pubkey == &fleet)
+            .copied()
+            .collect();
+        assert_eq!(fleet_log, vec![(0, fleet, false), (0, fleet, true)]);
+    }
+}
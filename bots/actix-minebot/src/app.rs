@@ -2,6 +2,7 @@ use anchor_client::anchor_lang::prelude::Pubkey;
 use color_eyre::Result;
 use staratlas_sage_based_sdk::{Fleet, Game};
 
+use std::collections::HashMap;
 use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
 
@@ -11,13 +12,16 @@ pub fn init(
     db: Arc<Mutex<db::MinebotDB>>,
     game: (Pubkey, Game),
     fleets: Vec<(Pubkey, Fleet)>,
+    sol_price: Option<f64>,
 ) -> App {
     App::new(
         db,
+        sol_price,
         AppData {
             game_ui: game.into(),
             fleets_ui: fleets.into(),
             bot_ops: ui::BotOpsUI::from(vec![]),
+            ledger_ui: ui::LedgerUI::from(vec![]),
         },
     )
 }
@@ -26,6 +30,8 @@ pub struct App {
     mode: Mode,
     db: Arc<Mutex<db::MinebotDB>>,
     db_timer: timers::Timer,
+    prices: HashMap<Pubkey, f64>,
+    sol_price: Option<f64>,
     pub(crate) stopwatch: timers::Stopwatch,
     dt: Duration,
     last_time: Instant,
@@ -36,6 +42,7 @@ pub struct AppData {
     pub(crate) game_ui: ui::GameUI,
     pub(crate) fleets_ui: ui::FleetsUI,
     pub(crate) bot_ops: ui::BotOpsUI,
+    pub(crate) ledger_ui: ui::LedgerUI,
 }
 
 #[derive(Default, PartialEq)]
@@ -46,11 +53,13 @@ enum Mode {
 }
 
 impl App {
-    pub fn new(db: Arc<Mutex<db::MinebotDB>>, data: AppData) -> Self {
+    pub fn new(db: Arc<Mutex<db::MinebotDB>>, sol_price: Option<f64>, data: AppData) -> Self {
         App {
             mode: Mode::default(),
             db,
             db_timer: timers::Timer::from_seconds(5.0),
+            prices: HashMap::new(),
+            sol_price,
             stopwatch: timers::Stopwatch::default(),
             dt: Duration::ZERO,
             last_time: Instant::now(),
@@ -58,6 +67,11 @@ impl App {
         }
     }
 
+    /// The marketplace mid prices the ledger is valued with (until the next refresh).
+    pub fn set_prices(&mut self, prices: HashMap<Pubkey, f64>) {
+        self.prices = prices;
+    }
+
     pub fn is_running(&self) -> bool {
         self.mode != Mode::Quit
     }
@@ -90,6 +104,11 @@ impl App {
                 // self.data.bot_ops = ui::BotOpsUI::from(bot_ops);
                 self.data.bot_ops.update(&bot_ops);
 
+                // value the ledger's cycles for the P&L panel
+                let pnl_rows =
+                    db.pnl_report(&self.data.game_ui.1.mints, &self.prices, self.sol_price)?;
+                self.data.ledger_ui = ui::LedgerUI::from(pnl_rows);
+
                 self.db_timer.reset();
             };
         }
@@ -0,0 +1,272 @@
+use anchor_client::{
+    solana_sdk::{commitment_config::CommitmentConfig, pubkey::Pubkey, signature::Keypair},
+    Client,
+};
+use color_eyre::{eyre::eyre, Result};
+
+use staratlas_sage_based_sdk::{program::SAGE_ID, SageBasedGameHandler};
+
+use std::rc::Rc;
+use std::str::FromStr;
+
+use crate::{actors, config};
+
+/// Validates the bots of the config and prints their projected cycles (nothing is signed).
+pub async fn run(sage_bot_cfg_path: &str, registry: actors::RoleRegistry) -> Result<()> {
+    let sage_bot_cfg = config::load_sage_bot_cfg(sage_bot_cfg_path)?;
+    let endpoints = config::init_endpoints(&sage_bot_cfg.rpc)?;
+    endpoints.check_health().await;
+    let cluster = config::cluster(endpoints.read_url());
+    let game_id = Pubkey::from_str(&sage_bot_cfg.game_id)?;
+
+    // read-only, the check never signs a transaction
+    let client = Client::new_with_options(
+        cluster.clone(),
+        Rc::new(Keypair::new()),
+        CommitmentConfig::confirmed(),
+    );
+    let program = client.program(SAGE_ID)?;
+    let (_, game) = SageBasedGameHandler::get_game(&program, &game_id).await?;
+    let cargo_weights =
+        SageBasedGameHandler::get_cargo_weights(&program.async_rpc(), &game).await?;
+
+    let ctx = actors::RoleContext {
+        program,
+        game_id,
+        game,
+        cargo_weights,
+    };
+
+    let mut failures = 0;
+    for bot_cfg in &sage_bot_cfg.bots {
+        println!("Fleet {}", bot_cfg.fleet_id);
+
+        match check_bot(&sage_bot_cfg, bot_cfg, &registry, &ctx).await {
+            Ok((role_name, plan)) => {
+                println!("  ok: {}", role_name);
+                for (label, value) in plan {
+                    println!("  {:<18}{}", label, value);
+                }
+            }
+            Err(err) => {
+                println!("  error: {}", err);
+                failures += 1;
+            }
+        }
+    }
+
+    if failures > 0 {
+        return Err(eyre!(
+            "{} of {} bots failed the check",
+            failures,
+            sage_bot_cfg.bots.len()
+        ));
+    }
+
+    Ok(())
+}
+
+async fn check_bot(
+    sage_bot_cfg: &config::SageBotCfg,
+    bot_cfg: &config::BotCfg,
+    registry: &actors::RoleRegistry,
+    ctx: &actors::RoleContext,
+) -> Result<(String, actors::CyclePlan)> {
+    if let (Some(alias), None) = (&bot_cfg.wallet, &sage_bot_cfg.wallet_path) {
+        return Err(eyre!("Wallet alias {} requires a \"wallet_path\"", alias));
+    }
+
+    let fleet_id = Pubkey::from_str(&bot_cfg.fleet_id)?;
+    let (_, fleet_with_state) = SageBasedGameHandler::get_fleet_with_state(&ctx.program, &fleet_id)
+        .await
+        .map_err(|err| eyre!("Fleet not found: {}", err))?;
+
+    let fleet = &fleet_with_state.0;
+    if fleet.game_id != ctx.game_id {
+        return Err(eyre!("Fleet belongs to the game {}", fleet.game_id));
+    }
+    if let Some(profile_id) = bot_cfg
+        .profile_id
+        .as_ref()
+        .or(sage_bot_cfg.profile_id.as_ref())
+    {
+        if fleet.owner_profile != Pubkey::from_str(profile_id)? {
+            return Err(eyre!(
+                "Fleet belongs to the profile {}",
+                fleet.owner_profile
+            ));
+        }
+    }
+
+    let (role_name, role_args) = bot_cfg.role.name_and_args()?;
+    let role = registry.build(role_name, role_args, ctx).await?;
+
+    let risk_zones = &ctx.game.risk_zones;
+    let risk = match &bot_cfg.max_risk {
+        Some(max_risk) => Some(actors::RiskPolicy::new(max_risk, *risk_zones)?),
+        None => None,
+    };
+    if let Some(risk) = &risk {
+        risk.check_role(&*role)?;
+    }
+
+    let mut plan = role.check(&fleet_with_state, ctx, risk.as_ref()).await?;
+
+    let mut sectors = role.sectors();
+    sectors.sort();
+    sectors.dedup();
+    if !sectors.is_empty() {
+        let zones: Vec<String> = sectors
+            .iter()
+            .map(|sector| format!("{:?} {:?}", sector, risk_zones.zone(*sector)))
+            .collect();
+        plan.push(("Risk zones".to_string(), zones.join(", ")));
+    }
+
+    Ok((role_name.to_string(), plan))
+}
+
+// cargo test -p actix-minebot
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use anchor_client::{
+        anchor_lang::{AnchorDeserialize, AnchorSerialize, Discriminator},
+        Cluster,
+    };
+    use staratlas_sage_based_sdk::{state, CargoWeights, Fleet, Game};
+
+    use std::collections::HashMap;
+
+    use crate::rpc_stub::rpc_stub;
+
+    fn anchor_account<T: AnchorSerialize + Discriminator>(account: &T) -> Vec<u8> {
+        let mut account_data = T::DISCRIMINATOR.to_vec();
+        account_data.extend(account.try_to_vec().unwrap());
+        account_data
+    }
+
+    /// An idle fleet of the game in sector [0, 0], warping up to 5 sectors.
+    fn fleet_account(game_id: Pubkey) -> Vec<u8> {
+        let mut fleet: Fleet = borsh::BorshDeserialize::deserialize(&mut &[0u8; 1024][..]).unwrap();
+        fleet.game_id = game_id;
+        fleet.stats.movement_stats.max_warp_distance = 500;
+
+        let mut account_data = vec![7u8; 8];
+        account_data.extend(borsh::to_vec(&fleet).unwrap());
+        account_data.push(1);
+        account_data.extend(borsh::to_vec(&[0i64, 0]).unwrap());
+        account_data
+    }
+
+    struct Fixture {
+        sage_bot_cfg: config::SageBotCfg,
+        ctx: actors::RoleContext,
+    }
+
+    impl Fixture {
+        fn new(role: serde_json::Value, mut accounts: HashMap<Pubkey, Vec<u8>>) -> Self {
+            let game_id = Pubkey::new_unique();
+            let fleet_id = Pubkey::new_unique();
+            accounts.insert(fleet_id, fleet_account(game_id));
+
+            let url = rpc_stub(accounts);
+            let client = Client::new(
+                Cluster::Custom(url.clone(), url.replace("http", "ws")),
+                Rc::new(Keypair::new()),
+            );
+            let game: Game = borsh::BorshDeserialize::deserialize(&mut &[0u8; 2048][..]).unwrap();
+
+            let sage_bot_cfg = serde_json::from_value(serde_json::json!({
+                "game_id": game_id.to_string(),
+                "bots": [{ "fleet_id": fleet_id.to_string(), "role": role }],
+            }))
+            .unwrap();
+
+            Fixture {
+                sage_bot_cfg,
+                ctx: actors::RoleContext {
+                    program: client.program(SAGE_ID).unwrap(),
+                    game_id,
+                    game,
+                    cargo_weights: CargoWeights::new(&[], 1),
+                },
+            }
+        }
+
+        async fn check(&self) -> Result<(String, actors::CyclePlan)> {
+            let registry = actors::RoleRegistry::default();
+            let bot_cfg = &self.sage_bot_cfg.bots[0];
+            check_bot(&self.sage_bot_cfg, bot_cfg, &registry, &self.ctx).await
+        }
+    }
+
+    fn mission(steps: serde_json::Value) -> Fixture {
+        Fixture::new(
+            serde_json::json!({ "Mission": { "steps": steps } }),
+            HashMap::new(),
+        )
+    }
+
+    #[actix::test]
+    async fn a_mission_within_reach_passes_the_check() {
+        let (role_name, plan) = mission(serde_json::json!([{ "Warp": [3, 4] }]))
+            .check()
+            .await
+            .unwrap();
+
+        assert_eq!(role_name, "Mission");
+        assert_eq!(plan[0], ("Steps".to_string(), "1".to_string()));
+    }
+
+    #[actix::test]
+    async fn a_missing_resource_fails_the_check() {
+        let planet_id = Pubkey::new_unique();
+        let mine_item_id = Pubkey::new_unique();
+
+        let planet = state::Planet::deserialize(&mut &[0u8; 1024][..]).unwrap();
+        let mine_item = state::MineItem::deserialize(&mut &[0u8; 1024][..]).unwrap();
+        let accounts = HashMap::from([
+            (planet_id, anchor_account(&planet)),
+            (mine_item_id, anchor_account(&mine_item)),
+        ]);
+
+        let role = serde_json::json!({ "MineAsteroid": {
+            "planet_id": planet_id.to_string(),
+            "mine_item_id": mine_item_id.to_string(),
+        }});
+        let err = Fixture::new(role, accounts).check().await.unwrap_err();
+
+        assert_eq!(
+            err.to_string(),
+            format!(
+                "No resource of the mine item {} on the planet {}",
+                mine_item_id, planet_id
+            )
+        );
+    }
+
+    #[actix::test]
+    async fn a_sector_without_a_starbase_fails_the_check() {
+        let err = mission(serde_json::json!([{ "Warp": [3, 4] }, "Dock"]))
+            .check()
+            .await
+            .unwrap_err();
+
+        assert_eq!(err.to_string(), "No starbase in sector [3, 4]");
+    }
+
+    #[actix::test]
+    async fn a_warp_beyond_the_max_warp_distance_fails_the_check() {
+        let err = mission(serde_json::json!([{ "Warp": [6, 0] }]))
+            .check()
+            .await
+            .unwrap_err();
+
+        assert_eq!(
+            err.to_string(),
+            "Warp from [0, 0] to [6, 0] (6.00) exceeds the max warp distance (5.00)"
+        );
+    }
+}
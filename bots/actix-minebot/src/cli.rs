@@ -0,0 +1,35 @@
+use clap::{Parser, Subcommand};
+
+/// Star Atlas: Actix-Minebot --> donations: 2yodqKtkdNJXxJv21s5YMVG8bjscaezLVFRfnWra5D77 <--
+#[derive(Debug, Parser)]
+#[command(
+    about,
+    long_about = None,
+    args_conflicts_with_subcommands = true,
+    subcommand_negates_reqs = true
+)]
+pub struct Cli {
+    /// Path to the bot's config (e.g. 'minebot-config.json')
+    #[arg(required = true)]
+    pub config: Option<String>,
+    #[command(subcommand)]
+    pub command: Option<Commands>,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Commands {
+    /// Validate the config's bots and print their projected cycles (dry-run)
+    Check {
+        /// Path to the bot's config (e.g. 'minebot-config.json')
+        config: String,
+    },
+    /// Print the per-fleet and per-role P&L from the bot's ledger
+    Report {
+        /// Path to the bot's config (e.g. 'minebot-config.json')
+        config: String,
+    },
+}
+
+pub fn cli_parse() -> Cli {
+    Cli::parse()
+}
@@ -2,73 +2,275 @@ use anchor_client::{
     solana_sdk::signature::{read_keypair_file, Keypair},
     Cluster,
 };
-use color_eyre::Result;
+use color_eyre::{eyre::eyre, Result};
 use dotenv::dotenv;
 use serde::Deserialize;
 
+use staratlas_utils_rpc::{EndpointsCfg, RpcEndpoints};
+use staratlas_utils_wallet::{open_wallet_from_file_with_password_prompt, Wallet};
+
+use std::collections::HashMap;
 use std::fs;
 use std::rc::Rc;
 
 pub struct Config {
-    pub payer: Rc<Keypair>,
-    pub cluster: Cluster,
+    pub payer: Option<Rc<Keypair>>,
+    pub wallet: Option<Wallet>,
+    pub endpoints: RpcEndpoints,
     pub sage_bot_cfg: SageBotCfg,
 }
 
+impl Config {
+    /// The keypair of a bot, its wallet alias or the `PROVIDER_WALLET` payer.
+    pub fn payer(&self, wallet_alias: Option<&str>) -> Result<Rc<Keypair>> {
+        match wallet_alias {
+            Some(alias) => self
+                .wallet
+                .as_ref()
+                .ok_or(eyre!(
+                    "Requires a \"wallet_path\" for the wallet alias: {}",
+                    alias
+                ))?
+                .get_keypair(alias)
+                .map(Rc::new)
+                .ok_or(eyre!("Wallet alias not found: {}", alias)),
+            None => self
+                .payer
+                .clone()
+                .ok_or(eyre!("Requires PROVIDER_WALLET (or a bot's wallet alias)")),
+        }
+    }
+}
+
 #[derive(Deserialize)]
 pub struct SageBotCfg {
     pub game_id: String,
+    #[serde(default)]
+    pub profile_id: Option<String>,
+    #[serde(default = "default_ledger_path")]
+    pub ledger_path: String,
+    #[serde(default)]
+    pub sol_price: Option<f64>, // ATLAS per SOL, values the ledger's fees (reported in SOL without)
+    #[serde(default)]
+    pub restock: Option<RestockCfg>,
+    #[serde(default)]
+    pub wallet_path: Option<String>,
+    #[serde(default)]
+    pub rpc: RpcCfg,
     pub bots: Vec<BotCfg>,
 }
 
-#[derive(Deserialize)]
+fn default_ledger_path() -> String {
+    "minebot-ledger.db".to_string()
+}
+
+/// Budget of the RPC requests (shared by all bots and signers), and the RPC endpoints.
+#[derive(Clone, PartialEq, Deserialize)]
+pub struct RpcCfg {
+    #[serde(default = "default_requests_per_second")]
+    pub requests_per_second: f64,
+    #[serde(default = "default_batch_window_ms")]
+    pub batch_window_ms: u64, // accounts requested within the window share a getMultipleAccounts
+    #[serde(default = "default_subscriptions")]
+    pub subscriptions: bool, // websocket account subscriptions of the fleets (and the clock)
+    #[serde(flatten)]
+    pub endpoints: EndpointsCfg, // the endpoints of PROVIDER_CLUSTER without "endpoints"
+}
+
+impl Default for RpcCfg {
+    fn default() -> Self {
+        RpcCfg {
+            requests_per_second: default_requests_per_second(),
+            batch_window_ms: default_batch_window_ms(),
+            subscriptions: default_subscriptions(),
+            endpoints: EndpointsCfg::default(),
+        }
+    }
+}
+
+fn default_requests_per_second() -> f64 {
+    10.0
+}
+
+fn default_batch_window_ms() -> u64 {
+    50
+}
+
+fn default_subscriptions() -> bool {
+    true
+}
+
+fn default_budget_period_hours() -> f64 {
+    24.0
+}
+
+/// Restock policy of the starbase consumables (buy orders on the Galactic Marketplace).
+#[derive(Clone, PartialEq, Deserialize)]
+pub struct RestockCfg {
+    pub atlas_budget: f64,
+    #[serde(default = "default_budget_period_hours")]
+    pub budget_period_hours: f64, // the atlas_budget is spent per period
+    pub fuel: Option<ConsumableRestockCfg>,
+    pub ammo: Option<ConsumableRestockCfg>,
+    pub food: Option<ConsumableRestockCfg>,
+}
+
+#[derive(Clone, PartialEq, Deserialize)]
+pub struct ConsumableRestockCfg {
+    pub threshold: u64,
+    pub amount: u64,
+    pub max_price: f64,
+}
+
+#[derive(Clone, PartialEq, Deserialize)]
 pub struct BotCfg {
     pub fleet_id: String,
+    #[serde(default)]
+    pub wallet: Option<String>, // alias of the keypair in the config's "wallet_path"
+    #[serde(default)]
+    pub profile_id: Option<String>, // the fleet's profile, the config's "profile_id" by default
+    #[serde(default)]
+    pub max_risk: Option<String>, // the riskiest zone of the role (secure, medium or high)
     pub role: RoleCfg,
 }
 
-#[derive(Deserialize)]
-pub struct RoleCfg(pub BotRoleArgs);
+/// Role of a bot, `{ "<RoleName>": { ..args } }` with the name of a registered role.
+#[derive(Clone, PartialEq, Deserialize)]
+pub struct RoleCfg(pub HashMap<String, serde_json::Value>);
 
-#[derive(Deserialize)]
-pub enum BotRoleArgs {
-    MineAsteroid {
-        planet_id: String,
-        mine_item_id: String,
-    },
-    CargoTransport {
-        cargo_mint: String,
-        cargo_amount: u64,
-        from_sector: [i64; 2],
-        to_sector: [i64; 2],
-    },
-}
-
-pub fn init_config() -> Result<Config> {
+impl RoleCfg {
+    pub fn name_and_args(&self) -> Result<(&str, serde_json::Value)> {
+        match self.0.iter().next() {
+            Some((name, args)) if self.0.len() == 1 => Ok((name, args.clone())),
+            _ => Err(eyre!("A bot's role requires exactly one role name")),
+        }
+    }
+}
+
+pub fn init_config(sage_bot_cfg_path: &str) -> Result<Config> {
     dotenv().ok();
 
-    let path = std::env::var("PROVIDER_WALLET")?;
-    let payer = read_keypair_file(&path).expect("Failed to read keypair file");
-    let payer = Rc::new(payer);
+    let payer = match std::env::var("PROVIDER_WALLET") {
+        Ok(path) => {
+            let payer = read_keypair_file(&path).expect("Failed to read keypair file");
+            Some(Rc::new(payer))
+        }
+        Err(_) => None,
+    };
 
-    let url = std::env::var("PROVIDER_CLUSTER")?;
-    let cluster = Cluster::Custom(url.clone(), url);
+    let sage_bot_cfg = load_sage_bot_cfg(sage_bot_cfg_path)?;
+    let endpoints = init_endpoints(&sage_bot_cfg.rpc)?;
 
-    let args: Vec<String> = std::env::args().collect();
-    let sage_bot_cfg_path = &args[1];
-    let sage_bot_cfg_json = fs::read_to_string(sage_bot_cfg_path)?;
-    let sage_bot_cfg: SageBotCfg = serde_json::from_str(&sage_bot_cfg_json)?;
+    // the encrypted wallet is unlocked once, its aliases are available to the bots (and reloads)
+    let wallet = match &sage_bot_cfg.wallet_path {
+        Some(wallet_path) => {
+            println!("Unlock wallet: {}", wallet_path);
+            let wallet = open_wallet_from_file_with_password_prompt(wallet_path)
+                .map_err(|err| eyre!("{}", err))?;
+            Some(wallet)
+        }
+        None => None,
+    };
 
     Ok(Config {
         payer,
-        cluster,
+        wallet,
+        endpoints,
         sage_bot_cfg,
     })
 }
 
+/// The endpoints of the config's `"rpc"`, or of `PROVIDER_CLUSTER` (a comma separated list).
+pub fn init_endpoints(rpc_cfg: &RpcCfg) -> Result<RpcEndpoints> {
+    dotenv().ok();
+
+    let mut endpoints_cfg = rpc_cfg.endpoints.clone();
+
+    if endpoints_cfg.endpoints.is_empty() {
+        let list = std::env::var("PROVIDER_CLUSTER")?;
+        endpoints_cfg.endpoints = EndpointsCfg::parse_list(&list)
+            .map_err(|err| eyre!("{}", err))?
+            .endpoints;
+    }
+
+    RpcEndpoints::new(endpoints_cfg).map_err(|err| eyre!("{}", err))
+}
+
+pub fn cluster(url: String) -> Cluster {
+    Cluster::Custom(url.clone(), url)
+}
+
+pub fn load_sage_bot_cfg(sage_bot_cfg_path: &str) -> Result<SageBotCfg> {
+    let sage_bot_cfg_json = fs::read_to_string(sage_bot_cfg_path)?;
+    let sage_bot_cfg: SageBotCfg = serde_json::from_str(&sage_bot_cfg_json)?;
+
+    Ok(sage_bot_cfg)
+}
+
 pub fn init_logger() -> Result<()> {
     tui_logger::init_logger(log::LevelFilter::Info)?;
     tui_logger::set_default_level(log::LevelFilter::Info);
     log::info!("logging initialized");
     Ok(())
 }
+
+// cargo test -p actix-minebot
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    use anchor_client::solana_sdk::signature::Signer;
+
+    /// The config of the endpoint, with a default payer and the wallet (of its aliases).
+    pub(crate) fn config(url: &str, aliases: &[(&str, &Keypair)]) -> Config {
+        let wallet = (!aliases.is_empty()).then(|| {
+            let mut wallet = Wallet::new();
+            for (alias, keypair) in aliases {
+                wallet.insert(*alias, keypair.to_base58_string());
+            }
+            wallet
+        });
+
+        Config {
+            payer: Some(Rc::new(Keypair::new())),
+            wallet,
+            endpoints: RpcEndpoints::new(EndpointsCfg::parse_list(url).unwrap()).unwrap(),
+            sage_bot_cfg: serde_json::from_value(serde_json::json!({
+                "game_id": "11111111111111111111111111111111",
+                "bots": [],
+            }))
+            .unwrap(),
+        }
+    }
+
+    #[test]
+    fn a_bot_signs_with_its_wallet_alias() {
+        let hot = Keypair::new();
+        let config = config("http://localhost:8899", &[("hot", &hot)]);
+
+        assert_eq!(config.payer(Some("hot")).unwrap().pubkey(), hot.pubkey());
+        assert_eq!(
+            config.payer(None).unwrap().pubkey(),
+            config.payer.as_ref().unwrap().pubkey()
+        );
+    }
+
+    #[test]
+    fn an_unknown_alias_is_an_error() {
+        let config = config("http://localhost:8899", &[("hot", &Keypair::new())]);
+
+        let err = config.payer(Some("cold")).unwrap_err();
+        assert_eq!(err.to_string(), "Wallet alias not found: cold");
+    }
+
+    #[test]
+    fn an_alias_requires_a_wallet_path() {
+        let config = config("http://localhost:8899", &[]);
+
+        let err = config.payer(Some("hot")).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Requires a \"wallet_path\" for the wallet alias: hot"
+        );
+    }
+}
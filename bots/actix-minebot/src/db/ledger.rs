@@ -0,0 +1,333 @@
+use anchor_client::anchor_lang::prelude::Pubkey;
+use color_eyre::Result;
+use staratlas_sage_based_sdk::types::Mints;
+
+use std::collections::HashMap;
+use std::str::FromStr;
+
+use super::MinebotDB;
+
+const LAMPORTS_PER_SOL: f64 = 1_000_000_000.0;
+const SECONDS_PER_HOUR: f64 = 3_600.0;
+
+/// A completed mining or transport cycle of a fleet.
+#[derive(Debug, Clone)]
+pub struct CycleRecord {
+    pub fleet: String,
+    pub role: String,
+    pub started_at: f64,
+    pub finished_at: f64,
+    pub resource_mint: Option<String>,
+    pub resource_amount: f64,
+    pub fuel_burned: f64,
+    pub ammo_burned: f64,
+    pub food_burned: f64,
+    pub fees_lamports: u64,
+}
+
+/// Profit and loss (priced in ATLAS) of a fleet's cycles, or of all fleets of a role. A value is
+/// `None` when a mint it needs has no price.
+#[derive(Debug, Clone, Default)]
+pub struct PnlRow {
+    pub fleet: Option<String>,
+    pub role: String,
+    pub cycles: u64,
+    pub hours: f64,
+    pub revenue: Option<f64>,
+    pub costs: Option<f64>,
+    pub fees_sol: f64,
+    /// The fees priced in ATLAS (at the config's `sol_price`), `None` without it.
+    pub fees: Option<f64>,
+}
+
+impl PnlRow {
+    /// The P&L after the fees, before them without a SOL price (the fees are reported in SOL).
+    pub fn pnl(&self) -> Option<f64> {
+        Some(self.revenue? - self.costs? - self.fees.unwrap_or(0.0))
+    }
+
+    pub fn pnl_per_hour(&self) -> Option<f64> {
+        if self.hours > 0.0 {
+            self.pnl().map(|pnl| pnl / self.hours)
+        } else {
+            Some(0.0)
+        }
+    }
+
+    pub fn pnl_per_day(&self) -> Option<f64> {
+        self.pnl_per_hour().map(|pnl| pnl * 24.0)
+    }
+
+    fn add(&mut self, other: &PnlRow) {
+        let sum = |a: Option<f64>, b: Option<f64>| Some(a? + b?);
+
+        self.cycles += other.cycles;
+        self.hours += other.hours;
+        self.revenue = sum(self.revenue, other.revenue);
+        self.costs = sum(self.costs, other.costs);
+        self.fees_sol += other.fees_sol;
+        self.fees = sum(self.fees, other.fees);
+    }
+}
+
+/// The value of an amount, `None` without a price (nothing is worth 0).
+fn value(amount: f64, price: Option<f64>) -> Option<f64> {
+    if amount == 0.0 {
+        return Some(0.0);
+    }
+    price.map(|price| amount * price)
+}
+
+impl MinebotDB {
+    pub(crate) fn insert_cycle(&self, cycle: &CycleRecord) -> Result<()> {
+        self.conn.execute(
+            "INSERT INTO ledger.cycles (
+                fleet, role, started_at, finished_at, resource_mint, resource_amount,
+                fuel_burned, ammo_burned, food_burned, fees_lamports
+            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+            rusqlite::params![
+                cycle.fleet,
+                cycle.role,
+                cycle.started_at,
+                cycle.finished_at,
+                cycle.resource_mint,
+                cycle.resource_amount,
+                cycle.fuel_burned,
+                cycle.ammo_burned,
+                cycle.food_burned,
+                cycle.fees_lamports,
+            ],
+        )?;
+
+        Ok(())
+    }
+
+    pub(crate) fn ledger_resource_mints(&self) -> Result<Vec<Pubkey>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT DISTINCT resource_mint FROM ledger.cycles WHERE resource_mint IS NOT NULL",
+        )?;
+
+        let mints_iter = stmt.query_map([], |row| row.get::<_, String>(0))?;
+        let mints = mints_iter
+            .filter_map(Result::ok)
+            .filter_map(|mint| Pubkey::from_str(&mint).ok())
+            .collect();
+
+        Ok(mints)
+    }
+
+    /// Per-fleet rows followed by the per-role totals, valued with `prices` (ATLAS per unit), the
+    /// fees with `sol_price` (ATLAS per SOL, the marketplace has no SOL order book).
+    pub(crate) fn pnl_report(
+        &self,
+        mints: &Mints,
+        prices: &HashMap<Pubkey, f64>,
+        sol_price: Option<f64>,
+    ) -> Result<Vec<PnlRow>> {
+        let price = |mint: &Pubkey| prices.get(mint).copied();
+
+        let mut stmt = self.conn.prepare(
+            "SELECT fleet, role, resource_mint, COUNT(*), SUM(finished_at - started_at),
+                SUM(resource_amount), SUM(fuel_burned), SUM(ammo_burned), SUM(food_burned),
+                SUM(fees_lamports)
+            FROM ledger.cycles
+            GROUP BY fleet, role, resource_mint
+            ORDER BY role, fleet",
+        )?;
+
+        let rows_iter = stmt.query_map([], |row| {
+            let resource_mint: Option<String> = row.get(2)?;
+            let resource_price = resource_mint
+                .and_then(|mint| Pubkey::from_str(&mint).ok())
+                .and_then(|mint| price(&mint));
+
+            let resource_amount: f64 = row.get(5)?;
+            let fuel_burned: f64 = row.get(6)?;
+            let ammo_burned: f64 = row.get(7)?;
+            let food_burned: f64 = row.get(8)?;
+            let fees_lamports: i64 = row.get(9)?;
+            let seconds: f64 = row.get(4)?;
+            let fees_sol = fees_lamports as f64 / LAMPORTS_PER_SOL;
+
+            Ok(PnlRow {
+                fleet: Some(row.get(0)?),
+                role: row.get(1)?,
+                cycles: row.get::<_, i64>(3)? as u64,
+                hours: seconds / SECONDS_PER_HOUR,
+                revenue: value(resource_amount, resource_price),
+                costs: [
+                    value(fuel_burned, price(&mints.fuel)),
+                    value(ammo_burned, price(&mints.ammo)),
+                    value(food_burned, price(&mints.food)),
+                ]
+                .into_iter()
+                .sum(),
+                fees_sol,
+                fees: value(fees_sol, sol_price),
+            })
+        })?;
+
+        // merge the rows of a fleet that produced more than one resource
+        let mut fleet_rows: Vec<PnlRow> = vec![];
+        for row in rows_iter.filter_map(Result::ok) {
+            match fleet_rows
+                .iter_mut()
+                .find(|r| r.fleet == row.fleet && r.role == row.role)
+            {
+                Some(entry) => entry.add(&row),
+                None => fleet_rows.push(row),
+            }
+        }
+
+        let mut role_rows: Vec<PnlRow> = vec![];
+        for row in &fleet_rows {
+            match role_rows.iter_mut().find(|r| r.role == row.role) {
+                Some(entry) => entry.add(row),
+                None => role_rows.push(PnlRow {
+                    fleet: None,
+                    ..row.clone()
+                }),
+            }
+        }
+
+        fleet_rows.extend(role_rows);
+        Ok(fleet_rows)
+    }
+}
+
+// cargo test -p actix-minebot
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cycle(fleet: &str, role: &str, resource_mint: Option<&Pubkey>) -> CycleRecord {
+        CycleRecord {
+            fleet: fleet.to_string(),
+            role: role.to_string(),
+            started_at: 0.0,
+            finished_at: SECONDS_PER_HOUR,
+            resource_mint: resource_mint.map(ToString::to_string),
+            resource_amount: resource_mint.map_or(0.0, |_| 100.0),
+            fuel_burned: 10.0,
+            ammo_burned: 5.0,
+            food_burned: 2.0,
+            fees_lamports: 10_000_000,
+        }
+    }
+
+    #[test]
+    fn pnl_report_values_cycles_and_fees() {
+        let db = MinebotDB::open(":memory:").unwrap();
+        let mints = Mints {
+            atlas: Pubkey::new_unique(),
+            polis: Pubkey::new_unique(),
+            ammo: Pubkey::new_unique(),
+            food: Pubkey::new_unique(),
+            fuel: Pubkey::new_unique(),
+            repair_kit: Pubkey::new_unique(),
+        };
+        let (ore, hydrogen) = (Pubkey::new_unique(), Pubkey::new_unique());
+
+        db.insert_cycle(&cycle("fleet-a", "MineAsteroid", Some(&ore)))
+            .unwrap();
+        db.insert_cycle(&cycle("fleet-a", "MineAsteroid", Some(&hydrogen)))
+            .unwrap();
+        db.insert_cycle(&cycle("fleet-b", "CargoTransport", None))
+            .unwrap();
+
+        let prices = HashMap::from([
+            (ore, 0.1),
+            (hydrogen, 0.2),
+            (mints.fuel, 0.01),
+            (mints.ammo, 0.02),
+            (mints.food, 0.03),
+        ]);
+        let rows = db.pnl_report(&mints, &prices, Some(100.0)).unwrap();
+
+        // the fleets' rows (a fleet's resources merged), then the roles' totals
+        assert_eq!(rows.len(), 4);
+        let fleet_a = &rows[1];
+        assert_eq!(fleet_a.fleet.as_deref(), Some("fleet-a"));
+        assert_eq!(fleet_a.cycles, 2);
+        assert_eq!(fleet_a.hours, 2.0);
+        assert!((fleet_a.revenue.unwrap() - 30.0).abs() < 1e-9);
+        assert!((fleet_a.costs.unwrap() - 0.52).abs() < 1e-9);
+        assert!((fleet_a.fees_sol - 0.02).abs() < 1e-9);
+        assert!((fleet_a.fees.unwrap() - 2.0).abs() < 1e-9);
+        assert!((fleet_a.pnl().unwrap() - 27.48).abs() < 1e-9);
+        assert!((fleet_a.pnl_per_day().unwrap() - 27.48 * 12.0).abs() < 1e-9);
+
+        // a transport books no revenue, only its costs and fees
+        let fleet_b = &rows[0];
+        assert_eq!(fleet_b.role, "CargoTransport");
+        assert_eq!(fleet_b.revenue, Some(0.0));
+        assert!((fleet_b.pnl().unwrap() + 0.26 + 1.0).abs() < 1e-9);
+
+        let totals: Vec<_> = rows.iter().filter(|row| row.fleet.is_none()).collect();
+        assert_eq!(totals.len(), 2);
+        assert_eq!(totals[1].cycles, 2);
+    }
+
+    #[test]
+    fn pnl_report_without_a_price() {
+        let db = MinebotDB::open(":memory:").unwrap();
+        let mints = Mints {
+            atlas: Pubkey::new_unique(),
+            polis: Pubkey::new_unique(),
+            ammo: Pubkey::new_unique(),
+            food: Pubkey::new_unique(),
+            fuel: Pubkey::new_unique(),
+            repair_kit: Pubkey::new_unique(),
+        };
+        let (ore, hydrogen) = (Pubkey::new_unique(), Pubkey::new_unique());
+
+        db.insert_cycle(&cycle("fleet-a", "MineAsteroid", Some(&ore)))
+            .unwrap();
+        db.insert_cycle(&cycle("fleet-b", "MineAsteroid", Some(&hydrogen)))
+            .unwrap();
+
+        // no price of the hydrogen, nor of SOL (the fees stay in SOL, out of the P&L)
+        let prices = HashMap::from([
+            (ore, 0.1),
+            (mints.fuel, 0.01),
+            (mints.ammo, 0.02),
+            (mints.food, 0.03),
+        ]);
+        let rows = db.pnl_report(&mints, &prices, None).unwrap();
+
+        let fleet_a = &rows[0];
+        assert_eq!(fleet_a.fees, None);
+        assert!((fleet_a.fees_sol - 0.01).abs() < 1e-9);
+        assert!((fleet_a.pnl().unwrap() - (10.0 - 0.26)).abs() < 1e-9);
+
+        let fleet_b = &rows[1];
+        assert_eq!(fleet_b.revenue, None);
+        assert_eq!(fleet_b.pnl(), None);
+
+        // the role's total lacks the hydrogen's price too
+        assert_eq!(rows[2].fleet, None);
+        assert_eq!(rows[2].revenue, None);
+        assert!((rows[2].costs.unwrap() - 0.52).abs() < 1e-9);
+
+        // a consumable without a price
+        let rows = db
+            .pnl_report(&mints, &HashMap::from([(ore, 0.1)]), None)
+            .unwrap();
+        assert_eq!(rows[0].costs, None);
+    }
+
+    #[test]
+    fn ledger_resource_mints_are_distinct() {
+        let db = MinebotDB::open(":memory:").unwrap();
+        let ore = Pubkey::new_unique();
+
+        db.insert_cycle(&cycle("fleet-a", "MineAsteroid", Some(&ore)))
+            .unwrap();
+        db.insert_cycle(&cycle("fleet-a", "MineAsteroid", Some(&ore)))
+            .unwrap();
+        db.insert_cycle(&cycle("fleet-b", "CargoTransport", None))
+            .unwrap();
+
+        assert_eq!(db.ledger_resource_mints().unwrap(), vec![ore]);
+    }
+}
@@ -1,12 +1,17 @@
 use color_eyre::Result;
 use rusqlite::Connection;
 
+mod ledger;
+pub use ledger::*;
+
+mod restock;
+
 pub struct MinebotDB {
     pub(crate) conn: Connection,
 }
 
 impl MinebotDB {
-    pub(crate) fn open() -> Result<Self> {
+    pub(crate) fn open(ledger_path: &str) -> Result<Self> {
         let conn = rusqlite::Connection::open_in_memory()?;
         conn.execute(
             "CREATE TABLE IF NOT EXISTS bot_ops (
@@ -17,6 +22,51 @@ impl MinebotDB {
             (),
         )?;
 
+        // the ledger outlives the bot process, keep it in a file attached to the in-memory db
+        conn.execute("ATTACH DATABASE ?1 AS ledger", [ledger_path])?;
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS ledger.cycles (
+                id INTEGER PRIMARY KEY,
+                fleet TEXT NOT NULL,
+                role TEXT NOT NULL,
+                started_at REAL NOT NULL,
+                finished_at REAL NOT NULL,
+                resource_mint TEXT,
+                resource_amount REAL NOT NULL,
+                fuel_burned REAL NOT NULL,
+                ammo_burned REAL NOT NULL,
+                food_burned REAL NOT NULL,
+                fees_lamports INTEGER NOT NULL
+            )",
+            (),
+        )?;
+
+        // the restocks spend the ATLAS budget of a period of all the bots (and their restarts), a
+        // purchase in flight reserves its share of the budget
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS ledger.restocks (
+                id INTEGER PRIMARY KEY,
+                fleet TEXT NOT NULL,
+                mint TEXT NOT NULL,
+                amount INTEGER NOT NULL,
+                atlas_cost INTEGER NOT NULL,
+                reserved INTEGER NOT NULL,
+                created_at INTEGER NOT NULL DEFAULT 0
+            )",
+            (),
+        )?;
+
+        // a ledger of the lifetime budget, its restocks fall out of the periods
+        if conn
+            .prepare("SELECT created_at FROM ledger.restocks LIMIT 0")
+            .is_err()
+        {
+            conn.execute(
+                "ALTER TABLE ledger.restocks ADD COLUMN created_at INTEGER NOT NULL DEFAULT 0",
+                (),
+            )?;
+        }
+
         let db = MinebotDB { conn };
         Ok(db)
     }
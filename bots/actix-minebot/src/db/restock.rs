@@ -0,0 +1,184 @@
+use color_eyre::Result;
+
+use super::MinebotDB;
+
+impl MinebotDB {
+    /// Reserves the ATLAS (base units) of a purchase at `now` (unix seconds), up to `max_cost`
+    /// within what remains of the `atlas_budget` of the last `period` seconds, `None` when the
+    /// budget is spent. The check and the reservation are one statement, the bots (and processes)
+    /// sharing the ledger can't overspend.
+    pub(crate) fn reserve_restock(
+        &self,
+        fleet: &str,
+        mint: &str,
+        atlas_budget: u64,
+        max_cost: u64,
+        now: i64,
+        period: i64,
+    ) -> Result<Option<(i64, u64)>> {
+        let reserved = self.conn.execute(
+            "INSERT INTO ledger.restocks (fleet, mint, amount, atlas_cost, reserved, created_at)
+            SELECT ?1, ?2, 0, MIN(?4, ?3 - COALESCE(SUM(atlas_cost), 0)), 1, ?5
+            FROM ledger.restocks
+            WHERE created_at > ?5 - ?6
+            HAVING ?3 - COALESCE(SUM(atlas_cost), 0) > 0",
+            rusqlite::params![
+                fleet,
+                mint,
+                atlas_budget as i64,
+                max_cost as i64,
+                now,
+                period
+            ],
+        )?;
+        if reserved == 0 {
+            return Ok(None);
+        }
+
+        let id = self.conn.last_insert_rowid();
+        let atlas_cost: i64 = self.conn.query_row(
+            "SELECT atlas_cost FROM ledger.restocks WHERE id = ?1",
+            [id],
+            |row| row.get(0),
+        )?;
+
+        Ok(Some((id, atlas_cost as u64)))
+    }
+
+    /// Returns the reservations older than `ttl` seconds to the budget, a purchase left in flight
+    /// by a stopped bot (or a crash) doesn't hold the budget. Returns the number expired.
+    pub(crate) fn expire_restock_reservations(&self, now: i64, ttl: i64) -> Result<usize> {
+        let expired = self.conn.execute(
+            "DELETE FROM ledger.restocks WHERE reserved = 1 AND created_at < ?1 - ?2",
+            rusqlite::params![now, ttl],
+        )?;
+
+        Ok(expired)
+    }
+
+    /// Books the purchase of a reservation, the unspent part returns to the budget.
+    pub(crate) fn settle_restock(&self, id: i64, amount: u64, atlas_cost: u64) -> Result<()> {
+        self.conn.execute(
+            "UPDATE ledger.restocks SET amount = ?2, atlas_cost = ?3, reserved = 0 WHERE id = ?1",
+            rusqlite::params![id, amount as i64, atlas_cost as i64],
+        )?;
+
+        Ok(())
+    }
+
+    /// Returns the reservation of a purchase that didn't happen to the budget.
+    pub(crate) fn release_restock(&self, id: i64) -> Result<()> {
+        self.conn.execute(
+            "DELETE FROM ledger.restocks WHERE id = ?1 AND reserved = 1",
+            [id],
+        )?;
+
+        Ok(())
+    }
+}
+
+// cargo test -p actix-minebot
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DAY: i64 = 86_400;
+    const NOW: i64 = 1_700_000_000;
+
+    #[test]
+    fn reservations_stay_within_the_budget() {
+        let db = MinebotDB::open(":memory:").unwrap();
+        let reserve = |fleet, mint| db.reserve_restock(fleet, mint, 1_000, 600, NOW, DAY);
+
+        let (first, atlas) = reserve("fleet-a", "fuel").unwrap().unwrap();
+        assert_eq!(atlas, 600);
+
+        // a purchase in flight holds its reservation, the next one gets what remains
+        let (second, atlas) = reserve("fleet-b", "ammo").unwrap().unwrap();
+        assert_eq!(atlas, 400);
+        assert!(reserve("fleet-c", "food").unwrap().is_none());
+
+        // the unspent part of a purchase and a failed purchase return to the budget
+        db.settle_restock(first, 10, 500).unwrap();
+        db.release_restock(second).unwrap();
+        let (_, atlas) = reserve("fleet-c", "food").unwrap().unwrap();
+        assert_eq!(atlas, 500);
+    }
+
+    #[test]
+    fn a_settled_purchase_is_not_released() {
+        let db = MinebotDB::open(":memory:").unwrap();
+
+        let (id, _) = db
+            .reserve_restock("fleet-a", "fuel", 1_000, 1_000, NOW, DAY)
+            .unwrap()
+            .unwrap();
+        db.settle_restock(id, 10, 1_000).unwrap();
+        db.release_restock(id).unwrap();
+
+        assert!(db
+            .reserve_restock("fleet-a", "fuel", 1_000, 1, NOW, DAY)
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn the_budget_resets_each_period() {
+        let db = MinebotDB::open(":memory:").unwrap();
+
+        let (id, _) = db
+            .reserve_restock("fleet-a", "fuel", 1_000, 1_000, NOW, DAY)
+            .unwrap()
+            .unwrap();
+        db.settle_restock(id, 10, 1_000).unwrap();
+
+        let reserve = |now| db.reserve_restock("fleet-a", "fuel", 1_000, 700, now, DAY);
+        assert!(reserve(NOW + DAY - 1).unwrap().is_none());
+        assert_eq!(reserve(NOW + DAY).unwrap().unwrap().1, 700);
+    }
+
+    #[test]
+    fn stale_reservations_expire() {
+        let db = MinebotDB::open(":memory:").unwrap();
+
+        // a reservation orphaned by a crash, and one in flight
+        db.reserve_restock("fleet-a", "fuel", 1_000, 600, NOW - 700, DAY)
+            .unwrap()
+            .unwrap();
+        db.reserve_restock("fleet-b", "fuel", 1_000, 300, NOW - 10, DAY)
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(db.expire_restock_reservations(NOW, 600).unwrap(), 1);
+        let (_, atlas) = db
+            .reserve_restock("fleet-c", "fuel", 1_000, 1_000, NOW, DAY)
+            .unwrap()
+            .unwrap();
+        assert_eq!(atlas, 700);
+    }
+
+    #[test]
+    fn the_budget_outlives_the_bot() {
+        let path = std::env::temp_dir().join(format!("minebot-restock-{}.db", std::process::id()));
+        let ledger_path = path.to_str().unwrap();
+
+        {
+            let db = MinebotDB::open(ledger_path).unwrap();
+            let (id, _) = db
+                .reserve_restock("fleet-a", "fuel", 1_000, 700, NOW, DAY)
+                .unwrap()
+                .unwrap();
+            db.settle_restock(id, 10, 700).unwrap();
+        }
+
+        // within the period
+        let db = MinebotDB::open(ledger_path).unwrap();
+        let (_, atlas) = db
+            .reserve_restock("fleet-a", "fuel", 1_000, 700, NOW + 60, DAY)
+            .unwrap()
+            .unwrap();
+        assert_eq!(atlas, 300);
+
+        std::fs::remove_file(&path).ok();
+    }
+}
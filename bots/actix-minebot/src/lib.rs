@@ -0,0 +1,171 @@
+use actix::prelude::*;
+use anchor_client::{
+    solana_sdk::{commitment_config::CommitmentConfig, pubkey::Pubkey, signature::Keypair},
+    Client,
+};
+use color_eyre::Result;
+use tokio::time;
+
+use staratlas_sage_based_sdk::{program::SAGE_ID, state, Game, SageBasedGameHandler};
+
+use std::rc::Rc;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+
+pub use shared_time as timers;
+
+pub mod actors;
+mod app;
+pub mod check;
+pub mod cli;
+pub mod config;
+mod db;
+mod errors;
+pub mod report;
+#[cfg(test)]
+mod rpc_stub;
+mod supervisor;
+mod term;
+mod tui;
+
+/// How often the bot refreshes the marketplace prices of the ledger.
+const PRICES_REFRESH: time::Duration = time::Duration::from_secs(300);
+
+/// Runs the bots of the config, their roles are built through the `registry`.
+pub async fn run(sage_bot_cfg_path: &str, registry: actors::RoleRegistry) -> Result<()> {
+    errors::init_hooks()?;
+    config::init_logger()?;
+
+    // initialize the configuration (includes hot wallet's payer/keyair, and the unlocked wallet)
+    let cfg = Rc::new(config::init_config(sage_bot_cfg_path)?);
+    let game_id = Pubkey::from_str(&cfg.sage_bot_cfg.game_id)?;
+
+    // the healthy endpoints (checked again on each interval tick)
+    cfg.endpoints.check_health().await;
+    let cluster = config::cluster(cfg.endpoints.read_url());
+
+    // create a new client and program (read-only, the bots sign through their Sage Based actor)
+    let client = Client::new_with_options(
+        cluster.clone(),
+        Rc::new(Keypair::new()),
+        CommitmentConfig::confirmed(),
+    );
+    let program = client.program(SAGE_ID)?;
+
+    let account = program.account::<state::Game>(game_id).await?;
+    let game = Game::from(account);
+
+    // the cargo space of a unit of each mint (the cargo types of the game)
+    let cargo_weights =
+        SageBasedGameHandler::get_cargo_weights(&program.async_rpc(), &game).await?;
+
+    let roles = Rc::new(actors::RoleFactory {
+        registry,
+        ctx: actors::RoleContext {
+            program,
+            game_id,
+            game,
+            cargo_weights,
+        },
+        handovers: Default::default(),
+    });
+
+    // in-memory database for bot operations (and the file backed ledger)
+    let db = db::MinebotDB::open(&cfg.sage_bot_cfg.ledger_path)?;
+    let expired =
+        db.expire_restock_reservations(timers::get_time() as i64, actors::RESTOCK_RESERVATION_TTL)?;
+    if expired > 0 {
+        log::warn!("Released {} stale restock reservations", expired);
+    }
+    let mut mints = vec![game.mints.fuel, game.mints.ammo, game.mints.food];
+    let db = Arc::new(Mutex::new(db));
+
+    // one Sage Based actor per signer (the bots' wallet aliases, or the default payer)
+    let mut supervisor = supervisor::Supervisor::new(cfg.clone(), db.clone(), roles);
+
+    for bot_cfg in &cfg.sage_bot_cfg.bots {
+        let role_mints = supervisor.start(bot_cfg).await?;
+        mints.extend(role_mints);
+    }
+
+    // the bots follow the changes of the config (without a restart)
+    let mut config_watcher = supervisor::ConfigWatcher::new(sage_bot_cfg_path);
+
+    let mut interval = time::interval(time::Duration::from_secs(10));
+    let mut delta = time::Instant::now();
+
+    // value the ledger with the marketplace mid prices (refreshed on the interval tick)
+    let (prices_tx, mut prices_rx) = tokio::sync::mpsc::unbounded_channel();
+    let mut prices_refresh = time::Instant::now();
+
+    let app = app::init(
+        db.clone(),
+        (game_id, game),
+        supervisor.fleets(),
+        cfg.sage_bot_cfg.sol_price,
+    );
+    let terminal = &mut term::init()?;
+    let mut tui = tui::init(app);
+
+    loop {
+        tokio::select! {
+            _ = interval.tick() => {
+                let dt = delta.elapsed();
+
+                // the ledger's resources and the roles' mints, the previous prices kept on error
+                if prices_refresh <= time::Instant::now() {
+                    prices_refresh = time::Instant::now() + PRICES_REFRESH;
+
+                    let mut mints = mints.clone();
+                    if let Ok(db) = db.lock() {
+                        mints.extend(db.ledger_resource_mints()?);
+                    }
+                    mints.sort();
+                    mints.dedup();
+
+                    let (cluster, prices_tx) = (cluster.clone(), prices_tx.clone());
+                    actix::spawn(async move {
+                        match sa_marketplace_cli::fetch_mid_prices(cluster.url().to_string(), mints).await {
+                            Ok(prices) => {
+                                prices_tx.send(prices).ok();
+                            }
+                            Err(err) => log::error!("Marketplace prices: {:?}", err),
+                        }
+                    });
+                }
+
+                // send tick with delta-time to all bot actors
+                supervisor.tick(dt).await?;
+
+                let endpoints = cfg.endpoints.clone();
+                actix::spawn(async move { endpoints.check_health().await });
+
+                delta = time::Instant::now();
+
+                if let Some(sage_bot_cfg) = config_watcher.poll() {
+                    supervisor.reload(&sage_bot_cfg).await;
+                    tui.set_fleets(supervisor.fleets());
+                }
+
+                // the moved fleets, once their retiring bots stopped
+                if supervisor.start_replacements().await {
+                    tui.set_fleets(supervisor.fleets());
+                }
+            }
+            Some(prices) = prices_rx.recv() => {
+                tui.set_prices(prices);
+            }
+            _ = tui.run(terminal) => {
+                break;
+            }
+            _ = tokio::signal::ctrl_c() => {
+                break;
+            }
+        }
+    }
+
+    System::current().stop();
+    term::restore()?;
+
+    Ok(())
+}
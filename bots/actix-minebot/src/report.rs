@@ -0,0 +1,43 @@
+use anchor_client::{
+    solana_sdk::{commitment_config::CommitmentConfig, pubkey::Pubkey, signature::Keypair},
+    Client,
+};
+use color_eyre::{eyre::eyre, Result};
+
+use staratlas_sage_based_sdk::{program::SAGE_ID, SageBasedGameHandler};
+
+use std::rc::Rc;
+use std::str::FromStr;
+
+use crate::{config, db, tui::ui};
+
+pub async fn run(sage_bot_cfg_path: &str) -> Result<()> {
+    let sage_bot_cfg = config::load_sage_bot_cfg(sage_bot_cfg_path)?;
+    let endpoints = config::init_endpoints(&sage_bot_cfg.rpc)?;
+    endpoints.check_health().await;
+    let cluster = config::cluster(endpoints.read_url());
+    let game_id = Pubkey::from_str(&sage_bot_cfg.game_id)?;
+
+    // read-only, the report never signs a transaction
+    let client = Client::new_with_options(
+        cluster.clone(),
+        Rc::new(Keypair::new()),
+        CommitmentConfig::confirmed(),
+    );
+    let program = client.program(SAGE_ID)?;
+    let (_, game) = SageBasedGameHandler::get_game(&program, &game_id).await?;
+
+    let db = db::MinebotDB::open(&sage_bot_cfg.ledger_path)?;
+
+    let mut mints = db.ledger_resource_mints()?;
+    mints.extend([game.mints.fuel, game.mints.ammo, game.mints.food]);
+    let prices = sa_marketplace_cli::fetch_mid_prices(cluster.url().to_string(), mints)
+        .await
+        .map_err(|err| eyre!("{:?}", err))?;
+
+    let pnl_rows = db.pnl_report(&game.mints, &prices, sage_bot_cfg.sol_price)?;
+    let table = ui::LedgerUI::from(pnl_rows).table();
+    println!("{table}");
+
+    Ok(())
+}
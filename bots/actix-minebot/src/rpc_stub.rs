@@ -0,0 +1,88 @@
+//! A JSON-RPC endpoint of canned accounts for the tests (`cargo test -p actix-minebot`).
+
+use anchor_client::solana_sdk::{account::Account, pubkey::Pubkey};
+use solana_account_decoder::{UiAccount, UiAccountEncoding};
+use staratlas_sage_based_sdk::program::SAGE_ID;
+
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::str::FromStr;
+use std::sync::Arc;
+
+/// A JSON-RPC endpoint of the accounts (their data), an account it doesn't hold is missing
+/// and a query of the program accounts finds none.
+pub(crate) fn rpc_stub(accounts: HashMap<Pubkey, Vec<u8>>) -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    let accounts = Arc::new(accounts);
+
+    std::thread::spawn(move || {
+        for stream in listener.incoming().flatten() {
+            let accounts = accounts.clone();
+            std::thread::spawn(move || serve(stream, &accounts));
+        }
+    });
+    url
+}
+
+fn serve(mut stream: TcpStream, accounts: &HashMap<Pubkey, Vec<u8>>) {
+    let mut reader = BufReader::new(stream.try_clone().unwrap());
+
+    // the requests of a kept-alive connection, their headers then their content
+    loop {
+        let mut content_length = 0;
+        loop {
+            let mut line = String::new();
+            if reader.read_line(&mut line).unwrap_or(0) == 0 {
+                return;
+            }
+            if line == "\r\n" {
+                break;
+            }
+            if let Some((name, value)) = line.split_once(':') {
+                if name.eq_ignore_ascii_case("content-length") {
+                    content_length = value.trim().parse().unwrap();
+                }
+            }
+        }
+
+        let mut body = vec![0; content_length];
+        reader.read_exact(&mut body).unwrap();
+        let request: serde_json::Value = serde_json::from_slice(&body).unwrap();
+
+        let result = match request["method"].as_str() {
+            Some("getAccountInfo") => {
+                let pubkey = Pubkey::from_str(request["params"][0].as_str().unwrap()).unwrap();
+                let account = accounts.get(&pubkey).map(|data| {
+                    let account = Account {
+                        lamports: 1,
+                        data: data.clone(),
+                        owner: SAGE_ID,
+                        executable: false,
+                        rent_epoch: 0,
+                    };
+                    UiAccount::encode(&pubkey, &account, UiAccountEncoding::Base64, None, None)
+                });
+                serde_json::json!({ "context": { "slot": 1 }, "value": account })
+            }
+            Some("getProgramAccounts") => serde_json::json!([]),
+            Some("getVersion") => serde_json::json!({ "solana-core": "1.18.26" }),
+            _ => serde_json::Value::Null,
+        };
+
+        let response = serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": request["id"],
+            "result": result,
+        })
+        .to_string();
+        write!(
+            stream,
+            "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n{}",
+            response.len(),
+            response
+        )
+        .unwrap();
+    }
+}
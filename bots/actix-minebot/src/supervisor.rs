@@ -0,0 +1,498 @@
+use actix::prelude::*;
+use anchor_client::solana_sdk::{pubkey::Pubkey, signature::Signer};
+use color_eyre::Result;
+
+use staratlas_sage_based_sdk::{Fleet, SageBasedGameHandler};
+
+use std::collections::HashMap;
+use std::rc::Rc;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
+
+use crate::{actors, config, db};
+
+/// Detects the changes of the config file (by its modification time).
+pub(crate) struct ConfigWatcher {
+    path: String,
+    modified: Option<SystemTime>,
+}
+
+impl ConfigWatcher {
+    pub(crate) fn new(path: &str) -> Self {
+        ConfigWatcher {
+            path: path.to_string(),
+            modified: Self::modified(path),
+        }
+    }
+
+    fn modified(path: &str) -> Option<SystemTime> {
+        std::fs::metadata(path)
+            .and_then(|metadata| metadata.modified())
+            .ok()
+    }
+
+    /// The reloaded config when the file changed, an invalid config is logged and ignored.
+    pub(crate) fn poll(&mut self) -> Option<config::SageBotCfg> {
+        let modified = Self::modified(&self.path);
+        if modified == self.modified {
+            return None;
+        }
+        self.modified = modified;
+
+        match config::load_sage_bot_cfg(&self.path) {
+            Ok(sage_bot_cfg) => {
+                log::info!("Config reloaded: {}", self.path);
+                Some(sage_bot_cfg)
+            }
+            Err(err) => {
+                log::error!("Config {}: {:?}", self.path, err);
+                None
+            }
+        }
+    }
+}
+
+/// The bot of a moved fleet, started once the retiring bot (and its transaction in flight) stops.
+struct Replacement {
+    retiring: Addr<actors::BotActor>,
+    role: config::RoleCfg, // of the retiring bot
+    bot_cfg: config::BotCfg,
+}
+
+struct RunningBot {
+    fleet_id: Pubkey,
+    fleet: Fleet,
+    bot_cfg: config::BotCfg,
+    addr: Addr<actors::BotActor>,
+}
+
+/// The change of a fleet's bot to follow a reloaded config.
+#[derive(Debug, PartialEq)]
+enum BotChange {
+    Start,   // a fleet added to the config
+    Retire,  // a fleet removed from the config
+    Replace, // a fleet moved to another wallet (or risk policy), started once its bot retires
+    SetRole, // a changed role (or role arguments)
+}
+
+/// The changes of the running bots (and the fleets waiting on a retiring bot) to the bots of a
+/// reloaded config, `(FleetId, Change)` with the retirements first.
+fn config_diff(
+    running: &[&config::BotCfg],
+    replacing: &[&str],
+    bots: &[config::BotCfg],
+) -> Vec<(String, BotChange)> {
+    let mut changes = vec![];
+
+    for bot in running {
+        let change = match bots.iter().find(|c| c.fleet_id == bot.fleet_id) {
+            None => BotChange::Retire,
+            Some(c) if c.wallet != bot.wallet || c.max_risk != bot.max_risk => BotChange::Replace,
+            Some(_) => continue,
+        };
+        changes.push((bot.fleet_id.clone(), change));
+    }
+
+    for bot_cfg in bots {
+        let running = running.iter().find(|bot| bot.fleet_id == bot_cfg.fleet_id);
+
+        let change = match running {
+            Some(bot) if bot.wallet != bot_cfg.wallet || bot.max_risk != bot_cfg.max_risk => {
+                continue; // replaced
+            }
+            Some(bot) if bot.role != bot_cfg.role => BotChange::SetRole,
+            Some(_) => continue,
+            // waits on the retiring bot
+            None if replacing.contains(&bot_cfg.fleet_id.as_str()) => continue,
+            None => BotChange::Start,
+        };
+        changes.push((bot_cfg.fleet_id.clone(), change));
+    }
+
+    changes
+}
+
+/// Starts the bots of the config and applies the changes of a reloaded config to them.
+pub(crate) struct Supervisor {
+    cfg: Rc<config::Config>,
+    db: Arc<Mutex<db::MinebotDB>>,
+    signers: HashMap<Option<String>, Addr<actors::SageBasedActor>>, // (Wallet Alias, Actor)
+    scheduler: actors::RpcScheduler,
+    roles: Rc<actors::RoleFactory>,
+    restock: Option<config::RestockCfg>,
+    bots: Vec<RunningBot>,
+    replacements: Vec<Replacement>,
+}
+
+impl Supervisor {
+    pub(crate) fn new(
+        cfg: Rc<config::Config>,
+        db: Arc<Mutex<db::MinebotDB>>,
+        roles: Rc<actors::RoleFactory>,
+    ) -> Self {
+        let restock = cfg.sage_bot_cfg.restock.clone();
+
+        // one RPC budget for all signers (the same endpoints)
+        let scheduler = actors::RpcScheduler::new(cfg.endpoints.clone(), &cfg.sage_bot_cfg.rpc);
+
+        Supervisor {
+            cfg,
+            db,
+            signers: HashMap::new(),
+            scheduler,
+            roles,
+            restock,
+            bots: vec![],
+            replacements: vec![],
+        }
+    }
+
+    /// The Sage Based actor of a wallet alias (or the default payer), one actor per signer.
+    async fn sage_addr(&mut self, wallet: Option<&String>) -> Result<Addr<actors::SageBasedActor>> {
+        if let Some(sage_addr) = self.signers.get(&wallet.cloned()) {
+            return Ok(sage_addr.clone());
+        }
+
+        let payer = self.cfg.payer(wallet.map(String::as_str))?;
+        log::info!("Signer {:?}: {}", wallet, payer.pubkey());
+
+        // create a new Sage Based actor (take "ownership" of the payer, its clients per endpoint)
+        let ctx = &self.roles.ctx;
+        let sage_addr = actors::SageBasedActor::new(
+            payer,
+            ctx.game_id,
+            ctx.game,
+            self.scheduler.clone(),
+            self.cfg.sage_bot_cfg.rpc.subscriptions,
+        )
+        .start();
+        sage_addr.send(actors::BlockHeight).await?;
+
+        self.signers.insert(wallet.cloned(), sage_addr.clone());
+        Ok(sage_addr)
+    }
+
+    fn restock_policy(&self) -> Option<actors::RestockPolicy> {
+        self.restock
+            .as_ref()
+            .map(|restock| actors::RestockPolicy::new(restock, &self.roles.ctx.game.mints))
+    }
+
+    fn risk_policy(&self, bot_cfg: &config::BotCfg) -> Result<Option<actors::RiskPolicy>> {
+        bot_cfg
+            .max_risk
+            .as_ref()
+            .map(|max_risk| actors::RiskPolicy::new(max_risk, self.roles.ctx.game.risk_zones))
+            .transpose()
+    }
+
+    pub(crate) fn fleets(&self) -> Vec<(Pubkey, Fleet)> {
+        self.bots
+            .iter()
+            .map(|bot| (bot.fleet_id, bot.fleet))
+            .collect()
+    }
+
+    /// Starts the bot of a fleet, returns the mints of its role.
+    pub(crate) async fn start(&mut self, bot_cfg: &config::BotCfg) -> Result<Vec<Pubkey>> {
+        self.start_bot(bot_cfg, None, Default::default()).await
+    }
+
+    /// Starts the bot of a fleet, with the role (and cycle in progress) of a retired bot.
+    async fn start_bot(
+        &mut self,
+        bot_cfg: &config::BotCfg,
+        role: Option<Box<dyn actors::Role>>,
+        ledger: actors::CycleLedger,
+    ) -> Result<Vec<Pubkey>> {
+        let fleet_id = Pubkey::from_str(&bot_cfg.fleet_id)?;
+
+        let (fleet_id, fleet_with_state) =
+            SageBasedGameHandler::get_fleet_with_state(&self.roles.ctx.program, &fleet_id).await?;
+
+        // create a role assignment for the bot (or continue the handed over role)
+        let role = match role {
+            Some(role) => role,
+            None => self.roles.build(&bot_cfg.role).await?,
+        };
+        let mints = role.mints();
+
+        // the bot refuses a role beyond its risk policy
+        let risk = self.risk_policy(bot_cfg)?;
+        if let Some(risk) = &risk {
+            risk.check_role(&*role)?;
+        }
+
+        let sage_addr = self.sage_addr(bot_cfg.wallet.as_ref()).await?;
+
+        // create a new bot actor
+        let mut bot = actors::BotActor::new(
+            self.db.clone(),
+            sage_addr.clone(),
+            (fleet_id, fleet_with_state.clone()),
+            role,
+            self.roles.clone(),
+            self.restock_policy(),
+            risk,
+        );
+        bot.ledger = ledger;
+        let addr = bot.start();
+
+        // subscribe to the clock time, and request the current clock time to kick-off the bot
+        sage_addr
+            .send(actors::SubscribeClockTime(addr.clone().recipient()))
+            .await?;
+        sage_addr.do_send(actors::ClockTimeRequest(addr.clone()));
+
+        // the role's mints are read with the game's mints from the cargo hold
+        sage_addr.do_send(actors::CargoMints(mints.clone()));
+
+        // the fleet's (and its cargo pods') changes are pushed to the bot
+        sage_addr.do_send(actors::SubscribeFleet(
+            fleet_id,
+            fleet_with_state.0,
+            addr.clone(),
+        ));
+
+        self.bots.push(RunningBot {
+            fleet_id,
+            fleet: fleet_with_state.0,
+            bot_cfg: bot_cfg.clone(),
+            addr,
+        });
+
+        Ok(mints)
+    }
+
+    /// Sends the tick with delta-time to all bot actors.
+    pub(crate) async fn tick(&self, dt: std::time::Duration) -> Result<()> {
+        for bot in &self.bots {
+            bot.addr.send(actors::Tick(dt)).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Starts the new bots, retires the removed ones and replaces the changed roles.
+    pub(crate) async fn reload(&mut self, sage_bot_cfg: &config::SageBotCfg) {
+        if sage_bot_cfg.restock != self.restock {
+            log::info!("Restock policy changed");
+            self.restock = sage_bot_cfg.restock.clone();
+
+            let restock = self.restock_policy();
+            for bot in &self.bots {
+                bot.addr.do_send(actors::SetRestock(restock.clone()));
+            }
+        }
+
+        // the replacements follow the config, a fleet removed from it isn't replaced
+        self.replacements.retain_mut(|replacement| {
+            let fleet_id = &replacement.bot_cfg.fleet_id;
+            match sage_bot_cfg.bots.iter().find(|c| &c.fleet_id == fleet_id) {
+                Some(bot_cfg) => {
+                    replacement.bot_cfg = bot_cfg.clone();
+                    true
+                }
+                None => false,
+            }
+        });
+
+        let running: Vec<_> = self.bots.iter().map(|bot| &bot.bot_cfg).collect();
+        let replacing: Vec<_> = self
+            .replacements
+            .iter()
+            .map(|replacement| replacement.bot_cfg.fleet_id.as_str())
+            .collect();
+        let changes = config_diff(&running, &replacing, &sage_bot_cfg.bots);
+
+        for (fleet_id, change) in changes {
+            let bot_cfg = sage_bot_cfg.bots.iter().find(|c| c.fleet_id == fleet_id);
+            let running = self
+                .bots
+                .iter()
+                .position(|bot| bot.bot_cfg.fleet_id == fleet_id);
+
+            match (change, bot_cfg, running) {
+                (BotChange::Start, Some(bot_cfg), _) => {
+                    log::info!("Starting bot: {}", fleet_id);
+                    if let Err(err) = self.start(bot_cfg).await {
+                        log::error!("Bot {}: {:?}", fleet_id, err);
+                    }
+                }
+                (BotChange::SetRole, Some(bot_cfg), Some(index)) => {
+                    log::info!("Changing the role of bot: {}", fleet_id);
+                    let bot = &mut self.bots[index];
+                    bot.bot_cfg = bot_cfg.clone();
+                    bot.addr.do_send(actors::SetRole(bot_cfg.role.clone()));
+                }
+                (change @ (BotChange::Retire | BotChange::Replace), bot_cfg, Some(index)) => {
+                    let bot = self.bots.remove(index);
+                    let handover = change == BotChange::Replace;
+
+                    log::info!("Retiring bot: {}", fleet_id);
+                    bot.addr.do_send(actors::Retire { handover });
+
+                    if let (true, Some(bot_cfg)) = (handover, bot_cfg) {
+                        self.replacements.push(Replacement {
+                            retiring: bot.addr,
+                            role: bot.bot_cfg.role,
+                            bot_cfg: bot_cfg.clone(),
+                        });
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Starts the bots of the moved fleets whose retiring bots stopped, with their handed over
+    /// roles when the role didn't change.
+    pub(crate) async fn start_replacements(&mut self) -> bool {
+        let (stopped, waiting): (Vec<_>, Vec<_>) = self
+            .replacements
+            .drain(..)
+            .partition(|replacement| !replacement.retiring.connected());
+        self.replacements = waiting;
+
+        for replacement in &stopped {
+            let fleet_id = Pubkey::from_str(&replacement.bot_cfg.fleet_id).ok();
+            let handover =
+                fleet_id.and_then(|fleet_id| self.roles.handovers.borrow_mut().remove(&fleet_id));
+
+            // a changed role starts over, the cycle in progress goes on
+            let (role, ledger) = match handover {
+                Some(actors::Handover { role, ledger }) => {
+                    let unchanged = replacement.role == replacement.bot_cfg.role;
+                    (unchanged.then_some(role), ledger)
+                }
+                None => (None, Default::default()),
+            };
+
+            log::info!("Starting bot: {}", replacement.bot_cfg.fleet_id);
+            if let Err(err) = self.start_bot(&replacement.bot_cfg, role, ledger).await {
+                log::error!("Bot {}: {:?}", replacement.bot_cfg.fleet_id, err);
+            }
+        }
+
+        !stopped.is_empty()
+    }
+}
+
+// cargo test -p actix-minebot
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use anchor_client::solana_sdk::signature::Keypair;
+
+    fn bot_cfg(fleet_id: &str, role: serde_json::Value) -> config::BotCfg {
+        serde_json::from_value(serde_json::json!({ "fleet_id": fleet_id, "role": role })).unwrap()
+    }
+
+    fn mission(wait: u64) -> serde_json::Value {
+        serde_json::json!({ "Mission": { "steps": [{ "Wait": wait }] } })
+    }
+
+    fn supervisor(aliases: &[(&str, &Keypair)]) -> Supervisor {
+        let url = crate::rpc_stub::rpc_stub(Default::default());
+        let cfg = config::tests::config(&url, aliases);
+        let db = db::MinebotDB::open(":memory:").unwrap();
+
+        let roles = actors::RoleFactory {
+            registry: actors::RoleRegistry::default(),
+            ctx: actors::tests::ctx(),
+            handovers: Default::default(),
+        };
+        Supervisor::new(Rc::new(cfg), Arc::new(Mutex::new(db)), Rc::new(roles))
+    }
+
+    fn diff(running: &[config::BotCfg], bots: &[config::BotCfg]) -> Vec<(String, BotChange)> {
+        let running: Vec<_> = running.iter().collect();
+        config_diff(&running, &[], bots)
+    }
+
+    #[test]
+    fn a_new_bot_is_started() {
+        let running = [bot_cfg("a", mission(60))];
+        let bots = [bot_cfg("a", mission(60)), bot_cfg("b", mission(60))];
+
+        assert_eq!(
+            diff(&running, &bots),
+            vec![("b".to_string(), BotChange::Start)]
+        );
+    }
+
+    #[test]
+    fn a_removed_bot_is_retired() {
+        let running = [bot_cfg("a", mission(60)), bot_cfg("b", mission(60))];
+        let bots = [bot_cfg("b", mission(60))];
+
+        assert_eq!(
+            diff(&running, &bots),
+            vec![("a".to_string(), BotChange::Retire)]
+        );
+    }
+
+    #[test]
+    fn a_role_change_is_set_on_the_running_bot() {
+        let running = [bot_cfg("a", mission(60))];
+
+        // the role's arguments are part of the role
+        let bots = [bot_cfg("a", mission(120))];
+        assert_eq!(
+            diff(&running, &bots),
+            vec![("a".to_string(), BotChange::SetRole)]
+        );
+
+        assert!(diff(&running, &running).is_empty());
+    }
+
+    #[test]
+    fn a_wallet_or_risk_change_replaces_the_bot() {
+        let running = [bot_cfg("a", mission(60)), bot_cfg("b", mission(60))];
+
+        let mut bots = running.clone();
+        bots[0].wallet = Some("hot".to_string());
+        bots[1].max_risk = Some("secure".to_string());
+        // a replaced bot's role change goes to its replacement
+        bots[1].role = config::RoleCfg(serde_json::from_value(mission(120)).unwrap());
+
+        assert_eq!(
+            diff(&running, &bots),
+            vec![
+                ("a".to_string(), BotChange::Replace),
+                ("b".to_string(), BotChange::Replace),
+            ]
+        );
+
+        // the replacements start once the retiring bots stop, not on the next reload
+        let running: Vec<_> = running.iter().collect();
+        assert!(config_diff(&running[..0], &["a", "b"], &bots).is_empty());
+    }
+
+    #[actix::test]
+    async fn one_sage_based_actor_per_signer() {
+        let mut supervisor = supervisor(&[("hot", &Keypair::new()), ("cold", &Keypair::new())]);
+        let (hot, cold) = (Some("hot".to_string()), Some("cold".to_string()));
+
+        let payer = supervisor.sage_addr(None).await.unwrap();
+        let hot_a = supervisor.sage_addr(hot.as_ref()).await.unwrap();
+        let hot_b = supervisor.sage_addr(hot.as_ref()).await.unwrap();
+        let cold = supervisor.sage_addr(cold.as_ref()).await.unwrap();
+
+        // the bots of a wallet alias share its actor
+        assert!(hot_a == hot_b);
+        assert!(payer != hot_a && hot_a != cold && payer != cold);
+        assert_eq!(supervisor.signers.len(), 3);
+    }
+
+    #[actix::test]
+    async fn an_unknown_signer_gets_no_actor() {
+        let mut supervisor = supervisor(&[("hot", &Keypair::new())]);
+
+        let alias = Some("warm".to_string());
+        assert!(supervisor.sage_addr(alias.as_ref()).await.is_err());
+        assert!(supervisor.signers.is_empty());
+    }
+}
@@ -5,6 +5,11 @@ use ratatui::{prelude::*, widgets::*};
 use strum::{Display, EnumIter, FromRepr, IntoEnumIterator};
 use tui_logger::TuiLoggerWidget;
 
+use anchor_client::anchor_lang::prelude::Pubkey;
+use staratlas_sage_based_sdk::Fleet;
+
+use std::collections::HashMap;
+
 use crate::app;
 
 mod events;
@@ -23,6 +28,7 @@ enum Tab {
     BotOps,
     Game,
     Fleets,
+    Ledger,
 }
 
 impl Tab {
@@ -59,6 +65,14 @@ impl Tui {
         }
     }
 
+    pub fn set_fleets(&mut self, fleets: Vec<(Pubkey, Fleet)>) {
+        self.app.data.fleets_ui = fleets.into();
+    }
+
+    pub fn set_prices(&mut self, prices: HashMap<Pubkey, f64>) {
+        self.app.set_prices(prices);
+    }
+
     pub async fn run(&mut self, terminal: &mut Terminal<impl Backend>) -> Result<()> {
         while self.app.is_running() {
             self.update().await?;
@@ -162,6 +176,10 @@ impl Widget for &mut Tui {
                 let table = self.app.data.fleets_ui.table();
                 Paragraph::new(Text::raw(format!("{table}"))).render(content, buf);
             }
+            Tab::Ledger => {
+                let table = self.app.data.ledger_ui.table();
+                Paragraph::new(Text::raw(format!("{table}"))).render(content, buf);
+            }
         }
 
         // render footer
@@ -5,7 +5,7 @@ use staratlas_sage_based_sdk::{Fleet, Game};
 
 use std::time::Duration;
 
-use crate::{actors, timers};
+use crate::{actors, db, timers};
 
 fn ui_pubkey(pubkey: &Pubkey) -> String {
     let id = pubkey.to_string();
@@ -193,3 +193,59 @@ impl BotOpsUI {
         table
     }
 }
+
+pub(crate) struct LedgerUI(pub Vec<db::PnlRow>);
+
+impl From<Vec<db::PnlRow>> for LedgerUI {
+    fn from(rows: Vec<db::PnlRow>) -> Self {
+        LedgerUI(rows)
+    }
+}
+
+impl LedgerUI {
+    pub fn table(&self) -> Table {
+        let mut table = Table::new();
+        table.set_header(vec![
+            "Fleet ID",
+            "Role",
+            "Cycles",
+            "Hours",
+            "Revenue (ATLAS)",
+            "Costs (ATLAS)",
+            "P&L (ATLAS)",
+            "P&L/Hour",
+            "P&L/Day",
+            "Fees (SOL)",
+            "Fees (ATLAS)",
+        ]);
+
+        for row in self.0.iter() {
+            let fleet = match &row.fleet {
+                Some(pubkey_str) => ui_pubkey_str(pubkey_str),
+                None => "All".to_string(),
+            };
+
+            // a value without a price isn't 0
+            let atlas = |value: Option<f64>| match value {
+                Some(value) => format!("{:.4}", value),
+                None => "no price".to_string(),
+            };
+
+            table.add_row(vec![
+                fleet,
+                row.role.clone(),
+                format!("{}", row.cycles),
+                format!("{:.2}", row.hours),
+                atlas(row.revenue),
+                atlas(row.costs),
+                atlas(row.pnl()),
+                atlas(row.pnl_per_hour()),
+                atlas(row.pnl_per_day()),
+                format!("{:.6}", row.fees_sol),
+                atlas(row.fees),
+            ]);
+        }
+
+        table
+    }
+}
@@ -0,0 +1,230 @@
+use anchor_client::{
+    solana_client::rpc_client::RpcClient,
+    solana_sdk::{commitment_config::CommitmentConfig, pubkey::Pubkey, signature::Signature},
+    Cluster,
+};
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use clap::{Parser, Subcommand, ValueEnum};
+use serde_json::Value;
+
+use staratlas_inspect_sdk::{
+    decode_account, decode_any_account, decode_serialized_transaction, fetch_transaction,
+    find_program, find_program_by_name, DecodedAccount, DecodedTransaction,
+};
+
+/// Star Atlas: Inspect CLI --> donations: 2yodqKtkdNJXxJv21s5YMVG8bjscaezLVFRfnWra5D77 <--
+#[derive(Parser)]
+#[command(about, long_about = None)]
+struct Cli {
+    #[clap(flatten)]
+    provider_config: ProviderConfig,
+    #[command(subcommand)]
+    command: Commands,
+}
+
+#[derive(Default, Parser)]
+struct ProviderConfig {
+    /// RPC URL for the Solana cluster
+    #[clap(long = "provider.cluster", env = "PROVIDER_CLUSTER")]
+    pub cluster: Option<Cluster>,
+}
+
+#[derive(Subcommand)]
+enum Commands {
+    /// Decode an account of any bundled Star Atlas program
+    Account {
+        /// Account address (pubkey)
+        #[arg(required_unless_present = "raw")]
+        pubkey: Option<Pubkey>,
+        /// Account data (base64) to decode offline, instead of fetching the account
+        #[arg(long, conflicts_with = "pubkey")]
+        raw: Option<String>,
+        /// Program (name or ID) of the raw data, found by its discriminator otherwise
+        #[arg(long, requires = "raw")]
+        program: Option<String>,
+        #[arg(long, value_enum, default_value = "text")]
+        output: Output,
+    },
+    /// Decode the instructions of a transaction targeting the bundled Star Atlas programs
+    Transaction {
+        /// Transaction signature
+        #[arg(required_unless_present = "raw")]
+        signature: Option<Signature>,
+        /// Serialized transaction (base64) to decode offline, instead of fetching it
+        #[arg(long, conflicts_with = "signature")]
+        raw: Option<String>,
+        #[arg(long, value_enum, default_value = "text")]
+        output: Output,
+    },
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, ValueEnum)]
+enum Output {
+    /// A table of an account's fields, the instructions' args and accounts of a transaction
+    Text,
+    Json,
+}
+
+/// The rows (`path`, `value`) of a decoded value, an array of numbers (bytes, coordinates) on one
+/// row.
+fn flatten(path: String, value: &Value, rows: &mut Vec<(String, String)>) {
+    match value {
+        Value::Object(object) => {
+            for (key, value) in object {
+                let path = match path.is_empty() {
+                    true => key.clone(),
+                    false => format!("{}.{}", path, key),
+                };
+                flatten(path, value, rows);
+            }
+        }
+        Value::Array(values) if values.iter().any(|v| v.is_object() || v.is_array()) => {
+            for (i, value) in values.iter().enumerate() {
+                flatten(format!("{}[{}]", path, i), value, rows);
+            }
+        }
+        Value::String(s) => rows.push((path, s.clone())),
+        _ => rows.push((path, value.to_string())),
+    }
+}
+
+fn print_account(account: &DecodedAccount, output: Output) -> anyhow::Result<()> {
+    match output {
+        Output::Json => println!("{}", serde_json::to_string_pretty(&account.to_json())?),
+        Output::Text => {
+            let mut rows = vec![];
+            flatten(String::new(), &account.data, &mut rows);
+
+            let mut table = comfy_table::Table::new();
+            table.set_header(vec!["Field", "Value"]);
+            for (field, value) in rows {
+                table.add_row(vec![field, value]);
+            }
+
+            println!(
+                "{} {} ({})",
+                account.program, account.name, account.program_id
+            );
+            println!("{table}");
+            if !account.remaining_data.is_empty() {
+                println!("Remaining data: {} bytes", account.remaining_data.len());
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn print_transaction(transaction: &DecodedTransaction, output: Output) -> anyhow::Result<()> {
+    if output == Output::Json {
+        println!("{}", serde_json::to_string_pretty(&transaction.to_json())?);
+        return Ok(());
+    }
+
+    println!("Signature: {}", transaction.signature);
+    if let Some(err) = &transaction.err {
+        println!("Error: {}", err);
+    }
+
+    for instruction in &transaction.instructions {
+        let index = match instruction.inner_index {
+            Some(inner_index) => format!("  #{}.{}", instruction.index, inner_index),
+            None => format!("#{}", instruction.index),
+        };
+
+        match &instruction.decoded {
+            None => println!("{} {}", index, instruction.program_id),
+            Some(Err(err)) => println!("{} {}: {}", index, instruction.program_id, err),
+            Some(Ok(decoded)) => {
+                println!("{} {} {}", index, decoded.program, decoded.name);
+
+                let mut rows = vec![];
+                flatten(String::new(), &decoded.args, &mut rows);
+                for (arg, value) in rows {
+                    println!("      {}: {}", arg, value);
+                }
+                for (name, pubkey) in &decoded.accounts {
+                    println!("      [{}] {}", name, pubkey);
+                }
+            }
+        }
+    }
+
+    // what a failed transaction did
+    if transaction.err.is_some() {
+        println!("Logs:");
+        for log in &transaction.log_messages {
+            println!("  {}", log);
+        }
+    }
+
+    Ok(())
+}
+
+fn main() -> anyhow::Result<()> {
+    let cli = Cli::parse();
+
+    let url = match cli.provider_config.cluster {
+        Some(cluster) => cluster,
+        None => Cluster::Devnet,
+    };
+
+    match &cli.command {
+        Commands::Account {
+            pubkey,
+            raw,
+            program,
+            output,
+        } => {
+            let account = match (pubkey, raw) {
+                (Some(pubkey), _) => {
+                    let rpc = RpcClient::new_with_commitment(
+                        url.url().to_string(),
+                        CommitmentConfig::confirmed(),
+                    );
+                    let account = rpc.get_account(pubkey)?;
+                    let program = find_program(&account.owner).ok_or(anyhow::anyhow!(
+                        "Account owned by {}, not a bundled program",
+                        account.owner
+                    ))?;
+                    decode_account(program, &account.data)?
+                }
+                (None, Some(raw)) => {
+                    let data = BASE64.decode(raw.trim())?;
+                    match program {
+                        Some(program) => {
+                            let program = find_program_by_name(program)
+                                .ok_or(anyhow::anyhow!("Unknown program {}", program))?;
+                            decode_account(program, &data)?
+                        }
+                        None => decode_any_account(&data)?,
+                    }
+                }
+                (None, None) => unreachable!("clap requires a pubkey or --raw"),
+            };
+
+            print_account(&account, *output)?;
+        }
+        Commands::Transaction {
+            signature,
+            raw,
+            output,
+        } => {
+            let transaction = match (signature, raw) {
+                (Some(signature), _) => {
+                    let rpc = RpcClient::new_with_commitment(
+                        url.url().to_string(),
+                        CommitmentConfig::confirmed(),
+                    );
+                    fetch_transaction(&rpc, signature)?
+                }
+                (None, Some(raw)) => decode_serialized_transaction(&BASE64.decode(raw.trim())?)?,
+                (None, None) => unreachable!("clap requires a signature or --raw"),
+            };
+
+            print_transaction(&transaction, *output)?;
+        }
+    }
+
+    Ok(())
+}
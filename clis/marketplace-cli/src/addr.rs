@@ -0,0 +1,54 @@
+use anchor_client::anchor_lang::prelude::Pubkey;
+
+use staratlas_atlas_staking::ID as ATLAS_STAKING_ID;
+use staratlas_marketplace::ID as PROGRAM_ID;
+
+pub fn fee_reduction_address(fee_exempt_target: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"fee-exempt", fee_exempt_target.as_ref()], &PROGRAM_ID)
+}
+
+pub fn open_orders_counter_address(
+    order_initializer: &Pubkey,
+    deposit_mint: &Pubkey,
+) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            b"open-orders-counter",
+            order_initializer.as_ref(),
+            deposit_mint.as_ref(),
+        ],
+        &PROGRAM_ID,
+    )
+}
+
+pub fn order_vault_address(order_initializer: &Pubkey, deposit_mint: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            b"order-vault-account",
+            order_initializer.as_ref(),
+            deposit_mint.as_ref(),
+        ],
+        &PROGRAM_ID,
+    )
+}
+
+pub fn order_vault_authority_address(order_initializer: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[b"order-vault-auth", order_initializer.as_ref()],
+        &PROGRAM_ID,
+    )
+}
+
+pub fn registered_currency_address(currency_mint: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[b"registered-currency", currency_mint.as_ref()],
+        &PROGRAM_ID,
+    )
+}
+
+pub fn staking_account_address(user: &Pubkey, registered_stake: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[b"staking-account", user.as_ref(), registered_stake.as_ref()],
+        &ATLAS_STAKING_ID,
+    )
+}
@@ -0,0 +1,112 @@
+use anchor_client::{
+    anchor_lang::{
+        prelude::{AccountMeta, Pubkey},
+        InstructionData,
+    },
+    solana_sdk::{instruction::Instruction, signature::Signer},
+    Client,
+};
+use spl_associated_token_account::get_associated_token_address;
+
+use staratlas_atlas_staking::{RegisteredStake, ID as ATLAS_STAKING_ID};
+use staratlas_marketplace::{
+    instruction, typedefs::OrderSide, OrderAccount, RegisteredCurrency, ID as PROGRAM_ID,
+};
+use staratlas_utils_query::Query;
+
+use std::ops::Deref;
+
+use crate::addr;
+
+/// Builds a `processExchange` instruction that fills `purchase_quantity` of an open order.
+///
+/// The taker's token accounts are the associated token accounts of `order_taker`, the asset's
+/// account must exist before the exchange is processed.
+pub fn process_exchange<C: Deref<Target = impl Signer> + Clone>(
+    client: &Client<C>,
+    order_taker: &Pubkey,
+    order: (&Pubkey, &OrderAccount),
+    purchase_quantity: u64,
+) -> anyhow::Result<Instruction> {
+    let program = client.program(PROGRAM_ID);
+    let staking_program = client.program(ATLAS_STAKING_ID);
+
+    let (order_id, order) = order;
+    let currency_mint = order.currency_mint;
+    let asset_mint = order.asset_mint;
+    let order_initializer = order.order_initializer_pubkey;
+
+    // (taker's deposit, taker's receive, initializer's deposit, initializer's receive, order's deposit mint)
+    let (taker_deposit, taker_receive, initializer_deposit, initializer_receive, deposit_mint) =
+        match order.order_side {
+            OrderSide::Sell => (
+                get_associated_token_address(order_taker, &currency_mint),
+                get_associated_token_address(order_taker, &asset_mint),
+                order.initializer_asset_token_account,
+                order.initializer_currency_token_account,
+                asset_mint,
+            ),
+            OrderSide::Buy => (
+                get_associated_token_address(order_taker, &asset_mint),
+                get_associated_token_address(order_taker, &currency_mint),
+                order.initializer_currency_token_account,
+                order.initializer_asset_token_account,
+                currency_mint,
+            ),
+        };
+
+    let (order_vault, _) = addr::order_vault_address(&order_initializer, &deposit_mint);
+    let (order_vault_authority, _) = addr::order_vault_authority_address(&order_initializer);
+    let (open_orders_counter, _) =
+        addr::open_orders_counter_address(&order_initializer, &deposit_mint);
+
+    let (registered_currency_id, _) = addr::registered_currency_address(&currency_mint);
+    let registered_currency = program.account::<RegisteredCurrency>(registered_currency_id)?;
+
+    // the ATLAS stake of the taker (if any) reduces the marketplace fee
+    let registered_stakes = staking_program.accounts::<RegisteredStake>(
+        Query::<RegisteredStake>::new()
+            .eq(|f| f.stake_mint, currency_mint)
+            .filters(),
+    )?;
+    let registered_stake = registered_stakes
+        .first()
+        .map(|(pubkey, _)| *pubkey)
+        .ok_or(anyhow::anyhow!("RegisteredStake not found"))?;
+    let (staking_account, _) = addr::staking_account_address(order_taker, &registered_stake);
+    let (fee_reduction, _) = addr::fee_reduction_address(order_taker);
+
+    let instr = instruction::ProcessExchange {
+        _purchase_quantity: purchase_quantity,
+        _expected_price: order.price,
+        _seller: order_initializer,
+    };
+
+    let ix = Instruction::new_with_bytes(
+        PROGRAM_ID,
+        &instr.data(),
+        vec![
+            AccountMeta::new(*order_taker, true),
+            AccountMeta::new(taker_deposit, false),
+            AccountMeta::new(taker_receive, false),
+            AccountMeta::new_readonly(currency_mint, false),
+            AccountMeta::new_readonly(asset_mint, false),
+            AccountMeta::new(order_initializer, false),
+            AccountMeta::new(initializer_deposit, false),
+            AccountMeta::new(initializer_receive, false),
+            AccountMeta::new(order_vault, false),
+            AccountMeta::new_readonly(order_vault_authority, false),
+            AccountMeta::new(*order_id, false),
+            AccountMeta::new(registered_currency.sa_currency_vault, false),
+            AccountMeta::new_readonly(registered_currency_id, false),
+            AccountMeta::new(open_orders_counter, false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+            AccountMeta::new_readonly(ATLAS_STAKING_ID, false),
+            AccountMeta::new_readonly(registered_stake, false),
+            AccountMeta::new_readonly(staking_account, false),
+            AccountMeta::new_readonly(fee_reduction, false),
+        ],
+    );
+
+    Ok(ix)
+}
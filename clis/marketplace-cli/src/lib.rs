@@ -0,0 +1,255 @@
+use anchor_client::{
+    anchor_lang::prelude::Pubkey,
+    solana_sdk::{instruction::Instruction, signature::Signer, signer::null_signer::NullSigner},
+    Client, Cluster,
+};
+use spl_associated_token_account::instruction::create_associated_token_account_idempotent;
+
+use staratlas_marketplace::{typedefs::OrderSide, OrderAccount, ID as PROGRAM_ID};
+use staratlas_utils_query::Query;
+
+use std::collections::HashMap;
+use std::ops::Deref;
+use std::str::FromStr;
+
+pub mod addr;
+pub mod ixs;
+
+pub const ATLAS_MINT: &str = "ATLASXmbPQxBUYbxPsV97usA3fPQYEqzQBUHgiFCUsXx";
+pub const ATLAS_DECIMALS: u8 = 8;
+
+/// Instructions of an exchange against an open order, `price` is in the currency's base units.
+#[derive(Debug, Clone)]
+pub struct Exchange {
+    pub instructions: Vec<Instruction>,
+    pub quantity: u64,
+    pub price: u64,
+}
+
+pub fn get_open_orders_for_asset<C: Deref<Target = impl Signer> + Clone>(
+    client: &Client<C>,
+    asset_mint: &Pubkey,
+) -> anyhow::Result<Vec<(Pubkey, OrderAccount)>> {
+    let program = client.program(PROGRAM_ID);
+
+    let orders = program.accounts::<OrderAccount>(
+        Query::<OrderAccount>::new()
+            .data_size()
+            .eq(|f| f.asset_mint, asset_mint)
+            .filters(),
+    )?;
+
+    Ok(orders)
+}
+
+pub fn get_all_open_orders<C: Deref<Target = impl Signer> + Clone>(
+    client: &Client<C>,
+) -> anyhow::Result<Vec<(Pubkey, OrderAccount)>> {
+    let program = client.program(PROGRAM_ID);
+
+    // Only really care about the ATLAS orders (currency mint) for Galactic/Local Marketplace data
+    let orders = program.accounts::<OrderAccount>(
+        Query::<OrderAccount>::new()
+            .data_size()
+            .eq(|f| f.currency_mint, Pubkey::from_str(ATLAS_MINT).unwrap())
+            .filters(),
+    )?;
+
+    Ok(orders)
+}
+
+/// Returns the cheapest open sell order for an asset, priced in `currency_mint`.
+pub fn get_best_sell_order<C: Deref<Target = impl Signer> + Clone>(
+    client: &Client<C>,
+    asset_mint: &Pubkey,
+    currency_mint: &Pubkey,
+) -> anyhow::Result<Option<(Pubkey, OrderAccount)>> {
+    let orders = get_open_orders_for_asset(client, asset_mint)?;
+
+    let best_sell = orders
+        .into_iter()
+        .filter(|(_, order)| &order.currency_mint == currency_mint)
+        .filter(|(_, order)| matches!(order.order_side, OrderSide::Sell))
+        .filter(|(_, order)| order.order_remaining_qty > 0)
+        .min_by_key(|(_, order)| order.price);
+
+    Ok(best_sell)
+}
+
+/// Builds the exchange buying up to `max_quantity` of an asset from its cheapest sell order.
+///
+/// The purchase is capped by `max_price` per unit and by `budget` in total (both in the
+/// currency's base units), `None` is returned when no sell order fits within the caps.
+pub fn get_buy_exchange(
+    rpc_url: &str,
+    order_taker: &Pubkey,
+    asset_mint: &Pubkey,
+    currency_mint: &Pubkey,
+    max_quantity: u64,
+    max_price: u64,
+    budget: u64,
+) -> anyhow::Result<Option<Exchange>> {
+    // The order taker signs the transaction, the client only reads accounts
+    let null_signer = NullSigner::new(order_taker);
+
+    let client = Client::new(
+        Cluster::Custom(rpc_url.to_string(), rpc_url.to_string()),
+        &null_signer,
+    );
+
+    let (order_id, order) = match get_best_sell_order(&client, asset_mint, currency_mint)? {
+        Some(best_sell) => best_sell,
+        None => return Ok(None),
+    };
+
+    if order.price == 0 || order.price > max_price {
+        return Ok(None);
+    }
+
+    let quantity = max_quantity
+        .min(order.order_remaining_qty)
+        .min(budget / order.price);
+
+    if quantity == 0 {
+        return Ok(None);
+    }
+
+    let instructions = vec![
+        create_associated_token_account_idempotent(
+            order_taker,
+            order_taker,
+            asset_mint,
+            &spl_token::id(),
+        ),
+        ixs::process_exchange(&client, order_taker, (&order_id, &order), quantity)?,
+    ];
+
+    Ok(Some(Exchange {
+        instructions,
+        quantity,
+        price: order.price,
+    }))
+}
+
+/// Returns the mid price (in the currency's base units) of the order book for an asset.
+///
+/// The mid price is the average of the best buy and the best sell, if only one side of the
+/// book has open orders its best price is used, and `None` is returned for an empty book.
+pub fn get_order_book_mid_price<C: Deref<Target = impl Signer> + Clone>(
+    client: &Client<C>,
+    asset_mint: &Pubkey,
+    currency_mint: &Pubkey,
+) -> anyhow::Result<Option<f64>> {
+    let orders = get_open_orders_for_asset(client, asset_mint)?;
+
+    let mut best_buy: Option<u64> = None;
+    let mut best_sell: Option<u64> = None;
+
+    for (_pubkey, order) in orders {
+        // Filter out orders that are not priced in "currency_mint"
+        if &order.currency_mint != currency_mint {
+            continue;
+        }
+
+        match order.order_side {
+            OrderSide::Buy => best_buy = best_buy.max(Some(order.price)),
+            OrderSide::Sell => {
+                best_sell = Some(best_sell.map_or(order.price, |p| p.min(order.price)))
+            }
+        }
+    }
+
+    let mid_price = match (best_buy, best_sell) {
+        (Some(buy), Some(sell)) => Some((buy as f64 + sell as f64) / 2.0),
+        (Some(price), None) | (None, Some(price)) => Some(price as f64),
+        (None, None) => None,
+    };
+
+    Ok(mid_price)
+}
+
+/// Returns the order book mid prices for a set of assets, priced in `currency_mint`.
+///
+/// Assets without any open orders in the currency are left out of the map.
+pub fn get_order_book_mid_prices(
+    rpc_url: &str,
+    asset_mints: &[Pubkey],
+    currency_mint: &Pubkey,
+) -> anyhow::Result<HashMap<Pubkey, f64>> {
+    // Create a new wallet and payer
+    let wallet = Pubkey::new_unique();
+    let null_signer = NullSigner::new(&wallet);
+
+    // Create a new Anchor client
+    let client = Client::new(
+        Cluster::Custom(rpc_url.to_string(), rpc_url.to_string()),
+        &null_signer,
+    );
+
+    let mut mid_prices = HashMap::new();
+
+    for asset_mint in asset_mints {
+        if let Some(price) = get_order_book_mid_price(&client, asset_mint, currency_mint)? {
+            mid_prices.insert(*asset_mint, price);
+        }
+    }
+
+    Ok(mid_prices)
+}
+
+/// Runs the marketplace queries off the async runtime, they use a blocking rpc client.
+async fn spawn_query<T: Send + 'static>(
+    query: impl FnOnce() -> anyhow::Result<T> + Send + 'static,
+) -> anyhow::Result<T> {
+    tokio::task::spawn_blocking(query).await?
+}
+
+/// Returns the order book mid prices (in ATLAS) for a set of assets, off the async runtime.
+///
+/// Assets without any open orders in ATLAS are left out of the map.
+pub async fn fetch_mid_prices(
+    rpc_url: String,
+    asset_mints: Vec<Pubkey>,
+) -> anyhow::Result<HashMap<Pubkey, f64>> {
+    let atlas_mint = Pubkey::from_str(ATLAS_MINT)?;
+
+    let mid_prices =
+        spawn_query(move || get_order_book_mid_prices(&rpc_url, &asset_mints, &atlas_mint)).await?;
+
+    let scale = 10f64.powi(ATLAS_DECIMALS as i32);
+    let prices = mid_prices
+        .into_iter()
+        .map(|(mint, price)| (mint, price / scale))
+        .collect();
+
+    Ok(prices)
+}
+
+/// RPC calls of `fetch_buy_exchange` (the open orders and the registered currency).
+pub const BUY_EXCHANGE_RPC_CALLS: usize = 2;
+
+/// Builds the exchange buying an asset priced in ATLAS (see `get_buy_exchange`), off the async
+/// runtime.
+pub async fn fetch_buy_exchange(
+    rpc_url: String,
+    order_taker: Pubkey,
+    asset_mint: Pubkey,
+    max_quantity: u64,
+    max_price: u64,
+    budget: u64,
+) -> anyhow::Result<Option<Exchange>> {
+    let atlas_mint = Pubkey::from_str(ATLAS_MINT)?;
+
+    spawn_query(move || {
+        get_buy_exchange(
+            &rpc_url,
+            &order_taker,
+            &asset_mint,
+            &atlas_mint,
+            max_quantity,
+            max_price,
+            budget,
+        )
+    })
+    .await
+}
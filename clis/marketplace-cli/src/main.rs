@@ -1,19 +1,18 @@
 use anchor_client::{
-    anchor_lang::prelude::Pubkey,
-    solana_client::rpc_filter::{Memcmp, RpcFilterType},
-    solana_sdk::{signature::Signer, signer::null_signer::NullSigner},
-    Client, Cluster,
+    anchor_lang::prelude::Pubkey, solana_sdk::signer::null_signer::NullSigner, Client, Cluster,
 };
 use clap::{Parser, Subcommand, ValueEnum};
 use dotenv::dotenv;
 
 use staratlas_galaxy::Galaxy;
-use staratlas_marketplace::{typedefs::OrderSide, OrderAccount, ID as PROGRAM_ID};
+use staratlas_marketplace::typedefs::OrderSide;
+use staratlas_utils_rpc::{is_endpoint_error, EndpointRole, EndpointsCfg, RpcEndpoints};
 
 use std::fs::File;
-use std::ops::Deref;
 use std::str::FromStr;
 
+use sa_marketplace_cli::{get_all_open_orders, get_open_orders_for_asset, ATLAS_MINT};
+
 /// Star Atlas: Marketplace CLI --> donations: 2yodqKtkdNJXxJv21s5YMVG8bjscaezLVFRfnWra5D77 <--
 #[derive(Parser)]
 #[command(about, long_about = None)]
@@ -29,7 +28,7 @@ enum Commands {
     Dump {
         /// The path to the CSV file to write
         output: String,
-        /// Solana RCP URL, if none is provided the cli will attempt to read from the SOLANA_RPC_URL environment variable
+        /// Solana RCP URL (or a comma separated list of `url[|weight[|role]]` endpoints), if none is provided the cli will attempt to read from the SOLANA_RPC_URL environment variable
         #[arg(long, value_name = "SOLANA_RPC_URL")]
         rpc_url: Option<String>,
         /// The currency of price for orders to dump
@@ -43,7 +42,7 @@ enum Commands {
     DumpAllOpen {
         /// The path to the CSV file to write
         output: String,
-        /// Solana RCP URL, if none is provided the cli will attempt to read from the SOLANA_RPC_URL environment variable
+        /// Solana RCP URL (or a comma separated list of `url[|weight[|role]]` endpoints), if none is provided the cli will attempt to read from the SOLANA_RPC_URL environment variable
         #[arg(long, value_name = "SOLANA_RPC_URL")]
         rpc_url: Option<String>,
     },
@@ -55,39 +54,6 @@ enum Currency {
     USDC,
 }
 
-pub fn get_open_orders_for_asset<C: Deref<Target = impl Signer> + Clone>(
-    client: &Client<C>,
-    asset_mint: &Pubkey,
-) -> anyhow::Result<Vec<(Pubkey, OrderAccount)>> {
-    let program = client.program(PROGRAM_ID);
-
-    let orders = program.accounts::<OrderAccount>(vec![
-        RpcFilterType::DataSize(201),
-        RpcFilterType::Memcmp(Memcmp::new_base58_encoded(72, &asset_mint.to_bytes())),
-    ])?;
-
-    Ok(orders)
-}
-
-pub fn get_all_open_orders<C: Deref<Target = impl Signer> + Clone>(
-    client: &Client<C>,
-) -> anyhow::Result<Vec<(Pubkey, OrderAccount)>> {
-    let program = client.program(PROGRAM_ID);
-
-    // Only really care about the ATLAS orders (currency mint) for Galactic/Local Marketplace data
-    let orders = program.accounts::<OrderAccount>(vec![
-        RpcFilterType::DataSize(201),
-        RpcFilterType::Memcmp(Memcmp::new_base58_encoded(
-            40,
-            &Pubkey::from_str("ATLASXmbPQxBUYbxPsV97usA3fPQYEqzQBUHgiFCUsXx")
-                .unwrap()
-                .to_bytes(),
-        )),
-    ])?;
-
-    Ok(orders)
-}
-
 fn run_dump(output: &str, currency: &Currency, rpc_url: &str, depth: &u8) -> anyhow::Result<()> {
     // Create a new wallet and payer
     let wallet = Pubkey::new_unique();
@@ -119,7 +85,7 @@ fn run_dump(output: &str, currency: &Currency, rpc_url: &str, depth: &u8) -> any
     let resources = galaxy.get_resources();
 
     let currency_mint = match currency {
-        Currency::ATLAS => "ATLASXmbPQxBUYbxPsV97usA3fPQYEqzQBUHgiFCUsXx",
+        Currency::ATLAS => ATLAS_MINT,
         Currency::USDC => "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v",
     };
 
@@ -249,6 +215,19 @@ fn run_dump_all_open(output: &str, rpc_url: &str) -> anyhow::Result<()> {
     Ok(())
 }
 
+/// The healthy (read) endpoints of a list of endpoints.
+fn healthy_endpoints(rpc_url: &str) -> anyhow::Result<RpcEndpoints> {
+    let endpoints = RpcEndpoints::new(EndpointsCfg::parse_list(rpc_url)?)?;
+    endpoints.check_health_blocking();
+
+    Ok(endpoints)
+}
+
+/// An endpoint error of a dump (see `is_endpoint_error`), the dump runs again on the next endpoint.
+fn is_dump_endpoint_error(err: &anyhow::Error) -> bool {
+    is_endpoint_error(err.as_ref())
+}
+
 fn main() -> anyhow::Result<()> {
     let cli = Cli::parse();
 
@@ -263,16 +242,26 @@ fn main() -> anyhow::Result<()> {
                 dotenv().ok();
                 dotenv::var("SOLANA_RPC_URL").expect("SOLANA_RPC_URL must be set")
             });
+            let endpoints = healthy_endpoints(&rpc_url)?;
 
-            run_dump(&output, currency, &rpc_url, depth)?;
+            endpoints.with_failover_blocking(
+                EndpointRole::Read,
+                is_dump_endpoint_error,
+                |rpc_url| run_dump(output, currency, &rpc_url, depth),
+            )?;
         }
         Commands::DumpAllOpen { output, rpc_url } => {
             let rpc_url = rpc_url.clone().unwrap_or_else(|| {
                 dotenv().ok();
                 dotenv::var("SOLANA_RPC_URL").expect("SOLANA_RPC_URL must be set")
             });
+            let endpoints = healthy_endpoints(&rpc_url)?;
 
-            run_dump_all_open(&output, &rpc_url)?;
+            endpoints.with_failover_blocking(
+                EndpointRole::Read,
+                is_dump_endpoint_error,
+                |rpc_url| run_dump_all_open(output, &rpc_url),
+            )?;
         }
     };
 
@@ -78,8 +78,10 @@ fn derive_permissioned_profile_keys(
 ) -> anyhow::Result<Vec<typedefs::ProfileKey>> {
     let mut profile_keys = vec![];
 
-    // first 30 bytes are the profile and each subsequent 80 bytes is a permissioned account
-    let permissioned_data = account.data[30..].chunks_exact(80);
+    // the profile and then its keys
+    let permissioned_data = derive::PROFILE_KEYS
+        .elements(&account.data)
+        .ok_or_else(|| anyhow::anyhow!("profile account data too short"))?;
     for data in permissioned_data {
         let profile_key = typedefs::ProfileKey::try_from_slice(&mut &data[..])?;
         profile_keys.push(profile_key)
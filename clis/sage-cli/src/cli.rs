@@ -1,5 +1,9 @@
-use anchor_client::{anchor_lang::prelude::Pubkey, Cluster};
+use anchor_client::anchor_lang::prelude::Pubkey;
 use clap::{Parser, Subcommand};
+use staratlas_sage_based_sdk::{
+    risk::RiskLevel,
+    route::{RouteCost, WarpLane},
+};
 
 /// Star Atlas: Sage CLI --> donations: 2yodqKtkdNJXxJv21s5YMVG8bjscaezLVFRfnWra5D77 <--
 #[derive(Debug, Parser)]
@@ -15,9 +19,9 @@ pub struct Cli {
 
 #[derive(Debug, Default, Parser)]
 pub struct ProviderConfig {
-    /// RPC URL for the Solana cluster.
+    /// RPC URL for the Solana cluster, or a comma separated list of `url[|weight[|role]]` endpoints.
     #[clap(long = "provider.cluster", env = "PROVIDER_CLUSTER")]
-    pub cluster: Option<Cluster>,
+    pub cluster: Option<String>,
     /// Wallet keypair to use.
     #[clap(long = "provider.wallet", env = "PROVIDER_WALLET")]
     pub wallet: Option<String>,
@@ -38,6 +42,8 @@ pub enum Commands {
     #[command(subcommand)]
     Actions(Actions),
     #[command(subcommand)]
+    Calc(Calc),
+    #[command(subcommand)]
     Find(Find),
     #[command(subcommand)]
     Show(Show),
@@ -78,6 +84,74 @@ pub enum Actions {
     },
 }
 
+#[derive(Debug, Subcommand)]
+pub enum Calc {
+    /// Warp and subwarp time, fuel and cooldown of a fleet between two sectors
+    #[command(allow_negative_numbers = true)]
+    Travel {
+        /// Fleet's Pubkey
+        fleet_id: Pubkey,
+        from_x: i64,
+        from_y: i64,
+        to_x: i64,
+        to_y: i64,
+    },
+    /// Multi-hop route (warps and warp lanes) of a fleet between two sectors
+    #[command(allow_negative_numbers = true)]
+    Route {
+        /// Fleet's Pubkey
+        fleet_id: Pubkey,
+        from_x: i64,
+        from_y: i64,
+        to_x: i64,
+        to_y: i64,
+        /// Minimize the route's time or fuel
+        #[arg(long, default_value = "time")]
+        cost: RouteCost,
+        /// Warp lane `from_x,from_y,to_x,to_y,duration,fuel` (repeatable)
+        #[arg(long = "lane", value_parser = parse_warp_lane)]
+        lanes: Vec<WarpLane>,
+        /// Sector `x,y` the route doesn't stop at (repeatable)
+        #[arg(long = "avoid", value_parser = parse_sector)]
+        avoid: Vec<[i64; 2]>,
+    },
+}
+
+fn parse_sector(s: &str) -> Result<[i64; 2], String> {
+    match s
+        .split(',')
+        .map(|v| v.trim().parse::<i64>())
+        .collect::<Result<Vec<_>, _>>()
+    {
+        Ok(coords) if coords.len() == 2 => Ok([coords[0], coords[1]]),
+        _ => Err(format!("Invalid sector: {} (x,y)", s)),
+    }
+}
+
+fn parse_warp_lane(s: &str) -> Result<WarpLane, String> {
+    let values: Vec<&str> = s.split(',').map(|v| v.trim()).collect();
+    let invalid = || {
+        format!(
+            "Invalid warp lane: {} (from_x,from_y,to_x,to_y,duration,fuel)",
+            s
+        )
+    };
+
+    if values.len() != 6 {
+        return Err(invalid());
+    }
+
+    let coord = |v: &str| v.parse::<i64>().map_err(|_| invalid());
+    let amount = |v: &str| v.parse::<f32>().map_err(|_| invalid());
+
+    Ok(WarpLane {
+        from_sector: [coord(values[0])?, coord(values[1])?],
+        to_sector: [coord(values[2])?, coord(values[3])?],
+        duration: amount(values[4])?,
+        fuel: amount(values[5])?,
+    })
+}
+
 #[derive(Debug, Subcommand)]
 pub enum Find {
     Games,
@@ -90,6 +164,23 @@ pub enum Find {
         x: i64,
         y: i64,
     },
+    /// Resources of the game by value per hour (ATLAS) of a fleet's mining cycles
+    MiningOpportunities {
+        /// Fleet's Pubkey
+        fleet_id: Pubkey,
+        /// Home sector `x,y` of the mining cycles (default: the fleet's sector)
+        #[arg(long, value_parser = parse_sector)]
+        home: Option<[i64; 2]>,
+        /// Leave out the planets (and route sectors) above the risk level (secure, medium or high)
+        #[arg(long)]
+        max_risk: Option<RiskLevel>,
+        /// Number of opportunities
+        #[arg(long, default_value_t = 20)]
+        limit: usize,
+        /// Write CSV instead of a table
+        #[arg(long, default_value_t = false)]
+        csv: bool,
+    },
     PointsModifiers,
 }
 
@@ -102,9 +193,23 @@ pub enum Show {
         /// Show Fleet's State (default: false)
         #[arg(long, default_value_t = false)]
         with_state: bool,
+        /// Show Fleet's Cargo Pods (default: false)
+        #[arg(long, default_value_t = false)]
+        inventory: bool,
+        /// Resource Mints of the Cargo Hold (besides the Game's Mints)
+        #[arg(long = "mint")]
+        mints: Vec<Pubkey>,
+        /// Show Fleet's Ships and check its Stats against them (default: false)
+        #[arg(long, default_value_t = false)]
+        ships: bool,
     },
     Game {
         /// Game's Pubkey
         game_id: Pubkey,
     },
+    /// Game's GameState (fleet info, starbase levels and upkeep, misc variables)
+    GameState {
+        /// Game's Pubkey
+        game_id: Pubkey,
+    },
 }
@@ -0,0 +1,255 @@
+use anchor_client::anchor_lang::prelude::Pubkey;
+use staratlas_sage_based_sdk::{
+    calc,
+    risk::{RiskLevel, RiskZone},
+    route::{self, RouteOptions},
+    CargoWeights, Fleet, Game, MineItem, Planet, Resource,
+};
+
+use std::collections::HashMap;
+
+/// The planets, mine items and resources of a game.
+#[derive(Debug, Clone, Default)]
+pub struct MiningSites {
+    pub planets: Vec<(Pubkey, Planet)>,
+    pub mine_items: Vec<(Pubkey, MineItem)>,
+    pub resources: Vec<(Pubkey, Resource)>,
+}
+
+/// A resource of a planet mined by a fleet from its home sector, valued at the marketplace prices.
+#[derive(Debug, Clone)]
+pub struct MiningOpportunity {
+    pub planet: (Pubkey, Planet),
+    pub mine_item: (Pubkey, MineItem),
+    pub resource: (Pubkey, Resource),
+    pub risk_zone: RiskZone,
+    pub travel_duration: f32, // seconds, one way
+    pub travel_fuel: f32,     // both ways and the planet exit
+    pub plan: calc::MiningPlan,
+    pub cycle_duration: f32, // seconds, travel both ways and mining
+    pub price: Option<f64>,  // ATLAS per unit
+    pub value_per_hour: f64, // ATLAS, the output less the fuel, ammo and food
+}
+
+/// The resources of the game a fleet mines (a full cargo hold per cycle) from its home sector,
+/// by value per hour (the highest first). The planets beyond the fleet's routes, or above the max
+/// risk level, are left out.
+pub fn mining_opportunities(
+    fleet: &Fleet,
+    game: &Game,
+    home: [i64; 2],
+    sites: &MiningSites,
+    cargo_weights: &CargoWeights,
+    prices: &HashMap<Pubkey, f64>,
+    max_risk: Option<RiskLevel>,
+) -> Vec<MiningOpportunity> {
+    let stats = &fleet.stats;
+    let (mints, risk_zones) = (&game.mints, &game.risk_zones);
+    let planets: HashMap<&Pubkey, &Planet> = sites.planets.iter().map(|(k, p)| (k, p)).collect();
+    let mine_items: HashMap<&Pubkey, &MineItem> =
+        sites.mine_items.iter().map(|(k, m)| (k, m)).collect();
+    let price = |mint: &Pubkey| prices.get(mint).copied().unwrap_or(0.0);
+
+    // the routes to the planets' sectors (one per sector)
+    let zones = *risk_zones;
+    let options = RouteOptions {
+        avoid: max_risk.map(|max_risk| {
+            Box::new(move |sector| zones.risk_level(sector) > max_risk)
+                as Box<dyn Fn([i64; 2]) -> bool>
+        }),
+        ..Default::default()
+    };
+    let mut routes = HashMap::new();
+
+    let mut opportunities = vec![];
+    for (resource_id, resource) in &sites.resources {
+        let (Some(planet), Some(mine_item)) = (
+            planets.get(&resource.location),
+            mine_items.get(&resource.mine_item),
+        ) else {
+            continue;
+        };
+
+        let risk_zone = risk_zones.zone(planet.sector);
+        if max_risk.is_some_and(|max_risk| risk_zone.level() > max_risk) {
+            continue;
+        }
+
+        let route = routes
+            .entry(planet.sector)
+            .or_insert_with(|| route::plan_route(stats, home, planet.sector, &options));
+        let Some(route) = route else {
+            continue;
+        };
+
+        let cargo_space =
+            cargo_weights.units_fit(&mine_item.mint, stats.cargo_stats.cargo_capacity as u64);
+        let plan =
+            calc::asteroid_mining_plan(stats, mine_item, resource, cargo_space as u32, None, None);
+
+        let cycle_duration = plan.max_duration + route.duration * 2.0;
+        if cycle_duration <= 0.0 {
+            continue;
+        }
+
+        let travel_fuel = route.fuel * 2.0 + stats.movement_stats.planet_exit_fuel_amount as f32;
+        let price_per_unit = prices.get(&mine_item.mint).copied();
+        let value = plan.output as f64 * price_per_unit.unwrap_or(0.0)
+            - travel_fuel as f64 * price(&mints.fuel)
+            - plan.ammo_for_full_hold as f64 * price(&mints.ammo)
+            - plan.food_for_full_hold as f64 * price(&mints.food);
+
+        opportunities.push(MiningOpportunity {
+            planet: (resource.location, **planet),
+            mine_item: (resource.mine_item, **mine_item),
+            resource: (*resource_id, *resource),
+            risk_zone,
+            travel_duration: route.duration,
+            travel_fuel,
+            plan,
+            cycle_duration,
+            price: price_per_unit,
+            value_per_hour: value * 3600.0 / cycle_duration as f64,
+        });
+    }
+
+    opportunities.sort_by(|a, b| b.value_per_hour.total_cmp(&a.value_per_hour));
+    opportunities
+}
+
+// cargo test -p sa-sage-cli
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use borsh::BorshDeserialize;
+
+    /// A value of an account (or type) of the SDK with every field zeroed.
+    fn zeroed<T: BorshDeserialize>() -> T {
+        T::deserialize(&mut &[0u8; 2048][..]).unwrap()
+    }
+
+    /// A fleet warping 2 sectors (1 sector/s, 1 fuel/sector), mining 1 unit/s into 1000 units.
+    fn fleet() -> Fleet {
+        let mut fleet: Fleet = zeroed();
+        let stats = &mut fleet.stats;
+        stats.movement_stats.warp_speed = 1_000_000;
+        stats.movement_stats.max_warp_distance = 200;
+        stats.movement_stats.warp_cool_down = 10;
+        stats.movement_stats.warp_fuel_consumption_rate = 100;
+        stats.cargo_stats.cargo_capacity = 1000;
+        stats.cargo_stats.mining_rate = 10_000;
+        fleet
+    }
+
+    /// A game securing 3 sectors around [0, 0], medium risk up to 8 and high risk beyond.
+    fn game() -> Game {
+        let mut game: Game = zeroed();
+        game.mints.fuel = Pubkey::new_unique();
+        game.mints.ammo = Pubkey::new_unique();
+        game.mints.food = Pubkey::new_unique();
+        game.risk_zones.mud_security_zone.radius = 3;
+        game.risk_zones.medium_risk_zone.radius = 8;
+        game.risk_zones.high_risk_zone.radius = 100;
+        game
+    }
+
+    /// A resource (of its own mine item and mint) on a planet of each sector.
+    fn sites(sectors: &[[i64; 2]]) -> MiningSites {
+        let mut sites = MiningSites::default();
+
+        for sector in sectors {
+            let mut planet: Planet = zeroed();
+            planet.sector = *sector;
+            let mut mine_item: MineItem = zeroed();
+            mine_item.mint = Pubkey::new_unique();
+            mine_item.resource_hardness = 100;
+            let mut resource: Resource = zeroed();
+            resource.system_richness = 100;
+
+            let (planet_id, mine_item_id) = (Pubkey::new_unique(), Pubkey::new_unique());
+            resource.location = planet_id;
+            resource.mine_item = mine_item_id;
+
+            sites.planets.push((planet_id, planet));
+            sites.mine_items.push((mine_item_id, mine_item));
+            sites.resources.push((Pubkey::new_unique(), resource));
+        }
+        sites
+    }
+
+    fn mints(sites: &MiningSites) -> Vec<Pubkey> {
+        sites.mine_items.iter().map(|(_, m)| m.mint).collect()
+    }
+
+    fn sectors(opportunities: &[MiningOpportunity]) -> Vec<[i64; 2]> {
+        opportunities.iter().map(|o| o.planet.1.sector).collect()
+    }
+
+    fn weights() -> CargoWeights {
+        CargoWeights {
+            weights: HashMap::new(),
+            default_weight: 1,
+        }
+    }
+
+    #[test]
+    fn orders_by_value_per_hour() {
+        let (fleet, game) = (fleet(), game());
+        let sites = sites(&[[2, 0], [6, 0], [50, 0]]);
+        let mints = mints(&sites);
+        let prices = HashMap::from([(mints[0], 1.0), (mints[1], 2.0), (mints[2], 0.5)]);
+
+        let opportunities =
+            mining_opportunities(&fleet, &game, [0, 0], &sites, &weights(), &prices, None);
+
+        assert_eq!(sectors(&opportunities), vec![[6, 0], [2, 0], [50, 0]]);
+        assert!(opportunities
+            .windows(2)
+            .all(|o| o[0].value_per_hour >= o[1].value_per_hour));
+        assert_eq!(opportunities[1].risk_zone, RiskZone::MudSecurity);
+        assert_eq!(opportunities[1].travel_duration, 2.0);
+        assert_eq!(opportunities[1].cycle_duration, 1000.0 + 2.0 * 2.0);
+    }
+
+    #[test]
+    fn skips_the_planets_above_the_max_risk_or_beyond_the_routes() {
+        let (fleet, game) = (fleet(), game());
+        let sites = sites(&[[2, 0], [6, 0], [50, 0]]);
+        let prices = HashMap::new();
+        let opportunities = |fleet: &Fleet, home, max_risk| {
+            let weights = weights();
+            let opportunities =
+                mining_opportunities(fleet, &game, home, &sites, &weights, &prices, max_risk);
+            sectors(&opportunities)
+        };
+
+        assert_eq!(
+            opportunities(&fleet, [0, 0], Some(RiskLevel::Secure)),
+            vec![[2, 0]]
+        );
+        let mut medium = opportunities(&fleet, [0, 0], Some(RiskLevel::Medium));
+        medium.sort();
+        assert_eq!(medium, vec![[2, 0], [6, 0]]);
+
+        // a fleet without a warp drive mines at home only
+        let mut grounded = fleet;
+        grounded.stats.movement_stats.warp_speed = 0;
+        assert_eq!(opportunities(&grounded, [6, 0], None), vec![[6, 0]]);
+    }
+
+    #[test]
+    fn values_a_resource_without_a_price_at_its_costs() {
+        let (fleet, game) = (fleet(), game());
+        let sites = sites(&[[2, 0]]);
+        let prices = HashMap::from([(game.mints.fuel, 1.0)]);
+
+        let opportunities =
+            mining_opportunities(&fleet, &game, [0, 0], &sites, &weights(), &prices, None);
+
+        assert_eq!(opportunities.len(), 1);
+        assert_eq!(opportunities[0].price, None);
+        assert_eq!(opportunities[0].travel_fuel, 4.0); // both ways, no planet exit fuel
+        assert!(opportunities[0].value_per_hour < 0.0);
+    }
+}
@@ -1 +1,2 @@
-
+mod mining_opportunities;
+pub use mining_opportunities::*;
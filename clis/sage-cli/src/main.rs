@@ -9,15 +9,21 @@ use anchor_client::{
 use clap::Parser;
 
 use staratlas_sage_based_sdk::{
-    addr, filter, ixs,
+    addr, calc, filter, ixs,
     program::{staratlas_sage::state, SAGE_ID},
-    FleetState, FleetWithState, SageBasedGameHandler,
+    route::{self, RouteOptions},
+    sage_permissions, FleetState, FleetWithState, SageBasedGameHandler,
 };
 
+use staratlas_utils_rpc::{is_endpoint_error, EndpointRole, EndpointsCfg, RpcEndpoints};
+
 // use std::io::{self, Write};
 use std::rc::Rc;
+use std::str::FromStr;
 
-use sa_sage_cli::{Actions, Cli, Commands, Find, SageConfig, Show};
+use sa_sage_cli::{
+    mining_opportunities, Actions, Calc, Cli, Commands, Find, MiningSites, SageConfig, Show,
+};
 
 fn default_keypair() -> Keypair {
     read_keypair_file(&*shellexpand::tilde("~/.config/solana/id.json"))
@@ -36,19 +42,67 @@ fn parse_sage_config(sage_config: &SageConfig) -> (Pubkey, Pubkey) {
     (game_id, profile_id)
 }
 
+/// The sector of a fleet (its destination while moving).
+async fn fleet_sector(
+    sage_program: &anchor_client::Program<Rc<Keypair>>,
+    state: &FleetState,
+) -> anyhow::Result<[i64; 2]> {
+    let sector = match state {
+        FleetState::Idle(idle) => idle.sector,
+        FleetState::MoveWarp(move_warp) => move_warp.to_sector,
+        FleetState::MoveSubwarp(move_subwarp) => move_subwarp.to_sector,
+        FleetState::Respawn(respawn) => respawn.sector,
+        FleetState::MineAsteroid(mine_asteroid) => {
+            let (_, planet) =
+                SageBasedGameHandler::get_planet(sage_program, &mine_asteroid.asteroid).await?;
+            planet.sector
+        }
+        FleetState::StarbaseLoadingBay(loading_bay) => {
+            let starbase = sage_program
+                .account::<state::Starbase>(loading_bay.starbase)
+                .await?;
+            starbase.sector
+        }
+        FleetState::Unknown { discriminator, .. } => {
+            anyhow::bail!("Unknown fleet state ({}), no sector", discriminator)
+        }
+    };
+
+    Ok(sector)
+}
+
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
     let cli = Cli::parse();
 
-    let payer = match cli.provider_config.wallet {
+    let payer = match &cli.provider_config.wallet {
         Some(wallet) => read_keypair_file(wallet).expect("Requires a keypair file"),
         None => default_keypair(),
     };
 
-    let url = match cli.provider_config.cluster {
-        Some(cluster) => cluster,
-        None => Cluster::Devnet,
-    };
+    // the healthy endpoints of the cluster (of a list of endpoints), failing over on an endpoint error
+    match &cli.provider_config.cluster {
+        Some(cluster) => {
+            let endpoints = RpcEndpoints::new(EndpointsCfg::parse_list(cluster)?)?;
+            endpoints.check_health().await;
+
+            endpoints
+                .with_failover(EndpointRole::Read, is_cli_endpoint_error, |url| {
+                    run(&cli, &payer, url)
+                })
+                .await
+        }
+        None => run(&cli, &payer, Cluster::Devnet.url().to_string()).await,
+    }
+}
+
+/// An endpoint error of a command (the command runs again on the next endpoint).
+fn is_cli_endpoint_error(err: &anyhow::Error) -> bool {
+    is_endpoint_error(err.as_ref())
+}
+
+async fn run(cli: &Cli, payer: &Keypair, url: String) -> anyhow::Result<()> {
+    let url = Cluster::from_str(&url)?;
 
     let client = Client::new_with_options(
         url,
@@ -157,11 +211,19 @@ async fn main() -> anyhow::Result<()> {
                         }
                     };
 
+                    let key_index = SageBasedGameHandler::payer_key_index(
+                        &sage_program,
+                        &fleet.owner_profile,
+                        &sage_permissions::DOCK_UNDOCK,
+                    )
+                    .await?;
+
                     let ix = ixs::dock_to_starbase(
                         &sage_program,
                         (&game_id, &game),
                         (&fleet_id, &fleet),
                         sector,
+                        key_index,
                     );
                     Some(ix)
                 }
@@ -181,11 +243,19 @@ async fn main() -> anyhow::Result<()> {
                         }
                     };
 
+                    let key_index = SageBasedGameHandler::payer_key_index(
+                        &sage_program,
+                        &fleet.owner_profile,
+                        &sage_permissions::DOCK_UNDOCK,
+                    )
+                    .await?;
+
                     let ix = ixs::undock_from_starbase(
                         &sage_program,
                         (&game_id, &game),
                         (&fleet_id, &fleet),
                         &starbase,
+                        key_index,
                     );
                     Some(ix)
                 }
@@ -235,11 +305,19 @@ async fn main() -> anyhow::Result<()> {
                         SageBasedGameHandler::get_fleet_with_state(&sage_program, &fleet_id)
                             .await?;
 
+                    let key_index = SageBasedGameHandler::payer_key_index(
+                        &sage_program,
+                        &fleet.owner_profile,
+                        &sage_permissions::WARP,
+                    )
+                    .await?;
+
                     let ix = ixs::warp_to_coordinate(
                         &sage_program,
                         (&game_id, &game),
                         (&fleet_id, &fleet),
                         [*x_coord, *y_coord],
+                        key_index,
                     );
                     Some(ix)
                 }
@@ -286,6 +364,126 @@ async fn main() -> anyhow::Result<()> {
                 // dbg!(result);
             }
         }
+        Commands::Calc(calc) => match calc {
+            Calc::Travel {
+                fleet_id,
+                from_x,
+                from_y,
+                to_x,
+                to_y,
+            } => {
+                let (_, FleetWithState(fleet, _)) =
+                    SageBasedGameHandler::get_fleet_with_state(&sage_program, &fleet_id).await?;
+                let (_, game) =
+                    SageBasedGameHandler::get_game(&sage_program, &fleet.game_id).await?;
+                let (_, game_state) =
+                    SageBasedGameHandler::get_game_state(&sage_program, &game.game_state).await?;
+                let travel = calc::travel(&fleet.stats, [*from_x, *from_y], [*to_x, *to_y]);
+
+                let mut table = comfy_table::Table::new();
+                table.set_header(vec!["Travel", "Time", "Fuel", "Cooldown"]);
+                table.add_row(vec![
+                    "Warp".to_string(),
+                    format!("{:.0}s", travel.warp_duration),
+                    format!("{:.0}", travel.warp_fuel),
+                    format!("{:.0}s", travel.warp_cool_down),
+                ]);
+                table.add_row(vec![
+                    "Warp Lane".to_string(),
+                    "-".to_string(),
+                    format!(
+                        "{:.0}",
+                        calc::warp_lane_fuel_consumption(
+                            &fleet.stats,
+                            &game_state,
+                            [*from_x, *from_y],
+                            [*to_x, *to_y]
+                        )
+                    ),
+                    "-".to_string(),
+                ]);
+                table.add_row(vec![
+                    "Subwarp".to_string(),
+                    format!("{:.0}s", travel.subwarp_duration),
+                    format!("{:.0}", travel.subwarp_fuel),
+                    "-".to_string(),
+                ]);
+                table.add_row(vec![
+                    "Planet Exit".to_string(),
+                    "-".to_string(),
+                    format!("{:.0}", travel.planet_exit_fuel),
+                    "-".to_string(),
+                ]);
+
+                println!("{table}");
+                println!(
+                    "Distance: {:.2} (max warp distance: {:.2}), single warp: {}",
+                    travel.distance,
+                    travel.max_warp_distance,
+                    travel.is_single_warp()
+                );
+                println!(
+                    "Respawn: {:.0}s, {} ATLAS",
+                    fleet.stats.misc_stats.respawn_time,
+                    calc::respawn_fee(&game_state)
+                );
+            }
+            Calc::Route {
+                fleet_id,
+                from_x,
+                from_y,
+                to_x,
+                to_y,
+                cost,
+                lanes,
+                avoid,
+            } => {
+                let (_, FleetWithState(fleet, _)) =
+                    SageBasedGameHandler::get_fleet_with_state(&sage_program, &fleet_id).await?;
+
+                let avoid = avoid.clone();
+                let options = RouteOptions {
+                    cost: *cost,
+                    warp_lanes: lanes.clone(),
+                    avoid: Some(Box::new(move |sector| avoid.contains(&sector))),
+                };
+                let route =
+                    route::plan_route(&fleet.stats, [*from_x, *from_y], [*to_x, *to_y], &options)
+                        .ok_or_else(|| {
+                        anyhow::anyhow!(
+                            "No route from [{}, {}] to [{}, {}] (max warp distance {:.2})",
+                            from_x,
+                            from_y,
+                            to_x,
+                            to_y,
+                            calc::max_warp_distance(&fleet.stats)
+                        )
+                    })?;
+
+                let mut table = comfy_table::Table::new();
+                table.set_header(vec!["Hop", "Kind", "From", "To", "Time", "Fuel"]);
+
+                for (i, hop) in route.hops.iter().enumerate() {
+                    table.add_row(vec![
+                        (i + 1).to_string(),
+                        format!("{:?}", hop.kind),
+                        format!("{:?}", hop.from_sector),
+                        format!("{:?}", hop.to_sector),
+                        format!("{:.0}s", hop.duration + hop.cool_down),
+                        format!("{:.0}", hop.fuel),
+                    ]);
+                }
+
+                println!("{table}");
+                println!(
+                    "Total: {:.0}s, {:.0} fuel (max hop fuel: {:.0}, fuel capacity: {})",
+                    route.duration,
+                    route.fuel,
+                    route.max_hop_fuel(),
+                    fleet.stats.cargo_stats.fuel_capacity
+                );
+            }
+        },
         Commands::Find(find) => match find {
             Find::Games => {
                 let games = sage_program.accounts::<state::Game>(vec![]).await?;
@@ -339,7 +537,23 @@ async fn main() -> anyhow::Result<()> {
             Find::Planets { x, y } => {
                 let planets =
                     filter::planets_by_game_and_coords(&sage_program, &game_id, [*x, *y]).await?;
-                dbg!(&planets);
+                let (_, game) = SageBasedGameHandler::get_game(&sage_program, &game_id).await?;
+
+                let mut table = comfy_table::Table::new();
+                table.set_header(vec!["Planet", "Name", "Sector", "Resources", "Risk Zone"]);
+
+                for (pubkey, planet) in &planets {
+                    let zone = game.risk_zones.zone(planet.sector);
+                    table.add_row(vec![
+                        pubkey.to_string(),
+                        planet.name().to_string(),
+                        format!("{:?}", planet.sector),
+                        planet.num_resources.to_string(),
+                        format!("{:?} ({:?})", zone, zone.level()),
+                    ]);
+                }
+
+                println!("{table}");
 
                 // let planets = derive::planet_accounts(&sage_program, &game_id, [*x, *y])?;
                 // let (pubkey, planet) = planets
@@ -361,6 +575,110 @@ async fn main() -> anyhow::Result<()> {
                 //     dbg!(mine_item);
                 // }
             }
+            Find::MiningOpportunities {
+                fleet_id,
+                home,
+                max_risk,
+                limit,
+                csv,
+            } => {
+                let (_, FleetWithState(fleet, state)) =
+                    SageBasedGameHandler::get_fleet_with_state(&sage_program, &fleet_id).await?;
+                let (_, game) = SageBasedGameHandler::get_game(&sage_program, &game_id).await?;
+                let home = match home {
+                    Some(home) => *home,
+                    None => fleet_sector(&sage_program, &state).await?,
+                };
+
+                let sites = MiningSites {
+                    planets: filter::planets_by_game(&sage_program, &game_id).await?,
+                    mine_items: filter::mine_items_by_game(&sage_program, &game_id).await?,
+                    resources: filter::resources_by_game(&sage_program, &game_id).await?,
+                };
+                let cargo_weights =
+                    SageBasedGameHandler::get_cargo_weights(&sage_program.async_rpc(), &game)
+                        .await?;
+
+                // the resources and the consumables of a cycle
+                let mut mints: Vec<Pubkey> = sites
+                    .mine_items
+                    .iter()
+                    .map(|(_, mine_item)| mine_item.mint)
+                    .collect();
+                mints.extend([game.mints.fuel, game.mints.ammo, game.mints.food]);
+                let prices =
+                    sa_marketplace_cli::fetch_mid_prices(sage_program.async_rpc().url(), mints)
+                        .await?;
+
+                let opportunities = mining_opportunities(
+                    &fleet,
+                    &game,
+                    home,
+                    &sites,
+                    &cargo_weights,
+                    &prices,
+                    *max_risk,
+                );
+
+                let headers = vec![
+                    "Planet",
+                    "Sector",
+                    "Risk Zone",
+                    "Mine Item",
+                    "Resource",
+                    "Emission Rate",
+                    "Travel",
+                    "Mining",
+                    "Output",
+                    "Price",
+                    "ATLAS / Hour",
+                ];
+                let rows = opportunities.iter().take(*limit).map(|opportunity| {
+                    let (planet_id, planet) = &opportunity.planet;
+                    let (_, mine_item) = &opportunity.mine_item;
+                    let (resource_id, _) = &opportunity.resource;
+                    let plan = &opportunity.plan;
+
+                    vec![
+                        format!("{} ({})", planet.name(), planet_id),
+                        format!("{:?}", planet.sector),
+                        format!("{:?}", opportunity.risk_zone),
+                        mine_item.name().to_string(),
+                        resource_id.to_string(),
+                        format!("{:.4}", plan.emission_rate),
+                        format!("{:.0}s", opportunity.travel_duration),
+                        format!("{:.0}s", plan.max_duration),
+                        format!("{:.0}", plan.output),
+                        opportunity
+                            .price
+                            .map_or("-".to_string(), |price| format!("{:.6}", price)),
+                        format!("{:.2}", opportunity.value_per_hour),
+                    ]
+                });
+
+                if *csv {
+                    let mut writer = csv::Writer::from_writer(std::io::stdout());
+                    writer.write_record(&headers)?;
+                    for row in rows {
+                        writer.write_record(&row)?;
+                    }
+                    writer.flush()?;
+                } else {
+                    let mut table = comfy_table::Table::new();
+                    table.set_header(headers);
+                    for row in rows {
+                        table.add_row(row);
+                    }
+
+                    println!("{table}");
+                    println!(
+                        "Home: {:?}, {} of {} opportunities",
+                        home,
+                        opportunities.len().min(*limit),
+                        opportunities.len()
+                    );
+                }
+            }
             Find::PointsModifiers => {
                 unimplemented!("Find::PointsModifiers");
                 // let accounts = points_program.accounts::<staratlas_sage_sdk::programs::staratlas_points::state::PointsModifier>(vec![])?;
@@ -408,20 +726,214 @@ async fn main() -> anyhow::Result<()> {
             Show::Fleet {
                 fleet_id,
                 with_state,
+                inventory,
+                mints,
+                ships,
             } => {
                 let (_, FleetWithState(fleet, state)) =
                     SageBasedGameHandler::get_fleet_with_state(&sage_program, &fleet_id).await?;
 
                 if *with_state {
-                    println!("{:#?}", (fleet, state));
+                    println!("{:#?}", (fleet, &state));
                 } else {
                     println!("{:#?}", fleet);
                 }
+
+                let (_, game) = SageBasedGameHandler::get_game(&sage_program, &game_id).await?;
+                let sector = fleet_sector(&sage_program, &state).await?;
+                let zone = game.risk_zones.zone(sector);
+                println!(
+                    "Sector: {:?}, Risk Zone: {:?} ({:?})",
+                    sector,
+                    zone,
+                    zone.level()
+                );
+
+                if *ships {
+                    let fleet_ships =
+                        SageBasedGameHandler::get_fleet_composition(&sage_program, &fleet).await?;
+
+                    let mut table = comfy_table::Table::new();
+                    table.set_header(vec![
+                        "Ship",
+                        "Name",
+                        "Size Class",
+                        "Amount",
+                        "Cargo Capacity",
+                        "Fuel Capacity",
+                        "Ammo Capacity",
+                        "Mining Rate",
+                        "Warp Speed",
+                        "Max Warp Distance",
+                    ]);
+
+                    for fleet_ship in &fleet_ships {
+                        let stats = &fleet_ship.ship.stats;
+                        table.add_row(vec![
+                            fleet_ship.ship_id.to_string(),
+                            fleet_ship.ship.name().to_string(),
+                            fleet_ship
+                                .ship
+                                .size_class()
+                                .map_or("-".to_string(), |size_class| format!("{:?}", size_class)),
+                            fleet_ship.amount.to_string(),
+                            stats.cargo_stats.cargo_capacity.to_string(),
+                            stats.cargo_stats.fuel_capacity.to_string(),
+                            stats.cargo_stats.ammo_capacity.to_string(),
+                            stats.cargo_stats.mining_rate.to_string(),
+                            stats.movement_stats.warp_speed.to_string(),
+                            stats.movement_stats.max_warp_distance.to_string(),
+                        ]);
+                    }
+
+                    println!("{table}");
+                    println!("{:#?}", calc::fleet_ship_counts(&fleet_ships));
+
+                    let mismatches = calc::fleet_stats_mismatches(&fleet.stats, &fleet_ships);
+                    if mismatches.is_empty() {
+                        println!("Fleet stats match its ships");
+                    } else {
+                        let mut table = comfy_table::Table::new();
+                        table.set_header(vec!["Stat", "Ships", "Fleet"]);
+
+                        for mismatch in mismatches {
+                            table.add_row(vec![
+                                mismatch.stat.to_string(),
+                                mismatch.ships.to_string(),
+                                mismatch.fleet.to_string(),
+                            ]);
+                        }
+
+                        println!("Fleet stats differ from its ships:");
+                        println!("{table}");
+                    }
+                }
+
+                if *inventory {
+                    let inventory = SageBasedGameHandler::get_fleet_inventory(
+                        &sage_program.async_rpc(),
+                        &game,
+                        &fleet,
+                        mints,
+                    )
+                    .await?;
+                    let cargo_weights =
+                        SageBasedGameHandler::get_cargo_weights(&sage_program.async_rpc(), &game)
+                            .await?;
+
+                    let mut table = comfy_table::Table::new();
+                    table.set_header(vec!["Cargo Pod", "Mint", "Amount", "Used / Capacity"]);
+
+                    let cargo_pods = [
+                        ("Cargo Hold", &inventory.cargo_hold),
+                        ("Fuel Tank", &inventory.fuel_tank),
+                        ("Ammo Bank", &inventory.ammo_bank),
+                    ];
+
+                    for (name, pod) in cargo_pods {
+                        let usage =
+                            format!("{} / {}", pod.used_space(&cargo_weights), pod.capacity);
+
+                        if pod.amounts.is_empty() {
+                            table.add_row(vec![
+                                name.to_string(),
+                                "-".into(),
+                                "0".into(),
+                                usage.clone(),
+                            ]);
+                        }
+
+                        for (mint, amount) in &pod.amounts {
+                            table.add_row(vec![
+                                name.to_string(),
+                                mint.to_string(),
+                                amount.to_string(),
+                                usage.clone(),
+                            ]);
+                        }
+                    }
+
+                    println!("{table}");
+                }
             }
             Show::Game { game_id } => {
                 let (_, game) = SageBasedGameHandler::get_game(&sage_program, &game_id).await?;
                 println!("{:#?}", game);
             }
+            Show::GameState { game_id } => {
+                let (_, game) = SageBasedGameHandler::get_game(&sage_program, &game_id).await?;
+                let (game_state_id, game_state) =
+                    SageBasedGameHandler::get_game_state(&sage_program, &game.game_state).await?;
+
+                println!("GameState: {}", game_state_id);
+                println!(
+                    "Version: {}, Update ID: {}, Max Fleet Size: {}",
+                    game_state.version, game_state.update_id, game_state.fleet.max_fleet_size
+                );
+                println!(
+                    "Warp Lane Fuel Cost Reduction: {}%, Upkeep Mining Emissions Penalty: {}%, Respawn Fee: {} ATLAS",
+                    game_state.misc.warp_lane_fuel_cost_reduction,
+                    game_state.misc.upkeep_mining_emissions_penalty,
+                    calc::respawn_fee(&game_state)
+                );
+
+                let mut table = comfy_table::Table::new();
+                table.set_header(vec![
+                    "Faction",
+                    "Level",
+                    "HP",
+                    "SP",
+                    "Sector Ring",
+                    "Warp Lane Fee (ATLAS)",
+                ]);
+
+                let starbase_levels = &game_state.fleet.starbase_levels;
+                for (faction_id, faction, levels) in [
+                    (1, "MUD", &starbase_levels.mud),
+                    (2, "ONI", &starbase_levels.oni),
+                    (3, "Ustur", &starbase_levels.ustur),
+                ] {
+                    for (level, info) in levels.iter().enumerate() {
+                        table.add_row(vec![
+                            faction.to_string(),
+                            level.to_string(),
+                            info.hp.to_string(),
+                            info.sp.to_string(),
+                            info.sector_ring_available.to_string(),
+                            calc::warp_lane_fee(&game_state, faction_id, level as u8)
+                                .unwrap_or_default()
+                                .to_string(),
+                        ]);
+                    }
+                }
+
+                println!("{table}");
+
+                let mut table = comfy_table::Table::new();
+                table.set_header(vec![
+                    "Upkeep Level",
+                    "Ammo Reserve",
+                    "Ammo Depletion Rate",
+                    "Food Reserve",
+                    "Food Depletion Rate",
+                    "Toolkit Reserve",
+                    "Toolkit Depletion Rate",
+                ]);
+
+                for (level, upkeep) in game_state.fleet.upkeep.levels().iter().enumerate() {
+                    table.add_row(vec![
+                        level.to_string(),
+                        upkeep.ammo_reserve.to_string(),
+                        upkeep.ammo_depletion_rate.to_string(),
+                        upkeep.food_reserve.to_string(),
+                        upkeep.food_depletion_rate.to_string(),
+                        upkeep.toolkit_reserve.to_string(),
+                        upkeep.toolkit_depletion_rate.to_string(),
+                    ]);
+                }
+
+                println!("{table}");
+            }
         },
     }
 
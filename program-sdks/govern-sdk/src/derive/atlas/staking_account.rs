@@ -26,10 +26,12 @@ pub fn get_staking_accounts<C: Deref<Target = impl Signer> + Clone>(
     owner: &Pubkey,
 ) -> anyhow::Result<Vec<(Pubkey, Account)>> {
     let config = RpcProgramAccountsConfig {
-        filters: Some(vec![
-            RpcFilterType::DataSize(171),
-            RpcFilterType::Memcmp(Memcmp::new_base58_encoded(8, owner.as_ref())),
-        ]),
+        filters: Some(
+            Query::<staratlas_atlas_staking::state::StakingAccount>::new()
+                .data_size()
+                .eq(|f| f.owner, owner)
+                .filters(),
+        ),
         account_config: RpcAccountInfoConfig {
             encoding: Some(UiAccountEncoding::Base64),
             commitment: Some(CommitmentConfig::confirmed()),
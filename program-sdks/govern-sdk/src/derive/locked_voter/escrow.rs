@@ -27,10 +27,11 @@ pub fn get_escrow_accounts<C: Deref<Target = impl Signer> + Clone>(
     owner: &Pubkey,
 ) -> anyhow::Result<Vec<(Pubkey, Account)>> {
     let config = RpcProgramAccountsConfig {
-        filters: Some(vec![
-            // RpcFilterType::DataSize(171),
-            RpcFilterType::Memcmp(Memcmp::new_base58_encoded(40, owner.as_ref())),
-        ]),
+        filters: Some(
+            Query::<staratlas_locked_voter::state::Escrow>::new()
+                .eq(|f| f.owner, owner)
+                .filters(),
+        ),
         account_config: RpcAccountInfoConfig {
             encoding: Some(UiAccountEncoding::Base64),
             commitment: Some(CommitmentConfig::confirmed()),
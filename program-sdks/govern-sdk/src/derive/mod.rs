@@ -1,14 +1,12 @@
 use anchor_client::{
-    solana_client::{
-        rpc_config::{RpcAccountInfoConfig, RpcProgramAccountsConfig},
-        rpc_filter::{Memcmp, RpcFilterType},
-    },
+    solana_client::rpc_config::{RpcAccountInfoConfig, RpcProgramAccountsConfig},
     solana_sdk::{
         account::Account, commitment_config::CommitmentConfig, pubkey::Pubkey, signature::Signer,
     },
     Program,
 };
 use solana_account_decoder::UiAccountEncoding;
+use staratlas_utils_query::Query;
 
 use std::ops::Deref;
 
@@ -33,10 +33,12 @@ pub fn get_proxy_accounts<C: Deref<Target = impl Signer> + Clone>(
     owner: &Pubkey,
 ) -> anyhow::Result<Vec<(Pubkey, Account)>> {
     let config = RpcProgramAccountsConfig {
-        filters: Some(vec![
-            RpcFilterType::DataSize(137),
-            RpcFilterType::Memcmp(Memcmp::new_base58_encoded(40, owner.as_ref())),
-        ]),
+        filters: Some(
+            Query::<staratlas_proxy_rewarder::state::Proxy>::new()
+                .data_size()
+                .eq(|f| f.owner, owner)
+                .filters(),
+        ),
         account_config: RpcAccountInfoConfig {
             encoding: Some(UiAccountEncoding::Base64),
             commitment: Some(CommitmentConfig::confirmed()),
@@ -35,10 +35,12 @@ pub fn get_proxy_escrow_accounts<C: Deref<Target = impl Signer> + Clone>(
     escrow_owner: &Pubkey,
 ) -> anyhow::Result<Vec<(Pubkey, Account)>> {
     let config = RpcProgramAccountsConfig {
-        filters: Some(vec![
-            RpcFilterType::DataSize(81),
-            RpcFilterType::Memcmp(Memcmp::new_base58_encoded(8, escrow_owner.as_ref())),
-        ]),
+        filters: Some(
+            Query::<staratlas_proxy_rewarder::state::ProxyEscrow>::new()
+                .data_size()
+                .eq(|f| f.escrow_owner, escrow_owner)
+                .filters(),
+        ),
         account_config: RpcAccountInfoConfig {
             encoding: Some(UiAccountEncoding::Base64),
             commitment: Some(CommitmentConfig::confirmed()),
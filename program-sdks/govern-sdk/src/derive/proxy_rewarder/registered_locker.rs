@@ -35,10 +35,12 @@ pub fn get_registered_locker_accounts<C: Deref<Target = impl Signer> + Clone>(
     locker: &Pubkey,
 ) -> anyhow::Result<Vec<(Pubkey, Account)>> {
     let config = RpcProgramAccountsConfig {
-        filters: Some(vec![
-            RpcFilterType::DataSize(6256),
-            RpcFilterType::Memcmp(Memcmp::new_base58_encoded(73, locker.as_ref())),
-        ]),
+        filters: Some(
+            Query::<staratlas_proxy_rewarder::state::RegisteredLocker>::new()
+                .data_size()
+                .eq(|f| f.locker, locker)
+                .filters(),
+        ),
         account_config: RpcAccountInfoConfig {
             encoding: Some(UiAccountEncoding::Base64),
             commitment: Some(CommitmentConfig::confirmed()),
@@ -0,0 +1,100 @@
+//! Generates the instructions of the programs' generated crates (`<crate>::instruction::<Name>`)
+//! from their Anchor IDL, their discriminator and a check that their struct deserializes the args.
+
+use serde_json::Value;
+
+use std::fmt::Write;
+use std::path::Path;
+
+// (program dir, crate)
+const PROGRAMS: &[(&str, &str)] = &[
+    ("atlas-staking", "staratlas_atlas_staking"),
+    ("cargo", "staratlas_cargo"),
+    ("claim", "staratlas_claim"),
+    ("crafting", "staratlas_crafting"),
+    ("locked-voter", "staratlas_locked_voter"),
+    ("marketplace", "staratlas_marketplace"),
+    ("player-profile", "staratlas_player_profile"),
+    ("points", "staratlas_points"),
+    ("profile-vault", "staratlas_profile_vault"),
+    ("proxy-rewarder", "staratlas_proxy_rewarder"),
+    ("sage", "staratlas_sage"),
+    ("score", "staratlas_score"),
+];
+
+/// The name of an instruction's struct, the IDL's (camel case) name in upper camel case.
+fn struct_name(name: &str) -> String {
+    let mut chars = name.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+/// The anchor-lang crate of a program's generated crate (0.27 or 0.29).
+fn anchor_lang(manifest: &str) -> &'static str {
+    match manifest.contains("anchor-lang = \"0.27\"") {
+        true => "anchor_lang_027",
+        false => "anchor_lang",
+    }
+}
+
+fn main() {
+    let manifest_dir = std::env::var("CARGO_MANIFEST_DIR").unwrap();
+    let programs_dir = Path::new(&manifest_dir).join("../../programs");
+
+    let mut out = String::new();
+    let mut consts = vec![];
+
+    for (dir, krate) in PROGRAMS {
+        let idl_path = programs_dir.join(dir).join("idl.json");
+        let manifest_path = programs_dir.join(dir).join("Cargo.toml");
+        println!("cargo:rerun-if-changed={}", idl_path.display());
+        println!("cargo:rerun-if-changed={}", manifest_path.display());
+
+        let idl = std::fs::read_to_string(&idl_path).unwrap();
+        let idl: Value = serde_json::from_str(&idl).unwrap();
+        let anchor_lang = anchor_lang(&std::fs::read_to_string(&manifest_path).unwrap());
+
+        let name = format!("{}_INSTRUCTIONS", dir.replace('-', "_").to_uppercase());
+        writeln!(out, "const {}: &[GeneratedInstruction] = &[", name).unwrap();
+        for instruction in idl["instructions"].as_array().into_iter().flatten() {
+            let ix_name = instruction["name"].as_str().unwrap();
+            let ty = format!("{}::instruction::{}", krate, struct_name(ix_name));
+
+            writeln!(out, "    GeneratedInstruction {{").unwrap();
+            writeln!(out, "        name: \"{}\",", ix_name).unwrap();
+            writeln!(
+                out,
+                "        discriminator: <{} as {}::Discriminator>::DISCRIMINATOR,",
+                ty, anchor_lang
+            )
+            .unwrap();
+            writeln!(
+                out,
+                "        deserialize: |data| <{} as {}::AnchorDeserialize>::deserialize(&mut &data[..]).is_ok(),",
+                ty, anchor_lang
+            )
+            .unwrap();
+            writeln!(out, "    }},").unwrap();
+        }
+        writeln!(out, "];\n").unwrap();
+        consts.push((dir, name));
+    }
+
+    writeln!(
+        out,
+        "pub(crate) fn generated_instructions(program: &str) -> &'static [GeneratedInstruction] {{"
+    )
+    .unwrap();
+    writeln!(out, "    match program {{").unwrap();
+    for (dir, name) in consts {
+        writeln!(out, "        \"{}\" => {},", dir, name).unwrap();
+    }
+    writeln!(out, "        _ => &[],").unwrap();
+    writeln!(out, "    }}").unwrap();
+    writeln!(out, "}}").unwrap();
+
+    let out_dir = std::env::var("OUT_DIR").unwrap();
+    std::fs::write(Path::new(&out_dir).join("instructions.rs"), out).unwrap();
+}
@@ -0,0 +1,140 @@
+use anyhow::{bail, Context};
+use serde_json::{json, Value};
+use solana_program::pubkey::Pubkey;
+
+use crate::decode::Decoder;
+use crate::idl::{programs, Program};
+
+/// An account decoded by its program's IDL.
+#[derive(Debug, Clone)]
+pub struct DecodedAccount {
+    pub program: &'static str,
+    pub program_id: Pubkey,
+    /// The account's name (type) in the IDL.
+    pub name: String,
+    /// The account's fields by their IDL names.
+    pub data: Value,
+    /// The data following the IDL's fields (e.g. a fleet's state, a profile's keys).
+    pub remaining_data: Vec<u8>,
+}
+
+impl DecodedAccount {
+    pub fn to_json(&self) -> Value {
+        json!({
+            "program": self.program,
+            "programId": self.program_id.to_string(),
+            "account": self.name,
+            "data": self.data,
+            "remainingData": self.remaining_data,
+        })
+    }
+}
+
+/// Decodes the data of an account owned by a program, by its discriminator.
+pub fn decode_account(program: &'static Program, data: &[u8]) -> anyhow::Result<DecodedAccount> {
+    let discriminator = data
+        .get(..8)
+        .context("Account data shorter than a discriminator")?;
+    let account = program.account(discriminator).with_context(|| {
+        format!(
+            "Unknown {} account discriminator {:?}",
+            program.name, discriminator
+        )
+    })?;
+    let name = account["name"].as_str().unwrap_or_default();
+
+    let mut decoder = Decoder::new(program, &data[8..]);
+    let fields = decoder
+        .decode_fields(&account["type"]["fields"])
+        .with_context(|| format!("Failed to decode a {} {} account", program.name, name))?;
+
+    Ok(DecodedAccount {
+        program: program.name,
+        program_id: program.id,
+        name: name.to_string(),
+        data: fields,
+        remaining_data: decoder.remaining().to_vec(),
+    })
+}
+
+/// Decodes the data of an account of an unknown owner, the bundled program with an account of its
+/// discriminator.
+pub fn decode_any_account(data: &[u8]) -> anyhow::Result<DecodedAccount> {
+    let discriminator = data
+        .get(..8)
+        .context("Account data shorter than a discriminator")?;
+
+    let candidates: Vec<_> = programs()
+        .iter()
+        .filter(|program| program.account(discriminator).is_some())
+        .collect();
+
+    match candidates.as_slice() {
+        [] => bail!("Unknown account discriminator {:?}", discriminator),
+        [program] => decode_account(program, data),
+        _ => {
+            let names: Vec<_> = candidates.iter().map(|program| program.name).collect();
+            bail!(
+                "Account discriminator of several programs ({})",
+                names.join(", ")
+            )
+        }
+    }
+}
+
+// cargo test -p staratlas-inspect-sdk
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use anchor_lang::AccountSerialize;
+    use staratlas_sage::state;
+
+    use crate::idl::find_program_by_name;
+
+    #[test]
+    fn it_works() {
+        let resource = state::Resource {
+            version: 0,
+            game_id: Pubkey::new_unique(),
+            location: Pubkey::new_unique(),
+            mine_item: Pubkey::new_unique(),
+            location_type: 1,
+            system_richness: 150,
+            amount_mined: 1_000_000,
+            num_miners: 3,
+            bump: 255,
+        };
+        let mut data = vec![];
+        resource.try_serialize(&mut data).unwrap();
+        data.extend([1, 2, 3]);
+
+        let account = decode_account(find_program_by_name("sage").unwrap(), &data).unwrap();
+        assert_eq!(account.name, "Resource");
+        assert_eq!(account.data["gameId"], resource.game_id.to_string());
+        assert_eq!(account.data["systemRichness"], 150);
+        assert_eq!(account.data["amountMined"], 1_000_000);
+        assert_eq!(account.data["bump"], 255);
+        assert_eq!(account.remaining_data, [1, 2, 3]);
+
+        // the raw data of an unknown owner
+        let account = decode_any_account(&data).unwrap();
+        assert_eq!(account.program, "sage");
+        assert_eq!(account.program_id, staratlas_sage::ID);
+
+        assert!(decode_account(find_program_by_name("cargo").unwrap(), &data).is_err());
+        assert!(decode_any_account(&data[..40]).is_err());
+        assert!(decode_any_account(&[0; 8]).is_err());
+    }
+
+    #[test]
+    fn decodes_enums() {
+        let program = find_program_by_name("marketplace").unwrap();
+        let order_side = json!({ "defined": "OrderSide" });
+
+        let mut decoder = Decoder::new(program, &[1, 0, 7]);
+        assert_eq!(decoder.decode(&order_side).unwrap(), "Sell");
+        assert_eq!(decoder.decode(&order_side).unwrap(), "Buy");
+        assert!(decoder.decode(&order_side).is_err());
+    }
+}
@@ -0,0 +1,150 @@
+use anyhow::{bail, Context};
+use serde_json::{json, Map, Value};
+use solana_program::pubkey::Pubkey;
+
+use crate::Program;
+
+/// Decodes borsh data by the types of a program's IDL, a struct to an object (by the IDL's field
+/// names), an enum to its variant's name (or `{ "<Variant>": { fields } }`).
+pub(crate) struct Decoder<'a> {
+    program: &'a Program,
+    data: &'a [u8],
+}
+
+impl<'a> Decoder<'a> {
+    pub fn new(program: &'a Program, data: &'a [u8]) -> Self {
+        Decoder { program, data }
+    }
+
+    /// The data not decoded (yet).
+    pub fn remaining(&self) -> &'a [u8] {
+        self.data
+    }
+
+    fn take(&mut self, len: usize) -> anyhow::Result<&'a [u8]> {
+        if self.data.len() < len {
+            bail!(
+                "Unexpected end of data, {} bytes left of {}",
+                self.data.len(),
+                len
+            );
+        }
+        let (bytes, data) = self.data.split_at(len);
+        self.data = data;
+        Ok(bytes)
+    }
+
+    fn take_array<const N: usize>(&mut self) -> anyhow::Result<[u8; N]> {
+        Ok(self.take(N)?.try_into().unwrap())
+    }
+
+    fn len(&mut self) -> anyhow::Result<usize> {
+        Ok(u32::from_le_bytes(self.take_array()?) as usize)
+    }
+
+    pub fn decode_fields(&mut self, fields: &Value) -> anyhow::Result<Value> {
+        let mut object = Map::new();
+        for field in fields.as_array().into_iter().flatten() {
+            let name = field["name"].as_str().unwrap_or_default();
+            let value = self
+                .decode(&field["type"])
+                .with_context(|| format!("Field {}", name))?;
+            object.insert(name.to_string(), value);
+        }
+        Ok(Value::Object(object))
+    }
+
+    pub fn decode(&mut self, ty: &Value) -> anyhow::Result<Value> {
+        let value = match ty {
+            Value::String(ty) => match ty.as_str() {
+                "bool" => json!(self.take(1)?[0] != 0),
+                "u8" => json!(self.take(1)?[0]),
+                "i8" => json!(self.take(1)?[0] as i8),
+                "u16" => json!(u16::from_le_bytes(self.take_array()?)),
+                "i16" => json!(i16::from_le_bytes(self.take_array()?)),
+                "u32" => json!(u32::from_le_bytes(self.take_array()?)),
+                "i32" => json!(i32::from_le_bytes(self.take_array()?)),
+                "f32" => json!(f32::from_le_bytes(self.take_array()?)),
+                "u64" => json!(u64::from_le_bytes(self.take_array()?)),
+                "i64" => json!(i64::from_le_bytes(self.take_array()?)),
+                "f64" => json!(f64::from_le_bytes(self.take_array()?)),
+                // a string, JSON numbers aren't 128 bits
+                "u128" => json!(u128::from_le_bytes(self.take_array()?).to_string()),
+                "i128" => json!(i128::from_le_bytes(self.take_array()?).to_string()),
+                "publicKey" => json!(Pubkey::new_from_array(self.take_array()?).to_string()),
+                "string" => {
+                    let len = self.len()?;
+                    json!(String::from_utf8_lossy(self.take(len)?))
+                }
+                "bytes" => {
+                    let len = self.len()?;
+                    json!(self.take(len)?)
+                }
+                _ => bail!("Unsupported type {}", ty),
+            },
+            Value::Object(object) => {
+                if let Some(ty) = object.get("vec") {
+                    let len = self.len()?;
+                    let values = (0..len)
+                        .map(|_| self.decode(ty))
+                        .collect::<anyhow::Result<Vec<_>>>()?;
+                    Value::Array(values)
+                } else if let Some(ty) = object.get("option") {
+                    match self.take(1)?[0] {
+                        0 => Value::Null,
+                        1 => self.decode(ty)?,
+                        tag => bail!("Invalid option tag {}", tag),
+                    }
+                } else if let Some(array) = object.get("array") {
+                    let len = array[1].as_u64().unwrap_or_default();
+                    let values = (0..len)
+                        .map(|_| self.decode(&array[0]))
+                        .collect::<anyhow::Result<Vec<_>>>()?;
+                    Value::Array(values)
+                } else if let Some(name) = object.get("defined").and_then(Value::as_str) {
+                    self.decode_defined(name)?
+                } else {
+                    bail!("Unsupported type {}", ty)
+                }
+            }
+            _ => bail!("Unsupported type {}", ty),
+        };
+
+        Ok(value)
+    }
+
+    fn decode_defined(&mut self, name: &str) -> anyhow::Result<Value> {
+        let def = self
+            .program
+            .type_def(name)
+            .with_context(|| format!("Type {} not in the IDL", name))?;
+
+        match def["type"]["kind"].as_str() {
+            Some("struct") => self.decode_fields(&def["type"]["fields"]),
+            Some("enum") => {
+                let index = self.take(1)?[0] as usize;
+                let variant = def["type"]["variants"]
+                    .get(index)
+                    .with_context(|| format!("Invalid {} variant {}", name, index))?;
+                let variant_name = variant["name"].as_str().unwrap_or_default();
+
+                let Some(fields) = variant.get("fields").and_then(Value::as_array) else {
+                    return Ok(json!(variant_name));
+                };
+                let value = match fields.first() {
+                    Some(field) if field.get("name").is_some() => {
+                        self.decode_fields(&variant["fields"])?
+                    }
+                    _ => Value::Array(
+                        fields
+                            .iter()
+                            .map(|ty| self.decode(ty))
+                            .collect::<anyhow::Result<Vec<_>>>()?,
+                    ),
+                };
+                Ok(json!({ variant_name: value }))
+            }
+            _ => bail!("Unsupported type {}", name),
+        }
+    }
+}
@@ -0,0 +1,98 @@
+use serde_json::Value;
+use solana_program::{hash::hash, pubkey::Pubkey};
+
+use std::sync::LazyLock;
+
+/// A bundled program, the ID of its generated crate and its Anchor IDL.
+#[derive(Debug)]
+pub struct Program {
+    /// The program's directory (`sage`, `player-profile`...).
+    pub name: &'static str,
+    pub id: Pubkey,
+    pub idl: Value,
+}
+
+macro_rules! program {
+    ($name:literal, $krate:ident) => {
+        Program::new(
+            $name,
+            $krate::ID,
+            include_str!(concat!("../../../programs/", $name, "/idl.json")),
+        )
+    };
+}
+
+static PROGRAMS: LazyLock<Vec<Program>> = LazyLock::new(|| {
+    vec![
+        program!("atlas-staking", staratlas_atlas_staking),
+        program!("cargo", staratlas_cargo),
+        program!("claim", staratlas_claim),
+        program!("crafting", staratlas_crafting),
+        program!("locked-voter", staratlas_locked_voter),
+        program!("marketplace", staratlas_marketplace),
+        program!("player-profile", staratlas_player_profile),
+        program!("points", staratlas_points),
+        program!("profile-vault", staratlas_profile_vault),
+        program!("proxy-rewarder", staratlas_proxy_rewarder),
+        program!("sage", staratlas_sage),
+        program!("score", staratlas_score),
+    ]
+});
+
+pub fn programs() -> &'static [Program] {
+    &PROGRAMS
+}
+
+pub fn find_program(id: &Pubkey) -> Option<&'static Program> {
+    programs().iter().find(|program| &program.id == id)
+}
+
+/// A program by its name or ID.
+pub fn find_program_by_name(name: &str) -> Option<&'static Program> {
+    programs()
+        .iter()
+        .find(|program| program.name == name || program.id.to_string() == name)
+}
+
+/// The Anchor discriminator of an account, the first 8 bytes of `sha256("account:<Name>")`.
+pub fn account_discriminator(name: &str) -> [u8; 8] {
+    let hash = hash(format!("account:{}", name).as_bytes());
+    hash.to_bytes()[..8].try_into().unwrap()
+}
+
+impl Program {
+    fn new(name: &'static str, id: Pubkey, idl: &str) -> Self {
+        let idl = serde_json::from_str(idl).expect("a program's IDL");
+        Program { name, id, idl }
+    }
+
+    /// The definitions of the program's accounts.
+    pub fn accounts(&self) -> impl Iterator<Item = &Value> {
+        self.idl["accounts"].as_array().into_iter().flatten()
+    }
+
+    /// The definition of the account of a discriminator.
+    pub fn account(&self, discriminator: &[u8]) -> Option<&Value> {
+        self.accounts().find(|account| {
+            let name = account["name"].as_str().unwrap_or_default();
+            account_discriminator(name) == discriminator
+        })
+    }
+
+    /// The definition of an instruction by name.
+    pub fn instruction(&self, name: &str) -> Option<&Value> {
+        self.idl["instructions"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .find(|instruction| instruction["name"] == name)
+    }
+
+    /// The definition of a type (or account) by name.
+    pub fn type_def(&self, name: &str) -> Option<&Value> {
+        ["types", "accounts"]
+            .into_iter()
+            .flat_map(|key| self.idl[key].as_array().into_iter().flatten())
+            .find(|def| def["name"] == name)
+    }
+}
@@ -0,0 +1,125 @@
+use anyhow::{bail, Context};
+use serde_json::{json, Value};
+use solana_program::pubkey::Pubkey;
+
+use crate::decode::Decoder;
+use crate::idl::Program;
+
+/// An instruction of a program's generated crate (`<crate>::instruction::<Name>`).
+pub(crate) struct GeneratedInstruction {
+    /// The instruction's name in the IDL.
+    pub name: &'static str,
+    pub discriminator: [u8; 8],
+    /// Whether the instruction's struct deserializes the args (the data following the
+    /// discriminator).
+    pub deserialize: fn(&[u8]) -> bool,
+}
+
+include!(concat!(env!("OUT_DIR"), "/instructions.rs"));
+
+/// An instruction of a bundled program decoded by its generated crate and IDL.
+#[derive(Debug, Clone)]
+pub struct DecodedInstruction {
+    pub program: &'static str,
+    pub program_id: Pubkey,
+    /// The instruction's name in the IDL.
+    pub name: String,
+    /// The instruction's args by their IDL names.
+    pub args: Value,
+    /// The instruction's accounts by their IDL names (`<accounts>.<account>` of nested accounts),
+    /// the extra accounts (remaining accounts) by `remaining[<index>]`.
+    pub accounts: Vec<(String, Pubkey)>,
+}
+
+impl DecodedInstruction {
+    pub fn to_json(&self) -> Value {
+        let accounts: Vec<_> = self
+            .accounts
+            .iter()
+            .map(|(name, pubkey)| json!({ "name": name, "pubkey": pubkey.to_string() }))
+            .collect();
+
+        json!({
+            "program": self.program,
+            "programId": self.program_id.to_string(),
+            "instruction": self.name,
+            "args": self.args,
+            "accounts": accounts,
+        })
+    }
+}
+
+/// The names of an instruction's accounts (IDL), nested accounts by `<accounts>.<account>`.
+fn account_names(prefix: &str, accounts: &Value, names: &mut Vec<String>) {
+    for account in accounts.as_array().into_iter().flatten() {
+        let name = format!("{}{}", prefix, account["name"].as_str().unwrap_or_default());
+        match account.get("accounts") {
+            Some(accounts) => account_names(&format!("{}.", name), accounts, names),
+            None => names.push(name),
+        }
+    }
+}
+
+/// Decodes an instruction of a bundled program, its discriminator and args by the program's
+/// generated `instruction` struct, the args' values and accounts' names by the IDL.
+pub fn decode_instruction(
+    program: &'static Program,
+    accounts: &[Pubkey],
+    data: &[u8],
+) -> anyhow::Result<DecodedInstruction> {
+    let discriminator = data
+        .get(..8)
+        .context("Instruction data shorter than a discriminator")?;
+    let generated = generated_instructions(program.name)
+        .iter()
+        .find(|instruction| instruction.discriminator == discriminator)
+        .with_context(|| {
+            format!(
+                "Unknown {} instruction discriminator {:?}",
+                program.name, discriminator
+            )
+        })?;
+
+    let args = &data[8..];
+    if !(generated.deserialize)(args) {
+        bail!(
+            "Failed to deserialize the args of a {} {} instruction",
+            program.name,
+            generated.name
+        );
+    }
+
+    let instruction = program
+        .instruction(generated.name)
+        .with_context(|| format!("Instruction {} not in the IDL", generated.name))?;
+    let args = Decoder::new(program, args)
+        .decode_fields(&instruction["args"])
+        .with_context(|| {
+            format!(
+                "Failed to decode a {} {} instruction",
+                program.name, generated.name
+            )
+        })?;
+
+    let mut names = vec![];
+    account_names("", &instruction["accounts"], &mut names);
+    let accounts = accounts
+        .iter()
+        .enumerate()
+        .map(|(i, pubkey)| {
+            let name = match names.get(i) {
+                Some(name) => name.clone(),
+                None => format!("remaining[{}]", i - names.len()),
+            };
+            (name, *pubkey)
+        })
+        .collect();
+
+    Ok(DecodedInstruction {
+        program: program.name,
+        program_id: program.id,
+        name: generated.name.to_string(),
+        args,
+        accounts,
+    })
+}
@@ -0,0 +1,17 @@
+//! Inspects the accounts and transactions of the bundled Star Atlas programs, whatever their type:
+//! the owner and the Anchor discriminator of an account (or instruction) find its IDL definition,
+//! which decodes its data.
+
+pub mod account;
+mod decode;
+pub mod idl;
+pub mod instruction;
+pub mod transaction;
+
+pub use account::{decode_account, decode_any_account, DecodedAccount};
+pub use idl::{find_program, find_program_by_name, programs, Program};
+pub use instruction::{decode_instruction, DecodedInstruction};
+pub use transaction::{
+    decode_serialized_transaction, decode_transaction, fetch_transaction, DecodedTransaction,
+    TransactionInstruction,
+};
@@ -0,0 +1,274 @@
+use anyhow::{bail, Context};
+use serde_json::{json, Value};
+use solana_client::{rpc_client::RpcClient, rpc_config::RpcTransactionConfig};
+use solana_sdk::{
+    bs58, commitment_config::CommitmentConfig, pubkey::Pubkey, signature::Signature,
+    transaction::VersionedTransaction,
+};
+use solana_transaction_status::{
+    UiInstruction, UiLoadedAddresses, UiTransactionEncoding, UiTransactionStatusMeta,
+};
+
+use std::str::FromStr;
+
+use crate::idl::find_program;
+use crate::instruction::{decode_instruction, DecodedInstruction};
+
+/// An instruction (or inner instruction) of a transaction.
+#[derive(Debug, Clone)]
+pub struct TransactionInstruction {
+    /// The instruction's index in the transaction.
+    pub index: usize,
+    /// The inner instruction's (CPI) index among the instruction's.
+    pub inner_index: Option<usize>,
+    pub program_id: Pubkey,
+    /// The instruction decoded, `None` when not a bundled program's, the error when it fails.
+    pub decoded: Option<Result<DecodedInstruction, String>>,
+}
+
+impl TransactionInstruction {
+    pub fn to_json(&self) -> Value {
+        let mut value = match &self.decoded {
+            Some(Ok(decoded)) => decoded.to_json(),
+            Some(Err(err)) => json!({ "error": err }),
+            None => json!({}),
+        };
+        value["index"] = json!(self.index);
+        value["innerIndex"] = json!(self.inner_index);
+        value["programId"] = json!(self.program_id.to_string());
+        value
+    }
+}
+
+/// The instructions of a transaction decoded, with its status (of a fetched transaction).
+#[derive(Debug, Clone)]
+pub struct DecodedTransaction {
+    pub signature: Signature,
+    /// The error of a failed transaction.
+    pub err: Option<String>,
+    pub instructions: Vec<TransactionInstruction>,
+    pub log_messages: Vec<String>,
+}
+
+impl DecodedTransaction {
+    pub fn to_json(&self) -> Value {
+        let instructions: Vec<_> = self
+            .instructions
+            .iter()
+            .map(TransactionInstruction::to_json)
+            .collect();
+
+        json!({
+            "signature": self.signature.to_string(),
+            "err": self.err,
+            "instructions": instructions,
+            "logMessages": self.log_messages,
+        })
+    }
+}
+
+fn transaction_instruction(
+    keys: &[Pubkey],
+    (index, inner_index): (usize, Option<usize>),
+    program_id_index: u8,
+    accounts: &[u8],
+    data: &[u8],
+) -> anyhow::Result<TransactionInstruction> {
+    let key = |i: u8| {
+        keys.get(i as usize)
+            .copied()
+            .with_context(|| format!("Account index {} out of the transaction's keys", i))
+    };
+
+    let program_id = key(program_id_index)?;
+    let decoded = match find_program(&program_id) {
+        Some(program) => {
+            let accounts = accounts
+                .iter()
+                .map(|i| key(*i))
+                .collect::<anyhow::Result<Vec<_>>>()?;
+            let decoded = decode_instruction(program, &accounts, data);
+            Some(decoded.map_err(|err| format!("{:#}", err)))
+        }
+        None => None,
+    };
+
+    Ok(TransactionInstruction {
+        index,
+        inner_index,
+        program_id,
+        decoded,
+    })
+}
+
+/// Decodes the instructions (and inner instructions, of its status) of a transaction.
+pub fn decode_transaction(
+    transaction: &VersionedTransaction,
+    meta: Option<&UiTransactionStatusMeta>,
+) -> anyhow::Result<DecodedTransaction> {
+    let message = &transaction.message;
+
+    // the static keys followed by the ones of the address lookup tables (loaded, of its status)
+    let mut keys = message.static_account_keys().to_vec();
+    let loaded_addresses: Option<&UiLoadedAddresses> =
+        meta.and_then(|meta| Option::from(meta.loaded_addresses.as_ref()));
+    match loaded_addresses {
+        Some(loaded) => {
+            for address in loaded.writable.iter().chain(&loaded.readonly) {
+                keys.push(Pubkey::from_str(address)?);
+            }
+        }
+        None => {
+            if message
+                .address_table_lookups()
+                .is_some_and(|lookups| !lookups.is_empty())
+            {
+                bail!("Transaction of address lookup tables, its accounts aren't loaded");
+            }
+        }
+    }
+
+    let inner_instructions: Vec<_> = meta
+        .and_then(|meta| Option::from(meta.inner_instructions.clone()))
+        .unwrap_or_default();
+
+    let mut instructions = vec![];
+    for (index, instruction) in message.instructions().iter().enumerate() {
+        instructions.push(transaction_instruction(
+            &keys,
+            (index, None),
+            instruction.program_id_index,
+            &instruction.accounts,
+            &instruction.data,
+        )?);
+
+        let inner = inner_instructions
+            .iter()
+            .filter(|inner| inner.index as usize == index)
+            .flat_map(|inner| &inner.instructions);
+        for (inner_index, instruction) in inner.enumerate() {
+            let UiInstruction::Compiled(instruction) = instruction else {
+                continue; // parsed, not of a base64 encoded transaction
+            };
+            let data = bs58::decode(&instruction.data).into_vec()?;
+            instructions.push(transaction_instruction(
+                &keys,
+                (index, Some(inner_index)),
+                instruction.program_id_index,
+                &instruction.accounts,
+                &data,
+            )?);
+        }
+    }
+
+    Ok(DecodedTransaction {
+        signature: transaction.signatures.first().copied().unwrap_or_default(),
+        err: meta.and_then(|meta| meta.err.as_ref().map(ToString::to_string)),
+        instructions,
+        log_messages: meta
+            .and_then(|meta| Option::from(meta.log_messages.clone()))
+            .unwrap_or_default(),
+    })
+}
+
+/// Decodes a serialized (bincode) transaction, without its status.
+pub fn decode_serialized_transaction(data: &[u8]) -> anyhow::Result<DecodedTransaction> {
+    let transaction: VersionedTransaction = bincode::deserialize(data)?;
+    decode_transaction(&transaction, None)
+}
+
+/// Fetches a transaction (and its status) by its signature and decodes it.
+pub fn fetch_transaction(
+    rpc: &RpcClient,
+    signature: &Signature,
+) -> anyhow::Result<DecodedTransaction> {
+    let config = RpcTransactionConfig {
+        encoding: Some(UiTransactionEncoding::Base64),
+        commitment: Some(CommitmentConfig::confirmed()),
+        max_supported_transaction_version: Some(0),
+    };
+    let transaction = rpc.get_transaction_with_config(signature, config)?;
+
+    let meta = transaction.transaction.meta;
+    let transaction = transaction
+        .transaction
+        .transaction
+        .decode()
+        .context("Failed to decode the transaction")?;
+
+    decode_transaction(&transaction, meta.as_ref())
+}
+
+// cargo test -p staratlas-inspect-sdk
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use anchor_lang::InstructionData;
+    use solana_sdk::{
+        instruction::{AccountMeta, Instruction},
+        message::Message,
+        transaction::Transaction,
+    };
+    use staratlas_sage::{instruction, typedefs};
+
+    #[test]
+    fn it_works() {
+        let accounts: Vec<_> = (0..14).map(|_| Pubkey::new_unique()).collect();
+        let data = instruction::WarpToCoordinate {
+            _input: typedefs::WarpToCoordinateInput {
+                key_index: 1,
+                to_sector: [-40, 30],
+            },
+        }
+        .data();
+
+        let instructions = [
+            Instruction::new_with_bytes(Pubkey::new_unique(), b"memo", vec![]),
+            Instruction::new_with_bytes(
+                staratlas_sage::ID,
+                &data,
+                accounts
+                    .iter()
+                    .map(|pubkey| AccountMeta::new(*pubkey, false))
+                    .collect(),
+            ),
+            Instruction::new_with_bytes(staratlas_sage::ID, &data[..12], vec![]),
+            Instruction::new_with_bytes(staratlas_sage::ID, &[0; 8], vec![]),
+        ];
+        let message = Message::new(&instructions, Some(&accounts[0]));
+        let transaction = VersionedTransaction::from(Transaction::new_unsigned(message));
+
+        let decoded =
+            decode_serialized_transaction(&bincode::serialize(&transaction).unwrap()).unwrap();
+        assert_eq!(decoded.instructions.len(), 4);
+        assert!(decoded.instructions[0].decoded.is_none());
+
+        let warp = decoded.instructions[1].decoded.clone().unwrap().unwrap();
+        assert_eq!(warp.program, "sage");
+        assert_eq!(warp.name, "warpToCoordinate");
+        assert_eq!(
+            warp.args,
+            json!({ "input": { "keyIndex": 1, "toSector": [-40, 30] } })
+        );
+        assert_eq!(
+            warp.accounts[0],
+            (
+                "gameAccountsFleetAndOwner.gameFleetAndOwner.fleetAndOwner.key".to_string(),
+                accounts[0]
+            )
+        );
+        assert_eq!(
+            warp.accounts[12],
+            ("tokenProgram".to_string(), accounts[12])
+        );
+        assert_eq!(
+            warp.accounts[13],
+            ("remaining[0]".to_string(), accounts[13])
+        );
+
+        // truncated args, an unknown discriminator
+        assert!(decoded.instructions[2].decoded.clone().unwrap().is_err());
+        assert!(decoded.instructions[3].decoded.clone().unwrap().is_err());
+    }
+}
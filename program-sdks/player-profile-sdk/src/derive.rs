@@ -1,25 +1,32 @@
 use anchor_client::{
     solana_client::{
         rpc_config::{RpcAccountInfoConfig, RpcProgramAccountsConfig},
-        rpc_filter::{Memcmp, RpcFilterType},
+        rpc_filter::RpcFilterType,
     },
     solana_sdk::{account::Account, commitment_config::CommitmentConfig, signature::Signer},
     Program,
 };
 use anchor_lang::prelude::Pubkey;
 use solana_account_decoder::UiAccountEncoding;
+use staratlas_utils_query::Query;
+pub use staratlas_utils_query::PROFILE_KEYS;
 
 use crate::{programs::staratlas_player_profile::state, Profile};
 
 use std::ops::Deref;
 
+/// The filters of a player's profiles, the player's key is the first of their keys.
+fn player_filters(player_pubkey: &Pubkey) -> Vec<RpcFilterType> {
+    Query::<state::Profile>::new()
+        .eq_field(PROFILE_KEYS.field(0, |f| f.key), player_pubkey)
+        .filters()
+}
+
 pub fn profile_accounts<C: Deref<Target = impl Signer> + Clone>(
     program: &Program<C>,
     player_pubkey: &Pubkey,
 ) -> anyhow::Result<Vec<(Pubkey, Profile)>> {
-    let accounts = program.accounts::<state::Profile>(vec![RpcFilterType::Memcmp(
-        Memcmp::new_base58_encoded(30, player_pubkey.as_ref()),
-    )])?;
+    let accounts = program.accounts::<state::Profile>(player_filters(player_pubkey))?;
 
     let profile_accounts = accounts
         .iter()
@@ -34,10 +41,7 @@ pub fn get_profile_accounts<C: Deref<Target = impl Signer> + Clone>(
     player_pubkey: &Pubkey,
 ) -> anyhow::Result<Vec<(Pubkey, Account)>> {
     let config = RpcProgramAccountsConfig {
-        filters: Some(vec![RpcFilterType::Memcmp(Memcmp::new_base58_encoded(
-            30,
-            player_pubkey.as_ref(),
-        ))]),
+        filters: Some(player_filters(player_pubkey)),
         account_config: RpcAccountInfoConfig {
             encoding: Some(UiAccountEncoding::Base64),
             commitment: Some(CommitmentConfig::confirmed()),
@@ -1,7 +1,10 @@
-use anchor_client::anchor_lang::{prelude::Pubkey, AnchorDeserialize};
+use anchor_client::anchor_lang::{prelude::Pubkey, AnchorDeserialize, Discriminator};
 use borsh::{BorshDeserialize, BorshSerialize};
+use staratlas_utils_query::{Field, FieldValue, IdlAccount};
 
+use std::collections::HashSet;
 use std::io::Read;
+use std::sync::{LazyLock, Mutex};
 
 pub use staratlas_sage::{state, typedefs};
 
@@ -11,38 +14,126 @@ pub use cargo::*;
 mod fleet;
 pub use fleet::*;
 
+mod fleet_ships;
+pub use fleet_ships::*;
+
 mod fleet_state;
 pub use fleet_state::*;
 
 mod game;
 pub use game::*;
 
+mod inventory;
+pub use inventory::*;
+
 mod mine_item;
 pub use mine_item::*;
 
 mod planet;
 pub use planet::*;
 
+mod profile;
+pub use profile::*;
+
 mod resource;
 pub use resource::*;
 
+mod ship;
+pub use ship::*;
+
 pub mod types;
 
+/// A field of an account's data at its IDL offset, an error when the data is too short.
+fn read_field<V: FieldValue>(field: Field<V>, account_data: &[u8]) -> borsh::io::Result<V> {
+    field
+        .read(account_data)
+        .ok_or(borsh::io::ErrorKind::UnexpectedEof.into())
+}
+
+/// The latest `Fleet` account version the SDK decodes (the IDL's layout).
+pub const FLEET_VERSION: u8 = 0;
+
+/// The warnings of the `Fleet` accounts newer than `FLEET_VERSION` (decoded by the latest layout),
+/// once per version.
+pub struct FleetVersionWarnings {
+    warned: Mutex<HashSet<u8>>,
+    warn: Box<dyn Fn(u8) + Send + Sync>,
+}
+
+impl FleetVersionWarnings {
+    pub fn new(warn: impl Fn(u8) + Send + Sync + 'static) -> Self {
+        FleetVersionWarnings {
+            warned: Mutex::default(),
+            warn: Box::new(warn),
+        }
+    }
+
+    fn newer_version(&self, version: u8) {
+        if self.warned.lock().unwrap().insert(version) {
+            (self.warn)(version);
+        }
+    }
+}
+
+impl Default for FleetVersionWarnings {
+    fn default() -> Self {
+        FleetVersionWarnings::new(|version| {
+            log::warn!(
+                "Fleet account version {} is newer than {}, decoding it as {}",
+                version,
+                FLEET_VERSION,
+                FLEET_VERSION
+            )
+        })
+    }
+}
+
+/// The warnings of the fleets decoded by `BorshDeserialize` (logged).
+static FLEET_VERSION_WARNINGS: LazyLock<FleetVersionWarnings> = LazyLock::new(Default::default);
+
 #[derive(Debug, Clone)]
 pub struct FleetWithState(pub Fleet, pub FleetState);
 
-impl borsh::de::BorshDeserialize for FleetWithState {
-    fn deserialize_reader<R: Read>(reader: &mut R) -> borsh::io::Result<Self> {
-        let mut account_data = vec![];
-        reader.read_to_end(&mut account_data)?;
+impl FleetWithState {
+    /// Decodes a `Fleet` account (its discriminator included), see `deserialize_fleet`.
+    pub fn deserialize_with(
+        account_data: &[u8],
+        warnings: &FleetVersionWarnings,
+    ) -> borsh::io::Result<Self> {
+        // (discriminator, version, ..)
+        let version = *account_data
+            .get(8)
+            .ok_or(borsh::io::ErrorKind::UnexpectedEof)?;
 
-        let account_data = account_data.as_slice();
         let mut account_data = &account_data[8..];
+        let fleet = Self::deserialize_fleet(version, &mut account_data, warnings)?;
+        let state = Self::deserialize_state(account_data)?;
 
-        let fleet = Fleet::deserialize_reader(&mut account_data)?;
+        Ok(FleetWithState(fleet, state))
+    }
+
+    /// Decodes the `Fleet` by the layout of its version. The accounts grow at their end, a newer
+    /// version is decoded by the latest layout (warned about once per version).
+    fn deserialize_fleet(
+        version: u8,
+        account_data: &mut &[u8],
+        warnings: &FleetVersionWarnings,
+    ) -> borsh::io::Result<Fleet> {
+        match version {
+            0 => Fleet::deserialize_reader(account_data),
+            _ => {
+                warnings.newer_version(version);
+                Self::deserialize_fleet(FLEET_VERSION, account_data, warnings)
+            }
+        }
+    }
 
-        let discriminator = account_data[0];
-        let mut remaining_data = &account_data[1..];
+    /// Decodes the fleet state after the `Fleet` (its discriminator and data), a discriminator
+    /// the SDK doesn't know is `FleetState::Unknown`.
+    pub fn deserialize_state(account_data: &[u8]) -> borsh::io::Result<FleetState> {
+        let (&discriminator, mut remaining_data) = account_data
+            .split_first()
+            .ok_or(borsh::io::ErrorKind::UnexpectedEof)?;
 
         let state = match discriminator {
             0 => {
@@ -70,11 +161,185 @@ impl borsh::de::BorshDeserialize for FleetWithState {
                 let respawn = typedefs::Respawn::deserialize(&mut remaining_data)?;
                 FleetState::Respawn(respawn.into())
             }
-            _ => {
-                unreachable!("Fleet account has invalid FleetState discriminator")
-            }
+            _ => FleetState::Unknown {
+                discriminator,
+                raw: remaining_data.to_vec(),
+            },
         };
 
-        Ok(FleetWithState(fleet, state))
+        Ok(state)
+    }
+}
+
+impl borsh::de::BorshDeserialize for FleetWithState {
+    fn deserialize_reader<R: Read>(reader: &mut R) -> borsh::io::Result<Self> {
+        let mut account_data = vec![];
+        reader.read_to_end(&mut account_data)?;
+
+        Self::deserialize_with(&account_data, &FLEET_VERSION_WARNINGS)
+    }
+}
+
+// cargo test -p staratlas-sage-based-sdk --features anchor-client/async
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use base64::{engine::general_purpose::STANDARD, Engine};
+    use std::sync::Arc;
+
+    /// `Fleet` accounts of the version 0 layout, one per state (`<State> <base64 account data>`,
+    /// see `fixtures/README.md`).
+    const FLEET_V0: &str = include_str!("../fixtures/fleet_v0.txt");
+
+    /// The offset of the state in a fleet account (after the discriminator and the fleet).
+    const STATE_OFFSET: usize = 8 + 431;
+
+    fn fleet_accounts(fixtures: &str) -> Vec<(&str, Vec<u8>)> {
+        fixtures
+            .lines()
+            .map(|line| {
+                let (state, data) = line.split_once(' ').unwrap();
+                (state, STANDARD.decode(data).unwrap())
+            })
+            .collect()
+    }
+
+    fn key(byte: u8) -> Pubkey {
+        Pubkey::new_from_array([byte; 32])
+    }
+
+    /// The states of the fixtures, by name.
+    fn fixture_state(state: &str) -> FleetState {
+        match state {
+            "StarbaseLoadingBay" => FleetState::StarbaseLoadingBay(StarbaseLoadingBay {
+                starbase: key(8),
+                last_update: 1_700_000_000,
+            }),
+            "Idle" => FleetState::Idle(Idle { sector: [-40, 30] }),
+            "MineAsteroid" => FleetState::MineAsteroid(MineAsteroid {
+                asteroid: key(9),
+                resource: key(10),
+                start: 1_700_000_000,
+                end: 0,
+                last_update: 1_700_000_100,
+            }),
+            "MoveWarp" => FleetState::MoveWarp(MoveWarp {
+                from_sector: [40, 30],
+                to_sector: [38, 25],
+                warp_start: 1_700_000_000,
+                warp_finish: 1_700_000_060,
+            }),
+            "MoveSubwarp" => FleetState::MoveSubwarp(MoveSubwarp {
+                from_sector: [40, 30],
+                to_sector: [41, 30],
+                current_sector: [40, 30],
+                departure_time: 1_700_000_000,
+                arrival_time: 1_700_000_600,
+                fuel_expenditure: 12,
+                last_update: 1_700_000_000,
+            }),
+            "Respawn" => FleetState::Respawn(Respawn {
+                sector: [0, -39],
+                start: 1_700_000_000,
+            }),
+            state => panic!("no fixture state {}", state),
+        }
+    }
+
+    /// Warnings recording the versions warned about.
+    fn recorded_warnings() -> (FleetVersionWarnings, Arc<Mutex<Vec<u8>>>) {
+        let warned = Arc::new(Mutex::new(vec![]));
+        let recorded = warned.clone();
+        let warnings = FleetVersionWarnings::new(move |v| recorded.lock().unwrap().push(v));
+        (warnings, warned)
+    }
+
+    fn assert_decodes_every_state(version: u8, warnings: &FleetVersionWarnings) {
+        let accounts = fleet_accounts(FLEET_V0);
+        assert_eq!(accounts.len(), 6);
+
+        for (state, mut account_data) in accounts {
+            account_data[8] = version;
+            let FleetWithState(fleet, decoded_state) =
+                FleetWithState::deserialize_with(&account_data, warnings).unwrap();
+
+            assert_eq!(fleet.version, version);
+            assert_eq!(fleet.name(), "Fixture Fleet");
+            assert_eq!(
+                (fleet.game_id, fleet.owner_profile, fleet.fleet_ships),
+                (key(1), key(2), key(3))
+            );
+            assert_eq!(fleet.faction, 1);
+            assert_eq!((fleet.ship_counts.total, fleet.ship_counts.small), (3, 3));
+            assert_eq!(fleet.warp_cooldown_expires_at, 1_700_000_000);
+            assert_eq!(fleet.stats.movement_stats.max_warp_distance, 200);
+            assert_eq!(fleet.stats.cargo_stats.cargo_capacity, 1_000);
+            assert_eq!(fleet.stats.cargo_stats.mining_rate, 10_000);
+            assert_eq!((fleet.cargo_hold, fleet.ammo_bank), (key(4), key(6)));
+            assert_eq!((fleet.update_id, fleet.bump), (7, 254));
+            assert_eq!(decoded_state, fixture_state(state));
+            assert_eq!(account_data[STATE_OFFSET], decoded_state.discriminator());
+        }
+    }
+
+    #[test]
+    fn decodes_every_state_of_the_version_0_layout() {
+        let (warnings, warned) = recorded_warnings();
+        assert_decodes_every_state(0, &warnings);
+        assert!(warned.lock().unwrap().is_empty());
+
+        // the default decoding
+        for (_, account_data) in fleet_accounts(FLEET_V0) {
+            assert!(FleetWithState::deserialize(&mut account_data.as_slice()).is_ok());
+        }
+    }
+
+    #[test]
+    fn decodes_a_newer_version_by_the_latest_layout() {
+        let (warnings, warned) = recorded_warnings();
+
+        assert_decodes_every_state(FLEET_VERSION + 1, &warnings);
+        assert_decodes_every_state(FLEET_VERSION + 2, &warnings);
+        assert_decodes_every_state(FLEET_VERSION + 1, &warnings);
+
+        // once per version
+        assert_eq!(
+            *warned.lock().unwrap(),
+            vec![FLEET_VERSION + 1, FLEET_VERSION + 2]
+        );
+    }
+
+    #[test]
+    fn decodes_unknown_state() {
+        let (_, mut account_data) = fleet_accounts(FLEET_V0).remove(1);
+        account_data[STATE_OFFSET..STATE_OFFSET + 4].copy_from_slice(&[9, 1, 2, 3]);
+
+        let FleetWithState(_, state) =
+            FleetWithState::deserialize(&mut account_data.as_slice()).unwrap();
+
+        match state {
+            FleetState::Unknown { discriminator, raw } => {
+                assert_eq!(discriminator, 9);
+                assert_eq!(&raw[..3], &[1, 2, 3]);
+                assert_eq!(raw.len(), 96); // the rest of the account
+            }
+            state => panic!("expected an unknown state, got {:?}", state),
+        }
+    }
+
+    #[test]
+    fn rejects_truncated_accounts() {
+        let accounts = fleet_accounts(FLEET_V0);
+
+        // no state, then a state cut short
+        let (_, account_data) = &accounts[0];
+        assert!(FleetWithState::deserialize(&mut &account_data[..STATE_OFFSET]).is_err());
+
+        let (state, account_data) = &accounts[4];
+        assert_eq!(*state, "MoveSubwarp");
+        let subwarp_len = STATE_OFFSET + 1 + 6 * 8 + 4 * 8;
+        assert!(FleetWithState::deserialize(&mut &account_data[..subwarp_len - 1]).is_err());
+
+        assert!(FleetWithState::deserialize(&mut &[0u8; 8][..]).is_err());
     }
 }
@@ -68,3 +68,101 @@ impl From<state::CargoType> for CargoType {
         }
     }
 }
+
+/// A cargo type and its stats, the first stat is the cargo space of one unit of its mint.
+#[derive(Debug, Clone)]
+pub struct CargoTypeStats {
+    pub stats_definition: Pubkey,
+    pub mint: Pubkey,
+    pub seq_id: u16,
+    pub stats: Vec<u64>,
+}
+
+impl CargoTypeStats {
+    pub fn cargo_space(&self) -> u64 {
+        self.stats.first().copied().unwrap_or(1)
+    }
+}
+
+impl borsh::de::BorshDeserialize for CargoTypeStats {
+    fn deserialize_reader<R: Read>(reader: &mut R) -> borsh::io::Result<Self> {
+        let mut account_data = vec![];
+        reader.read_to_end(&mut account_data)?;
+
+        // the cargo type (the IDL's fields) then its stats (u64 each)
+        let fields = state::CargoType::FIELDS;
+        let stats_count = read_field(fields.stats_count, &account_data)? as usize;
+        let stats_offset = state::CargoType::DATA_SIZE.unwrap();
+
+        let stats: Vec<u64> = account_data
+            .get(stats_offset..)
+            .unwrap_or_default()
+            .chunks_exact(8)
+            .take(stats_count)
+            .map(|stat| u64::from_le_bytes(stat.try_into().unwrap()))
+            .collect();
+
+        if stats.len() < stats_count {
+            return Err(borsh::io::ErrorKind::UnexpectedEof.into());
+        }
+
+        Ok(CargoTypeStats {
+            stats_definition: read_field(fields.stats_definition, &account_data)?,
+            mint: read_field(fields.mint, &account_data)?,
+            seq_id: read_field(fields.seq_id, &account_data)?,
+            stats,
+        })
+    }
+}
+
+// cargo test -p staratlas-sage-based-sdk --features anchor-client/async
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use anchor_client::anchor_lang::{AnchorSerialize, Discriminator};
+
+    /// A cargo type account: the discriminator, the cargo type and its stats.
+    fn cargo_type_account(mint: &Pubkey, stats_count: u16, stats: &[u64]) -> Vec<u8> {
+        let cargo_type = state::CargoType {
+            version: 0,
+            stats_definition: Pubkey::new_unique(),
+            mint: *mint,
+            creator: Pubkey::new_unique(),
+            bump: 255,
+            stats_count,
+            seq_id: 3,
+        };
+
+        let mut account_data = state::CargoType::DISCRIMINATOR.to_vec();
+        cargo_type.serialize(&mut account_data).unwrap();
+        for stat in stats {
+            account_data.extend(stat.to_le_bytes());
+        }
+        account_data
+    }
+
+    #[test]
+    fn decodes_the_cargo_type_stats() {
+        let mint = Pubkey::new_unique();
+        let account_data = cargo_type_account(&mint, 2, &[5, 42, 7]);
+
+        // the stats follow the cargo type (110 bytes, the discriminator included)
+        assert_eq!(&account_data[110..118], &5u64.to_le_bytes());
+
+        let cargo_type = CargoTypeStats::deserialize(&mut account_data.as_slice()).unwrap();
+        assert_eq!(cargo_type.mint, mint);
+        assert_eq!(cargo_type.seq_id, 3);
+        assert_eq!(cargo_type.stats, vec![5, 42]); // its stats count
+        assert_eq!(cargo_type.cargo_space(), 5);
+    }
+
+    #[test]
+    fn refuses_a_truncated_cargo_type() {
+        let account_data = cargo_type_account(&Pubkey::new_unique(), 2, &[5]);
+        assert!(CargoTypeStats::deserialize(&mut account_data.as_slice()).is_err());
+
+        assert!(CargoTypeStats::deserialize(&mut &account_data[..100]).is_err());
+        assert!(CargoTypeStats::deserialize(&mut &account_data[..4]).is_err());
+    }
+}
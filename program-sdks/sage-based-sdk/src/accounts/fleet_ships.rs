@@ -0,0 +1,139 @@
+use super::*;
+
+#[derive(Debug, Clone, Copy, BorshSerialize, BorshDeserialize)]
+pub struct FleetShipsInfo {
+    pub ship: Pubkey,
+    pub amount: u64,
+    pub update_id: u64,
+}
+
+impl From<typedefs::FleetShipsInfo> for FleetShipsInfo {
+    fn from(info: typedefs::FleetShipsInfo) -> Self {
+        FleetShipsInfo {
+            ship: info.ship,
+            amount: info.amount,
+            update_id: info.update_id,
+        }
+    }
+}
+
+/// The ships of a fleet (`Fleet.fleet_ships`), one info per ship type.
+#[derive(Debug, Clone)]
+pub struct FleetShips {
+    pub version: u8,
+    pub fleet: Pubkey,
+    pub fleet_ships_info_count: u32,
+    pub bump: u8,
+    pub ships: Vec<FleetShipsInfo>,
+}
+
+impl borsh::de::BorshDeserialize for FleetShips {
+    fn deserialize_reader<R: Read>(reader: &mut R) -> borsh::io::Result<Self> {
+        let mut account_data = vec![];
+        reader.read_to_end(&mut account_data)?;
+
+        // the fleet ships (the IDL's fields) then a ship info per ship type
+        let fields = state::FleetShips::FIELDS;
+        let fleet_ships_info_count = read_field(fields.fleet_ships_info_count, &account_data)?;
+
+        let mut data = account_data
+            .get(state::FleetShips::DATA_SIZE.unwrap()..)
+            .unwrap_or_default();
+        let mut ships = vec![];
+        for _ in 0..fleet_ships_info_count {
+            let info: typedefs::FleetShipsInfo = AnchorDeserialize::deserialize(&mut data)?;
+            ships.push(info.into());
+        }
+
+        Ok(FleetShips {
+            version: read_field(fields.version, &account_data)?,
+            fleet: read_field(fields.fleet, &account_data)?,
+            fleet_ships_info_count,
+            bump: read_field(fields.bump, &account_data)?,
+            ships,
+        })
+    }
+}
+
+/// A ship type of a fleet and the number of ships.
+#[derive(Debug, Clone, Copy)]
+pub struct FleetShip {
+    pub ship_id: Pubkey,
+    pub ship: Ship,
+    pub amount: u64,
+}
+
+// cargo test -p staratlas-sage-based-sdk --features anchor-client/async
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::fixtures::zeroed;
+    use borsh::to_vec;
+
+    #[test]
+    fn decodes_the_fleet_ships() {
+        let fleet = Pubkey::new_unique();
+        let infos = [
+            FleetShipsInfo {
+                ship: Pubkey::new_unique(),
+                amount: 3,
+                update_id: 1,
+            },
+            FleetShipsInfo {
+                ship: Pubkey::new_unique(),
+                amount: 1,
+                update_id: 2,
+            },
+        ];
+
+        let mut account_data = state::FleetShips::DISCRIMINATOR.to_vec();
+        account_data.push(0); // version
+        account_data.extend(fleet.as_ref());
+        account_data.extend(2u32.to_le_bytes());
+        account_data.push(254); // bump
+        assert_eq!(Some(account_data.len()), state::FleetShips::DATA_SIZE);
+        for info in &infos {
+            account_data.extend(to_vec(info).unwrap());
+        }
+
+        let fleet_ships = FleetShips::deserialize(&mut account_data.as_slice()).unwrap();
+        assert_eq!(fleet_ships.fleet, fleet);
+        assert_eq!(fleet_ships.bump, 254);
+        assert_eq!(fleet_ships.ships.len(), 2);
+        assert_eq!(fleet_ships.ships[1].ship, infos[1].ship);
+        assert_eq!(fleet_ships.ships[1].amount, 1);
+
+        // an info cut short
+        account_data.pop();
+        assert!(FleetShips::deserialize(&mut account_data.as_slice()).is_err());
+    }
+
+    #[test]
+    fn decodes_a_ship_account() {
+        let mut ship: Ship = zeroed();
+        ship.game_id = Pubkey::new_unique();
+        ship.name[..4].copy_from_slice(b"Pear");
+        ship.size_class = 3;
+        ship.stats.cargo_stats.cargo_capacity = 1_000;
+        ship.stats.misc_stats.crew = 4;
+        ship.max_update_id = 7;
+        ship.next = Pubkey::new_unique();
+
+        let mut account_data = state::Ship::DISCRIMINATOR.to_vec();
+        account_data.extend(to_vec(&ship).unwrap());
+        assert_eq!(Some(account_data.len()), state::Ship::DATA_SIZE);
+
+        let decoded = Ship::from_account_data(&account_data).unwrap();
+        assert_eq!(decoded.game_id, ship.game_id);
+        assert_eq!(decoded.name(), "Pear");
+        assert_eq!(decoded.size_class(), Some(SizeClass::Small));
+        assert_eq!(decoded.stats.cargo_stats.cargo_capacity, 1_000);
+        assert_eq!(decoded.stats.misc_stats.crew, 4);
+        assert_eq!(decoded.max_update_id, 7);
+        assert_eq!(decoded.next, ship.next);
+
+        assert!(Ship::from_account_data(&account_data[..200]).is_err());
+        assert!(Ship::from_account_data(&account_data[8..]).is_err());
+    }
+}
@@ -8,6 +8,30 @@ pub enum FleetState {
     MoveWarp(MoveWarp),
     MoveSubwarp(MoveSubwarp),
     Respawn(Respawn),
+    /// A state the SDK doesn't know (a newer SAGE), the data after its discriminator.
+    Unknown {
+        discriminator: u8,
+        raw: Vec<u8>,
+    },
+}
+
+impl FleetState {
+    /// The state's discriminator in the `Fleet` account.
+    pub fn discriminator(&self) -> u8 {
+        match self {
+            FleetState::StarbaseLoadingBay(_) => 0,
+            FleetState::Idle(_) => 1,
+            FleetState::MineAsteroid(_) => 2,
+            FleetState::MoveWarp(_) => 3,
+            FleetState::MoveSubwarp(_) => 4,
+            FleetState::Respawn(_) => 5,
+            FleetState::Unknown { discriminator, .. } => *discriminator,
+        }
+    }
+
+    pub fn is_unknown(&self) -> bool {
+        matches!(self, FleetState::Unknown { .. })
+    }
 }
 
 #[derive(Debug, Clone, BorshSerialize, BorshDeserialize, PartialEq)]
@@ -36,10 +36,25 @@ pub struct GameState {
     pub version: u8,
     pub update_id: u64,
     pub game_id: Pubkey,
-    // pub fleet: types::FleetInfo,
-    // pub levers: types::Levers,
-    // pub misc: types::MiscVariables,
-    // pub bump: u8,
+    pub fleet: types::FleetInfo,
+    pub misc: types::MiscVariables,
+    pub bump: u8,
+}
+
+/// The scale of the misc variables' percentages.
+const PERCENTAGE: f32 = 100.0;
+
+impl GameState {
+    /// The multiplier of the warp fuel by a warp lane (its fuel cost reduction).
+    pub fn warp_lane_fuel_multiplier(&self) -> f32 {
+        (1.0 - self.misc.warp_lane_fuel_cost_reduction as f32 / PERCENTAGE).max(0.0)
+    }
+
+    /// The multiplier of the asteroid mining emissions while a starbase's ammo upkeep coffer is
+    /// empty (its emissions penalty).
+    pub fn upkeep_mining_emissions_multiplier(&self) -> f32 {
+        (1.0 - self.misc.upkeep_mining_emissions_penalty as f32 / PERCENTAGE).max(0.0)
+    }
 }
 
 impl From<state::GameState> for GameState {
@@ -48,10 +63,9 @@ impl From<state::GameState> for GameState {
             version: g.version,
             update_id: g.update_id,
             game_id: g.game_id,
-            // fleet: g.fleet.into(),
-            // levers: g.levers.into(),
-            // misc: g.misc.into(),
-            // bump: g.bump,
+            fleet: g.fleet.into(),
+            misc: g.misc.into(),
+            bump: g.bump,
         }
     }
 }
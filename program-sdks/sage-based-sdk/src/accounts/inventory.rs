@@ -0,0 +1,415 @@
+use super::*;
+
+use anchor_client::solana_sdk::account::Account;
+use spl_associated_token_account::get_associated_token_address;
+use spl_token::solana_program::program_pack::Pack;
+
+use std::collections::HashMap;
+use std::str::FromStr;
+
+/// The cargo space of one unit of each mint (the cargo types of the game's stats definition).
+#[derive(Debug, Clone, Default)]
+pub struct CargoWeights {
+    pub weights: HashMap<Pubkey, u64>, // (Mint, Cargo Space)
+    pub default_weight: u64,           // the stats definition's default cargo type
+}
+
+impl CargoWeights {
+    pub fn new(cargo_types: &[CargoTypeStats], default_weight: u64) -> Self {
+        CargoWeights {
+            weights: cargo_types
+                .iter()
+                .map(|cargo_type| (cargo_type.mint, cargo_type.cargo_space()))
+                .collect(),
+            default_weight,
+        }
+    }
+
+    /// The cargo space of one unit, a mint without a cargo type takes the space of the default
+    /// cargo type (as the cargo program does).
+    pub fn weight(&self, mint: &Pubkey) -> u64 {
+        self.weights
+            .get(mint)
+            .copied()
+            .unwrap_or(self.default_weight)
+    }
+
+    /// The cargo space of the amounts of mints.
+    pub fn space<'a>(&self, amounts: impl IntoIterator<Item = (&'a Pubkey, u64)>) -> u64 {
+        amounts
+            .into_iter()
+            .map(|(mint, amount)| amount.saturating_mul(self.weight(mint)))
+            .sum()
+    }
+
+    /// How many units of a mint fit in the free cargo space.
+    pub fn units_fit(&self, mint: &Pubkey, free_space: u64) -> u64 {
+        free_space / self.weight(mint).max(1)
+    }
+}
+
+/// The token amounts (by mint) of a cargo pod and its capacity.
+#[derive(Debug, Clone, Default)]
+pub struct PodInventory {
+    pub cargo_pod: Pubkey,
+    pub capacity: u64,
+    pub amounts: Vec<(Pubkey, u64)>, // (Mint, Amount)
+}
+
+impl PodInventory {
+    /// The (associated) token accounts of the mints in a cargo pod.
+    pub fn token_accounts(cargo_pod: &Pubkey, mints: &[Pubkey]) -> Vec<Pubkey> {
+        mints
+            .iter()
+            .map(|mint| get_associated_token_address(cargo_pod, mint))
+            .collect()
+    }
+
+    /// The amounts of the token accounts of `token_accounts` (in the order of `mints`), a
+    /// missing account holds none of its mint.
+    pub fn decode_amounts(mints: &[Pubkey], accounts: &[Option<Account>]) -> Vec<(Pubkey, u64)> {
+        mints
+            .iter()
+            .zip(accounts)
+            .filter_map(|(mint, account)| {
+                let account = account.as_ref()?;
+                let token_account = spl_token::state::Account::unpack(&account.data).ok()?;
+                Some((*mint, token_account.amount))
+            })
+            .collect()
+    }
+
+    /// Decodes the token accounts of `token_accounts` (in the order of `mints`).
+    pub fn from_token_accounts(
+        cargo_pod: &Pubkey,
+        capacity: u64,
+        mints: &[Pubkey],
+        accounts: &[Option<Account>],
+    ) -> Self {
+        PodInventory {
+            cargo_pod: *cargo_pod,
+            capacity,
+            amounts: Self::decode_amounts(mints, accounts),
+        }
+    }
+
+    /// A pod of `(Mint, Amount)` strings (of `parsed_token_account_amounts`), an invalid mint is
+    /// skipped.
+    pub fn from_token_amounts(
+        cargo_pod: &Pubkey,
+        capacity: u64,
+        token_amounts: &[(String, u64)],
+    ) -> Self {
+        let amounts = token_amounts
+            .iter()
+            .filter_map(|(mint, amount)| Some((Pubkey::from_str(mint).ok()?, *amount)))
+            .collect();
+
+        PodInventory {
+            cargo_pod: *cargo_pod,
+            capacity,
+            amounts,
+        }
+    }
+
+    pub fn amount(&self, mint: &Pubkey) -> u64 {
+        self.amounts
+            .iter()
+            .find(|(m, _)| m == mint)
+            .map(|(_, amount)| *amount)
+            .unwrap_or(0)
+    }
+
+    pub fn used(&self) -> u64 {
+        self.amounts.iter().map(|(_, amount)| amount).sum()
+    }
+
+    pub fn free(&self) -> u64 {
+        self.capacity.saturating_sub(self.used())
+    }
+
+    /// The cargo space used (by the cargo types' weights).
+    pub fn used_space(&self, weights: &CargoWeights) -> u64 {
+        weights.space(self.amounts.iter().map(|(mint, amount)| (mint, *amount)))
+    }
+
+    pub fn free_space(&self, weights: &CargoWeights) -> u64 {
+        self.capacity.saturating_sub(self.used_space(weights))
+    }
+
+    /// How many more units of a mint fit in the pod.
+    pub fn units_fit(&self, weights: &CargoWeights, mint: &Pubkey) -> u64 {
+        weights.units_fit(mint, self.free_space(weights))
+    }
+
+    /// The amounts as `(Mint, Amount)` strings (of `parsed_token_account_amounts`).
+    pub fn token_amounts(&self) -> Vec<(String, u64)> {
+        self.amounts
+            .iter()
+            .map(|(mint, amount)| (mint.to_string(), *amount))
+            .collect()
+    }
+}
+
+/// A snapshot of a fleet's cargo pods.
+#[derive(Debug, Clone, Default)]
+pub struct FleetInventory {
+    pub cargo_hold: PodInventory,
+    pub fuel_tank: PodInventory,
+    pub ammo_bank: PodInventory,
+}
+
+impl FleetInventory {
+    /// The mints of the cargo hold, the game's consumables and the resources (or any other mints).
+    pub fn cargo_hold_mints(game_mints: &types::Mints, resource_mints: &[Pubkey]) -> Vec<Pubkey> {
+        let mut mints = vec![
+            game_mints.food,
+            game_mints.fuel,
+            game_mints.ammo,
+            game_mints.repair_kit,
+        ];
+
+        for mint in resource_mints {
+            if !mints.contains(mint) {
+                mints.push(*mint);
+            }
+        }
+
+        mints
+    }
+
+    /// The token accounts of the fleet's cargo pods (cargo hold, fuel tank, then ammo bank).
+    pub fn token_accounts(
+        fleet: &Fleet,
+        game_mints: &types::Mints,
+        resource_mints: &[Pubkey],
+    ) -> Vec<Pubkey> {
+        let cargo_hold_mints = Self::cargo_hold_mints(game_mints, resource_mints);
+
+        let mut token_accounts = PodInventory::token_accounts(&fleet.cargo_hold, &cargo_hold_mints);
+        token_accounts.extend(PodInventory::token_accounts(
+            &fleet.fuel_tank,
+            &[game_mints.fuel],
+        ));
+        token_accounts.extend(PodInventory::token_accounts(
+            &fleet.ammo_bank,
+            &[game_mints.ammo],
+        ));
+
+        token_accounts
+    }
+
+    /// Decodes the accounts of `token_accounts` (same fleet, mints and order).
+    pub fn from_token_accounts(
+        fleet: &Fleet,
+        game_mints: &types::Mints,
+        resource_mints: &[Pubkey],
+        accounts: &[Option<Account>],
+    ) -> Self {
+        let cargo_hold_mints = Self::cargo_hold_mints(game_mints, resource_mints);
+        let cargo_stats = &fleet.stats.cargo_stats;
+
+        let (cargo_hold_accounts, accounts) = accounts.split_at(cargo_hold_mints.len());
+        let (fuel_tank_accounts, ammo_bank_accounts) = accounts.split_at(1);
+
+        FleetInventory {
+            cargo_hold: PodInventory::from_token_accounts(
+                &fleet.cargo_hold,
+                cargo_stats.cargo_capacity as u64,
+                &cargo_hold_mints,
+                cargo_hold_accounts,
+            ),
+            fuel_tank: PodInventory::from_token_accounts(
+                &fleet.fuel_tank,
+                cargo_stats.fuel_capacity as u64,
+                &[game_mints.fuel],
+                fuel_tank_accounts,
+            ),
+            ammo_bank: PodInventory::from_token_accounts(
+                &fleet.ammo_bank,
+                cargo_stats.ammo_capacity as u64,
+                &[game_mints.ammo],
+                ammo_bank_accounts,
+            ),
+        }
+    }
+}
+
+// cargo test -p staratlas-sage-based-sdk --features anchor-client/async
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::fixtures::zeroed;
+    use spl_token::state::{Account as TokenAccount, AccountState};
+
+    fn mints() -> types::Mints {
+        types::Mints {
+            atlas: Pubkey::new_unique(),
+            polis: Pubkey::new_unique(),
+            ammo: Pubkey::new_unique(),
+            food: Pubkey::new_unique(),
+            fuel: Pubkey::new_unique(),
+            repair_kit: Pubkey::new_unique(),
+        }
+    }
+
+    /// A token account (of the token program) holding an amount of a mint.
+    fn token_account(mint: &Pubkey, amount: u64) -> Option<Account> {
+        let mut data = vec![0u8; TokenAccount::LEN];
+        let token_account = TokenAccount {
+            mint: *mint,
+            amount,
+            state: AccountState::Initialized,
+            ..Default::default()
+        };
+        TokenAccount::pack(token_account, &mut data).unwrap();
+
+        Some(Account {
+            data,
+            owner: spl_token::id(),
+            ..Default::default()
+        })
+    }
+
+    /// A fleet (zeroed) with its cargo pods and capacities.
+    fn fleet(cargo_capacity: u32, fuel_capacity: u32, ammo_capacity: u32) -> Fleet {
+        let mut fleet: Fleet = zeroed();
+        fleet.cargo_hold = Pubkey::new_unique();
+        fleet.fuel_tank = Pubkey::new_unique();
+        fleet.ammo_bank = Pubkey::new_unique();
+        fleet.stats.cargo_stats.cargo_capacity = cargo_capacity;
+        fleet.stats.cargo_stats.fuel_capacity = fuel_capacity;
+        fleet.stats.cargo_stats.ammo_capacity = ammo_capacity;
+        fleet
+    }
+
+    #[test]
+    fn cargo_hold_mints_are_unique() {
+        let mints = mints();
+        let ore = Pubkey::new_unique();
+
+        let cargo_hold_mints = FleetInventory::cargo_hold_mints(&mints, &[ore, mints.food, ore]);
+        assert_eq!(
+            cargo_hold_mints,
+            vec![mints.food, mints.fuel, mints.ammo, mints.repair_kit, ore]
+        );
+    }
+
+    #[test]
+    fn decodes_the_fleet_inventory() {
+        let mints = mints();
+        let ore = Pubkey::new_unique();
+        let fleet = fleet(1000, 500, 200);
+
+        let token_accounts = FleetInventory::token_accounts(&fleet, &mints, &[ore]);
+        assert_eq!(token_accounts.len(), 7); // 5 cargo hold mints, fuel and ammo
+        assert_eq!(
+            token_accounts[4],
+            get_associated_token_address(&fleet.cargo_hold, &ore)
+        );
+        assert_eq!(
+            token_accounts[5],
+            get_associated_token_address(&fleet.fuel_tank, &mints.fuel)
+        );
+        assert_eq!(
+            token_accounts[6],
+            get_associated_token_address(&fleet.ammo_bank, &mints.ammo)
+        );
+
+        // no fuel, ammo or repair kits in the cargo hold (missing accounts)
+        let accounts = vec![
+            token_account(&mints.food, 100),
+            None,
+            None,
+            None,
+            token_account(&ore, 250),
+            token_account(&mints.fuel, 400),
+            token_account(&mints.ammo, 200),
+        ];
+        let inventory = FleetInventory::from_token_accounts(&fleet, &mints, &[ore], &accounts);
+
+        let cargo_hold = &inventory.cargo_hold;
+        assert_eq!(cargo_hold.cargo_pod, fleet.cargo_hold);
+        assert_eq!(cargo_hold.capacity, 1000);
+        assert_eq!(cargo_hold.amounts, vec![(mints.food, 100), (ore, 250)]);
+        assert_eq!(cargo_hold.amount(&mints.fuel), 0);
+        assert_eq!(cargo_hold.used(), 350);
+        assert_eq!(cargo_hold.free(), 650);
+
+        assert_eq!(inventory.fuel_tank.capacity, 500);
+        assert_eq!(inventory.fuel_tank.amount(&mints.fuel), 400);
+        assert_eq!(inventory.fuel_tank.free(), 100);
+        assert_eq!(inventory.ammo_bank.free(), 0);
+    }
+
+    #[test]
+    fn pod_space_by_cargo_weights() {
+        let (ore, food) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let weights = CargoWeights::new(&[cargo_type(&ore, 2), cargo_type(&food, 1)], 1);
+
+        let pod = PodInventory {
+            cargo_pod: Pubkey::new_unique(),
+            capacity: 100,
+            amounts: vec![(ore, 20), (food, 10)],
+        };
+
+        assert_eq!(pod.used(), 30);
+        assert_eq!(pod.used_space(&weights), 50);
+        assert_eq!(pod.free_space(&weights), 50);
+        assert_eq!(pod.units_fit(&weights, &ore), 25);
+        assert_eq!(pod.units_fit(&weights, &food), 50);
+
+        // a full pod fits nothing more
+        let full = PodInventory {
+            amounts: vec![(ore, 60)],
+            ..pod.clone()
+        };
+        assert_eq!(full.free_space(&weights), 0);
+        assert_eq!(full.units_fit(&weights, &food), 0);
+    }
+
+    fn cargo_type(mint: &Pubkey, cargo_space: u64) -> CargoTypeStats {
+        CargoTypeStats {
+            stats_definition: Pubkey::default(),
+            mint: *mint,
+            seq_id: 0,
+            stats: vec![cargo_space],
+        }
+    }
+
+    #[test]
+    fn cargo_weights_of_the_cargo_types() {
+        let (ore, fuel, unknown) = (
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+        );
+        let weights = CargoWeights::new(&[cargo_type(&ore, 3), cargo_type(&fuel, 1)], 2);
+
+        assert_eq!(weights.weight(&ore), 3);
+        assert_eq!(weights.weight(&fuel), 1);
+        assert_eq!(weights.weight(&unknown), 2); // the default cargo type
+        assert_eq!(weights.space([(&ore, 10), (&fuel, 5), (&unknown, 1)]), 37);
+
+        assert_eq!(weights.units_fit(&ore, 10), 3);
+        assert_eq!(weights.units_fit(&fuel, 10), 10);
+        assert_eq!(weights.units_fit(&unknown, 1), 0);
+
+        // a weightless mint fits by unit
+        let weightless = CargoWeights::new(&[cargo_type(&ore, 0)], 1);
+        assert_eq!(weightless.units_fit(&ore, 10), 10);
+    }
+
+    #[test]
+    fn pod_of_token_amounts() {
+        let ore = Pubkey::new_unique();
+        let cargo_pod = Pubkey::new_unique();
+
+        let token_amounts = vec![(ore.to_string(), 5), ("not a mint".to_string(), 7)];
+        let pod = PodInventory::from_token_amounts(&cargo_pod, 10, &token_amounts);
+
+        assert_eq!(pod.amounts, vec![(ore, 5)]);
+        assert_eq!(pod.token_amounts(), vec![(ore.to_string(), 5)]);
+    }
+}
@@ -0,0 +1,355 @@
+use super::*;
+use staratlas_player_profile::state::Profile;
+use staratlas_utils_query::PROFILE_KEYS;
+
+use std::collections::HashMap;
+
+/// The SAGE permissions of a hot key added by `player-profile-cli add-key` (all of them but
+/// renting fleets).
+pub const SAGE_HOT_KEY_PERMISSIONS: [u8; 8] =
+    [0b1101_1111, 0b1101_1101, 0b1111_1111, 0, 0, 0, 0, 0];
+
+/// The permission bits a scoped key needs to sign each of the SDK's SAGE instructions (a single
+/// bit of `SAGE_HOT_KEY_PERMISSIONS` each).
+pub mod sage_permissions {
+    const fn permission(byte: usize, bit: u8) -> [u8; 8] {
+        let mut permissions = [0u8; 8];
+        permissions[byte] = 1 << bit;
+        permissions
+    }
+
+    /// Dock to and undock from a starbase (its loading bay).
+    pub const DOCK_UNDOCK: [u8; 8] = permission(1, 0);
+    pub const DEPOSIT_CARGO_TO_FLEET: [u8; 8] = permission(1, 2);
+    pub const WITHDRAW_CARGO_FROM_FLEET: [u8; 8] = permission(1, 3);
+    pub const DEPOSIT_CARGO_TO_GAME: [u8; 8] = permission(1, 4);
+    pub const START_MINING_ASTEROID: [u8; 8] = permission(1, 6);
+    pub const STOP_MINING_ASTEROID: [u8; 8] = permission(1, 7);
+    pub const WARP: [u8; 8] = permission(2, 0);
+}
+
+/// Seconds a looked up key index is used before the profile is read again (a removed key).
+const KEY_INDEX_TTL: i64 = 600;
+
+#[derive(Debug, Clone, Copy, BorshSerialize, BorshDeserialize)]
+pub struct ProfileKey {
+    pub key: Pubkey,
+    pub scope: Pubkey,
+    pub expire_time: i64, // "< 0" does not expire
+    pub permissions: [u8; 8],
+}
+
+impl ProfileKey {
+    /// The key expired at `now` (unix timestamp).
+    pub fn is_expired(&self, now: i64) -> bool {
+        self.expire_time >= 0 && self.expire_time <= now
+    }
+
+    /// The key has all the `permissions` bits.
+    pub fn has_permissions(&self, permissions: &[u8; 8]) -> bool {
+        self.permissions
+            .iter()
+            .zip(permissions)
+            .all(|(has, required)| has & required == *required)
+    }
+}
+
+/// Why a key can't sign for a profile.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProfileKeyError {
+    NotAKey,
+    Expired,
+    MissingPermissions,
+}
+
+impl std::fmt::Display for ProfileKeyError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ProfileKeyError::NotAKey => write!(f, "not a key of the profile"),
+            ProfileKeyError::Expired => write!(f, "the profile key expired"),
+            ProfileKeyError::MissingPermissions => {
+                write!(f, "the profile key misses SAGE permissions")
+            }
+        }
+    }
+}
+
+/// The keys of a player profile, the first `auth_key_count` keys are the profile's auth keys.
+#[derive(Debug, Clone)]
+pub struct ProfileKeys {
+    pub auth_key_count: u16,
+    pub keys: Vec<ProfileKey>,
+}
+
+impl ProfileKeys {
+    /// The index of a key in the profile's key list, an auth key or a key scoped to `scope` with
+    /// the `permissions`, unexpired at `now` (unix timestamp).
+    pub fn key_index(
+        &self,
+        key: &Pubkey,
+        scope: &Pubkey,
+        permissions: &[u8; 8],
+        now: i64,
+    ) -> Result<u16, ProfileKeyError> {
+        let mut err = ProfileKeyError::NotAKey;
+
+        for (index, profile_key) in self.keys.iter().enumerate() {
+            let is_auth_key = index < self.auth_key_count as usize;
+            if &profile_key.key != key || !(is_auth_key || &profile_key.scope == scope) {
+                continue;
+            }
+
+            // the next key of the list may still sign
+            if profile_key.is_expired(now) {
+                err = ProfileKeyError::Expired;
+            } else if !is_auth_key && !profile_key.has_permissions(permissions) {
+                err = ProfileKeyError::MissingPermissions;
+            } else {
+                return Ok(index as u16);
+            }
+        }
+
+        Err(err)
+    }
+}
+
+type KeyIndexKey = (Pubkey, Pubkey, [u8; 8]); // (Key, Player Profile, Permissions)
+
+/// The key indexes of the payers (by payer, profile and permissions), each used until its key
+/// expires or for `KEY_INDEX_TTL` seconds.
+#[derive(Debug, Default)]
+pub struct KeyIndexes {
+    entries: HashMap<KeyIndexKey, (u16, i64, i64)>, // (Key Index, Expire Time, Looked Up At)
+}
+
+impl KeyIndexes {
+    pub fn get(
+        &self,
+        key: &Pubkey,
+        player_profile: &Pubkey,
+        permissions: &[u8; 8],
+        now: i64,
+    ) -> Option<u16> {
+        let (key_index, expire_time, looked_up_at) =
+            self.entries.get(&(*key, *player_profile, *permissions))?;
+
+        let expired = *expire_time >= 0 && *expire_time <= now;
+        (!expired && now - looked_up_at < KEY_INDEX_TTL).then_some(*key_index)
+    }
+
+    pub fn insert(
+        &mut self,
+        key: &Pubkey,
+        player_profile: &Pubkey,
+        permissions: &[u8; 8],
+        key_index: u16,
+        expire_time: i64,
+        now: i64,
+    ) {
+        self.entries.insert(
+            (*key, *player_profile, *permissions),
+            (key_index, expire_time, now),
+        );
+    }
+}
+
+impl borsh::de::BorshDeserialize for ProfileKeys {
+    fn deserialize_reader<R: Read>(reader: &mut R) -> borsh::io::Result<Self> {
+        let mut account_data = vec![];
+        reader.read_to_end(&mut account_data)?;
+
+        let auth_key_count = read_field(Profile::FIELDS.auth_key_count, &account_data)?;
+
+        let mut keys = vec![];
+        let elements = PROFILE_KEYS
+            .elements(&account_data)
+            .ok_or(borsh::io::ErrorKind::UnexpectedEof)?;
+        for mut data in elements {
+            keys.push(ProfileKey::deserialize(&mut data)?);
+        }
+
+        Ok(ProfileKeys {
+            auth_key_count,
+            keys,
+        })
+    }
+}
+
+// cargo test -p staratlas-sage-based-sdk --features anchor-client/async
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NOW: i64 = 1_700_000_000;
+
+    fn profile_key(
+        key: Pubkey,
+        scope: Pubkey,
+        expire_time: i64,
+        permissions: [u8; 8],
+    ) -> ProfileKey {
+        ProfileKey {
+            key,
+            scope,
+            expire_time,
+            permissions,
+        }
+    }
+
+    /// A profile account: the header (its auth key count) followed by the keys.
+    fn profile_account(auth_key_count: u16, keys: &[ProfileKey]) -> Vec<u8> {
+        let mut account_data = vec![0u8; PROFILE_KEYS.offset];
+        let offset = Profile::FIELDS.auth_key_count.offset;
+        account_data[offset..offset + 2].copy_from_slice(&auth_key_count.to_le_bytes());
+        for key in keys {
+            account_data.extend(borsh::to_vec(key).unwrap());
+        }
+        account_data
+    }
+
+    #[test]
+    fn finds_the_key_index() {
+        let (owner, hot_key, sage) = (
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+        );
+        let other_scope = Pubkey::new_unique();
+
+        let account_data = profile_account(
+            1,
+            &[
+                profile_key(owner, other_scope, -1, [0xff; 8]),
+                profile_key(hot_key, other_scope, -1, SAGE_HOT_KEY_PERMISSIONS),
+                profile_key(hot_key, sage, NOW + 60, SAGE_HOT_KEY_PERMISSIONS),
+            ],
+        );
+        let profile_keys = ProfileKeys::deserialize(&mut account_data.as_slice()).unwrap();
+        assert_eq!(profile_keys.auth_key_count, 1);
+        assert_eq!(profile_keys.keys.len(), 3);
+        assert!(ProfileKeys::deserialize(&mut &account_data[..PROFILE_KEYS.offset - 1]).is_err());
+
+        let permissions = &SAGE_HOT_KEY_PERMISSIONS;
+        // an auth key signs whatever its scope, a hot key for its scope
+        assert_eq!(
+            profile_keys.key_index(&owner, &sage, permissions, NOW),
+            Ok(0)
+        );
+        assert_eq!(
+            profile_keys.key_index(&hot_key, &sage, permissions, NOW),
+            Ok(2)
+        );
+        assert_eq!(
+            profile_keys.key_index(&Pubkey::new_unique(), &sage, permissions, NOW),
+            Err(ProfileKeyError::NotAKey)
+        );
+        assert_eq!(
+            profile_keys.key_index(&hot_key, &sage, permissions, NOW + 60),
+            Err(ProfileKeyError::Expired)
+        );
+    }
+
+    #[test]
+    fn refuses_a_key_missing_permissions() {
+        let (hot_key, sage) = (Pubkey::new_unique(), Pubkey::new_unique());
+
+        let mut permissions = SAGE_HOT_KEY_PERMISSIONS;
+        permissions[2] = 0;
+        let profile_keys = ProfileKeys {
+            auth_key_count: 1,
+            keys: vec![
+                profile_key(Pubkey::new_unique(), sage, -1, [0xff; 8]),
+                profile_key(hot_key, sage, -1, permissions),
+            ],
+        };
+
+        assert!(!profile_keys.keys[1].has_permissions(&SAGE_HOT_KEY_PERMISSIONS));
+        assert_eq!(
+            profile_keys.key_index(&hot_key, &sage, &SAGE_HOT_KEY_PERMISSIONS, NOW),
+            Err(ProfileKeyError::MissingPermissions)
+        );
+        assert_eq!(
+            profile_keys.key_index(&hot_key, &sage, &permissions, NOW),
+            Ok(1)
+        );
+    }
+
+    #[test]
+    fn a_key_signs_the_instructions_of_its_permissions() {
+        let (hot_key, sage) = (Pubkey::new_unique(), Pubkey::new_unique());
+
+        let mut permissions = sage_permissions::START_MINING_ASTEROID;
+        permissions[1] |= sage_permissions::STOP_MINING_ASTEROID[1];
+        let profile_keys = ProfileKeys {
+            auth_key_count: 1,
+            keys: vec![
+                profile_key(Pubkey::new_unique(), sage, -1, [0; 8]),
+                profile_key(hot_key, sage, -1, permissions),
+            ],
+        };
+
+        for permissions in [
+            sage_permissions::START_MINING_ASTEROID,
+            sage_permissions::STOP_MINING_ASTEROID,
+        ] {
+            assert_eq!(
+                profile_keys.key_index(&hot_key, &sage, &permissions, NOW),
+                Ok(1)
+            );
+        }
+        assert_eq!(
+            profile_keys.key_index(&hot_key, &sage, &sage_permissions::WARP, NOW),
+            Err(ProfileKeyError::MissingPermissions)
+        );
+    }
+
+    #[test]
+    fn a_hot_key_signs_every_instruction() {
+        for permissions in [
+            sage_permissions::DOCK_UNDOCK,
+            sage_permissions::DEPOSIT_CARGO_TO_FLEET,
+            sage_permissions::WITHDRAW_CARGO_FROM_FLEET,
+            sage_permissions::DEPOSIT_CARGO_TO_GAME,
+            sage_permissions::START_MINING_ASTEROID,
+            sage_permissions::STOP_MINING_ASTEROID,
+            sage_permissions::WARP,
+        ] {
+            let hot_key = profile_key(
+                Pubkey::new_unique(),
+                Pubkey::new_unique(),
+                -1,
+                SAGE_HOT_KEY_PERMISSIONS,
+            );
+            assert!(hot_key.has_permissions(&permissions));
+        }
+    }
+
+    #[test]
+    fn key_indexes_are_looked_up_again() {
+        let (key, profile) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let warp = &sage_permissions::WARP;
+        let mut key_indexes = KeyIndexes::default();
+        assert_eq!(key_indexes.get(&key, &profile, warp, NOW), None);
+
+        key_indexes.insert(&key, &profile, warp, 2, -1, NOW);
+        assert_eq!(key_indexes.get(&key, &profile, warp, NOW + 1), Some(2));
+        assert_eq!(
+            key_indexes.get(&key, &Pubkey::new_unique(), warp, NOW + 1),
+            None
+        );
+        assert_eq!(
+            key_indexes.get(&key, &profile, warp, NOW + KEY_INDEX_TTL),
+            None
+        );
+        // another instruction's permissions
+        assert_eq!(
+            key_indexes.get(&key, &profile, &sage_permissions::DOCK_UNDOCK, NOW + 1),
+            None
+        );
+
+        // an expiring key
+        key_indexes.insert(&key, &profile, warp, 3, NOW + 10, NOW);
+        assert_eq!(key_indexes.get(&key, &profile, warp, NOW + 9), Some(3));
+        assert_eq!(key_indexes.get(&key, &profile, warp, NOW + 10), None);
+    }
+}
@@ -0,0 +1,96 @@
+use super::*;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SizeClass {
+    XxSmall,
+    XSmall,
+    Small,
+    Medium,
+    Large,
+    Capital,
+    Commander,
+    Titan,
+}
+
+impl SizeClass {
+    /// The size class of `Ship.size_class` (1 `XxSmall` to 8 `Titan`).
+    pub fn from_u8(size_class: u8) -> Option<Self> {
+        match size_class {
+            1 => Some(SizeClass::XxSmall),
+            2 => Some(SizeClass::XSmall),
+            3 => Some(SizeClass::Small),
+            4 => Some(SizeClass::Medium),
+            5 => Some(SizeClass::Large),
+            6 => Some(SizeClass::Capital),
+            7 => Some(SizeClass::Commander),
+            8 => Some(SizeClass::Titan),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, BorshSerialize, BorshDeserialize)]
+pub struct Ship {
+    pub version: u8,
+    pub game_id: Pubkey,
+    pub mint: Pubkey,
+    pub name: [u8; 64],
+    pub size_class: u8,
+    pub stats: types::ShipStats,
+    pub update_id: u64,
+    pub max_update_id: u64,
+    pub next: Pubkey, // the system program when none
+}
+
+impl Ship {
+    pub fn name(&self) -> &str {
+        let name = std::str::from_utf8(&self.name).unwrap();
+        let name_trimmed = name.trim_end_matches(char::from(0));
+        name_trimmed
+    }
+
+    pub fn size_class(&self) -> Option<SizeClass> {
+        SizeClass::from_u8(self.size_class)
+    }
+
+    /// Decodes a `Ship` account's data (its discriminator included) at the IDL's offsets.
+    pub fn from_account_data(account_data: &[u8]) -> borsh::io::Result<Self> {
+        if !account_data.starts_with(&state::Ship::DISCRIMINATOR) {
+            return Err(borsh::io::Error::new(
+                borsh::io::ErrorKind::InvalidData,
+                "not a Ship account",
+            ));
+        }
+
+        let fields = state::Ship::FIELDS;
+        let stats = read_field(fields.stats, account_data)?;
+
+        Ok(Ship {
+            version: read_field(fields.version, account_data)?,
+            game_id: read_field(fields.game_id, account_data)?,
+            mint: read_field(fields.mint, account_data)?,
+            name: read_field(fields.name, account_data)?,
+            size_class: read_field(fields.size_class, account_data)?,
+            stats: types::ShipStats::deserialize(&mut stats.as_slice())?,
+            update_id: read_field(fields.update_id, account_data)?,
+            max_update_id: read_field(fields.max_update_id, account_data)?,
+            next: read_field(fields.next_key, account_data)?,
+        })
+    }
+}
+
+impl From<state::Ship> for Ship {
+    fn from(s: state::Ship) -> Self {
+        Ship {
+            version: s.version,
+            game_id: s.game_id,
+            mint: s.mint,
+            name: s.name,
+            size_class: s.size_class,
+            stats: s.stats.into(),
+            update_id: s.update_id,
+            max_update_id: s.max_update_id,
+            next: s.next.key,
+        }
+    }
+}
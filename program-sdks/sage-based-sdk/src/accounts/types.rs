@@ -53,6 +53,40 @@ impl From<typedefs::Crafting> for Crafting {
     }
 }
 
+#[derive(Debug, Clone, Copy, BorshSerialize, BorshDeserialize)]
+pub struct FactionsStarbaseLevelInfo {
+    pub mud: [StarbaseLevelInfo; 7],
+    pub oni: [StarbaseLevelInfo; 7],
+    pub ustur: [StarbaseLevelInfo; 7],
+}
+
+impl From<typedefs::FactionsStarbaseLevelInfo> for FactionsStarbaseLevelInfo {
+    fn from(f: typedefs::FactionsStarbaseLevelInfo) -> Self {
+        FactionsStarbaseLevelInfo {
+            mud: f.mud.map(Into::into),
+            oni: f.oni.map(Into::into),
+            ustur: f.ustur.map(Into::into),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, BorshSerialize, BorshDeserialize)]
+pub struct FleetInfo {
+    pub starbase_levels: FactionsStarbaseLevelInfo,
+    pub upkeep: StarbaseUpkeepLevels,
+    pub max_fleet_size: u32,
+}
+
+impl From<typedefs::FleetInfo> for FleetInfo {
+    fn from(f: typedefs::FleetInfo) -> Self {
+        FleetInfo {
+            starbase_levels: f.starbase_levels.into(),
+            upkeep: f.upkeep.into(),
+            max_fleet_size: f.max_fleet_size,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy, BorshSerialize, BorshDeserialize)]
 pub struct Mints {
     pub atlas: Pubkey,
@@ -103,6 +137,23 @@ impl From<typedefs::MiscStats> for MiscStats {
     }
 }
 
+#[derive(Debug, Clone, Copy, BorshSerialize, BorshDeserialize)]
+pub struct MiscVariables {
+    pub warp_lane_fuel_cost_reduction: i16,   // percentage
+    pub respawn_fee: u64,                     // ATLAS (smallest unit)
+    pub upkeep_mining_emissions_penalty: i16, // percentage
+}
+
+impl From<typedefs::MiscVariables> for MiscVariables {
+    fn from(m: typedefs::MiscVariables) -> Self {
+        MiscVariables {
+            warp_lane_fuel_cost_reduction: m.warp_lane_fuel_cost_reduction,
+            respawn_fee: m.respawn_fee,
+            upkeep_mining_emissions_penalty: m.upkeep_mining_emissions_penalty,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy, BorshSerialize, BorshDeserialize)]
 pub struct MovementStats {
     pub subwarp_speed: u32,
@@ -252,6 +303,92 @@ impl From<typedefs::ShipStats> for ShipStats {
     }
 }
 
+#[derive(Debug, Clone, Copy, BorshSerialize, BorshDeserialize)]
+pub struct StarbaseLevelInfo {
+    pub recipe_for_upgrade: Pubkey,
+    pub recipe_category_for_level: Pubkey,
+    pub hp: u64,
+    pub sp: u64,
+    pub sector_ring_available: u8,
+    pub warp_lane_movement_fee: u64, // ATLAS (smallest unit)
+}
+
+impl From<typedefs::StarbaseLevelInfo> for StarbaseLevelInfo {
+    fn from(s: typedefs::StarbaseLevelInfo) -> Self {
+        StarbaseLevelInfo {
+            recipe_for_upgrade: s.recipe_for_upgrade,
+            recipe_category_for_level: s.recipe_category_for_level,
+            hp: s.hp,
+            sp: s.sp,
+            sector_ring_available: s.sector_ring_available,
+            warp_lane_movement_fee: s.warp_lane_movement_fee,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, BorshSerialize, BorshDeserialize)]
+pub struct StarbaseUpkeepInfo {
+    pub ammo_reserve: u64,
+    pub ammo_depletion_rate: u32,
+    pub food_reserve: u64,
+    pub food_depletion_rate: u32,
+    pub toolkit_reserve: u64,
+    pub toolkit_depletion_rate: u32,
+}
+
+impl From<typedefs::StarbaseUpkeepInfo> for StarbaseUpkeepInfo {
+    fn from(s: typedefs::StarbaseUpkeepInfo) -> Self {
+        StarbaseUpkeepInfo {
+            ammo_reserve: s.ammo_reserve,
+            ammo_depletion_rate: s.ammo_depletion_rate,
+            food_reserve: s.food_reserve,
+            food_depletion_rate: s.food_depletion_rate,
+            toolkit_reserve: s.toolkit_reserve,
+            toolkit_depletion_rate: s.toolkit_depletion_rate,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, BorshSerialize, BorshDeserialize)]
+pub struct StarbaseUpkeepLevels {
+    pub level0: StarbaseUpkeepInfo,
+    pub level1: StarbaseUpkeepInfo,
+    pub level2: StarbaseUpkeepInfo,
+    pub level3: StarbaseUpkeepInfo,
+    pub level4: StarbaseUpkeepInfo,
+    pub level5: StarbaseUpkeepInfo,
+    pub level6: StarbaseUpkeepInfo,
+}
+
+impl StarbaseUpkeepLevels {
+    /// The upkeep of the starbase levels, level 0 first.
+    pub fn levels(&self) -> [StarbaseUpkeepInfo; 7] {
+        [
+            self.level0,
+            self.level1,
+            self.level2,
+            self.level3,
+            self.level4,
+            self.level5,
+            self.level6,
+        ]
+    }
+}
+
+impl From<typedefs::StarbaseUpkeepLevels> for StarbaseUpkeepLevels {
+    fn from(s: typedefs::StarbaseUpkeepLevels) -> Self {
+        StarbaseUpkeepLevels {
+            level0: s.level0.into(),
+            level1: s.level1.into(),
+            level2: s.level2.into(),
+            level3: s.level3.into(),
+            level4: s.level4.into(),
+            level5: s.level5.into(),
+            level6: s.level6.into(),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy, BorshSerialize, BorshDeserialize)]
 pub struct Vaults {
     pub atlas: Pubkey,
@@ -1,11 +1,18 @@
 use crate::accounts::types;
 use crate::accounts::*;
 
+// the fixed point scales of the program's stats, the game state's multipliers are read from
+// `GameState` (see its `misc` multipliers)
 const GLOBAL_SCALE_DECIMALS_4: f32 = 10_000.0;
+const MOVEMENT_FUEL_DECIMALS: f32 = 100.0;
+const MOVEMENT_DISTANCE_DECIMALS: f32 = 100.0;
+const MOVEMENT_SPEED_DECIMALS: f32 = 1_000_000.0;
 
 const RESOURCE_HARDNESS_DECIMALS: f32 = 100.0;
 const SYSTEM_RICHNESS_DECIMALS: f32 = 100.0;
 
+const ATLAS_DECIMALS: f64 = 100_000_000.0;
+
 pub fn asteroid_mining_resource_extraction_duration(
     fleet_stats: &types::ShipStats,
     mine_item: &MineItem,
@@ -29,22 +36,32 @@ pub fn asteroid_mining_resource_extraction_duration_bare_bones(
     let emission_rate =
         asteroid_mining_emssion_rate_bare_bones(fleet_stats, resource_hardness, system_richness);
     if emission_rate > 0.0 {
-        return resource_amount as f32 / emission_rate;
+        resource_amount as f32 / emission_rate
+    } else {
+        0.0
     }
-
-    return 0.0;
 }
 
+/// The emission rate at an asteroid, penalized by the game state while the starbase's ammo upkeep
+/// coffer is empty.
 pub fn asteroid_mining_emission_rate(
     fleet_stats: &types::ShipStats,
+    game_state: &GameState,
     mine_item: &MineItem,
     resource: &Resource,
+    ammo_upkeep_empty: bool,
 ) -> f32 {
-    asteroid_mining_emssion_rate_bare_bones(
+    let emission_rate = asteroid_mining_emssion_rate_bare_bones(
         fleet_stats,
         mine_item.resource_hardness,
         resource.system_richness,
-    )
+    );
+
+    if ammo_upkeep_empty {
+        emission_rate * game_state.upkeep_mining_emissions_multiplier()
+    } else {
+        emission_rate
+    }
 }
 
 pub fn asteroid_mining_emssion_rate_bare_bones(
@@ -54,8 +71,682 @@ pub fn asteroid_mining_emssion_rate_bare_bones(
 ) -> f32 {
     let resource_hardness = resource_hardness as f32 / RESOURCE_HARDNESS_DECIMALS;
     let system_richness = system_richness as f32 / SYSTEM_RICHNESS_DECIMALS;
-    let base_rate = (fleet_stats.cargo_stats.mining_rate as f32 / GLOBAL_SCALE_DECIMALS_4)
-        * system_richness
-        / resource_hardness;
-    base_rate
+    (fleet_stats.cargo_stats.mining_rate as f32 / GLOBAL_SCALE_DECIMALS_4) * system_richness
+        / resource_hardness
+}
+
+pub fn asteroid_mining_ammo_consumption(fleet_stats: &types::ShipStats, duration: f32) -> f32 {
+    (fleet_stats.cargo_stats.ammo_consumption_rate as f32 / GLOBAL_SCALE_DECIMALS_4) * duration
+}
+
+pub fn asteroid_mining_food_consumption(fleet_stats: &types::ShipStats, duration: f32) -> f32 {
+    (fleet_stats.cargo_stats.food_consumption_rate as f32 / GLOBAL_SCALE_DECIMALS_4) * duration
+}
+
+/// The constraint that ends a mining cycle first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MiningConstraint {
+    CargoHold, // the cargo hold is full
+    Ammo,      // the ammo bank runs out
+    Food,      // the food in the cargo hold runs out
+}
+
+/// A mining cycle of a fleet at an asteroid.
+#[derive(Debug, Clone, Copy)]
+pub struct MiningPlan {
+    pub constraint: MiningConstraint,
+    pub emission_rate: f32,      // resource per second
+    pub max_duration: f32,       // seconds until the binding constraint
+    pub full_hold_duration: f32, // seconds to fill the cargo hold
+    pub ammo_for_full_hold: f32,
+    pub food_for_full_hold: f32,
+    pub output: f32, // resource mined within the max duration
+}
+
+impl MiningPlan {
+    /// The resource mined after `elapsed` seconds of the cycle.
+    pub fn mined(&self, elapsed: f32) -> f32 {
+        (elapsed.min(self.max_duration) * self.emission_rate).min(self.output)
+    }
+}
+
+/// Plans a mining cycle, `cargo_space` is the units of the resource that fit in the cargo hold
+/// and the ammo/food on hand are not a constraint when unknown (`None`).
+pub fn asteroid_mining_plan(
+    fleet_stats: &types::ShipStats,
+    mine_item: &MineItem,
+    resource: &Resource,
+    cargo_space: u32,
+    ammo_on_hand: Option<u64>,
+    food_on_hand: Option<u64>,
+) -> MiningPlan {
+    asteroid_mining_plan_bare_bones(
+        fleet_stats,
+        mine_item.resource_hardness,
+        resource.system_richness,
+        cargo_space,
+        ammo_on_hand,
+        food_on_hand,
+    )
+}
+
+pub fn asteroid_mining_plan_bare_bones(
+    fleet_stats: &types::ShipStats,
+    resource_hardness: u16,
+    system_richness: u16,
+    cargo_space: u32,
+    ammo_on_hand: Option<u64>,
+    food_on_hand: Option<u64>,
+) -> MiningPlan {
+    let emission_rate =
+        asteroid_mining_emssion_rate_bare_bones(fleet_stats, resource_hardness, system_richness);
+    let full_hold_duration = asteroid_mining_resource_extraction_duration_bare_bones(
+        fleet_stats,
+        resource_hardness,
+        system_richness,
+        cargo_space,
+    );
+
+    // seconds until a consumable runs out (without a consumption, never)
+    let consumable_duration = |on_hand: Option<u64>, per_second: f32| match on_hand {
+        Some(on_hand) if per_second > 0.0 => on_hand as f32 / per_second,
+        _ => f32::INFINITY,
+    };
+    let ammo_duration = consumable_duration(
+        ammo_on_hand,
+        asteroid_mining_ammo_consumption(fleet_stats, 1.0),
+    );
+    let food_duration = consumable_duration(
+        food_on_hand,
+        asteroid_mining_food_consumption(fleet_stats, 1.0),
+    );
+
+    let (constraint, max_duration) = [
+        (MiningConstraint::CargoHold, full_hold_duration),
+        (MiningConstraint::Ammo, ammo_duration),
+        (MiningConstraint::Food, food_duration),
+    ]
+    .into_iter()
+    .min_by(|(_, a), (_, b)| a.total_cmp(b))
+    .unwrap();
+
+    MiningPlan {
+        constraint,
+        emission_rate,
+        max_duration,
+        full_hold_duration,
+        ammo_for_full_hold: asteroid_mining_ammo_consumption(fleet_stats, full_hold_duration)
+            .ceil(),
+        food_for_full_hold: asteroid_mining_food_consumption(fleet_stats, full_hold_duration)
+            .ceil(),
+        output: (max_duration * emission_rate).min(cargo_space as f32),
+    }
+}
+
+pub fn sector_distance(from_sector: [i64; 2], to_sector: [i64; 2]) -> f32 {
+    let dx = (to_sector[0] - from_sector[0]) as f32;
+    let dy = (to_sector[1] - from_sector[1]) as f32;
+    (dx * dx + dy * dy).sqrt()
+}
+
+pub fn warp_fuel_consumption(
+    fleet_stats: &types::ShipStats,
+    from_sector: [i64; 2],
+    to_sector: [i64; 2],
+) -> f32 {
+    let distance = sector_distance(from_sector, to_sector);
+    (distance * fleet_stats.movement_stats.warp_fuel_consumption_rate as f32
+        / MOVEMENT_FUEL_DECIMALS)
+        .ceil()
+}
+
+/// The warp fuel reduced by the game state's warp lane fuel cost reduction.
+pub fn warp_lane_fuel_consumption(
+    fleet_stats: &types::ShipStats,
+    game_state: &GameState,
+    from_sector: [i64; 2],
+    to_sector: [i64; 2],
+) -> f32 {
+    (warp_fuel_consumption(fleet_stats, from_sector, to_sector)
+        * game_state.warp_lane_fuel_multiplier())
+    .ceil()
+}
+
+/// The ATLAS a warp lane costs from a starbase of the faction (`Starbase.faction`, 1 MUD, 2 ONI,
+/// 3 Ustur) and level, `None` for an unknown faction or level.
+pub fn warp_lane_fee(game_state: &GameState, faction: u8, level: u8) -> Option<f64> {
+    let starbase_levels = &game_state.fleet.starbase_levels;
+    let levels = match faction {
+        1 => &starbase_levels.mud,
+        2 => &starbase_levels.oni,
+        3 => &starbase_levels.ustur,
+        _ => return None,
+    };
+
+    levels
+        .get(level as usize)
+        .map(|level| level.warp_lane_movement_fee as f64 / ATLAS_DECIMALS)
+}
+
+/// The ATLAS a fleet pays to respawn.
+pub fn respawn_fee(game_state: &GameState) -> f64 {
+    game_state.misc.respawn_fee as f64 / ATLAS_DECIMALS
+}
+
+pub fn max_warp_distance(fleet_stats: &types::ShipStats) -> f32 {
+    fleet_stats.movement_stats.max_warp_distance as f32 / MOVEMENT_DISTANCE_DECIMALS
+}
+
+pub fn warp_duration(
+    fleet_stats: &types::ShipStats,
+    from_sector: [i64; 2],
+    to_sector: [i64; 2],
+) -> f32 {
+    let warp_speed = fleet_stats.movement_stats.warp_speed as f32 / MOVEMENT_SPEED_DECIMALS;
+    if warp_speed > 0.0 {
+        sector_distance(from_sector, to_sector) / warp_speed
+    } else {
+        0.0
+    }
+}
+
+pub fn subwarp_duration(
+    fleet_stats: &types::ShipStats,
+    from_sector: [i64; 2],
+    to_sector: [i64; 2],
+) -> f32 {
+    let subwarp_speed = fleet_stats.movement_stats.subwarp_speed as f32 / MOVEMENT_SPEED_DECIMALS;
+    if subwarp_speed > 0.0 {
+        sector_distance(from_sector, to_sector) / subwarp_speed
+    } else {
+        0.0
+    }
+}
+
+pub fn subwarp_fuel_consumption(
+    fleet_stats: &types::ShipStats,
+    from_sector: [i64; 2],
+    to_sector: [i64; 2],
+) -> f32 {
+    let distance = sector_distance(from_sector, to_sector);
+    (distance * fleet_stats.movement_stats.subwarp_fuel_consumption_rate as f32
+        / MOVEMENT_FUEL_DECIMALS)
+        .ceil()
+}
+
+/// The travel of a fleet between two sectors, by warp or by subwarp.
+#[derive(Debug, Clone, Copy)]
+pub struct Travel {
+    pub distance: f32,
+    pub warp_duration: f32, // seconds
+    pub warp_fuel: f32,
+    pub warp_cool_down: f32, // seconds, after a warp
+    pub subwarp_duration: f32,
+    pub subwarp_fuel: f32,
+    pub planet_exit_fuel: f32, // leaving an asteroid (stop mining)
+    pub max_warp_distance: f32,
+}
+
+impl Travel {
+    /// The sectors are within one warp of the fleet.
+    pub fn is_single_warp(&self) -> bool {
+        self.distance <= self.max_warp_distance
+    }
+}
+
+pub fn travel(
+    fleet_stats: &types::ShipStats,
+    from_sector: [i64; 2],
+    to_sector: [i64; 2],
+) -> Travel {
+    Travel {
+        distance: sector_distance(from_sector, to_sector),
+        warp_duration: warp_duration(fleet_stats, from_sector, to_sector),
+        warp_fuel: warp_fuel_consumption(fleet_stats, from_sector, to_sector),
+        warp_cool_down: fleet_stats.movement_stats.warp_cool_down as f32,
+        subwarp_duration: subwarp_duration(fleet_stats, from_sector, to_sector),
+        subwarp_fuel: subwarp_fuel_consumption(fleet_stats, from_sector, to_sector),
+        planet_exit_fuel: fleet_stats.movement_stats.planet_exit_fuel_amount as f32,
+        max_warp_distance: max_warp_distance(fleet_stats),
+    }
+}
+
+/// A count or stat narrowed to its field, saturated at the field's max (instead of wrapping).
+fn saturating_u16(value: u64) -> u16 {
+    u16::try_from(value).unwrap_or(u16::MAX)
+}
+
+fn saturating_u32(value: u64) -> u32 {
+    u32::try_from(value).unwrap_or(u32::MAX)
+}
+
+/// The ship counts of a fleet's ships, by size class.
+pub fn fleet_ship_counts(ships: &[FleetShip]) -> types::ShipCounts {
+    let count = |size_class: SizeClass| {
+        let count = ships
+            .iter()
+            .filter(|s| s.ship.size_class() == Some(size_class))
+            .map(|s| s.amount)
+            .fold(0, u64::saturating_add);
+        saturating_u16(count)
+    };
+    let total = saturating_u32(ships.iter().map(|s| s.amount).fold(0, u64::saturating_add));
+
+    types::ShipCounts {
+        total,
+        updated: total,
+        xx_small: count(SizeClass::XxSmall),
+        x_small: count(SizeClass::XSmall),
+        small: count(SizeClass::Small),
+        medium: count(SizeClass::Medium),
+        large: count(SizeClass::Large),
+        capital: count(SizeClass::Capital),
+        commander: count(SizeClass::Commander),
+        titan: count(SizeClass::Titan),
+    }
+}
+
+/// The stats of a fleet's ships combined: the capacities, rates and fuel amounts add up, the
+/// speeds and max warp distance are the slowest ship's and the cooldowns the longest.
+pub fn fleet_ship_stats(ships: &[FleetShip]) -> types::ShipStats {
+    let ships: Vec<&FleetShip> = ships.iter().filter(|s| s.amount > 0).collect();
+    let sum = |stat: fn(&types::ShipStats) -> u64| {
+        ships
+            .iter()
+            .map(|s| stat(&s.ship.stats).saturating_mul(s.amount))
+            .fold(0, u64::saturating_add)
+    };
+    let min = |stat: fn(&types::ShipStats) -> u64| {
+        ships.iter().map(|s| stat(&s.ship.stats)).min().unwrap_or(0)
+    };
+    let max = |stat: fn(&types::ShipStats) -> u64| {
+        ships.iter().map(|s| stat(&s.ship.stats)).max().unwrap_or(0)
+    };
+
+    types::ShipStats {
+        movement_stats: types::MovementStats {
+            subwarp_speed: saturating_u32(min(|s| s.movement_stats.subwarp_speed as u64)),
+            warp_speed: saturating_u32(min(|s| s.movement_stats.warp_speed as u64)),
+            max_warp_distance: saturating_u16(min(|s| s.movement_stats.max_warp_distance as u64)),
+            warp_cool_down: saturating_u16(max(|s| s.movement_stats.warp_cool_down as u64)),
+            subwarp_fuel_consumption_rate: saturating_u32(sum(|s| {
+                s.movement_stats.subwarp_fuel_consumption_rate as u64
+            })),
+            warp_fuel_consumption_rate: saturating_u32(sum(|s| {
+                s.movement_stats.warp_fuel_consumption_rate as u64
+            })),
+            planet_exit_fuel_amount: saturating_u32(sum(|s| {
+                s.movement_stats.planet_exit_fuel_amount as u64
+            })),
+        },
+        cargo_stats: types::CargoStats {
+            cargo_capacity: saturating_u32(sum(|s| s.cargo_stats.cargo_capacity as u64)),
+            fuel_capacity: saturating_u32(sum(|s| s.cargo_stats.fuel_capacity as u64)),
+            ammo_capacity: saturating_u32(sum(|s| s.cargo_stats.ammo_capacity as u64)),
+            ammo_consumption_rate: saturating_u32(sum(|s| {
+                s.cargo_stats.ammo_consumption_rate as u64
+            })),
+            food_consumption_rate: saturating_u32(sum(|s| {
+                s.cargo_stats.food_consumption_rate as u64
+            })),
+            mining_rate: saturating_u32(sum(|s| s.cargo_stats.mining_rate as u64)),
+            upgrade_rate: saturating_u32(sum(|s| s.cargo_stats.upgrade_rate as u64)),
+            cargo_transfer_rate: saturating_u32(sum(|s| s.cargo_stats.cargo_transfer_rate as u64)),
+            tractor_beam_gather_rate: saturating_u32(sum(|s| {
+                s.cargo_stats.tractor_beam_gather_rate as u64
+            })),
+        },
+        misc_stats: types::MiscStats {
+            crew: sum(|s| s.misc_stats.crew),
+            respawn_time: saturating_u16(max(|s| s.misc_stats.respawn_time as u64)),
+            scan_cool_down: saturating_u16(max(|s| s.misc_stats.scan_cool_down as u64)),
+            sdu_per_scan: saturating_u32(sum(|s| s.misc_stats.sdu_per_scan as u64)),
+            scan_cost: saturating_u32(sum(|s| s.misc_stats.scan_cost as u64)),
+            placeholder: saturating_u32(sum(|s| s.misc_stats.placeholder as u64)),
+            placeholder2: saturating_u32(sum(|s| s.misc_stats.placeholder2 as u64)),
+            placeholder3: saturating_u32(sum(|s| s.misc_stats.placeholder3 as u64)),
+        },
+    }
+}
+
+/// A stat of a fleet that differs from its ships' combined stat.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StatMismatch {
+    pub stat: &'static str,
+    pub ships: u64,
+    pub fleet: u64,
+}
+
+fn ship_stats_fields(stats: &types::ShipStats) -> [(&'static str, u64); 24] {
+    let (m, c, x) = (&stats.movement_stats, &stats.cargo_stats, &stats.misc_stats);
+    [
+        ("subwarp_speed", m.subwarp_speed as u64),
+        ("warp_speed", m.warp_speed as u64),
+        ("max_warp_distance", m.max_warp_distance as u64),
+        ("warp_cool_down", m.warp_cool_down as u64),
+        (
+            "subwarp_fuel_consumption_rate",
+            m.subwarp_fuel_consumption_rate as u64,
+        ),
+        (
+            "warp_fuel_consumption_rate",
+            m.warp_fuel_consumption_rate as u64,
+        ),
+        ("planet_exit_fuel_amount", m.planet_exit_fuel_amount as u64),
+        ("cargo_capacity", c.cargo_capacity as u64),
+        ("fuel_capacity", c.fuel_capacity as u64),
+        ("ammo_capacity", c.ammo_capacity as u64),
+        ("ammo_consumption_rate", c.ammo_consumption_rate as u64),
+        ("food_consumption_rate", c.food_consumption_rate as u64),
+        ("mining_rate", c.mining_rate as u64),
+        ("upgrade_rate", c.upgrade_rate as u64),
+        ("cargo_transfer_rate", c.cargo_transfer_rate as u64),
+        (
+            "tractor_beam_gather_rate",
+            c.tractor_beam_gather_rate as u64,
+        ),
+        ("crew", x.crew),
+        ("respawn_time", x.respawn_time as u64),
+        ("scan_cool_down", x.scan_cool_down as u64),
+        ("sdu_per_scan", x.sdu_per_scan as u64),
+        ("scan_cost", x.scan_cost as u64),
+        ("placeholder", x.placeholder as u64),
+        ("placeholder2", x.placeholder2 as u64),
+        ("placeholder3", x.placeholder3 as u64),
+    ]
+}
+
+/// The stats of a fleet (`Fleet.stats`) that differ from its ships' combined stats, empty when
+/// they match.
+pub fn fleet_stats_mismatches(
+    fleet_stats: &types::ShipStats,
+    ships: &[FleetShip],
+) -> Vec<StatMismatch> {
+    let ship_stats = fleet_ship_stats(ships);
+
+    ship_stats_fields(&ship_stats)
+        .into_iter()
+        .zip(ship_stats_fields(fleet_stats))
+        .filter(|((_, ships), (_, fleet))| ships != fleet)
+        .map(|((stat, ships), (_, fleet))| StatMismatch { stat, ships, fleet })
+        .collect()
+}
+
+// cargo test -p staratlas-sage-based-sdk --features anchor-client/async
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::zeroed;
+    use anchor_client::anchor_lang::prelude::Pubkey;
+
+    fn ship_stats() -> types::ShipStats {
+        let mut stats: types::ShipStats = zeroed();
+        stats.movement_stats.warp_speed = 2_000_000; // 2 sectors per second
+        stats.movement_stats.subwarp_speed = 10_000; // 0.01 sectors per second
+        stats.movement_stats.max_warp_distance = 500; // 5 sectors
+        stats.movement_stats.warp_cool_down = 30;
+        stats.movement_stats.warp_fuel_consumption_rate = 150;
+        stats.movement_stats.subwarp_fuel_consumption_rate = 25;
+        stats.movement_stats.planet_exit_fuel_amount = 20;
+        stats
+    }
+
+    fn mining_stats() -> types::ShipStats {
+        let mut stats = ship_stats();
+        stats.cargo_stats.mining_rate = 20_000; // 2 units per second (hardness and richness 1)
+        stats.cargo_stats.ammo_consumption_rate = 10_000; // 1 per second
+        stats.cargo_stats.food_consumption_rate = 5_000; // 0.5 per second
+        stats
+    }
+
+    #[test]
+    fn mining_plan_bound_by_the_cargo_hold() {
+        let stats = mining_stats();
+
+        for (ammo, food) in [(Some(1_000), Some(1_000)), (None, None)] {
+            let plan = asteroid_mining_plan_bare_bones(&stats, 100, 100, 1_000, ammo, food);
+            assert_eq!(plan.constraint, MiningConstraint::CargoHold);
+            assert_eq!(plan.emission_rate, 2.0);
+            assert_eq!(plan.max_duration, 500.0);
+            assert_eq!(plan.full_hold_duration, 500.0);
+            assert_eq!(plan.ammo_for_full_hold, 500.0);
+            assert_eq!(plan.food_for_full_hold, 250.0);
+            assert_eq!(plan.output, 1_000.0);
+        }
+    }
+
+    #[test]
+    fn mining_plan_bound_by_ammo_or_food() {
+        let stats = mining_stats();
+
+        let plan = asteroid_mining_plan_bare_bones(&stats, 100, 100, 1_000, Some(100), None);
+        assert_eq!(plan.constraint, MiningConstraint::Ammo);
+        assert_eq!(plan.max_duration, 100.0);
+        assert_eq!(plan.full_hold_duration, 500.0);
+        assert_eq!(plan.output, 200.0);
+        assert_eq!(plan.mined(50.0), 100.0);
+        assert_eq!(plan.mined(300.0), 200.0);
+
+        let plan = asteroid_mining_plan_bare_bones(&stats, 100, 100, 1_000, Some(100), Some(20));
+        assert_eq!(plan.constraint, MiningConstraint::Food);
+        assert_eq!(plan.max_duration, 40.0);
+        assert_eq!(plan.output, 80.0);
+
+        // a fleet that burns no ammo mines until the hold is full, whatever its ammo
+        let mut stats = stats;
+        stats.cargo_stats.ammo_consumption_rate = 0;
+        let plan = asteroid_mining_plan_bare_bones(&stats, 100, 100, 1_000, Some(0), None);
+        assert_eq!(plan.constraint, MiningConstraint::CargoHold);
+    }
+
+    #[test]
+    fn mining_plan_of_a_zero_rate() {
+        let mut stats = mining_stats();
+        stats.cargo_stats.mining_rate = 0;
+
+        let plan = asteroid_mining_plan_bare_bones(&stats, 100, 100, 1_000, Some(100), Some(20));
+        assert_eq!(plan.emission_rate, 0.0);
+        assert_eq!(plan.max_duration, 0.0);
+        assert_eq!(plan.full_hold_duration, 0.0);
+        assert_eq!(plan.output, 0.0);
+        assert_eq!(plan.mined(60.0), 0.0);
+
+        // no cargo space, nothing to mine
+        let plan = asteroid_mining_plan_bare_bones(&mining_stats(), 100, 100, 0, None, None);
+        assert_eq!(plan.max_duration, 0.0);
+        assert_eq!(plan.output, 0.0);
+    }
+
+    #[test]
+    fn warp_duration_is_the_distance_at_warp_speed() {
+        let stats = ship_stats();
+
+        assert_eq!(warp_duration(&stats, [0, 0], [3, 4]), 2.5);
+        assert_eq!(warp_duration(&stats, [3, 4], [0, 0]), 2.5);
+        assert_eq!(warp_duration(&stats, [-40, 30], [-40, 30]), 0.0);
+
+        let mut stats = stats;
+        stats.movement_stats.warp_speed = 0;
+        assert_eq!(warp_duration(&stats, [0, 0], [3, 4]), 0.0);
+    }
+
+    #[test]
+    fn subwarp_duration_is_the_distance_at_subwarp_speed() {
+        let stats = ship_stats();
+
+        assert_eq!(subwarp_duration(&stats, [0, 0], [3, 4]), 500.0);
+        assert_eq!(subwarp_duration(&stats, [40, 30], [41, 30]), 100.0);
+
+        let mut stats = stats;
+        stats.movement_stats.subwarp_speed = 0;
+        assert_eq!(subwarp_duration(&stats, [0, 0], [3, 4]), 0.0);
+    }
+
+    #[test]
+    fn travel_by_warp_or_subwarp() {
+        let stats = ship_stats();
+
+        let travel = travel(&stats, [0, 0], [3, 4]);
+        assert_eq!(travel.distance, 5.0);
+        assert_eq!(travel.warp_duration, 2.5);
+        assert_eq!(travel.warp_fuel, 8.0); // 7.5 rounded up
+        assert_eq!(travel.warp_cool_down, 30.0);
+        assert_eq!(travel.subwarp_duration, 500.0);
+        assert_eq!(travel.subwarp_fuel, 2.0); // 1.25 rounded up
+        assert_eq!(travel.planet_exit_fuel, 20.0);
+        assert_eq!(travel.max_warp_distance, 5.0);
+        assert!(travel.is_single_warp());
+
+        assert!(!super::travel(&stats, [0, 0], [3, 5]).is_single_warp());
+    }
+
+    /// A game state reducing the warp lane fuel by 40% and the emissions of an empty ammo upkeep
+    /// by 25%.
+    fn game_state() -> GameState {
+        let mut game_state: GameState = zeroed();
+        game_state.misc.warp_lane_fuel_cost_reduction = 40;
+        game_state.misc.upkeep_mining_emissions_penalty = 25;
+        game_state.misc.respawn_fee = 150_000_000;
+        game_state.fleet.starbase_levels.oni[2].warp_lane_movement_fee = 5_000_000;
+        game_state
+    }
+
+    #[test]
+    fn emission_rate_of_an_empty_ammo_upkeep() {
+        let (stats, game_state) = (mining_stats(), game_state());
+        let (mut mine_item, mut resource): (MineItem, Resource) = (zeroed(), zeroed());
+        mine_item.resource_hardness = 200;
+        resource.system_richness = 100;
+
+        let emission_rate = |empty| {
+            asteroid_mining_emission_rate(&stats, &game_state, &mine_item, &resource, empty)
+        };
+        assert_eq!(emission_rate(false), 1.0); // 2 units per second, hardness 2
+        assert_eq!(emission_rate(true), 0.75);
+
+        let mut no_penalty = game_state.clone();
+        no_penalty.misc.upkeep_mining_emissions_penalty = 0;
+        assert_eq!(
+            asteroid_mining_emission_rate(&stats, &no_penalty, &mine_item, &resource, true),
+            1.0
+        );
+    }
+
+    #[test]
+    fn warp_lane_fuel_of_the_game_state() {
+        let (stats, game_state) = (ship_stats(), game_state());
+
+        // 7.5 rounded up to 8, less 40% rounded up
+        assert_eq!(
+            warp_lane_fuel_consumption(&stats, &game_state, [0, 0], [3, 4]),
+            5.0
+        );
+        assert_eq!(game_state.warp_lane_fuel_multiplier(), 0.6);
+
+        let mut free = game_state.clone();
+        free.misc.warp_lane_fuel_cost_reduction = 150; // never below no fuel
+        assert_eq!(
+            warp_lane_fuel_consumption(&stats, &free, [0, 0], [3, 4]),
+            0.0
+        );
+    }
+
+    #[test]
+    fn fees_of_the_game_state() {
+        let game_state = game_state();
+
+        assert_eq!(respawn_fee(&game_state), 1.5);
+        assert_eq!(warp_lane_fee(&game_state, 2, 2), Some(0.05));
+        assert_eq!(warp_lane_fee(&game_state, 1, 2), Some(0.0));
+        assert_eq!(warp_lane_fee(&game_state, 4, 2), None); // no such faction
+        assert_eq!(warp_lane_fee(&game_state, 2, 7), None); // no such level
+    }
+
+    fn fleet_ship(stats: types::ShipStats, amount: u64) -> FleetShip {
+        let mut ship: Ship = zeroed();
+        ship.stats = stats;
+        FleetShip {
+            ship_id: Pubkey::new_unique(),
+            ship,
+            amount,
+        }
+    }
+
+    #[test]
+    fn fleet_ship_stats_combine_the_ships() {
+        let mut fast = ship_stats();
+        fast.cargo_stats.cargo_capacity = 100;
+        fast.misc_stats.crew = 2;
+        let mut slow = mining_stats();
+        slow.movement_stats.warp_speed = 1_000_000;
+        slow.movement_stats.max_warp_distance = 300;
+        slow.movement_stats.warp_cool_down = 60;
+        slow.cargo_stats.cargo_capacity = 500;
+        slow.misc_stats.crew = 5;
+
+        let ships = [
+            fleet_ship(fast, 3),
+            fleet_ship(slow, 2),
+            fleet_ship(zeroed::<types::ShipStats>(), 0),
+        ];
+        let stats = fleet_ship_stats(&ships);
+
+        // the slowest ship moves the fleet (the ships without an amount left out)
+        assert_eq!(stats.movement_stats.warp_speed, 1_000_000);
+        assert_eq!(stats.movement_stats.max_warp_distance, 300);
+        assert_eq!(stats.movement_stats.warp_cool_down, 60);
+        // the rates and capacities add up by amount
+        assert_eq!(stats.movement_stats.warp_fuel_consumption_rate, 150 * 5);
+        assert_eq!(stats.cargo_stats.cargo_capacity, 100 * 3 + 500 * 2);
+        assert_eq!(stats.cargo_stats.mining_rate, 20_000 * 2);
+        assert_eq!(stats.misc_stats.crew, 2 * 3 + 5 * 2);
+
+        let stats = fleet_ship_stats(&[]);
+        assert_eq!(stats.movement_stats.warp_speed, 0);
+        assert_eq!(stats.cargo_stats.cargo_capacity, 0);
+    }
+
+    #[test]
+    fn fleet_stats_mismatch_the_ships() {
+        let ships = [fleet_ship(mining_stats(), 2)];
+        let mut fleet_stats = fleet_ship_stats(&ships);
+        assert!(fleet_stats_mismatches(&fleet_stats, &ships).is_empty());
+
+        fleet_stats.cargo_stats.mining_rate = 30_000;
+        fleet_stats.misc_stats.crew = 1;
+        assert_eq!(
+            fleet_stats_mismatches(&fleet_stats, &ships),
+            vec![
+                StatMismatch {
+                    stat: "mining_rate",
+                    ships: 40_000,
+                    fleet: 30_000,
+                },
+                StatMismatch {
+                    stat: "crew",
+                    ships: 0,
+                    fleet: 1,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn fleet_ship_counts_and_stats_saturate() {
+        let mut stats = mining_stats();
+        stats.cargo_stats.cargo_capacity = u32::MAX;
+        stats.misc_stats.crew = u64::MAX;
+        let mut ship = fleet_ship(stats, 70_000);
+        ship.ship.size_class = 3;
+
+        let counts = fleet_ship_counts(&[ship, ship]);
+        assert_eq!(counts.small, u16::MAX); // 140_000 small ships
+        assert_eq!(counts.total, 140_000);
+
+        let stats = fleet_ship_stats(&[ship]);
+        assert_eq!(stats.cargo_stats.cargo_capacity, u32::MAX);
+        assert_eq!(stats.cargo_stats.mining_rate, 20_000 * 70_000);
+        assert_eq!(stats.misc_stats.crew, u64::MAX);
+        assert_eq!(stats.movement_stats.max_warp_distance, 500);
+    }
 }
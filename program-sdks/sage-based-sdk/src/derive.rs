@@ -1,11 +1,9 @@
 use anchor_client::{
-    anchor_lang::prelude::Pubkey,
-    solana_client::rpc_filter::{Memcmp, RpcFilterType},
-    solana_sdk::signature::Signer,
-    ClientError, Program,
+    anchor_lang::prelude::Pubkey, solana_sdk::signature::Signer, ClientError, Program,
 };
 
 use staratlas_cargo::state as cargo_state;
+use staratlas_utils_query::Query;
 
 use crate::accounts::CargoPod;
 
@@ -15,11 +13,13 @@ pub async fn cargo_pod_accounts<C: Deref<Target = impl Signer> + Clone>(
     cargo_program: &Program<C>,
     starbase_player: &Pubkey,
 ) -> Result<Vec<(Pubkey, CargoPod)>, ClientError> {
+    crate::utils::rpc_budget().await;
     let accounts = cargo_program
-        .accounts::<cargo_state::CargoPod>(vec![RpcFilterType::Memcmp(Memcmp::new_base58_encoded(
-            41,
-            starbase_player.as_ref(),
-        ))])
+        .accounts::<cargo_state::CargoPod>(
+            Query::<cargo_state::CargoPod>::new()
+                .eq(|f| f.authority, starbase_player)
+                .filters(),
+        )
         .await?;
 
     let cargo_pod_accounts = accounts
@@ -1,10 +1,18 @@
 use anchor_client::{
-    solana_client::rpc_filter::{Memcmp, RpcFilterType},
+    anchor_lang::Discriminator,
+    solana_client::{
+        nonblocking::rpc_client::RpcClient,
+        rpc_config::{RpcAccountInfoConfig, RpcProgramAccountsConfig},
+        rpc_filter::{Memcmp, RpcFilterType},
+    },
     solana_sdk::{pubkey::Pubkey, signature::Signer},
     ClientError, Program,
 };
+use borsh::BorshDeserialize;
+use solana_account_decoder::UiAccountEncoding;
+use staratlas_utils_query::Query;
 
-use crate::accounts::{Fleet, Planet};
+use crate::accounts::{CargoTypeStats, Fleet, MineItem, Planet, Resource};
 use staratlas_sage::state;
 
 use std::ops::Deref;
@@ -14,11 +22,14 @@ pub async fn fleets_by_game_and_player_profile<C: Deref<Target = impl Signer> +
     game_id: &Pubkey,
     player_profile_id: &Pubkey,
 ) -> Result<Vec<(Pubkey, Fleet)>, ClientError> {
+    crate::utils::rpc_budget().await;
     let accounts = program
-        .accounts::<state::Fleet>(vec![
-            RpcFilterType::Memcmp(Memcmp::new_base58_encoded(9, game_id.as_ref())),
-            RpcFilterType::Memcmp(Memcmp::new_base58_encoded(41, player_profile_id.as_ref())),
-        ])
+        .accounts::<state::Fleet>(
+            Query::<state::Fleet>::new()
+                .eq(|f| f.game_id, game_id)
+                .eq(|f| f.owner_profile, player_profile_id)
+                .filters(),
+        )
         .await?;
 
     let accounts = accounts
@@ -34,18 +45,14 @@ pub async fn planets_by_game_and_coords<C: Deref<Target = impl Signer> + Clone>(
     game_id: &Pubkey,
     sector_coordinates: [i64; 2],
 ) -> Result<Vec<(Pubkey, Planet)>, ClientError> {
+    crate::utils::rpc_budget().await;
     let accounts = program
-        .accounts::<state::Planet>(vec![
-            RpcFilterType::Memcmp(Memcmp::new_base58_encoded(73, game_id.as_ref())),
-            RpcFilterType::Memcmp(Memcmp::new_base58_encoded(
-                105,
-                &sector_coordinates[0].to_le_bytes(),
-            )),
-            RpcFilterType::Memcmp(Memcmp::new_base58_encoded(
-                113,
-                &sector_coordinates[1].to_le_bytes(),
-            )),
-        ])
+        .accounts::<state::Planet>(
+            Query::<state::Planet>::new()
+                .eq(|f| f.game_id, game_id)
+                .eq(|f| f.sector, sector_coordinates)
+                .filters(),
+        )
         .await?;
 
     let accounts = accounts
@@ -55,3 +62,109 @@ pub async fn planets_by_game_and_coords<C: Deref<Target = impl Signer> + Clone>(
 
     Ok(accounts)
 }
+
+pub async fn planets_by_game<C: Deref<Target = impl Signer> + Clone>(
+    program: &Program<C>,
+    game_id: &Pubkey,
+) -> Result<Vec<(Pubkey, Planet)>, ClientError> {
+    crate::utils::rpc_budget().await;
+    let accounts = program
+        .accounts::<state::Planet>(
+            Query::<state::Planet>::new()
+                .eq(|f| f.game_id, game_id)
+                .filters(),
+        )
+        .await?;
+
+    let accounts = accounts
+        .iter()
+        .map(|(pubkey, account)| (*pubkey, Planet::from(*account)))
+        .collect();
+
+    Ok(accounts)
+}
+
+pub async fn resources_by_game<C: Deref<Target = impl Signer> + Clone>(
+    program: &Program<C>,
+    game_id: &Pubkey,
+) -> Result<Vec<(Pubkey, Resource)>, ClientError> {
+    crate::utils::rpc_budget().await;
+    let accounts = program
+        .accounts::<state::Resource>(
+            Query::<state::Resource>::new()
+                .eq(|f| f.game_id, game_id)
+                .filters(),
+        )
+        .await?;
+
+    let accounts = accounts
+        .iter()
+        .map(|(pubkey, account)| (*pubkey, Resource::from(*account)))
+        .collect();
+
+    Ok(accounts)
+}
+
+pub async fn mine_items_by_game<C: Deref<Target = impl Signer> + Clone>(
+    program: &Program<C>,
+    game_id: &Pubkey,
+) -> Result<Vec<(Pubkey, MineItem)>, ClientError> {
+    crate::utils::rpc_budget().await;
+    let accounts = program
+        .accounts::<state::MineItem>(
+            Query::<state::MineItem>::new()
+                .eq(|f| f.game_id, game_id)
+                .filters(),
+        )
+        .await?;
+
+    let accounts = accounts
+        .iter()
+        .map(|(pubkey, account)| (*pubkey, MineItem::from(*account)))
+        .collect();
+
+    Ok(accounts)
+}
+
+pub async fn cargo_types_by_stats_definition(
+    rpc: &RpcClient,
+    stats_definition: &Pubkey,
+    seq_id: u16,
+) -> Result<Vec<(Pubkey, CargoTypeStats)>, ClientError> {
+    let config = RpcProgramAccountsConfig {
+        filters: Some(
+            [RpcFilterType::Memcmp(Memcmp::new_base58_encoded(
+                0,
+                &staratlas_cargo::state::CargoType::DISCRIMINATOR,
+            ))]
+            .into_iter()
+            .chain(
+                Query::<staratlas_cargo::state::CargoType>::new()
+                    .eq(|f| f.stats_definition, stats_definition)
+                    .eq(|f| f.seq_id, seq_id)
+                    .filters(),
+            )
+            .collect(),
+        ),
+        account_config: RpcAccountInfoConfig {
+            encoding: Some(UiAccountEncoding::Base64),
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+
+    crate::utils::rpc_budget().await;
+    let accounts = rpc
+        .get_program_accounts_with_config(&staratlas_cargo::ID, config)
+        .await?;
+
+    let cargo_types = accounts
+        .into_iter()
+        .filter_map(|(pubkey, account)| {
+            let cargo_type = CargoTypeStats::deserialize(&mut account.data.as_slice()).ok()?;
+            Some((pubkey, cargo_type))
+        })
+        .collect();
+
+    Ok(cargo_types)
+}
@@ -0,0 +1,12 @@
+//! Fixtures of the tests: the accounts and types of the SDK zeroed, a test sets the fields it
+//! needs (`cargo test -p staratlas-sage-based-sdk --features anchor-client/async`).
+
+use borsh::BorshDeserialize;
+
+/// Bytes of the largest type decoded from zeroes (a `GameState` with its starbase levels).
+const ZEROED_LEN: usize = 4096;
+
+/// A value of a (fixed size) type with every field zeroed.
+pub(crate) fn zeroed<T: BorshDeserialize>() -> T {
+    T::deserialize(&mut &[0u8; ZEROED_LEN][..]).unwrap()
+}
@@ -25,6 +25,7 @@ pub fn cargo_deposit_to_fleet<C: Deref<Target = impl Signer> + Clone>(
     cargo_pod_to: &Pubkey,
     mint: &Pubkey,
     amount: u64,
+    key_index: u16,
 ) -> Instruction {
     let (game_id, game) = game;
     let game_state_id = game.game_state;
@@ -48,10 +49,7 @@ pub fn cargo_deposit_to_fleet<C: Deref<Target = impl Signer> + Clone>(
     let ata_token_to = get_associated_token_address(cargo_pod_to, mint);
 
     let instr = instruction::DepositCargoToFleet {
-        _input: typedefs::DepositCargoToFleetInput {
-            amount,
-            key_index: 0,
-        },
+        _input: typedefs::DepositCargoToFleetInput { amount, key_index },
     };
 
     Instruction::new_with_bytes(
@@ -89,6 +87,7 @@ pub fn cargo_withdraw_from_fleet<C: Deref<Target = impl Signer> + Clone>(
     starbase: &Pubkey,
     mint: &Pubkey,
     amount: u64,
+    key_index: u16,
 ) -> Instruction {
     let (game_id, game) = game;
     let game_state_id = game.game_state;
@@ -112,10 +111,7 @@ pub fn cargo_withdraw_from_fleet<C: Deref<Target = impl Signer> + Clone>(
     let ata_token_to = get_associated_token_address(&cargo_pod_id, mint);
 
     let instr = instruction::WithdrawCargoFromFleet {
-        _input: typedefs::WithdrawCargoFromFleetInput {
-            amount,
-            key_index: 0,
-        },
+        _input: typedefs::WithdrawCargoFromFleetInput { amount, key_index },
     };
 
     Instruction::new_with_bytes(
@@ -145,11 +141,66 @@ pub fn cargo_withdraw_from_fleet<C: Deref<Target = impl Signer> + Clone>(
     )
 }
 
+pub fn cargo_deposit_to_game<C: Deref<Target = impl Signer> + Clone>(
+    sage_program: &Program<C>,
+    game: (&Pubkey, &Game),
+    player_profile: &Pubkey,
+    cargo_pod: (&Pubkey, &CargoPod),
+    starbase: &Pubkey,
+    mint: &Pubkey,
+    amount: u64,
+    key_index: u16,
+) -> Instruction {
+    let (game_id, game) = game;
+    let game_state_id = game.game_state;
+
+    let (cargo_pod_id, cargo_pod) = cargo_pod;
+
+    let (profile_faction, _) = addr::profile_faction_address(player_profile);
+    let (sage_player_profile, _) = addr::sage_player_profile_address(&game_id, player_profile);
+
+    let starbase_seq_id = 0; // TODO: this should come from the starbase account
+    let (starbase_player, _) =
+        addr::starbase_player_address(&starbase, &sage_player_profile, starbase_seq_id);
+
+    let (mint_cargo_type, _) =
+        addr::cargo_type_address(&cargo_pod.stats_definition, mint, cargo_pod.seq_id);
+
+    let ata_token_from = get_associated_token_address(&sage_program.payer(), mint);
+    let ata_token_to = get_associated_token_address(cargo_pod_id, mint);
+
+    let instr = instruction::DepositCargoToGame {
+        _input: typedefs::CargoToGameInput { amount, key_index },
+    };
+
+    Instruction::new_with_bytes(
+        sage_program.id(),
+        &instr.data(),
+        vec![
+            AccountMeta::new_readonly(*starbase, false),
+            AccountMeta::new_readonly(starbase_player, false),
+            AccountMeta::new(*cargo_pod_id, false),
+            AccountMeta::new_readonly(mint_cargo_type, false),
+            AccountMeta::new_readonly(cargo_pod.stats_definition, false),
+            AccountMeta::new_readonly(sage_program.payer(), true),
+            AccountMeta::new_readonly(*player_profile, false),
+            AccountMeta::new_readonly(profile_faction, false),
+            AccountMeta::new_readonly(*game_id, false),
+            AccountMeta::new_readonly(game_state_id, false),
+            AccountMeta::new(ata_token_from, false),
+            AccountMeta::new(ata_token_to, false),
+            AccountMeta::new_readonly(CARGO_ID, false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+        ],
+    )
+}
+
 pub fn dock_to_starbase<C: Deref<Target = impl Signer> + Clone>(
     sage_program: &Program<C>,
     game: (&Pubkey, &Game),
     fleet: (&Pubkey, &Fleet),
     sector: [i64; 2],
+    key_index: u16,
 ) -> Instruction {
     let (game_id, game) = game;
     let game_state_id = game.game_state;
@@ -167,7 +218,9 @@ pub fn dock_to_starbase<C: Deref<Target = impl Signer> + Clone>(
     let (starbase_player, _) =
         addr::starbase_player_address(&starbase, &sage_player_profile, starbase_seq_id);
 
-    let instr = instruction::IdleToLoadingBay { _key_index: 0 };
+    let instr = instruction::IdleToLoadingBay {
+        _key_index: key_index,
+    };
 
     Instruction::new_with_bytes(
         sage_program.id(),
@@ -190,6 +243,7 @@ pub fn undock_from_starbase<C: Deref<Target = impl Signer> + Clone>(
     game: (&Pubkey, &Game),
     fleet: (&Pubkey, &Fleet),
     starbase: &Pubkey,
+    key_index: u16,
 ) -> Instruction {
     let (game_id, game) = game;
     let game_state_id = game.game_state;
@@ -204,7 +258,9 @@ pub fn undock_from_starbase<C: Deref<Target = impl Signer> + Clone>(
     let (starbase_player, _) =
         addr::starbase_player_address(&starbase, &sage_player_profile, starbase_seq_id);
 
-    let instr = instruction::LoadingBayToIdle { _key_index: 0 };
+    let instr = instruction::LoadingBayToIdle {
+        _key_index: key_index,
+    };
 
     Instruction::new_with_bytes(
         sage_program.id(),
@@ -306,6 +362,7 @@ pub fn start_mining_asteroid<C: Deref<Target = impl Signer> + Clone>(
     resource: &Pubkey,
     planet: &Pubkey,
     sector: [i64; 2],
+    key_index: u16,
 ) -> Instruction {
     let (game_id, game) = game;
     let game_state_id = game.game_state;
@@ -326,7 +383,7 @@ pub fn start_mining_asteroid<C: Deref<Target = impl Signer> + Clone>(
         addr::starbase_player_address(&starbase, &sage_player_profile, starbase_seq_id);
 
     let instr = instruction::StartMiningAsteroid {
-        _input: typedefs::KeyIndexInput { key_index: 0 },
+        _input: typedefs::KeyIndexInput { key_index },
     };
 
     Instruction::new_with_bytes(
@@ -356,6 +413,7 @@ pub fn stop_mining_asteroid<C: Deref<Target = impl Signer> + Clone>(
     mine_item: &Pubkey,
     resource: &Pubkey,
     planet: &Pubkey,
+    key_index: u16,
 ) -> Instruction {
     let (game_id, game) = game;
     let game_state_id = game.game_state;
@@ -386,7 +444,7 @@ pub fn stop_mining_asteroid<C: Deref<Target = impl Signer> + Clone>(
     let (progress_config, _) = addr::progression_config_address(game_id);
 
     let instr = instruction::StopMiningAsteroid {
-        _input: typedefs::StopMiningAsteroidInput { key_index: 0 },
+        _input: typedefs::StopMiningAsteroidInput { key_index },
     };
 
     Instruction::new_with_bytes(
@@ -429,6 +487,7 @@ pub fn warp_to_coordinate<C: Deref<Target = impl Signer> + Clone>(
     game: (&Pubkey, &Game),
     fleet: (&Pubkey, &Fleet),
     coordinate: [i64; 2],
+    key_index: u16,
 ) -> Instruction {
     let (game_id, game) = game;
     let game_state_id = game.game_state;
@@ -447,7 +506,7 @@ pub fn warp_to_coordinate<C: Deref<Target = impl Signer> + Clone>(
 
     let instr = instruction::WarpToCoordinate {
         _input: typedefs::WarpToCoordinateInput {
-            key_index: 0,
+            key_index,
             to_sector: coordinate,
         },
     };
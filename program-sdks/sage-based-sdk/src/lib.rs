@@ -1,12 +1,14 @@
 use anchor_client::{
     anchor_lang::prelude::Pubkey,
     solana_client::{
+        client_error::{ClientError as SolanaClientError, ClientErrorKind},
         nonblocking::rpc_client::RpcClient,
-        rpc_filter::{Memcmp, RpcFilterType},
-        rpc_request::TokenAccountsFilter,
+        rpc_config::{RpcSendTransactionConfig, RpcTransactionConfig},
+        rpc_request::{RpcError, TokenAccountsFilter},
         rpc_response::{Response, RpcSimulateTransactionResult},
     },
     solana_sdk::{
+        commitment_config::CommitmentConfig,
         compute_budget::ComputeBudgetInstruction,
         instruction::Instruction,
         signature::{Keypair, Signature, Signer},
@@ -16,22 +18,59 @@ use anchor_client::{
 };
 use borsh::BorshDeserialize;
 use solana_account_decoder::UiAccountData;
-
-use std::ops::Deref;
+use solana_transaction_status::UiTransactionEncoding;
+use staratlas_utils_query::Query;
+
+use std::{
+    future::Future,
+    ops::Deref,
+    pin::Pin,
+    rc::Rc,
+    sync::{Arc, LazyLock, Mutex},
+    time::Duration,
+};
 
 mod accounts;
 pub mod addr;
 pub mod calc;
 pub mod derive;
 pub mod filter;
+#[cfg(test)]
+mod fixtures;
 pub mod ixs;
 pub mod program;
+pub mod risk;
+pub mod route;
 pub(crate) mod utils;
 
 pub use accounts::*;
 
 const MICRO_LAMPORTS: u64 = 100;
 
+/// Accounts of a `getMultipleAccounts` call (the RPC's limit).
+pub const MAX_MULTIPLE_ACCOUNTS: usize = 100;
+
+tokio::task_local! {
+    /// Extra RPC endpoints (url and client) of the transactions sent within the scope
+    /// (`BROADCAST_CLIENTS.scope`).
+    pub static BROADCAST_CLIENTS: Vec<(String, Arc<RpcClient>)>;
+
+    /// Waits for the budget of each RPC call made within the scope (`RPC_BUDGET.scope`).
+    pub static RPC_BUDGET: RpcBudget;
+}
+
+/// A rate limit of the RPC calls, its future completes when a call may go out.
+pub type RpcBudget = Rc<dyn Fn() -> Pin<Box<dyn Future<Output = ()>>>>;
+
+/// Interval of the confirmation polls of a sent transaction.
+const CONFIRM_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Confirmation polls between the checks of the transaction's blockhash (expired, not landed).
+const BLOCKHASH_CHECK_POLLS: u32 = 10;
+
+/// The payers' profile key indexes, shared by the handlers of the process.
+static KEY_INDEXES: LazyLock<Mutex<KeyIndexes>> = LazyLock::new(Default::default);
+
 pub struct SageBasedGameHandler {}
 
 // Game
@@ -40,10 +79,21 @@ impl SageBasedGameHandler {
         program: &Program<C>,
         game_id: &Pubkey,
     ) -> Result<(Pubkey, Game), ClientError> {
+        utils::rpc_budget().await;
         let account = program.account::<state::Game>(*game_id).await?;
         let game = Game::from(account);
         Ok((*game_id, game))
     }
+
+    pub async fn get_game_state<C: Deref<Target = impl Signer> + Clone>(
+        program: &Program<C>,
+        game_state_id: &Pubkey,
+    ) -> Result<(Pubkey, GameState), ClientError> {
+        utils::rpc_budget().await;
+        let account = program.account::<state::GameState>(*game_state_id).await?;
+        let game_state = GameState::from(account);
+        Ok((*game_state_id, game_state))
+    }
 }
 
 // MineItem
@@ -52,6 +102,7 @@ impl SageBasedGameHandler {
         program: &Program<C>,
         mine_item_id: &Pubkey,
     ) -> Result<(Pubkey, MineItem), ClientError> {
+        utils::rpc_budget().await;
         let account = program.account::<state::MineItem>(*mine_item_id).await?;
         let mine_item = MineItem::from(account);
         Ok((*mine_item_id, mine_item))
@@ -64,6 +115,7 @@ impl SageBasedGameHandler {
         program: &Program<C>,
         planet_id: &Pubkey,
     ) -> Result<(Pubkey, Planet), ClientError> {
+        utils::rpc_budget().await;
         let account = program.account::<state::Planet>(*planet_id).await?;
         let planet = Planet::from(account);
         Ok((*planet_id, planet))
@@ -78,16 +130,21 @@ impl SageBasedGameHandler {
         location: &Pubkey, // planet
         mine_item: &Pubkey,
     ) -> Result<(Pubkey, Resource), ClientError> {
+        utils::rpc_budget().await;
         let accounts = program
-            .accounts::<state::Resource>(vec![
-                RpcFilterType::Memcmp(Memcmp::new_base58_encoded(9, game_id.as_ref())),
-                RpcFilterType::Memcmp(Memcmp::new_base58_encoded(41, location.as_ref())),
-                RpcFilterType::Memcmp(Memcmp::new_base58_encoded(73, mine_item.as_ref())),
-            ])
-            .await
-            .unwrap();
-
-        let (pubkey, account) = accounts[0];
+            .accounts::<state::Resource>(
+                Query::<state::Resource>::new()
+                    .eq(|f| f.game_id, game_id)
+                    .eq(|f| f.location, location)
+                    .eq(|f| f.mine_item, mine_item)
+                    .filters(),
+            )
+            .await?;
+
+        let (pubkey, account) = accounts
+            .into_iter()
+            .next()
+            .ok_or(ClientError::AccountNotFound)?;
         let resource = Resource::from(account);
         Ok((pubkey, resource))
     }
@@ -100,6 +157,7 @@ impl SageBasedGameHandler {
         fleet_id: &Pubkey,
     ) -> Result<(Pubkey, FleetWithState), ClientError> {
         let rpc = program.async_rpc();
+        utils::rpc_budget().await;
         let account = rpc.get_account(&fleet_id).await?;
         let mut account_data = account.data.as_slice();
 
@@ -108,6 +166,120 @@ impl SageBasedGameHandler {
     }
 }
 
+// Fleet Ships
+impl SageBasedGameHandler {
+    pub async fn get_fleet_ships<C: Deref<Target = impl Signer> + Clone>(
+        program: &Program<C>,
+        fleet_ships_id: &Pubkey,
+    ) -> Result<(Pubkey, FleetShips), ClientError> {
+        let rpc = program.async_rpc();
+        utils::rpc_budget().await;
+        let account = rpc.get_account(fleet_ships_id).await?;
+        let mut account_data = account.data.as_slice();
+
+        let fleet_ships = FleetShips::deserialize(&mut account_data)?;
+        Ok((*fleet_ships_id, fleet_ships))
+    }
+
+    pub async fn get_ship<C: Deref<Target = impl Signer> + Clone>(
+        program: &Program<C>,
+        ship_id: &Pubkey,
+    ) -> Result<(Pubkey, Ship), ClientError> {
+        utils::rpc_budget().await;
+        let account = program.account::<state::Ship>(*ship_id).await?;
+        let ship = Ship::from(account);
+        Ok((*ship_id, ship))
+    }
+
+    /// The ship types of a fleet (its `FleetShips`) with their `Ship` accounts, in one
+    /// `getMultipleAccounts` call.
+    pub async fn get_fleet_composition<C: Deref<Target = impl Signer> + Clone>(
+        program: &Program<C>,
+        fleet: &Fleet,
+    ) -> Result<Vec<FleetShip>, ClientError> {
+        let (_, fleet_ships) = Self::get_fleet_ships(program, &fleet.fleet_ships).await?;
+
+        let ship_ids: Vec<Pubkey> = fleet_ships.ships.iter().map(|info| info.ship).collect();
+        let rpc = program.async_rpc();
+        let mut accounts = Vec::with_capacity(ship_ids.len());
+        for chunk in ship_ids.chunks(MAX_MULTIPLE_ACCOUNTS) {
+            utils::rpc_budget().await;
+            accounts.extend(rpc.get_multiple_accounts(chunk).await?);
+        }
+
+        let mut ships = vec![];
+        for (info, account) in fleet_ships.ships.iter().zip(accounts) {
+            let account = account.ok_or(ClientError::AccountNotFound)?;
+            ships.push(FleetShip {
+                ship_id: info.ship,
+                ship: Ship::from_account_data(&account.data)?,
+                amount: info.amount,
+            });
+        }
+
+        Ok(ships)
+    }
+}
+
+// Player Profile (Keys)
+impl SageBasedGameHandler {
+    pub async fn get_profile_keys<C: Deref<Target = impl Signer> + Clone>(
+        program: &Program<C>,
+        player_profile: &Pubkey,
+    ) -> Result<ProfileKeys, ClientError> {
+        let rpc = program.async_rpc();
+        utils::rpc_budget().await;
+        let account = rpc.get_account(player_profile).await?;
+        let mut account_data = account.data.as_slice();
+
+        let profile_keys = ProfileKeys::deserialize(&mut account_data)?;
+        Ok(profile_keys)
+    }
+
+    /// The index of the program's payer in the profile's keys, an auth key or an unexpired SAGE
+    /// hot key with the instruction's `permissions` (see `sage_permissions`), looked up once per
+    /// profile and permissions (see `KeyIndexes`).
+    pub async fn payer_key_index<C: Deref<Target = impl Signer> + Clone>(
+        program: &Program<C>,
+        player_profile: &Pubkey,
+        permissions: &[u8; 8],
+    ) -> Result<u16, ClientError> {
+        let payer = program.payer();
+        let now = utils::unix_timestamp();
+
+        let key_index = KEY_INDEXES
+            .lock()
+            .unwrap()
+            .get(&payer, player_profile, permissions, now);
+        if let Some(key_index) = key_index {
+            return Ok(key_index);
+        }
+
+        let profile_keys = Self::get_profile_keys(program, player_profile).await?;
+        let key_index = profile_keys
+            .key_index(&payer, &program::SAGE_ID, permissions, now)
+            .map_err(|err| {
+                let kind = ClientErrorKind::Custom(format!(
+                    "{} can't sign for the profile {}: {}",
+                    payer, player_profile, err
+                ));
+                ClientError::SolanaClientError(kind.into())
+            })?;
+
+        let expire_time = profile_keys.keys[key_index as usize].expire_time;
+        KEY_INDEXES.lock().unwrap().insert(
+            &payer,
+            player_profile,
+            permissions,
+            key_index,
+            expire_time,
+            now,
+        );
+
+        Ok(key_index)
+    }
+}
+
 // Starbase (Dock and Undock)
 impl SageBasedGameHandler {
     pub async fn dock_to_starbase<C: Deref<Target = impl Signer> + Clone>(
@@ -117,7 +289,17 @@ impl SageBasedGameHandler {
         fleet: (&Pubkey, &Fleet),
         sector: [i64; 2],
     ) -> Option<Result<Signature, ClientError>> {
-        let ix = ixs::dock_to_starbase(&program, game, fleet, sector);
+        let key_index = match Self::payer_key_index(
+            program,
+            &fleet.1.owner_profile,
+            &sage_permissions::DOCK_UNDOCK,
+        )
+        .await
+        {
+            Ok(key_index) => key_index,
+            Err(err) => return Some(Err(err)),
+        };
+        let ix = ixs::dock_to_starbase(&program, game, fleet, sector, key_index);
         Self::simulate_and_send_transaction(program, payer, &vec![ix]).await
     }
 
@@ -128,7 +310,17 @@ impl SageBasedGameHandler {
         fleet: (&Pubkey, &Fleet),
         starbase: &Pubkey,
     ) -> Option<Result<Signature, ClientError>> {
-        let ix = ixs::undock_from_starbase(&program, game, fleet, starbase);
+        let key_index = match Self::payer_key_index(
+            program,
+            &fleet.1.owner_profile,
+            &sage_permissions::DOCK_UNDOCK,
+        )
+        .await
+        {
+            Ok(key_index) => key_index,
+            Err(err) => return Some(Err(err)),
+        };
+        let ix = ixs::undock_from_starbase(&program, game, fleet, starbase, key_index);
         Self::simulate_and_send_transaction(program, payer, &vec![ix]).await
     }
 }
@@ -159,6 +351,16 @@ impl SageBasedGameHandler {
             .await
             .unwrap();
         let (cargo_pod_id, cargo_pod) = cargo_pods[0];
+        let key_index = match Self::payer_key_index(
+            sage_program,
+            player_profile,
+            &sage_permissions::DEPOSIT_CARGO_TO_FLEET,
+        )
+        .await
+        {
+            Ok(key_index) => key_index,
+            Err(err) => return Some(Err(err)),
+        };
 
         let ix: Instruction = ixs::cargo_deposit_to_fleet(
             sage_program,
@@ -169,6 +371,7 @@ impl SageBasedGameHandler {
             cargo_pod_to,
             mint,
             amount,
+            key_index,
         );
 
         Self::simulate_and_send_transaction(sage_program, payer, &vec![ix]).await
@@ -197,6 +400,16 @@ impl SageBasedGameHandler {
             .await
             .unwrap();
         let (cargo_pod_id, cargo_pod) = cargo_pods[0];
+        let key_index = match Self::payer_key_index(
+            sage_program,
+            player_profile,
+            &sage_permissions::WITHDRAW_CARGO_FROM_FLEET,
+        )
+        .await
+        {
+            Ok(key_index) => key_index,
+            Err(err) => return Some(Err(err)),
+        };
 
         let ix: Instruction = ixs::cargo_withdraw_from_fleet(
             sage_program,
@@ -206,11 +419,99 @@ impl SageBasedGameHandler {
             starbase,
             mint,
             amount,
+            key_index,
         );
         SageBasedGameHandler::simulate_and_send_transaction(sage_program, payer, &vec![ix]).await
     }
 }
 
+// Starbase Cargo (Inventory and Deposit)
+impl SageBasedGameHandler {
+    pub async fn starbase_cargo_pod<C: Deref<Target = impl Signer> + Clone>(
+        cargo_program: &Program<C>,
+        game_id: &Pubkey,
+        player_profile: &Pubkey,
+        starbase: &Pubkey,
+    ) -> Result<Option<(Pubkey, CargoPod)>, ClientError> {
+        let starbase_seq_id = 0;
+        let (sage_player_profile, _) = addr::sage_player_profile_address(&game_id, &player_profile);
+        let (starbase_player, _) =
+            addr::starbase_player_address(&starbase, &sage_player_profile, starbase_seq_id);
+
+        let cargo_pods = derive::cargo_pod_accounts(cargo_program, &starbase_player).await?;
+        Ok(cargo_pods.first().copied())
+    }
+
+    pub async fn starbase_cargo_amounts<C: Deref<Target = impl Signer> + Clone>(
+        cargo_program: &Program<C>,
+        game_id: &Pubkey,
+        player_profile: &Pubkey,
+        starbase: &Pubkey,
+    ) -> Result<Vec<(String, u64)>, ClientError> {
+        let cargo_pod =
+            Self::starbase_cargo_pod(cargo_program, game_id, player_profile, starbase).await?;
+
+        let token_amounts = match cargo_pod {
+            Some((cargo_pod_id, _)) => {
+                let rpc = cargo_program.async_rpc();
+                Self::parsed_token_account_amounts(&rpc, &cargo_pod_id).await?
+            }
+            None => vec![],
+        };
+
+        Ok(token_amounts)
+    }
+
+    pub async fn cargo_deposit_to_game<C: Deref<Target = impl Signer> + Clone>(
+        sage_program: &Program<C>,
+        cargo_program: &Program<C>,
+        payer: &Keypair,
+        game: (&Pubkey, &Game),
+        player_profile: &Pubkey,
+        starbase: &Pubkey,
+        mint: &Pubkey,
+        amount: u64,
+    ) -> Option<Result<Signature, ClientError>> {
+        let (game_id, _) = game;
+
+        // no cargo pod of the player at the starbase, nothing to deposit to
+        let (cargo_pod_id, cargo_pod) = match Self::starbase_cargo_pod(
+            cargo_program,
+            game_id,
+            player_profile,
+            starbase,
+        )
+        .await
+        {
+            Ok(cargo_pod) => cargo_pod?,
+            Err(err) => return Some(Err(err)),
+        };
+        let key_index = match Self::payer_key_index(
+            sage_program,
+            player_profile,
+            &sage_permissions::DEPOSIT_CARGO_TO_GAME,
+        )
+        .await
+        {
+            Ok(key_index) => key_index,
+            Err(err) => return Some(Err(err)),
+        };
+
+        let ix: Instruction = ixs::cargo_deposit_to_game(
+            sage_program,
+            game,
+            player_profile,
+            (&cargo_pod_id, &cargo_pod),
+            starbase,
+            mint,
+            amount,
+            key_index,
+        );
+
+        Self::simulate_and_send_transaction(sage_program, payer, &vec![ix]).await
+    }
+}
+
 // Asteroid Mining (Start and Stop)
 impl SageBasedGameHandler {
     pub async fn start_mining<C: Deref<Target = impl Signer> + Clone>(
@@ -223,8 +524,19 @@ impl SageBasedGameHandler {
         planet: &Pubkey,
         sector: [i64; 2],
     ) -> Option<Result<Signature, ClientError>> {
-        let ix =
-            ixs::start_mining_asteroid(&program, game, fleet, mine_item, resource, planet, sector);
+        let key_index = match Self::payer_key_index(
+            program,
+            &fleet.1.owner_profile,
+            &sage_permissions::START_MINING_ASTEROID,
+        )
+        .await
+        {
+            Ok(key_index) => key_index,
+            Err(err) => return Some(Err(err)),
+        };
+        let ix = ixs::start_mining_asteroid(
+            &program, game, fleet, mine_item, resource, planet, sector, key_index,
+        );
         Self::simulate_and_send_transaction(program, payer, &vec![ix]).await
     }
 
@@ -253,7 +565,19 @@ impl SageBasedGameHandler {
         );
         ixs.push(ix);
 
-        let ix = ixs::stop_mining_asteroid(&program, game, fleet, mine_item, resource, planet);
+        let key_index = match Self::payer_key_index(
+            program,
+            &fleet.1.owner_profile,
+            &sage_permissions::STOP_MINING_ASTEROID,
+        )
+        .await
+        {
+            Ok(key_index) => key_index,
+            Err(err) => return Some(Err(err)),
+        };
+        let ix = ixs::stop_mining_asteroid(
+            &program, game, fleet, mine_item, resource, planet, key_index,
+        );
         ixs.push(ix);
 
         let mut last_signature: Option<Result<Signature, ClientError>> = None;
@@ -286,7 +610,14 @@ impl SageBasedGameHandler {
         fleet: (&Pubkey, &Fleet),
         sector: [i64; 2],
     ) -> Option<Result<Signature, ClientError>> {
-        let ix = ixs::warp_to_coordinate(program, game, fleet, sector);
+        let key_index =
+            match Self::payer_key_index(program, &fleet.1.owner_profile, &sage_permissions::WARP)
+                .await
+            {
+                Ok(key_index) => key_index,
+                Err(err) => return Some(Err(err)),
+            };
+        let ix = ixs::warp_to_coordinate(program, game, fleet, sector, key_index);
 
         Self::simulate_and_send_transaction(program, payer, &vec![ix]).await
     }
@@ -303,16 +634,117 @@ impl SageBasedGameHandler {
     }
 }
 
+// Cargo Types (Weights)
+impl SageBasedGameHandler {
+    /// The cargo types of the game's stats definition (its current `seq_id`).
+    pub async fn get_cargo_types(
+        rpc: &RpcClient,
+        game: &Game,
+    ) -> Result<Vec<(Pubkey, CargoTypeStats)>, ClientError> {
+        let (_, cargo_types) = Self::get_cargo_types_with_default(rpc, game).await?;
+        Ok(cargo_types)
+    }
+
+    /// The cargo types and the default cargo type (its account) of the game's stats definition.
+    async fn get_cargo_types_with_default(
+        rpc: &RpcClient,
+        game: &Game,
+    ) -> Result<(Pubkey, Vec<(Pubkey, CargoTypeStats)>), ClientError> {
+        let stats_definition_id = game.cargo.stats_definition;
+        utils::rpc_budget().await;
+        let account = rpc.get_account(&stats_definition_id).await?;
+        let stats_definition = CargoStatsDefinition::deserialize(&mut &account.data[8..])?;
+
+        let cargo_types = filter::cargo_types_by_stats_definition(
+            rpc,
+            &stats_definition_id,
+            stats_definition.seq_id,
+        )
+        .await?;
+
+        Ok((stats_definition.default_cargo_type, cargo_types))
+    }
+
+    /// The cargo weights of the game, a mint without a cargo type weighs the default cargo type.
+    pub async fn get_cargo_weights(
+        rpc: &RpcClient,
+        game: &Game,
+    ) -> Result<CargoWeights, ClientError> {
+        let (default_cargo_type, cargo_types) =
+            Self::get_cargo_types_with_default(rpc, game).await?;
+
+        let default_weight = match cargo_types
+            .iter()
+            .find(|(cargo_type_id, _)| cargo_type_id == &default_cargo_type)
+        {
+            Some((_, cargo_type)) => cargo_type.cargo_space(),
+            None => {
+                log::error!("Default cargo type {} not found", default_cargo_type);
+                return Err(ClientError::AccountNotFound);
+            }
+        };
+
+        let cargo_types: Vec<CargoTypeStats> = cargo_types
+            .into_iter()
+            .map(|(_, cargo_type)| cargo_type)
+            .collect();
+
+        Ok(CargoWeights::new(&cargo_types, default_weight))
+    }
+}
+
+// Fleet Inventory
+impl SageBasedGameHandler {
+    /// The fleet's cargo pods (the game's consumables and the resources) in one
+    /// `getMultipleAccounts` call.
+    pub async fn get_fleet_inventory(
+        rpc: &RpcClient,
+        game: &Game,
+        fleet: &Fleet,
+        resource_mints: &[Pubkey],
+    ) -> Result<FleetInventory, ClientError> {
+        let token_accounts = FleetInventory::token_accounts(fleet, &game.mints, resource_mints);
+        utils::rpc_budget().await;
+        let accounts = rpc.get_multiple_accounts(&token_accounts).await?;
+
+        Ok(FleetInventory::from_token_accounts(
+            fleet,
+            &game.mints,
+            resource_mints,
+            &accounts,
+        ))
+    }
+}
+
 // Token Accounts (Parsed)
 impl SageBasedGameHandler {
+    /// The amount of `mint` in the associated token account of `owner` (none without an account).
+    pub async fn associated_token_amount(
+        rpc: &RpcClient,
+        owner: &Pubkey,
+        mint: &Pubkey,
+    ) -> Result<u64, ClientError> {
+        let mints = [*mint];
+        let token_accounts = PodInventory::token_accounts(owner, &mints);
+        utils::rpc_budget().await;
+        let accounts = rpc.get_multiple_accounts(&token_accounts).await?;
+
+        let amount = PodInventory::decode_amounts(&mints, &accounts)
+            .first()
+            .map(|(_, amount)| *amount)
+            .unwrap_or(0);
+
+        Ok(amount)
+    }
+
     pub async fn parsed_token_account_amounts(
         rpc: &RpcClient,
         owner: &Pubkey,
-    ) -> Vec<(String, u64)> {
+    ) -> Result<Vec<(String, u64)>, ClientError> {
+        utils::rpc_budget().await;
         let accounts = rpc
             .get_token_accounts_by_owner(owner, TokenAccountsFilter::ProgramId(spl_token::id()))
-            .await
-            .unwrap();
+            .await?;
 
         let token_amounts: Vec<(String, u64)> = accounts
             .iter()
@@ -345,7 +777,27 @@ impl SageBasedGameHandler {
             })
             .collect();
 
-        token_amounts
+        Ok(token_amounts)
+    }
+}
+
+// Transaction Fee
+impl SageBasedGameHandler {
+    pub async fn get_transaction_fee(
+        rpc: &RpcClient,
+        signature: &Signature,
+    ) -> Result<u64, ClientError> {
+        let config = RpcTransactionConfig {
+            encoding: Some(UiTransactionEncoding::Base64),
+            commitment: Some(CommitmentConfig::confirmed()),
+            max_supported_transaction_version: Some(0),
+        };
+
+        utils::rpc_budget().await;
+        let tx = rpc.get_transaction_with_config(signature, config).await?;
+        let fee = tx.transaction.meta.map(|meta| meta.fee).unwrap_or(0);
+
+        Ok(fee)
     }
 }
 
@@ -357,6 +809,7 @@ impl SageBasedGameHandler {
         signers: &Vec<&dyn Signer>,
     ) -> Result<Response<RpcSimulateTransactionResult>, ClientError> {
         let rpc_client = program.async_rpc();
+        utils::rpc_budget().await;
         let recent_blockhash = rpc_client.get_latest_blockhash().await?;
 
         let tx = Transaction::new_signed_with_payer(
@@ -366,6 +819,7 @@ impl SageBasedGameHandler {
             recent_blockhash,
         );
 
+        utils::rpc_budget().await;
         let response = rpc_client.simulate_transaction(&tx).await?;
         Ok(response)
     }
@@ -377,6 +831,13 @@ impl SageBasedGameHandler {
     ) -> Result<Signature, ClientError> {
         // protection against sending transactions to a program that is not the Sage program
         assert_eq!(program.id(), program::SAGE_ID, "invalid program id");
+        // ...and against instructions for programs other than Sage (or the marketplace restock)
+        for ix in instructions {
+            assert!(
+                program::ALLOWED_PROGRAM_IDS.contains(&ix.program_id),
+                "invalid instruction program id"
+            );
+        }
 
         let mut builder = program.request();
 
@@ -393,7 +854,59 @@ impl SageBasedGameHandler {
             .into_iter()
             .fold(builder, |builder, i| builder.instruction(i.clone()));
 
-        builder.send().await
+        utils::rpc_budget().await;
+        let tx = builder.signed_transaction().await?;
+        Self::broadcast_transaction(&tx).await;
+
+        let rpc_client = program.async_rpc();
+        utils::rpc_budget().await;
+        let signature = rpc_client.send_transaction(&tx).await?;
+
+        // the confirmation polls (each within the budget), until the blockhash expires
+        let commitment = rpc_client.commitment();
+        for poll in 1.. {
+            tokio::time::sleep(CONFIRM_POLL_INTERVAL).await;
+
+            utils::rpc_budget().await;
+            match rpc_client
+                .get_signature_status_with_commitment(&signature, commitment)
+                .await?
+            {
+                Some(Ok(())) => return Ok(signature),
+                Some(Err(err)) => return Err(SolanaClientError::from(err).into()),
+                None => {}
+            }
+
+            if poll % BLOCKHASH_CHECK_POLLS == 0 {
+                utils::rpc_budget().await;
+                let blockhash = &tx.message.recent_blockhash;
+                if !rpc_client.is_blockhash_valid(blockhash, commitment).await? {
+                    break;
+                }
+            }
+        }
+
+        let err = RpcError::ForUser(format!("Transaction {} expired unconfirmed", signature));
+        Err(SolanaClientError::from(err).into())
+    }
+
+    /// Sends a copy of the transaction to the `BROADCAST_CLIENTS` (if any, without confirmation).
+    async fn broadcast_transaction(tx: &Transaction) {
+        let broadcast_clients = BROADCAST_CLIENTS
+            .try_with(|clients| clients.clone())
+            .unwrap_or_default();
+
+        let config = RpcSendTransactionConfig {
+            skip_preflight: true,
+            ..Default::default()
+        };
+
+        for (url, rpc_client) in broadcast_clients {
+            utils::rpc_budget().await;
+            if let Err(err) = rpc_client.send_transaction_with_config(tx, config).await {
+                log::warn!("Broadcast to {} failed: {:?}", url, err);
+            }
+        }
     }
 
     pub async fn simulate_and_send_transaction<C: Deref<Target = impl Signer> + Clone>(
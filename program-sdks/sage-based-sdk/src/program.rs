@@ -1,5 +1,13 @@
+use anchor_client::anchor_lang::prelude::Pubkey;
+
 pub use staratlas_cargo;
+pub use staratlas_marketplace;
 pub use staratlas_sage;
 
 pub use staratlas_cargo::ID as CARGO_ID;
+pub use staratlas_marketplace::ID as MARKETPLACE_ID;
 pub use staratlas_sage::ID as SAGE_ID;
+
+/// Programs the handler's transactions are allowed to invoke.
+pub const ALLOWED_PROGRAM_IDS: [Pubkey; 3] =
+    [SAGE_ID, MARKETPLACE_ID, spl_associated_token_account::ID];
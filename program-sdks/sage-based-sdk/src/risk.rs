@@ -0,0 +1,93 @@
+//! Risk zones of the game (`Game.risk_zones`), the zone and risk level of a sector.
+
+use crate::accounts::types;
+use crate::calc;
+
+use std::str::FromStr;
+
+/// Risk of a zone, from the safest to the riskiest.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum RiskLevel {
+    Secure,
+    Medium,
+    High,
+}
+
+impl FromStr for RiskLevel {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "secure" => Ok(RiskLevel::Secure),
+            "medium" => Ok(RiskLevel::Medium),
+            "high" => Ok(RiskLevel::High),
+            _ => Err(format!(
+                "Invalid risk level: {} (secure, medium or high)",
+                s
+            )),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RiskZone {
+    MudSecurity,
+    OniSecurity,
+    UsturSecurity,
+    MediumRisk,
+    HighRisk,
+    Unzoned, // beyond the zones (treated as high risk)
+}
+
+impl RiskZone {
+    pub fn level(&self) -> RiskLevel {
+        match self {
+            RiskZone::MudSecurity | RiskZone::OniSecurity | RiskZone::UsturSecurity => {
+                RiskLevel::Secure
+            }
+            RiskZone::MediumRisk => RiskLevel::Medium,
+            RiskZone::HighRisk | RiskZone::Unzoned => RiskLevel::High,
+        }
+    }
+}
+
+impl types::RiskZoneData {
+    /// The sector is within the zone's radius (sectors) of its center.
+    pub fn contains(&self, sector: [i64; 2]) -> bool {
+        calc::sector_distance(self.center, sector) <= self.radius as f32
+    }
+}
+
+impl types::RiskZonesData {
+    /// The zone of a sector, a security zone first, then the smallest risk zone containing it.
+    pub fn zone(&self, sector: [i64; 2]) -> RiskZone {
+        let security_zones = [
+            (&self.mud_security_zone, RiskZone::MudSecurity),
+            (&self.oni_security_zone, RiskZone::OniSecurity),
+            (&self.ustur_security_zone, RiskZone::UsturSecurity),
+        ];
+
+        if let Some((_, zone)) = security_zones
+            .iter()
+            .find(|(zone_data, _)| zone_data.contains(sector))
+        {
+            return *zone;
+        }
+
+        let mut risk_zones = [
+            (&self.medium_risk_zone, RiskZone::MediumRisk),
+            (&self.high_risk_zone, RiskZone::HighRisk),
+        ];
+        risk_zones.sort_by_key(|(zone_data, _)| zone_data.radius);
+
+        risk_zones
+            .iter()
+            .find(|(zone_data, _)| zone_data.contains(sector))
+            .map(|(_, zone)| *zone)
+            .unwrap_or(RiskZone::Unzoned)
+    }
+
+    pub fn risk_level(&self, sector: [i64; 2]) -> RiskLevel {
+        self.zone(sector).level()
+    }
+}
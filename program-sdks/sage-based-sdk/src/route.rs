@@ -0,0 +1,527 @@
+//! Multi-hop routes over the sector grid, for the sectors beyond one warp of a fleet.
+
+use crate::accounts::types;
+use crate::calc;
+
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+use std::str::FromStr;
+
+/// What a route minimizes, the other one breaks the ties.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum RouteCost {
+    #[default]
+    Time,
+    Fuel,
+}
+
+impl FromStr for RouteCost {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "time" => Ok(RouteCost::Time),
+            "fuel" => Ok(RouteCost::Fuel),
+            _ => Err(format!("Invalid route cost: {} (time or fuel)", s)),
+        }
+    }
+}
+
+/// A warp lane between two sectors (both ways), the duration and fuel of one jump.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WarpLane {
+    pub from_sector: [i64; 2],
+    pub to_sector: [i64; 2],
+    pub duration: f32, // seconds
+    pub fuel: f32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HopKind {
+    Warp,
+    WarpLane,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Hop {
+    pub kind: HopKind,
+    pub from_sector: [i64; 2],
+    pub to_sector: [i64; 2],
+    pub duration: f32,  // seconds
+    pub cool_down: f32, // seconds, the wait for the warp cooldown before the next hop (none last)
+    pub fuel: f32,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Route {
+    pub hops: Vec<Hop>,
+    pub duration: f32, // seconds
+    pub fuel: f32,
+}
+
+impl Route {
+    /// The sectors the fleet stops at, the destination last.
+    pub fn waypoints(&self) -> Vec<[i64; 2]> {
+        self.hops.iter().map(|hop| hop.to_sector).collect()
+    }
+
+    /// The sector of the first hop, `None` at the destination.
+    pub fn next_waypoint(&self) -> Option<[i64; 2]> {
+        self.hops.first().map(|hop| hop.to_sector)
+    }
+
+    /// The fuel of the costliest hop (the fuel tank must hold it).
+    pub fn max_hop_fuel(&self) -> f32 {
+        self.hops.iter().map(|hop| hop.fuel).fold(0.0, f32::max)
+    }
+}
+
+#[derive(Default)]
+pub struct RouteOptions {
+    pub cost: RouteCost,
+    pub warp_lanes: Vec<WarpLane>,
+    /// The sectors a route doesn't stop at (the destination excluded).
+    pub avoid: Option<Box<dyn Fn([i64; 2]) -> bool>>,
+}
+
+impl RouteOptions {
+    fn key(&self, duration: f32, fuel: f32) -> (f32, f32) {
+        match self.cost {
+            RouteCost::Time => (duration, fuel),
+            RouteCost::Fuel => (fuel, duration),
+        }
+    }
+
+    fn avoids(&self, sector: [i64; 2]) -> bool {
+        self.avoid.as_ref().is_some_and(|avoid| avoid(sector))
+    }
+}
+
+/// A sector of the search, by its cost and the estimate of the rest of the route (the lowest
+/// first).
+struct Visit {
+    key: (f32, f32),
+    node: usize,
+}
+
+impl PartialEq for Visit {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Visit {}
+
+impl PartialOrd for Visit {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Visit {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other
+            .key
+            .0
+            .total_cmp(&self.key.0)
+            .then_with(|| other.key.1.total_cmp(&self.key.1))
+    }
+}
+
+/// The sectors a route may detour by around the bounding box of its sectors, in warps (or at
+/// least the size of the bounding box, to go around what it avoids).
+const SEARCH_MARGIN_WARPS: i64 = 4;
+
+/// The sectors of the search, the bounding box of the route's sectors (and warp lanes) and the
+/// detour margin around it.
+struct Grid {
+    min: [i64; 2],
+    width: i64,
+    height: i64,
+}
+
+impl Grid {
+    fn new(sectors: &[[i64; 2]], radius: i64) -> Self {
+        let min_x = sectors.iter().map(|s| s[0]).min().unwrap();
+        let min_y = sectors.iter().map(|s| s[1]).min().unwrap();
+        let max_x = sectors.iter().map(|s| s[0]).max().unwrap();
+        let max_y = sectors.iter().map(|s| s[1]).max().unwrap();
+
+        let margin = (radius * SEARCH_MARGIN_WARPS).max((max_x - min_x).max(max_y - min_y));
+        let (min_x, min_y) = (min_x - margin, min_y - margin);
+        let (max_x, max_y) = (max_x + margin, max_y + margin);
+
+        Grid {
+            min: [min_x, min_y],
+            width: max_x - min_x + 1,
+            height: max_y - min_y + 1,
+        }
+    }
+
+    fn len(&self) -> usize {
+        (self.width * self.height) as usize
+    }
+
+    fn node(&self, sector: [i64; 2]) -> Option<usize> {
+        let x = sector[0] - self.min[0];
+        let y = sector[1] - self.min[1];
+
+        if x < 0 || y < 0 || x >= self.width || y >= self.height {
+            return None;
+        }
+        Some((y * self.width + x) as usize)
+    }
+
+    fn sector(&self, node: usize) -> [i64; 2] {
+        let node = node as i64;
+        [
+            self.min[0] + node % self.width,
+            self.min[1] + node / self.width,
+        ]
+    }
+}
+
+fn warp_hop(fleet_stats: &types::ShipStats, from_sector: [i64; 2], to_sector: [i64; 2]) -> Hop {
+    let travel = calc::travel(fleet_stats, from_sector, to_sector);
+
+    Hop {
+        kind: HopKind::Warp,
+        from_sector,
+        to_sector,
+        duration: travel.warp_duration,
+        cool_down: (travel.warp_cool_down - travel.warp_duration).max(0.0),
+        fuel: travel.warp_fuel,
+    }
+}
+
+/// The lowest (duration, fuel) from a sector to the destination, the A* heuristic of the search:
+/// warps in a straight line, with the cooldowns of the fewest hops, or the warp to the nearest
+/// warp lane (a lane may shorten any route).
+struct Estimate<'a> {
+    fleet_stats: &'a types::ShipStats,
+    to_sector: [i64; 2],
+    lane_sectors: Vec<[i64; 2]>,
+    max_warp_distance: f32,
+    cool_down: f32, // seconds, the least wait between two warps
+}
+
+impl<'a> Estimate<'a> {
+    fn new(fleet_stats: &'a types::ShipStats, to_sector: [i64; 2], options: &RouteOptions) -> Self {
+        let max_warp_distance = calc::max_warp_distance(fleet_stats);
+        // the longest warp waits the least for the cooldown
+        let longest_warp = calc::warp_duration(fleet_stats, [0, 0], [1, 0]) * max_warp_distance;
+        let cool_down = fleet_stats.movement_stats.warp_cool_down as f32;
+
+        Estimate {
+            fleet_stats,
+            to_sector,
+            lane_sectors: options
+                .warp_lanes
+                .iter()
+                .flat_map(|lane| [lane.from_sector, lane.to_sector])
+                .collect(),
+            max_warp_distance,
+            cool_down: (cool_down - longest_warp).max(0.0),
+        }
+    }
+
+    /// The warps in a straight line, the fuel of each hop rounds up to at least the whole's.
+    fn warps(&self, from_sector: [i64; 2], to_sector: [i64; 2]) -> (f32, f32) {
+        (
+            calc::warp_duration(self.fleet_stats, from_sector, to_sector),
+            calc::warp_fuel_consumption(self.fleet_stats, from_sector, to_sector),
+        )
+    }
+
+    fn cost(&self, sector: [i64; 2]) -> (f32, f32) {
+        let (mut duration, mut fuel) = self.warps(sector, self.to_sector);
+
+        if self.max_warp_distance > 0.0 {
+            // the epsilon keeps a distance of whole warps from rounding up to one more hop
+            let distance = calc::sector_distance(sector, self.to_sector);
+            let hops = (distance / self.max_warp_distance - 1e-4).ceil().max(1.0);
+            duration += (hops - 1.0) * self.cool_down;
+        }
+
+        for lane_sector in &self.lane_sectors {
+            let (lane_duration, lane_fuel) = self.warps(sector, *lane_sector);
+            duration = duration.min(lane_duration);
+            fuel = fuel.min(lane_fuel);
+        }
+        (duration, fuel)
+    }
+}
+
+/// The route of a fleet between two sectors by warps (within its max warp distance) and warp
+/// lanes, `None` when the destination can't be reached.
+pub fn plan_route(
+    fleet_stats: &types::ShipStats,
+    from_sector: [i64; 2],
+    to_sector: [i64; 2],
+    options: &RouteOptions,
+) -> Option<Route> {
+    if from_sector == to_sector {
+        return Some(Route::default());
+    }
+
+    let max_warp_distance = calc::max_warp_distance(fleet_stats);
+    let radius = if fleet_stats.movement_stats.warp_speed > 0 {
+        max_warp_distance.floor() as i64
+    } else {
+        0
+    };
+
+    // the sectors within one warp
+    let mut offsets = vec![];
+    for dx in -radius..=radius {
+        for dy in -radius..=radius {
+            if (dx, dy) != (0, 0) && calc::sector_distance([0, 0], [dx, dy]) <= max_warp_distance {
+                offsets.push([dx, dy]);
+            }
+        }
+    }
+
+    let mut sectors = vec![from_sector, to_sector];
+    for lane in &options.warp_lanes {
+        sectors.push(lane.from_sector);
+        sectors.push(lane.to_sector);
+    }
+    let grid = Grid::new(&sectors, radius);
+
+    let start = grid.node(from_sector)?;
+    let target = grid.node(to_sector)?;
+
+    let estimate = Estimate::new(fleet_stats, to_sector, options);
+    let key = |(duration, fuel): (f32, f32), sector: [i64; 2]| {
+        let (rest_duration, rest_fuel) = estimate.cost(sector);
+        options.key(duration + rest_duration, fuel + rest_fuel)
+    };
+
+    let mut costs: Vec<Option<(f32, f32)>> = vec![None; grid.len()]; // (Duration, Fuel)
+    let mut previous: Vec<Option<Hop>> = vec![None; grid.len()];
+    let mut visited = vec![false; grid.len()];
+    let mut heap = BinaryHeap::new();
+
+    costs[start] = Some((0.0, 0.0));
+    heap.push(Visit {
+        key: key((0.0, 0.0), from_sector),
+        node: start,
+    });
+
+    while let Some(Visit { node, .. }) = heap.pop() {
+        if node == target {
+            break;
+        }
+        if visited[node] {
+            continue; // visited at a lower cost, the estimate never overshoots a hop
+        }
+        visited[node] = true;
+
+        let (duration, fuel) = costs[node].unwrap();
+        let sector = grid.sector(node);
+        let mut hops: Vec<Hop> = offsets
+            .iter()
+            .map(|offset| [sector[0] + offset[0], sector[1] + offset[1]])
+            .filter(|next| grid.node(*next).is_some_and(|next| !visited[next]))
+            .map(|next| warp_hop(fleet_stats, sector, next))
+            .collect();
+
+        for lane in &options.warp_lanes {
+            let to = if lane.from_sector == sector {
+                lane.to_sector
+            } else if lane.to_sector == sector {
+                lane.from_sector
+            } else {
+                continue;
+            };
+
+            hops.push(Hop {
+                kind: HopKind::WarpLane,
+                from_sector: sector,
+                to_sector: to,
+                duration: lane.duration,
+                cool_down: 0.0,
+                fuel: lane.fuel,
+            });
+        }
+
+        for mut hop in hops {
+            let next = grid.node(hop.to_sector).unwrap();
+            if next == target {
+                hop.cool_down = 0.0; // arrived, no next hop to wait for
+            } else if options.avoids(hop.to_sector) {
+                continue;
+            }
+
+            let cost = (duration + hop.duration + hop.cool_down, fuel + hop.fuel);
+            let lower = match costs[next] {
+                Some((d, f)) => options.key(cost.0, cost.1) < options.key(d, f),
+                None => true,
+            };
+
+            if lower {
+                costs[next] = Some(cost);
+                previous[next] = Some(hop);
+                heap.push(Visit {
+                    key: key(cost, hop.to_sector),
+                    node: next,
+                });
+            }
+        }
+    }
+
+    let (duration, fuel) = costs[target]?;
+
+    let mut hops = vec![];
+    let mut node = target;
+    while node != start {
+        let hop = previous[node]?;
+        node = grid.node(hop.from_sector)?;
+        hops.push(hop);
+    }
+    hops.reverse();
+
+    Some(Route {
+        hops,
+        duration,
+        fuel,
+    })
+}
+
+// cargo test -p staratlas-sage-based-sdk --features anchor-client/async
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::fixtures::zeroed;
+
+    /// A fleet warping 2 sectors (1 sector/s, 1 fuel/sector) with a 10s cooldown.
+    fn fleet_stats() -> types::ShipStats {
+        let mut stats: types::ShipStats = zeroed();
+        stats.movement_stats.warp_speed = 1_000_000;
+        stats.movement_stats.max_warp_distance = 200;
+        stats.movement_stats.warp_cool_down = 10;
+        stats.movement_stats.warp_fuel_consumption_rate = 100;
+        stats
+    }
+
+    #[test]
+    fn plans_a_direct_hop() {
+        let route = plan_route(&fleet_stats(), [0, 0], [2, 0], &RouteOptions::default()).unwrap();
+
+        assert_eq!(route.waypoints(), vec![[2, 0]]);
+        assert_eq!(route.hops[0].kind, HopKind::Warp);
+        assert_eq!(route.duration, 2.0); // no cooldown after the last hop
+        assert_eq!(route.hops[0].cool_down, 0.0);
+        assert_eq!(route.fuel, 2.0);
+
+        let route = plan_route(&fleet_stats(), [0, 0], [0, 0], &RouteOptions::default()).unwrap();
+        assert!(route.hops.is_empty());
+    }
+
+    #[test]
+    fn plans_multiple_hops() {
+        let route = plan_route(&fleet_stats(), [0, 0], [6, 0], &RouteOptions::default()).unwrap();
+
+        assert_eq!(route.waypoints(), vec![[2, 0], [4, 0], [6, 0]]);
+        // two cooldowns, the last hop arrives after its warp
+        assert_eq!(route.duration, 10.0 + 10.0 + 2.0);
+        assert_eq!(route.fuel, 6.0);
+        assert_eq!(route.max_hop_fuel(), 2.0);
+        assert_eq!(
+            route.duration,
+            route
+                .hops
+                .iter()
+                .map(|hop| hop.duration + hop.cool_down)
+                .sum::<f32>()
+        );
+    }
+
+    #[test]
+    fn plans_around_the_avoided_sectors() {
+        let options = RouteOptions {
+            avoid: Some(Box::new(|sector| sector[0] == 2 && sector[1].abs() <= 1)),
+            ..Default::default()
+        };
+        let route = plan_route(&fleet_stats(), [0, 0], [4, 0], &options).unwrap();
+
+        assert!(route
+            .waypoints()
+            .iter()
+            .all(|&[x, y]| x != 2 || y.abs() > 1));
+        assert_eq!(route.waypoints().last(), Some(&[4, 0]));
+
+        // a wall of avoided sectors wider than the bounding box and one warp
+        let wall = |[x, y]: [i64; 2]| (1..=3).contains(&x) && y.abs() <= 5;
+        let options = RouteOptions {
+            avoid: Some(Box::new(wall)),
+            ..Default::default()
+        };
+        let route = plan_route(&fleet_stats(), [0, 0], [4, 0], &options).unwrap();
+        assert!(route.waypoints().iter().all(|&sector| !wall(sector)));
+        assert_eq!(route.waypoints().last(), Some(&[4, 0]));
+    }
+
+    #[test]
+    fn plans_by_warp_lanes() {
+        let options = RouteOptions {
+            warp_lanes: vec![WarpLane {
+                from_sector: [10, 0],
+                to_sector: [0, 0],
+                duration: 1.0,
+                fuel: 1.0,
+            }],
+            ..Default::default()
+        };
+        let route = plan_route(&fleet_stats(), [0, 0], [10, 0], &options).unwrap();
+
+        assert_eq!(route.hops.len(), 1);
+        assert_eq!(route.hops[0].kind, HopKind::WarpLane);
+    }
+
+    #[test]
+    fn plans_across_the_galaxy_in_time() {
+        // a fleet warping 25 sectors, the search must not expand every sector within a warp
+        let mut stats = fleet_stats();
+        stats.movement_stats.max_warp_distance = 2_500;
+        stats.movement_stats.warp_cool_down = 120;
+
+        let start = std::time::Instant::now();
+        let route = plan_route(&stats, [-40, -40], [40, 40], &RouteOptions::default()).unwrap();
+        let elapsed = start.elapsed();
+
+        assert_eq!(route.hops.len(), 5); // 113 sectors
+        assert_eq!(route.waypoints().last(), Some(&[40, 40]));
+        assert!(route
+            .hops
+            .iter()
+            .all(|hop| calc::sector_distance(hop.from_sector, hop.to_sector) <= 25.0));
+        assert!(elapsed.as_secs_f32() < 1.0, "planned in {:?}", elapsed);
+
+        let options = RouteOptions {
+            cost: RouteCost::Fuel,
+            ..Default::default()
+        };
+        let start = std::time::Instant::now();
+        let route = plan_route(&stats, [-40, -40], [40, 40], &options).unwrap();
+        let elapsed = start.elapsed();
+
+        assert_eq!(route.fuel, 114.0); // rounded up by hop
+        assert!(elapsed.as_secs_f32() < 1.0, "planned in {:?}", elapsed);
+    }
+
+    #[test]
+    fn doesnt_plan_an_unreachable_destination() {
+        let options = RouteOptions {
+            avoid: Some(Box::new(|sector| sector != [0, 0])),
+            ..Default::default()
+        };
+        assert!(plan_route(&fleet_stats(), [0, 0], [6, 0], &options).is_none());
+
+        // a fleet without a warp drive
+        let mut stats = fleet_stats();
+        stats.movement_stats.warp_speed = 0;
+        assert!(plan_route(&stats, [0, 0], [1, 0], &RouteOptions::default()).is_none());
+    }
+}
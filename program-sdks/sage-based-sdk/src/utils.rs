@@ -8,3 +8,17 @@ pub(crate) fn str_to_u8_32(s: &str) -> [u8; 32] {
 
     array
 }
+
+/// The current unix timestamp (seconds).
+pub(crate) fn unix_timestamp() -> i64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs() as i64)
+}
+
+/// Waits for the budget of an RPC call (`RPC_BUDGET`), at once outside of a budget's scope.
+pub(crate) async fn rpc_budget() {
+    if let Ok(acquire) = crate::RPC_BUDGET.try_with(|acquire| acquire()) {
+        acquire.await;
+    }
+}
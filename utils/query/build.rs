@@ -0,0 +1,320 @@
+//! Generates the fields (offset and type) of the programs' accounts from their Anchor IDL, the
+//! fields at a fixed offset (up to the first variable size one) of each `state` account and of
+//! each fixed size `typedefs` struct.
+
+use serde_json::Value;
+
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::path::Path;
+
+// (program dir, crate, feature)
+const PROGRAMS: &[(&str, &str, &str)] = &[
+    ("atlas-staking", "staratlas_atlas_staking", "atlas-staking"),
+    ("cargo", "staratlas_cargo", "cargo"),
+    ("claim", "staratlas_claim", "claim"),
+    ("crafting", "staratlas_crafting", "crafting"),
+    ("locked-voter", "staratlas_locked_voter", "locked-voter"),
+    ("marketplace", "staratlas_marketplace", "marketplace"),
+    (
+        "player-profile",
+        "staratlas_player_profile",
+        "player-profile",
+    ),
+    ("points", "staratlas_points", "points"),
+    ("profile-vault", "staratlas_profile_vault", "profile-vault"),
+    (
+        "proxy-rewarder",
+        "staratlas_proxy_rewarder",
+        "proxy-rewarder",
+    ),
+    ("sage", "staratlas_sage", "sage"),
+    ("score", "staratlas_score", "score"),
+];
+
+const KEYWORDS: &[&str] = &[
+    "as", "break", "const", "continue", "crate", "else", "enum", "extern", "false", "fn", "for",
+    "if", "impl", "in", "let", "loop", "match", "mod", "move", "mut", "pub", "ref", "return",
+    "self", "static", "struct", "super", "trait", "true", "type", "unsafe", "use", "where",
+    "while", "async", "await", "dyn", "abstract", "become", "box", "do", "final", "macro",
+    "override", "priv", "typeof", "unsized", "virtual", "yield", "try",
+];
+
+/// The IDL's type definitions (`types` and `accounts`) by name.
+struct Defs<'a>(HashMap<&'a str, &'a Value>);
+
+impl<'a> Defs<'a> {
+    fn new(idl: &'a Value) -> Self {
+        let mut defs = HashMap::new();
+        for key in ["types", "accounts"] {
+            for def in idl[key].as_array().into_iter().flatten() {
+                defs.insert(def["name"].as_str().unwrap(), def);
+            }
+        }
+        Defs(defs)
+    }
+
+    /// The borsh size of a type, `None` when it varies (vec, string, option, enum with data).
+    fn size(&self, ty: &Value) -> Option<usize> {
+        match ty {
+            Value::String(ty) => match ty.as_str() {
+                "bool" | "u8" | "i8" => Some(1),
+                "u16" | "i16" => Some(2),
+                "u32" | "i32" | "f32" => Some(4),
+                "u64" | "i64" | "f64" => Some(8),
+                "u128" | "i128" => Some(16),
+                "publicKey" => Some(32),
+                _ => None,
+            },
+            Value::Object(ty) => {
+                if let Some(array) = ty.get("array") {
+                    let len = array[1].as_u64()? as usize;
+                    return self.size(&array[0]).map(|size| size * len);
+                }
+
+                let def = self.0.get(ty.get("defined")?.as_str()?)?;
+                match def["type"]["kind"].as_str()? {
+                    "struct" => def["type"]["fields"]
+                        .as_array()?
+                        .iter()
+                        .map(|field| self.size(&field["type"]))
+                        .sum(),
+                    "enum" => {
+                        let variants = def["type"]["variants"].as_array()?;
+                        variants
+                            .iter()
+                            .all(|variant| variant.get("fields").is_none())
+                            .then_some(1)
+                    }
+                    _ => None,
+                }
+            }
+            _ => None,
+        }
+    }
+
+    /// The Rust type of a field's value (a defined type is its bytes).
+    fn value_type(&self, ty: &Value) -> Option<String> {
+        match ty {
+            Value::String(ty) => match ty.as_str() {
+                "publicKey" => Some("Pubkey".to_string()),
+                "bool" | "u8" | "i8" | "u16" | "i16" | "u32" | "i32" | "f32" | "u64" | "i64"
+                | "f64" | "u128" | "i128" => Some(ty.to_string()),
+                _ => None,
+            },
+            Value::Object(object) => {
+                if let Some(array) = object.get("array") {
+                    let len = array[1].as_u64()?;
+                    return match self.value_type(&array[0]) {
+                        Some(value_type) => Some(format!("[{}; {}]", value_type, len)),
+                        None => Some(format!("[u8; {}]", self.size(ty)?)),
+                    };
+                }
+                Some(format!("[u8; {}]", self.size(ty)?))
+            }
+            _ => None,
+        }
+    }
+
+    /// The fields at a fixed offset, a struct's fields also by `<field>_<sub field>`, and the
+    /// offset after them (of the first variable size field), `Err` when there is one.
+    fn fields(
+        &self,
+        prefix: &str,
+        offset: usize,
+        fields: &Value,
+        out: &mut Vec<Field>,
+    ) -> Result<usize, usize> {
+        let mut offset = offset;
+
+        for field in fields.as_array().into_iter().flatten() {
+            let ty = &field["type"];
+            let name = format!("{}{}", prefix, snake_case(field["name"].as_str().unwrap()));
+
+            let Some(size) = self.size(ty) else {
+                return Err(offset); // the following fields have no fixed offset
+            };
+
+            if let Some(value_type) = self.value_type(ty) {
+                out.push(Field {
+                    name: name.clone(),
+                    offset,
+                    value_type,
+                });
+            }
+
+            let sub_fields = ty
+                .get("defined")
+                .and_then(|defined| self.0.get(defined.as_str()?))
+                .filter(|def| def["type"]["kind"] == "struct");
+            if let Some(def) = sub_fields {
+                let _ = self.fields(&format!("{}_", name), offset, &def["type"]["fields"], out);
+            }
+
+            offset += size;
+        }
+
+        Ok(offset)
+    }
+}
+
+struct Field {
+    name: String,
+    offset: usize,
+    value_type: String,
+}
+
+fn snake_case(name: &str) -> String {
+    let mut snake = String::new();
+    let mut previous: Option<char> = None;
+
+    for c in name.chars() {
+        if c.is_uppercase() {
+            if previous.is_some_and(|p| p.is_lowercase() || p.is_ascii_digit()) {
+                snake.push('_');
+            }
+            snake.extend(c.to_lowercase());
+        } else {
+            snake.push(c);
+        }
+        previous = Some(c);
+    }
+
+    snake
+}
+
+fn ident(name: &str) -> String {
+    if KEYWORDS.contains(&name) {
+        format!("r#{}", name)
+    } else {
+        name.to_string()
+    }
+}
+
+/// The fields' struct and its value (`Field`s at their offsets).
+fn write_fields(out: &mut String, doc: &str, name: &str, fields: &mut Vec<Field>) {
+    let mut seen = std::collections::HashSet::new();
+    fields.retain(|field| seen.insert(field.name.clone()));
+
+    writeln!(out, "    /// The fields of `{}`.", doc).unwrap();
+    writeln!(out, "    #[derive(Debug, Clone, Copy)]").unwrap();
+    writeln!(out, "    pub struct {}Fields {{", name).unwrap();
+    for field in fields.iter() {
+        writeln!(
+            out,
+            "        pub {}: Field<{}>,",
+            ident(&field.name),
+            field.value_type
+        )
+        .unwrap();
+    }
+    writeln!(out, "    }}\n").unwrap();
+
+    writeln!(out, "    impl {}Fields {{", name).unwrap();
+    writeln!(
+        out,
+        "        const FIELDS: {}Fields = {}Fields {{",
+        name, name
+    )
+    .unwrap();
+    for field in fields.iter() {
+        writeln!(
+            out,
+            "            {}: Field::new({}),",
+            ident(&field.name),
+            field.offset
+        )
+        .unwrap();
+    }
+    writeln!(out, "        }};").unwrap();
+    writeln!(out, "    }}\n").unwrap();
+}
+
+fn generate_program(out: &mut String, idl: &Value, krate: &str, feature: &str) {
+    let defs = Defs::new(idl);
+    let module = feature.replace('-', "_");
+
+    writeln!(out, "#[cfg(feature = \"{}\")]", feature).unwrap();
+    writeln!(out, "pub mod {} {{", module).unwrap();
+    writeln!(out, "    use super::*;\n").unwrap();
+
+    for account in idl["accounts"].as_array().into_iter().flatten() {
+        let name = account["name"].as_str().unwrap();
+
+        let mut fields = vec![];
+        let (header_size, data_size) =
+            match defs.fields("", 8, &account["type"]["fields"], &mut fields) {
+                Ok(size) => (size, format!("Some({})", size)),
+                Err(offset) => (offset, "None".to_string()),
+            };
+
+        let path = format!("{}::state::{}", krate, name);
+        write_fields(out, &path, name, &mut fields);
+
+        writeln!(out, "    impl IdlAccount for {} {{", path).unwrap();
+        writeln!(out, "        type Fields = {}Fields;\n", name).unwrap();
+        writeln!(out, "        const NAME: &'static str = \"{}\";", name).unwrap();
+        writeln!(out, "        const HEADER_SIZE: usize = {};", header_size).unwrap();
+        writeln!(
+            out,
+            "        const DATA_SIZE: Option<usize> = {};",
+            data_size
+        )
+        .unwrap();
+        writeln!(
+            out,
+            "        const FIELDS: {}Fields = {}Fields::FIELDS;",
+            name, name
+        )
+        .unwrap();
+        writeln!(out, "    }}\n").unwrap();
+    }
+
+    // the fixed size structs, the elements of the accounts' lists
+    for ty in idl["types"].as_array().into_iter().flatten() {
+        if ty["type"]["kind"] != "struct" {
+            continue;
+        }
+
+        let name = ty["name"].as_str().unwrap();
+        let mut fields = vec![];
+        let Ok(size) = defs.fields("", 0, &ty["type"]["fields"], &mut fields) else {
+            continue;
+        };
+
+        let path = format!("{}::typedefs::{}", krate, name);
+        write_fields(out, &path, name, &mut fields);
+
+        writeln!(out, "    impl IdlType for {} {{", path).unwrap();
+        writeln!(out, "        type Fields = {}Fields;\n", name).unwrap();
+        writeln!(out, "        const NAME: &'static str = \"{}\";", name).unwrap();
+        writeln!(out, "        const SIZE: usize = {};", size).unwrap();
+        writeln!(
+            out,
+            "        const FIELDS: {}Fields = {}Fields::FIELDS;",
+            name, name
+        )
+        .unwrap();
+        writeln!(out, "    }}\n").unwrap();
+    }
+
+    writeln!(out, "}}\n").unwrap();
+}
+
+fn main() {
+    let manifest_dir = std::env::var("CARGO_MANIFEST_DIR").unwrap();
+    let programs_dir = Path::new(&manifest_dir).join("../../programs");
+
+    let mut out = String::new();
+    for (dir, krate, feature) in PROGRAMS {
+        let idl_path = programs_dir.join(dir).join("idl.json");
+        println!("cargo:rerun-if-changed={}", idl_path.display());
+
+        let idl = std::fs::read_to_string(&idl_path).unwrap();
+        let idl: Value = serde_json::from_str(&idl).unwrap();
+        generate_program(&mut out, &idl, krate, feature);
+    }
+
+    let out_dir = std::env::var("OUT_DIR").unwrap();
+    std::fs::write(Path::new(&out_dir).join("accounts.rs"), out).unwrap();
+}
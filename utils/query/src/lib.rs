@@ -0,0 +1,379 @@
+//! `getProgramAccounts` filters of the programs' accounts, their fields' offsets are worked out
+//! from the Anchor IDL (`build.rs`) of the program crates (one feature per program):
+//!
+//! ```ignore
+//! let filters = Query::<state::Fleet>::new()
+//!     .eq(|f| f.game_id, game_id)
+//!     .eq(|f| f.owner_profile, player_profile_id)
+//!     .filters();
+//! ```
+//!
+//! The same fields read an account's data (`Field::read`), for the accounts the SDKs decode by hand.
+//! The data after an account's IDL fields is a `List` of an IDL struct (`PROFILE_KEYS`).
+//!
+//! A field missing from a new IDL fails to compile, a moved one gets its new offset.
+
+use solana_client::rpc_filter::{Memcmp, RpcFilterType};
+pub use solana_program::pubkey::Pubkey;
+
+use std::borrow::Borrow;
+use std::marker::PhantomData;
+
+/// A field of an account, its offset (the account's discriminator included) and value type.
+pub struct Field<V> {
+    pub offset: usize,
+    value: PhantomData<fn() -> V>,
+}
+
+impl<V> Field<V> {
+    pub const fn new(offset: usize) -> Self {
+        Field {
+            offset,
+            value: PhantomData,
+        }
+    }
+}
+
+impl<V: FieldValue> Field<V> {
+    /// The field's value in an account's data (the discriminator included), `None` when the
+    /// data is too short.
+    pub fn read(&self, account_data: &[u8]) -> Option<V> {
+        account_data
+            .get(self.offset..self.offset + V::SIZE)
+            .map(V::from_bytes)
+    }
+}
+
+impl<V> Clone for Field<V> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<V> Copy for Field<V> {}
+
+impl<V> std::fmt::Debug for Field<V> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Field")
+            .field("offset", &self.offset)
+            .field("value", &std::any::type_name::<V>())
+            .finish()
+    }
+}
+
+/// A value of a field, its borsh bytes.
+pub trait FieldValue: Sized {
+    /// The number of its bytes.
+    const SIZE: usize;
+
+    fn to_bytes(&self) -> Vec<u8>;
+
+    /// The value of its `SIZE` bytes.
+    fn from_bytes(bytes: &[u8]) -> Self;
+}
+
+macro_rules! impl_field_value {
+    ($($ty:ty),*) => {
+        $(
+            impl FieldValue for $ty {
+                const SIZE: usize = std::mem::size_of::<$ty>();
+
+                fn to_bytes(&self) -> Vec<u8> {
+                    self.to_le_bytes().to_vec()
+                }
+
+                fn from_bytes(bytes: &[u8]) -> Self {
+                    <$ty>::from_le_bytes(bytes[..Self::SIZE].try_into().unwrap())
+                }
+            }
+        )*
+    };
+}
+
+impl_field_value!(u8, i8, u16, i16, u32, i32, f32, u64, i64, f64, u128, i128);
+
+impl FieldValue for bool {
+    const SIZE: usize = 1;
+
+    fn to_bytes(&self) -> Vec<u8> {
+        vec![*self as u8]
+    }
+
+    fn from_bytes(bytes: &[u8]) -> Self {
+        bytes[0] != 0
+    }
+}
+
+impl FieldValue for Pubkey {
+    const SIZE: usize = 32;
+
+    fn to_bytes(&self) -> Vec<u8> {
+        self.as_ref().to_vec()
+    }
+
+    fn from_bytes(bytes: &[u8]) -> Self {
+        Pubkey::try_from(&bytes[..Self::SIZE]).unwrap()
+    }
+}
+
+impl<T: FieldValue, const N: usize> FieldValue for [T; N] {
+    const SIZE: usize = T::SIZE * N;
+
+    fn to_bytes(&self) -> Vec<u8> {
+        self.iter().flat_map(|value| value.to_bytes()).collect()
+    }
+
+    fn from_bytes(bytes: &[u8]) -> Self {
+        std::array::from_fn(|i| T::from_bytes(&bytes[i * T::SIZE..]))
+    }
+}
+
+/// An account of a program's IDL and its fields (at a fixed offset).
+pub trait IdlAccount {
+    type Fields;
+
+    const NAME: &'static str;
+    /// The offset after the fields at a fixed offset (the discriminator included), of the first
+    /// variable size field or of the data after the account (its lists).
+    const HEADER_SIZE: usize;
+    /// The size of the account's data (the discriminator included), `None` when it varies.
+    const DATA_SIZE: Option<usize>;
+    const FIELDS: Self::Fields;
+}
+
+/// A fixed size struct of a program's IDL (an element of an account's list) and its fields, their
+/// offsets from the struct's start.
+pub trait IdlType {
+    type Fields;
+
+    const NAME: &'static str;
+    /// The borsh size of the struct.
+    const SIZE: usize;
+    const FIELDS: Self::Fields;
+}
+
+/// A list of `T`s from an offset of an account's data to its end, the data after the account's
+/// IDL fields.
+pub struct List<T> {
+    pub offset: usize,
+    element: PhantomData<fn() -> T>,
+}
+
+impl<T> List<T> {
+    pub const fn new(offset: usize) -> Self {
+        List {
+            offset,
+            element: PhantomData,
+        }
+    }
+}
+
+impl<T: IdlType> List<T> {
+    /// A field of the `index`th element, its offset in the account's data.
+    pub fn field<V>(&self, index: usize, field: impl FnOnce(&T::Fields) -> Field<V>) -> Field<V> {
+        Field::new(self.offset + index * T::SIZE + field(&T::FIELDS).offset)
+    }
+
+    /// The elements' data in an account's data (a trailing partial element left out), `None`
+    /// when the data is shorter than the list's offset.
+    pub fn elements<'a>(&self, account_data: &'a [u8]) -> Option<std::slice::ChunksExact<'a, u8>> {
+        account_data
+            .get(self.offset..)
+            .map(|data| data.chunks_exact(T::SIZE))
+    }
+}
+
+impl<T> Clone for List<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for List<T> {}
+
+impl<T> std::fmt::Debug for List<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("List")
+            .field("offset", &self.offset)
+            .field("element", &std::any::type_name::<T>())
+            .finish()
+    }
+}
+
+/// The keys of a `Profile` (the auth keys first), after its IDL fields and the 2 bytes the IDL
+/// leaves out.
+#[cfg(feature = "player-profile")]
+pub const PROFILE_KEYS: List<staratlas_player_profile::typedefs::ProfileKey> =
+    List::new(<staratlas_player_profile::state::Profile as IdlAccount>::HEADER_SIZE + 2);
+
+/// The filters of a `getProgramAccounts` query of an account.
+pub struct Query<A: IdlAccount> {
+    filters: Vec<RpcFilterType>,
+    account: PhantomData<A>,
+}
+
+impl<A: IdlAccount> Default for Query<A> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<A: IdlAccount> Query<A> {
+    pub fn new() -> Self {
+        Query {
+            filters: vec![],
+            account: PhantomData,
+        }
+    }
+
+    /// The field equals the value.
+    pub fn eq<V: FieldValue>(
+        self,
+        field: impl FnOnce(&A::Fields) -> Field<V>,
+        value: impl Borrow<V>,
+    ) -> Self {
+        self.eq_field(field(&A::FIELDS), value)
+    }
+
+    /// A field out of the account's IDL fields (of an element of its list) equals the value.
+    pub fn eq_field<V: FieldValue>(mut self, field: Field<V>, value: impl Borrow<V>) -> Self {
+        self.filters
+            .push(RpcFilterType::Memcmp(Memcmp::new_base58_encoded(
+                field.offset,
+                &value.borrow().to_bytes(),
+            )));
+        self
+    }
+
+    /// The account's data size (IDL's), the accounts of an older or newer layout are left out.
+    ///
+    /// Panics on an account without a fixed size (a vec, string or option field).
+    pub fn data_size(mut self) -> Self {
+        let data_size =
+            A::DATA_SIZE.unwrap_or_else(|| panic!("{} has no fixed data size", A::NAME));
+        self.filters.push(RpcFilterType::DataSize(data_size as u64));
+        self
+    }
+
+    pub fn filters(self) -> Vec<RpcFilterType> {
+        self.filters
+    }
+}
+
+include!(concat!(env!("OUT_DIR"), "/accounts.rs"));
+
+// cargo test -p staratlas-utils-query
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn filters_fields_at_their_idl_offsets() {
+        use staratlas_sage::state;
+
+        let key = Pubkey::new_unique();
+        let profile = &key; // a value or a reference
+
+        let filters = Query::<state::Fleet>::new()
+            .eq(|f| f.game_id, key)
+            .eq(|f| f.owner_profile, profile)
+            .filters();
+        assert_eq!(
+            filters,
+            [
+                RpcFilterType::Memcmp(Memcmp::new_base58_encoded(9, key.as_ref())),
+                RpcFilterType::Memcmp(Memcmp::new_base58_encoded(41, key.as_ref())),
+            ]
+        );
+
+        let planet = state::Planet::FIELDS;
+        assert_eq!(planet.game_id.offset, 73);
+        assert_eq!(planet.sector.offset, 105);
+
+        let resource = state::Resource::FIELDS;
+        assert_eq!(resource.game_id.offset, 9);
+        assert_eq!(resource.location.offset, 41);
+        assert_eq!(resource.mine_item.offset, 73);
+
+        assert_eq!(state::MineItem::FIELDS.game_id.offset, 9);
+        assert_eq!(
+            staratlas_cargo::state::CargoPod::FIELDS.authority.offset,
+            41
+        );
+
+        let order = staratlas_marketplace::state::OrderAccount::FIELDS;
+        assert_eq!(order.currency_mint.offset, 40);
+        assert_eq!(order.asset_mint.offset, 72);
+        assert_eq!(
+            staratlas_marketplace::state::OrderAccount::DATA_SIZE,
+            Some(201)
+        );
+
+        // a sector is both its coordinates
+        let filters = Query::<state::Planet>::new()
+            .eq(|f| f.sector, [40i64, 30])
+            .filters();
+        let mut sector = 40i64.to_le_bytes().to_vec();
+        sector.extend(30i64.to_le_bytes());
+        assert_eq!(
+            filters,
+            [RpcFilterType::Memcmp(Memcmp::new_base58_encoded(
+                105, &sector
+            ))]
+        );
+    }
+
+    #[test]
+    fn reads_fields_of_account_data() {
+        use staratlas_sage::state;
+
+        let key = Pubkey::new_unique();
+        let fields = state::Planet::FIELDS;
+
+        let mut account_data = vec![0u8; fields.sector.offset];
+        account_data[fields.game_id.offset..fields.game_id.offset + 32]
+            .copy_from_slice(key.as_ref());
+        account_data.extend(40i64.to_le_bytes());
+        account_data.extend((-30i64).to_le_bytes());
+
+        assert_eq!(fields.game_id.read(&account_data), Some(key));
+        assert_eq!(fields.sector.read(&account_data), Some([40, -30]));
+        assert_eq!(fields.sector.read(&account_data[..120]), None);
+        assert!(Field::<bool>::new(0).read(&[1]).unwrap());
+    }
+
+    #[test]
+    fn filters_and_reads_the_elements_of_a_list() {
+        use staratlas_player_profile::{state, typedefs};
+
+        let profile = state::Profile::FIELDS;
+        assert_eq!(profile.auth_key_count.offset, 9);
+        assert_eq!(state::Profile::HEADER_SIZE, 28);
+        assert_eq!(typedefs::ProfileKey::SIZE, 80);
+        assert_eq!(PROFILE_KEYS.offset, 30);
+
+        let key = Pubkey::new_unique();
+        let filters = Query::<state::Profile>::new()
+            .eq_field(PROFILE_KEYS.field(0, |f| f.key), key)
+            .eq_field(PROFILE_KEYS.field(1, |f| f.scope), key)
+            .filters();
+        assert_eq!(
+            filters,
+            [
+                RpcFilterType::Memcmp(Memcmp::new_base58_encoded(30, key.as_ref())),
+                RpcFilterType::Memcmp(Memcmp::new_base58_encoded(142, key.as_ref())),
+            ]
+        );
+
+        // two keys and a partial one
+        let mut account_data = vec![0u8; 30 + 2 * 80 + 10];
+        account_data[110..142].copy_from_slice(key.as_ref());
+        let elements: Vec<_> = PROFILE_KEYS.elements(&account_data).unwrap().collect();
+        assert_eq!(elements.len(), 2);
+        assert_eq!(
+            PROFILE_KEYS.field(1, |f| f.key).read(&account_data),
+            Some(key)
+        );
+        assert!(PROFILE_KEYS.elements(&account_data[..29]).is_none());
+    }
+}
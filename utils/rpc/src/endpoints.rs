@@ -0,0 +1,547 @@
+use anyhow::{anyhow, bail};
+use serde::Deserialize;
+use solana_client::{
+    client_error::{ClientError, ClientErrorKind},
+    nonblocking::rpc_client::RpcClient,
+    rpc_custom_error::JSON_RPC_SERVER_ERROR_NODE_UNHEALTHY,
+    rpc_request::RpcError,
+};
+
+use std::collections::HashMap;
+use std::error::Error;
+use std::future::Future;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// Smoothing of the error rate (the weight of the latest request).
+const ERROR_RATE_ALPHA: f64 = 0.2;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EndpointRole {
+    Read,
+    Send,
+    #[default]
+    Any,
+}
+
+impl EndpointRole {
+    pub fn serves(&self, role: EndpointRole) -> bool {
+        *self == EndpointRole::Any || *self == role
+    }
+}
+
+impl FromStr for EndpointRole {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "read" => Ok(EndpointRole::Read),
+            "send" => Ok(EndpointRole::Send),
+            "any" => Ok(EndpointRole::Any),
+            _ => Err(anyhow!("Invalid endpoint role: {} (read, send or any)", s)),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+pub struct EndpointCfg {
+    pub url: String,
+    #[serde(default = "default_weight")]
+    pub weight: u32,
+    #[serde(default)]
+    pub role: EndpointRole,
+}
+
+fn default_weight() -> u32 {
+    1
+}
+
+impl FromStr for EndpointCfg {
+    type Err = anyhow::Error;
+
+    /// `url[|weight[|role]]`, e.g. `https://api.mainnet-beta.solana.com|2|read`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = s.trim().split('|');
+
+        let url = match parts.next() {
+            Some(url) if !url.is_empty() => url.to_string(),
+            _ => bail!("Requires an endpoint url: {}", s),
+        };
+        let weight = match parts.next() {
+            Some(weight) => weight.parse()?,
+            None => default_weight(),
+        };
+        let role = match parts.next() {
+            Some(role) => role.parse()?,
+            None => EndpointRole::default(),
+        };
+
+        Ok(EndpointCfg { url, weight, role })
+    }
+}
+
+/// Thresholds of a healthy endpoint.
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+pub struct HealthCfg {
+    #[serde(default = "default_max_slot_lag")]
+    pub max_slot_lag: u64, // behind the highest slot of the endpoints
+    #[serde(default = "default_max_latency_ms")]
+    pub max_latency_ms: u64,
+    #[serde(default = "default_max_error_rate")]
+    pub max_error_rate: f64,
+}
+
+fn default_max_slot_lag() -> u64 {
+    50
+}
+
+fn default_max_latency_ms() -> u64 {
+    2000
+}
+
+fn default_max_error_rate() -> f64 {
+    0.5
+}
+
+impl Default for HealthCfg {
+    fn default() -> Self {
+        HealthCfg {
+            max_slot_lag: default_max_slot_lag(),
+            max_latency_ms: default_max_latency_ms(),
+            max_error_rate: default_max_error_rate(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Deserialize)]
+pub struct EndpointsCfg {
+    #[serde(default)]
+    pub endpoints: Vec<EndpointCfg>,
+    #[serde(default)]
+    pub broadcast_sends: bool, // a transaction goes to every healthy send endpoint
+    #[serde(default)]
+    pub health: HealthCfg,
+}
+
+impl EndpointsCfg {
+    /// The endpoints of a comma separated list of `url[|weight[|role]]` (e.g. `PROVIDER_CLUSTER`).
+    pub fn parse_list(list: &str) -> anyhow::Result<Self> {
+        let endpoints = list
+            .split(',')
+            .filter(|endpoint| !endpoint.trim().is_empty())
+            .map(EndpointCfg::from_str)
+            .collect::<anyhow::Result<Vec<_>>>()?;
+
+        Ok(EndpointsCfg {
+            endpoints,
+            ..Default::default()
+        })
+    }
+}
+
+/// An error of the RPC endpoint (unreachable, timed out, rate limited or unhealthy), the request
+/// may succeed on another endpoint. The solana client error is looked up in the error's sources
+/// (e.g. of an anchor client error or an `anyhow::Error`).
+pub fn is_endpoint_error(err: &(dyn Error + 'static)) -> bool {
+    let mut source = Some(err);
+
+    while let Some(err) = source {
+        if let Some(err) = err.downcast_ref::<ClientError>() {
+            return match err.kind() {
+                ClientErrorKind::Io(_) | ClientErrorKind::Reqwest(_) => true,
+                ClientErrorKind::RpcError(RpcError::RpcRequestError(_)) => true,
+                ClientErrorKind::RpcError(RpcError::RpcResponseError { code, .. }) => {
+                    *code == JSON_RPC_SERVER_ERROR_NODE_UNHEALTHY
+                }
+                _ => false,
+            };
+        }
+        source = err.source();
+    }
+
+    false
+}
+
+/// Health of an endpoint, from the health checks and the reported requests.
+#[derive(Clone, Debug)]
+pub struct EndpointHealth {
+    pub url: String,
+    pub weight: u32,
+    pub role: EndpointRole,
+    pub reachable: bool,
+    pub slot: Option<u64>,
+    pub slot_lag: u64,
+    pub latency: Option<Duration>,
+    pub error_rate: f64,
+    current_weight: i64, // smooth weighted round-robin
+}
+
+impl EndpointHealth {
+    pub fn is_healthy(&self, health: &HealthCfg) -> bool {
+        let latency_ms = self.latency.map(|latency| latency.as_millis() as u64);
+
+        self.reachable
+            && self.slot_lag <= health.max_slot_lag
+            && latency_ms.unwrap_or(0) <= health.max_latency_ms
+            && self.error_rate <= health.max_error_rate
+    }
+
+    pub(crate) fn record(&mut self, ok: bool) {
+        let error = if ok { 0.0 } else { 1.0 };
+        self.error_rate = self.error_rate * (1.0 - ERROR_RATE_ALPHA) + error * ERROR_RATE_ALPHA;
+    }
+}
+
+/// The endpoints of a cluster, the requests go to the healthy endpoints of their role (by weight).
+#[derive(Clone)]
+pub struct RpcEndpoints {
+    pub(crate) health: HealthCfg,
+    broadcast_sends: bool,
+    pub(crate) endpoints: Arc<Mutex<Vec<EndpointHealth>>>,
+    clients: Arc<HashMap<String, Arc<RpcClient>>>, // by url, shared by the requests
+}
+
+impl RpcEndpoints {
+    pub fn new(cfg: EndpointsCfg) -> anyhow::Result<Self> {
+        if cfg.endpoints.is_empty() {
+            bail!("Requires at least one RPC endpoint");
+        }
+
+        let clients = cfg
+            .endpoints
+            .iter()
+            .map(|endpoint| {
+                let client = RpcClient::new(endpoint.url.clone());
+                (endpoint.url.clone(), Arc::new(client))
+            })
+            .collect();
+
+        let endpoints = cfg
+            .endpoints
+            .into_iter()
+            .map(|endpoint| EndpointHealth {
+                url: endpoint.url,
+                weight: endpoint.weight.max(1),
+                role: endpoint.role,
+                reachable: true, // until the first health check
+                slot: None,
+                slot_lag: 0,
+                latency: None,
+                error_rate: 0.0,
+                current_weight: 0,
+            })
+            .collect();
+
+        Ok(RpcEndpoints {
+            health: cfg.health,
+            broadcast_sends: cfg.broadcast_sends,
+            endpoints: Arc::new(Mutex::new(endpoints)),
+            clients: Arc::new(clients),
+        })
+    }
+
+    /// The endpoint of the next read.
+    pub fn read_url(&self) -> String {
+        self.select(EndpointRole::Read)
+    }
+
+    /// The endpoint of the next transaction.
+    pub fn send_url(&self) -> String {
+        self.select(EndpointRole::Send)
+    }
+
+    /// The other healthy send endpoints of a transaction sent to `send_url` (if broadcasting).
+    pub fn broadcast_urls(&self, send_url: &str) -> Vec<String> {
+        if !self.broadcast_sends {
+            return vec![];
+        }
+
+        let endpoints = self.endpoints.lock().unwrap();
+        endpoints
+            .iter()
+            .filter(|endpoint| endpoint.role.serves(EndpointRole::Send))
+            .filter(|endpoint| endpoint.url != send_url && endpoint.is_healthy(&self.health))
+            .map(|endpoint| endpoint.url.clone())
+            .collect()
+    }
+
+    /// The clients of the `broadcast_urls` (one client per endpoint, reused by every send).
+    pub fn broadcast_clients(&self, send_url: &str) -> Vec<(String, Arc<RpcClient>)> {
+        self.broadcast_urls(send_url)
+            .into_iter()
+            .filter_map(|url| {
+                let client = self.clients.get(&url)?.clone();
+                Some((url, client))
+            })
+            .collect()
+    }
+
+    /// Records the outcome of a request (the error rate of its endpoint).
+    pub fn report(&self, url: &str, ok: bool) {
+        let mut endpoints = self.endpoints.lock().unwrap();
+
+        if let Some(endpoint) = endpoints.iter_mut().find(|endpoint| endpoint.url == url) {
+            let was_healthy = endpoint.is_healthy(&self.health);
+            endpoint.record(ok);
+
+            if was_healthy && !endpoint.is_healthy(&self.health) {
+                log::warn!("RPC endpoint unhealthy (error rate): {}", endpoint.url);
+            }
+        }
+    }
+
+    /// The endpoints of a role in the order of a failover: the next selected endpoint, then the
+    /// other endpoints of the role (the healthy ones first, by error rate).
+    pub fn failover_urls(&self, role: EndpointRole) -> Vec<String> {
+        let selected = self.select(role);
+
+        let mut endpoints: Vec<EndpointHealth> = self
+            .status()
+            .into_iter()
+            .filter(|endpoint| endpoint.role.serves(role) && endpoint.url != selected)
+            .collect();
+        endpoints.sort_by(|a, b| {
+            (!a.is_healthy(&self.health), a.error_rate)
+                .partial_cmp(&(!b.is_healthy(&self.health), b.error_rate))
+                .unwrap()
+        });
+
+        std::iter::once(selected)
+            .chain(endpoints.into_iter().map(|endpoint| endpoint.url))
+            .collect()
+    }
+
+    /// Runs a request on the endpoints of a role until one succeeds (see `failover_urls`), the
+    /// endpoint errors (`is_endpoint_error`, e.g. a timeout) are reported and failed over, any
+    /// other error is returned at once.
+    pub async fn with_failover<T, E, F, Fut>(
+        &self,
+        role: EndpointRole,
+        is_endpoint_error: impl Fn(&E) -> bool,
+        mut request: F,
+    ) -> Result<T, E>
+    where
+        F: FnMut(String) -> Fut,
+        Fut: Future<Output = Result<T, E>>,
+    {
+        let urls = self.failover_urls(role);
+        let last = urls.len() - 1;
+
+        for (i, url) in urls.into_iter().enumerate() {
+            let result = request(url.clone()).await;
+
+            match &result {
+                Err(err) if is_endpoint_error(err) => {
+                    self.report(&url, false);
+
+                    if i < last {
+                        log::warn!("RPC endpoint failed, failing over: {}", url);
+                        continue;
+                    }
+                }
+                Err(_) => {}
+                Ok(_) => self.report(&url, true),
+            }
+
+            return result;
+        }
+
+        unreachable!("Requires at least one RPC endpoint")
+    }
+
+    /// `with_failover` of the blocking clis.
+    pub fn with_failover_blocking<T, E>(
+        &self,
+        role: EndpointRole,
+        is_endpoint_error: impl Fn(&E) -> bool,
+        mut request: impl FnMut(String) -> Result<T, E>,
+    ) -> Result<T, E> {
+        futures::executor::block_on(self.with_failover(role, is_endpoint_error, |url| {
+            futures::future::ready(request(url))
+        }))
+    }
+
+    pub fn status(&self) -> Vec<EndpointHealth> {
+        self.endpoints.lock().unwrap().clone()
+    }
+
+    fn select(&self, role: EndpointRole) -> String {
+        let mut endpoints = self.endpoints.lock().unwrap();
+
+        // the endpoints of the role (or all of them, without an endpoint of the role)
+        let mut candidates: Vec<usize> = (0..endpoints.len())
+            .filter(|i| endpoints[*i].role.serves(role))
+            .collect();
+        if candidates.is_empty() {
+            candidates = (0..endpoints.len()).collect();
+        }
+
+        let healthy: Vec<usize> = candidates
+            .iter()
+            .copied()
+            .filter(|i| endpoints[*i].is_healthy(&self.health))
+            .collect();
+
+        // without a healthy endpoint, the least failing one
+        if healthy.is_empty() {
+            let i = candidates
+                .into_iter()
+                .min_by(|a, b| {
+                    let (a, b) = (&endpoints[*a], &endpoints[*b]);
+                    (!a.reachable, a.error_rate)
+                        .partial_cmp(&(!b.reachable, b.error_rate))
+                        .unwrap()
+                })
+                .unwrap();
+            return endpoints[i].url.clone();
+        }
+
+        let total_weight: i64 = healthy.iter().map(|i| endpoints[*i].weight as i64).sum();
+        for i in &healthy {
+            endpoints[*i].current_weight += endpoints[*i].weight as i64;
+        }
+
+        let selected = *healthy
+            .iter()
+            .max_by_key(|i| endpoints[**i].current_weight)
+            .unwrap();
+        endpoints[selected].current_weight -= total_weight;
+
+        endpoints[selected].url.clone()
+    }
+}
+
+// cargo test -p staratlas-utils-rpc
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_list() {
+        let cfg =
+            EndpointsCfg::parse_list("https://a.rpc|3|read, https://b.rpc|1|send,https://c.rpc")
+                .unwrap();
+
+        assert_eq!(cfg.endpoints.len(), 3);
+        assert_eq!(cfg.endpoints[0].weight, 3);
+        assert_eq!(cfg.endpoints[0].role, EndpointRole::Read);
+        assert_eq!(cfg.endpoints[1].role, EndpointRole::Send);
+        assert_eq!(cfg.endpoints[2].weight, 1);
+        assert_eq!(cfg.endpoints[2].role, EndpointRole::Any);
+
+        assert!(EndpointsCfg::parse_list("https://a.rpc|3|write").is_err());
+    }
+
+    #[test]
+    fn weighted_failover() {
+        let cfg = EndpointsCfg::parse_list("https://a.rpc|3|read,https://b.rpc|1|any").unwrap();
+        let endpoints = RpcEndpoints::new(cfg).unwrap();
+
+        let reads: Vec<String> = (0..4).map(|_| endpoints.read_url()).collect();
+        assert_eq!(
+            reads.iter().filter(|url| *url == "https://a.rpc").count(),
+            3
+        );
+        assert_eq!(endpoints.send_url(), "https://b.rpc");
+
+        // failing requests fail over to the other endpoint
+        for _ in 0..5 {
+            endpoints.report("https://a.rpc", false);
+        }
+        assert!((0..4).all(|_| endpoints.read_url() == "https://b.rpc"));
+    }
+
+    #[test]
+    fn fails_over_endpoint_errors() {
+        let cfg = EndpointsCfg::parse_list(
+            "https://a.rpc|3|any,https://b.rpc|1|any,https://c.rpc|1|read",
+        )
+        .unwrap();
+        let endpoints = RpcEndpoints::new(cfg).unwrap();
+
+        let mut urls = endpoints.failover_urls(EndpointRole::Send);
+        urls.sort();
+        assert_eq!(urls, vec!["https://a.rpc", "https://b.rpc"]);
+
+        let is_endpoint_error = |err: &&str| *err == "timeout";
+
+        // a timeout fails over to the next endpoint (and counts against the failed one)
+        let mut tried: Vec<String> = vec![];
+        let result =
+            endpoints.with_failover_blocking(EndpointRole::Send, is_endpoint_error, |url| {
+                tried.push(url.clone());
+                match tried.len() {
+                    1 => Err("timeout"),
+                    _ => Ok(url),
+                }
+            });
+        assert_eq!(tried.len(), 2);
+        assert_ne!(tried[0], tried[1]);
+        assert_eq!(result.unwrap(), tried[1]);
+
+        for endpoint in endpoints.status() {
+            assert_eq!(endpoint.error_rate > 0.0, endpoint.url == tried[0]);
+        }
+
+        // any other error is returned at once
+        let mut tried = 0;
+        let result: Result<(), &str> =
+            endpoints.with_failover_blocking(EndpointRole::Send, is_endpoint_error, |_| {
+                tried += 1;
+                Err("account not found")
+            });
+        assert_eq!(result, Err("account not found"));
+        assert_eq!(tried, 1);
+
+        // the last endpoint's error, when every endpoint fails
+        let mut tried = 0;
+        let result: Result<(), &str> =
+            endpoints.with_failover_blocking(EndpointRole::Read, is_endpoint_error, |_| {
+                tried += 1;
+                Err("timeout")
+            });
+        assert_eq!(result, Err("timeout"));
+        assert_eq!(tried, 3);
+    }
+
+    #[test]
+    fn broadcasts_reuse_the_endpoint_clients() {
+        let mut cfg =
+            EndpointsCfg::parse_list("https://a.rpc|1|send,https://b.rpc,https://c.rpc|1|read")
+                .unwrap();
+        cfg.broadcast_sends = true;
+        let endpoints = RpcEndpoints::new(cfg).unwrap();
+
+        let clients = endpoints.broadcast_clients("https://a.rpc");
+        assert_eq!(clients.len(), 1);
+        assert_eq!(clients[0].0, "https://b.rpc");
+        assert_eq!(clients[0].1.url(), "https://b.rpc");
+
+        let again = endpoints.broadcast_clients("https://a.rpc");
+        assert!(Arc::ptr_eq(&clients[0].1, &again[0].1));
+    }
+
+    #[test]
+    fn finds_the_endpoint_errors() {
+        let io = ClientError::from(std::io::Error::from(std::io::ErrorKind::TimedOut));
+        let unhealthy = ClientError::from(RpcError::RpcResponseError {
+            code: JSON_RPC_SERVER_ERROR_NODE_UNHEALTHY,
+            message: "Node is unhealthy".to_string(),
+            data: solana_client::rpc_request::RpcResponseErrorData::Empty,
+        });
+        let not_found = ClientError::from(RpcError::ForUser("AccountNotFound".to_string()));
+
+        assert!(is_endpoint_error(&io));
+        assert!(is_endpoint_error(&unhealthy));
+        assert!(!is_endpoint_error(&not_found));
+
+        // within another error (the sources of an `anyhow::Error`)
+        let err = anyhow::Error::from(io).context("dump");
+        assert!(is_endpoint_error(err.as_ref()));
+        let err = anyhow::Error::from(not_found).context("dump");
+        assert!(!is_endpoint_error(err.as_ref()));
+        assert!(!is_endpoint_error(anyhow!("invalid mint").as_ref()));
+    }
+}
@@ -0,0 +1,138 @@
+use anyhow::anyhow;
+use serde_json::{json, Value};
+
+use std::time::{Duration, Instant};
+
+use crate::endpoints::RpcEndpoints;
+
+type SlotCheck = anyhow::Result<(u64, Duration)>; // (Slot, Latency)
+
+fn get_slot_request() -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "id": 1,
+        "method": "getSlot",
+        "params": [{ "commitment": "confirmed" }],
+    })
+}
+
+fn parse_slot(response: Value) -> anyhow::Result<u64> {
+    response["result"]
+        .as_u64()
+        .ok_or(anyhow!("Invalid getSlot response: {}", response))
+}
+
+async fn get_slot(client: &reqwest::Client, url: &str) -> SlotCheck {
+    let started = Instant::now();
+    let response: Value = client
+        .post(url)
+        .json(&get_slot_request())
+        .send()
+        .await?
+        .json()
+        .await?;
+
+    Ok((parse_slot(response)?, started.elapsed()))
+}
+
+fn get_slot_blocking(client: &reqwest::blocking::Client, url: &str) -> SlotCheck {
+    let started = Instant::now();
+    let response: Value = client.post(url).json(&get_slot_request()).send()?.json()?;
+
+    Ok((parse_slot(response)?, started.elapsed()))
+}
+
+impl RpcEndpoints {
+    fn check_timeout(&self) -> Duration {
+        Duration::from_millis(self.health.max_latency_ms * 2)
+    }
+
+    fn urls(&self) -> Vec<String> {
+        let endpoints = self.endpoints.lock().unwrap();
+        endpoints
+            .iter()
+            .map(|endpoint| endpoint.url.clone())
+            .collect()
+    }
+
+    /// Checks the slot (lag) and latency of every endpoint.
+    pub async fn check_health(&self) {
+        let client = match reqwest::Client::builder()
+            .timeout(self.check_timeout())
+            .build()
+        {
+            Ok(client) => client,
+            Err(err) => {
+                log::error!("{:?}", err);
+                return;
+            }
+        };
+
+        let urls = self.urls();
+        let checks = futures::future::join_all(urls.iter().map(|url| get_slot(&client, url))).await;
+
+        self.apply_checks(checks);
+    }
+
+    /// `check_health` of the blocking clis (outside of an async runtime).
+    pub fn check_health_blocking(&self) {
+        let client = match reqwest::blocking::Client::builder()
+            .timeout(self.check_timeout())
+            .build()
+        {
+            Ok(client) => client,
+            Err(err) => {
+                log::error!("{:?}", err);
+                return;
+            }
+        };
+
+        let checks = self
+            .urls()
+            .iter()
+            .map(|url| get_slot_blocking(&client, url))
+            .collect();
+
+        self.apply_checks(checks);
+    }
+
+    fn apply_checks(&self, checks: Vec<SlotCheck>) {
+        let max_slot = checks
+            .iter()
+            .filter_map(|check| check.as_ref().ok().map(|(slot, _)| *slot))
+            .max();
+
+        let mut endpoints = self.endpoints.lock().unwrap();
+
+        for (endpoint, check) in endpoints.iter_mut().zip(checks) {
+            let was_healthy = endpoint.is_healthy(&self.health);
+
+            match check {
+                Ok((slot, latency)) => {
+                    endpoint.reachable = true;
+                    endpoint.slot = Some(slot);
+                    endpoint.slot_lag = max_slot.unwrap_or(slot).saturating_sub(slot);
+                    endpoint.latency = Some(latency);
+                    endpoint.record(true);
+                }
+                Err(err) => {
+                    log::debug!("{}: {:?}", endpoint.url, err);
+                    endpoint.reachable = false;
+                    endpoint.record(false);
+                }
+            }
+
+            match (was_healthy, endpoint.is_healthy(&self.health)) {
+                (true, false) => log::warn!(
+                    "RPC endpoint unhealthy: {} (reachable: {}, slot lag: {}, latency: {:?})",
+                    endpoint.url,
+                    endpoint.reachable,
+                    endpoint.slot_lag,
+                    endpoint.latency
+                ),
+                (false, true) => log::info!("RPC endpoint recovered: {}", endpoint.url),
+                _ => {}
+            }
+        }
+    }
+}
@@ -0,0 +1,10 @@
+//! RPC endpoints of a Solana cluster (weighted, for reads and/or sends) with health checks and
+//! failover, shared by the clis and bots.
+
+pub mod endpoints;
+mod health;
+
+pub use endpoints::{
+    is_endpoint_error, EndpointCfg, EndpointHealth, EndpointRole, EndpointsCfg, HealthCfg,
+    RpcEndpoints,
+};